@@ -0,0 +1,415 @@
+package archive
+
+import (
+	"archive/tar"
+	"archive/zip"
+	"bufio"
+	"bytes"
+	"compress/bzip2"
+	"compress/gzip"
+	"errors"
+	"fmt"
+	"io"
+	"io/fs"
+	"os"
+	"path"
+	"path/filepath"
+	"slices"
+	"strings"
+	"sync"
+	"time"
+)
+
+// Extensions are the archive file extensions that are ingested like directories.
+var Extensions = []string{".zip", ".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tbz2"}
+
+// IsArchive reports whether the file at path is an archive by its extension.
+func IsArchive(path string) bool {
+	name := strings.ToLower(filepath.Base(path))
+	for _, ext := range Extensions {
+		if strings.HasSuffix(name, ext) {
+			return true
+		}
+	}
+	return false
+}
+
+// FS is an archive read as an fs.FS, with its entries in a directory named after the archive,
+// such as samples.zip/src/main.go, so walking it shows the archive's own structure. Entries
+// are read from the archive as they are opened rather than extracted. Zip archives and plain
+// tars are read entry by entry, but a compressed tar can only be read from its start, so its
+// files are read together in one pass by Prefetch. Symlinks and other special entries are left
+// out, as are nested archives, which are listed in Nested. It is safe for concurrent use.
+type FS struct {
+	Nested []string // The nested archives left out, by their path in the FS
+
+	root       string
+	path       string
+	file       *os.File
+	compressed bool
+	entries    map[string]*entry
+
+	mu         sync.Mutex
+	prefetched map[string][]byte
+}
+
+// entry is a file or directory in the archive.
+type entry struct {
+	name     string
+	mode     fs.FileMode
+	size     int64
+	modTime  time.Time
+	children []string // The names of a directory's entries
+
+	zipFile *zip.File
+	offset  int64 // Where the content of a plain tar's file starts
+}
+
+// Open reads the index of the archive at path. The FS must be closed once its files are read.
+func Open(path string) (*FS, error) {
+	file, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	info, err := file.Stat()
+	if err != nil {
+		file.Close()
+		return nil, err
+	}
+
+	f := &FS{
+		root:    filepath.Base(path),
+		path:    path,
+		file:    file,
+		entries: make(map[string]*entry),
+	}
+	f.entries["."] = &entry{name: ".", mode: fs.ModeDir | 0755, modTime: info.ModTime()}
+	f.add(f.root, &entry{mode: fs.ModeDir | 0755, modTime: info.ModTime()})
+
+	name := strings.ToLower(f.root)
+	switch {
+	case strings.HasSuffix(name, ".zip"):
+		err = f.indexZip(info.Size())
+	case strings.HasSuffix(name, ".tar"):
+		err = f.indexTar()
+	default:
+		f.compressed = true
+		err = f.readTar(true, func(*tar.Reader, string) error { return nil })
+	}
+	if err != nil {
+		file.Close()
+		return nil, fmt.Errorf("failed to read archive %s: %w", path, err)
+	}
+	return f, nil
+}
+
+// Close closes the archive.
+func (f *FS) Close() error {
+	return f.file.Close()
+}
+
+func (f *FS) indexZip(size int64) error {
+	r, err := zip.NewReader(f.file, size)
+	if err != nil {
+		return err
+	}
+	for _, zf := range r.File {
+		info := zf.FileInfo()
+		if !info.IsDir() && !info.Mode().IsRegular() {
+			continue
+		}
+		f.add(zf.Name, &entry{mode: info.Mode(), size: info.Size(), modTime: info.ModTime(), zipFile: zf})
+	}
+	return nil
+}
+
+// indexTar adds the entries of a plain tar, keeping the offset of each file's content so it can
+// be read on its own.
+func (f *FS) indexTar() error {
+	tr := tar.NewReader(f.file)
+	for {
+		header, err := tr.Next()
+		if err == io.EOF {
+			return nil
+		}
+		if err != nil {
+			return err
+		}
+		mode := header.FileInfo().Mode()
+		if !mode.IsRegular() && !mode.IsDir() {
+			continue
+		}
+
+		e := &entry{mode: mode, size: header.Size, modTime: header.ModTime}
+		if mode.IsRegular() {
+			// The tar reader seeks past content it skips, so the file is at this entry's content
+			if e.offset, err = f.file.Seek(0, io.SeekCurrent); err != nil {
+				return err
+			}
+		}
+		f.add(header.Name, e)
+	}
+}
+
+// readTar reads the compressed tar from its start, adding its entries if index is set, and
+// calls read with the reader positioned at the content of each file in the FS. Each read opens
+// the archive again, so reads can happen at once.
+func (f *FS) readTar(index bool, read func(tr *tar.Reader, name string) error) error {
+	file, err := os.Open(f.path)
+	if err != nil {
+		return err
+	}
+	defer file.Close()
+
+	var r io.Reader = bufio.NewReader(file)
+	name := strings.ToLower(f.root)
+	if strings.HasSuffix(name, ".bz2") || strings.HasSuffix(name, ".tbz2") {
+		r = bzip2.NewReader(r)
+	} else {
+		gz, err := gzip.NewReader(r)
+		if err != nil {
+			return err
+		}
+		defer gz.Close()
+		r = gz
+	}
+
+	tr := tar.NewReader(r)
+	for {
+		header, err := tr.Next()
+		if err == io.EOF {
+			return nil
+		}
+		if err != nil {
+			return err
+		}
+		mode := header.FileInfo().Mode()
+		if !mode.IsRegular() && !mode.IsDir() {
+			continue
+		}
+
+		if index {
+			f.add(header.Name, &entry{mode: mode, size: header.Size, modTime: header.ModTime})
+		}
+		if name, ok := f.entryName(header.Name); ok && mode.IsRegular() {
+			if err := read(tr, name); err != nil {
+				return err
+			}
+		}
+	}
+}
+
+// entryName returns the path in the FS of the archive entry name, or false if it is outside
+// the archive's directory.
+func (f *FS) entryName(name string) (string, bool) {
+	name = path.Clean("/" + strings.ReplaceAll(name, "\\", "/"))
+	if name == "/" {
+		return "", false
+	}
+	full := f.root + name
+	return full, fs.ValidPath(full)
+}
+
+// add adds the entry at the archive path name, along with any parent directories the archive
+// doesn't list itself. Nested archives are noted instead.
+func (f *FS) add(name string, e *entry) {
+	full := name
+	if name != f.root {
+		var ok bool
+		if full, ok = f.entryName(name); !ok {
+			return
+		}
+	}
+	if existing, ok := f.entries[full]; ok {
+		// A directory is added when its files are, so take the archive's own entry for it
+		if existing.mode.IsDir() && e.mode.IsDir() {
+			existing.mode, existing.modTime = e.mode, e.modTime
+		}
+		return
+	}
+	if !e.mode.IsDir() && IsArchive(full) {
+		f.Nested = append(f.Nested, full)
+		return
+	}
+
+	e.name = path.Base(full)
+	f.entries[full] = e
+	parent := path.Dir(full)
+	if _, ok := f.entries[parent]; !ok {
+		f.add(strings.TrimPrefix(parent, f.root+"/"), &entry{mode: fs.ModeDir | 0755, modTime: e.modTime})
+	}
+	f.entries[parent].children = append(f.entries[parent].children, e.name)
+}
+
+// Prefetch reads the named files of a compressed tar in one pass, so opening them doesn't read
+// the archive from its start each time. The files of other archives are read as they are opened.
+func (f *FS) Prefetch(names []string) error {
+	if !f.compressed {
+		return nil
+	}
+	wanted := make(map[string]bool, len(names))
+	for _, name := range names {
+		wanted[path.Clean(filepath.ToSlash(name))] = true
+	}
+
+	prefetched := make(map[string][]byte, len(names))
+	err := f.readTar(false, func(tr *tar.Reader, name string) error {
+		if !wanted[name] {
+			return nil
+		}
+		content, err := io.ReadAll(tr)
+		prefetched[name] = content
+		return err
+	})
+	if err != nil {
+		return fmt.Errorf("failed to read archive %s: %w", f.path, err)
+	}
+
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	f.prefetched = prefetched
+	return nil
+}
+
+// Open opens the named file or directory.
+func (f *FS) Open(name string) (fs.File, error) {
+	e, err := f.lookup("open", name)
+	if err != nil {
+		return nil, err
+	}
+	if e.mode.IsDir() {
+		return &dir{entry: e, fsys: f, path: name}, nil
+	}
+
+	r, err := f.content(name, e)
+	if err != nil {
+		return nil, &fs.PathError{Op: "open", Path: name, Err: err}
+	}
+	return &openFile{entry: e, ReadCloser: r}, nil
+}
+
+// content returns a reader of the content of the file e at name.
+func (f *FS) content(name string, e *entry) (io.ReadCloser, error) {
+	switch {
+	case e.zipFile != nil:
+		return e.zipFile.Open()
+	case !f.compressed:
+		return io.NopCloser(io.NewSectionReader(f.file, e.offset, e.size)), nil
+	}
+
+	f.mu.Lock()
+	content, ok := f.prefetched[name]
+	f.mu.Unlock()
+	if ok {
+		return io.NopCloser(bytes.NewReader(content)), nil
+	}
+
+	// Files that weren't prefetched are found by reading the archive up to them
+	errFound := errors.New("found")
+	err := f.readTar(false, func(tr *tar.Reader, entryName string) error {
+		if entryName != name {
+			return nil
+		}
+		var readErr error
+		if content, readErr = io.ReadAll(tr); readErr != nil {
+			return readErr
+		}
+		return errFound
+	})
+	if !errors.Is(err, errFound) {
+		if err == nil {
+			err = fs.ErrNotExist
+		}
+		return nil, err
+	}
+	return io.NopCloser(bytes.NewReader(content)), nil
+}
+
+// Stat returns the info of the named file or directory.
+func (f *FS) Stat(name string) (fs.FileInfo, error) {
+	e, err := f.lookup("stat", name)
+	if err != nil {
+		return nil, err
+	}
+	return fileInfo{e}, nil
+}
+
+// ReadDir returns the entries of the named directory, sorted by name.
+func (f *FS) ReadDir(name string) ([]fs.DirEntry, error) {
+	e, err := f.lookup("readdir", name)
+	if err != nil {
+		return nil, err
+	}
+	if !e.mode.IsDir() {
+		return nil, &fs.PathError{Op: "readdir", Path: name, Err: errors.New("not a directory")}
+	}
+
+	children := slices.Sorted(slices.Values(e.children))
+	entries := make([]fs.DirEntry, len(children))
+	for i, child := range children {
+		entries[i] = fs.FileInfoToDirEntry(fileInfo{f.entries[path.Join(name, child)]})
+	}
+	return entries, nil
+}
+
+func (f *FS) lookup(op, name string) (*entry, error) {
+	if !fs.ValidPath(name) {
+		return nil, &fs.PathError{Op: op, Path: name, Err: fs.ErrInvalid}
+	}
+	e, ok := f.entries[name]
+	if !ok {
+		return nil, &fs.PathError{Op: op, Path: name, Err: fs.ErrNotExist}
+	}
+	return e, nil
+}
+
+// fileInfo is the fs.FileInfo of an entry.
+type fileInfo struct {
+	e *entry
+}
+
+func (i fileInfo) Name() string       { return i.e.name }
+func (i fileInfo) Size() int64        { return i.e.size }
+func (i fileInfo) Mode() fs.FileMode  { return i.e.mode }
+func (i fileInfo) ModTime() time.Time { return i.e.modTime }
+func (i fileInfo) IsDir() bool        { return i.e.mode.IsDir() }
+func (i fileInfo) Sys() any           { return nil }
+
+// openFile is an open file in the archive.
+type openFile struct {
+	entry *entry
+	io.ReadCloser
+}
+
+func (f *openFile) Stat() (fs.FileInfo, error) { return fileInfo{f.entry}, nil }
+
+// dir is an open directory in the archive.
+type dir struct {
+	entry *entry
+	fsys  *FS
+	path  string
+	read  int // How many entries ReadDir has returned
+}
+
+func (d *dir) Stat() (fs.FileInfo, error) { return fileInfo{d.entry}, nil }
+func (d *dir) Close() error               { return nil }
+
+func (d *dir) Read([]byte) (int, error) {
+	return 0, &fs.PathError{Op: "read", Path: d.path, Err: errors.New("is a directory")}
+}
+
+func (d *dir) ReadDir(n int) ([]fs.DirEntry, error) {
+	entries, err := d.fsys.ReadDir(d.path)
+	if err != nil {
+		return nil, err
+	}
+	entries = entries[d.read:]
+	if n > 0 {
+		if len(entries) == 0 {
+			return nil, io.EOF
+		}
+		entries = entries[:min(n, len(entries))]
+	}
+	d.read += len(entries)
+	return entries, nil
+}
@@ -0,0 +1,119 @@
+package archive
+
+import (
+	"archive/tar"
+	"archive/zip"
+	"compress/gzip"
+	"io"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"testing/fstest"
+
+	"github.com/sammcj/ingest/filesystem"
+)
+
+var testEntries = []struct {
+	name    string
+	content string
+}{
+	{"src/main.go", "package main\n"},
+	{"src/util/util.go", "package util\n"},
+	{"README.md", "# Samples\n"},
+	{"vendor.zip", "PK"},
+}
+
+func writeZip(t *testing.T, path string) {
+	t.Helper()
+	file, err := os.Create(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	defer file.Close()
+	w := zip.NewWriter(file)
+	for _, e := range testEntries {
+		f, err := w.Create(e.name)
+		if err != nil {
+			t.Fatal(err)
+		}
+		if _, err := io.WriteString(f, e.content); err != nil {
+			t.Fatal(err)
+		}
+	}
+	if err := w.Close(); err != nil {
+		t.Fatal(err)
+	}
+}
+
+func writeTarGz(t *testing.T, path string) {
+	t.Helper()
+	file, err := os.Create(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	defer file.Close()
+	gz := gzip.NewWriter(file)
+	w := tar.NewWriter(gz)
+	for _, e := range testEntries {
+		if err := w.WriteHeader(&tar.Header{Name: "./" + e.name, Mode: 0644, Size: int64(len(e.content)), Typeflag: tar.TypeReg}); err != nil {
+			t.Fatal(err)
+		}
+		if _, err := io.WriteString(w, e.content); err != nil {
+			t.Fatal(err)
+		}
+	}
+	if err := w.Close(); err != nil {
+		t.Fatal(err)
+	}
+	if err := gz.Close(); err != nil {
+		t.Fatal(err)
+	}
+}
+
+func TestFS(t *testing.T) {
+	dir := t.TempDir()
+	writers := map[string]func(*testing.T, string){
+		"samples.zip":    writeZip,
+		"samples.tar.gz": writeTarGz,
+	}
+
+	for name, write := range writers {
+		t.Run(name, func(t *testing.T) {
+			path := filepath.Join(dir, name)
+			write(t, path)
+			fsys, err := Open(path)
+			if err != nil {
+				t.Fatalf("Open failed: %v", err)
+			}
+			defer fsys.Close()
+
+			if err := fstest.TestFS(fsys, name+"/src/main.go", name+"/src/util/util.go", name+"/README.md"); err != nil {
+				t.Error(err)
+			}
+			if len(fsys.Nested) != 1 || fsys.Nested[0] != name+"/vendor.zip" {
+				t.Errorf("Expected vendor.zip to be left out as a nested archive, got %v", fsys.Nested)
+			}
+
+			tree, files, _, err := filesystem.WalkDirectory(name, filesystem.Options{FS: fsys, NoCodeblock: true})
+			if err != nil {
+				t.Fatalf("WalkDirectory failed: %v", err)
+			}
+			if len(files) != 3 {
+				t.Fatalf("Expected 3 files, got %d", len(files))
+			}
+			for _, file := range files {
+				if file.RelPath == "src/main.go" && file.Code != "package main\n" {
+					t.Errorf("Expected the content of src/main.go, got %q", file.Code)
+				}
+			}
+			if !strings.Contains(tree, "util.go") {
+				t.Errorf("Expected the tree to show the archive's directories, got:\n%s", tree)
+			}
+		})
+	}
+
+	if IsArchive("main.go") || !IsArchive("Samples.TGZ") {
+		t.Error("Expected archives to be recognised by their extension")
+	}
+}
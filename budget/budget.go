@@ -0,0 +1,88 @@
+package budget
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+
+	"github.com/bmatcuk/doublestar/v4"
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/utils"
+)
+
+// Smallest is the --priority value that prefers smaller files.
+const Smallest = "smallest"
+
+// Priority controls the order in which files are considered when fitting them within a token
+// budget. Files matching an earlier glob come first, and files matching none come last.
+type Priority struct {
+	Globs         []string
+	SmallestFirst bool
+}
+
+// ParsePriority builds a Priority from --priority values, each of which is either "smallest"
+// or a glob such as src/**.
+func ParsePriority(values []string) (Priority, error) {
+	var p Priority
+	for _, value := range values {
+		if strings.EqualFold(value, Smallest) {
+			p.SmallestFirst = true
+			continue
+		}
+		if !doublestar.ValidatePattern(value) {
+			return Priority{}, fmt.Errorf("invalid priority pattern %q", value)
+		}
+		p.Globs = append(p.Globs, value)
+	}
+	return p, nil
+}
+
+// Order returns the indices of files in the order they should be included. Files are kept in
+// tree order within each glob group, or sorted by token count when SmallestFirst is set.
+func (p Priority) Order(files []filesystem.FileInfo) []int {
+	groups := make([]int, len(files))
+	order := make([]int, len(files))
+	for i, file := range files {
+		groups[i] = p.group(file.Path)
+		order[i] = i
+	}
+
+	sort.SliceStable(order, func(a, b int) bool {
+		i, j := order[a], order[b]
+		if groups[i] != groups[j] {
+			return groups[i] < groups[j]
+		}
+		if p.SmallestFirst {
+			return files[i].TokenCount < files[j].TokenCount
+		}
+		return false
+	})
+	return order
+}
+
+// group returns the index of the first glob matching path, or len(p.Globs) if none do. A glob
+// matches if it matches any trailing part of the path, so src/** matches /home/me/repo/src/main.go.
+func (p Priority) group(path string) int {
+	for g, glob := range p.Globs {
+		if utils.MatchPathSuffix(glob, path) {
+			return g
+		}
+	}
+	return len(p.Globs)
+}
+
+// Select returns the indices of the files to include, in the given order, taking files until
+// the next one would exceed the available tokens. cost returns the tokens a file adds to the
+// output, including its header.
+func Select(files []filesystem.FileInfo, order []int, available int, cost func(filesystem.FileInfo) int) []int {
+	var selected []int
+	for _, i := range order {
+		c := cost(files[i])
+		if c > available {
+			break
+		}
+		available -= c
+		selected = append(selected, i)
+	}
+	return selected
+}
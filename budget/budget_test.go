@@ -0,0 +1,63 @@
+package budget
+
+import (
+	"reflect"
+	"testing"
+
+	"github.com/sammcj/ingest/filesystem"
+)
+
+func testFiles() []filesystem.FileInfo {
+	return []filesystem.FileInfo{
+		{Path: "repo/README.md", TokenCount: 50},
+		{Path: "repo/src/main.go", TokenCount: 300},
+		{Path: "repo/tests/main_test.go", TokenCount: 20},
+		{Path: "repo/src/util.go", TokenCount: 100},
+	}
+}
+
+func TestOrder(t *testing.T) {
+	tests := []struct {
+		name     string
+		values   []string
+		expected []int
+	}{
+		{"tree order", nil, []int{0, 1, 2, 3}},
+		{"smallest", []string{"smallest"}, []int{2, 0, 3, 1}},
+		{"glob", []string{"src/**"}, []int{1, 3, 0, 2}},
+		{"glob then smallest", []string{"src/**", "smallest"}, []int{3, 1, 2, 0}},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			p, err := ParsePriority(tt.values)
+			if err != nil {
+				t.Fatalf("ParsePriority failed: %v", err)
+			}
+			if got := p.Order(testFiles()); !reflect.DeepEqual(got, tt.expected) {
+				t.Errorf("Expected order %v, got %v", tt.expected, got)
+			}
+		})
+	}
+}
+
+func TestSelect(t *testing.T) {
+	files := testFiles()
+	cost := func(f filesystem.FileInfo) int { return f.TokenCount + 10 }
+
+	// README (60) and main.go (310) fit in 400, tests (30) would fit but selection stops at util.go
+	got := Select(files, []int{0, 1, 3, 2}, 400, cost)
+	if expected := []int{0, 1}; !reflect.DeepEqual(got, expected) {
+		t.Errorf("Expected %v, got %v", expected, got)
+	}
+
+	if got := Select(files, []int{0, 1, 2, 3}, 10, cost); len(got) != 0 {
+		t.Errorf("Expected nothing to fit, got %v", got)
+	}
+}
+
+func TestParsePriorityInvalid(t *testing.T) {
+	if _, err := ParsePriority([]string{"src/[a"}); err == nil {
+		t.Error("Expected an error for an invalid pattern")
+	}
+}
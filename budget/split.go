@@ -0,0 +1,92 @@
+package budget
+
+import (
+	"fmt"
+	"strings"
+
+	"github.com/sammcj/ingest/filesystem"
+)
+
+// SplitModes are the accepted values of --split-large: how a file too large for a chunk of
+// its own is handled when splitting output. whole gives it a chunk over the budget, and lines
+// splits it into parts at line boundaries.
+var SplitModes = []string{"whole", "lines"}
+
+// SplitLines splits a file into parts at line boundaries, each holding at most available
+// tokens of content where a single line allows, as counted by count. Each part keeps the
+// file's code fence and starts with a note of the lines it holds, so a part read alone says
+// where it continues from. Anything before the code block, such as a file's diff, stays with
+// the first part. A file that fits is returned as it is.
+func SplitLines(file filesystem.FileInfo, available int, count func(string) int) []filesystem.FileInfo {
+	preamble, opening, body, closing := splitFencedCode(file.Code)
+	lines := strings.Split(body, "\n")
+
+	// The fences and note are counted once as an overhead of every part
+	overhead := count(opening + "\n" + closing + "\n" + partNote(len(lines), len(lines), len(lines), len(lines), len(lines)) + "\n")
+	limit := max(available-overhead, 1)
+
+	var groups [][2]int // The first and one past the last line of each part
+	start, used := 0, count(preamble)
+	for i, line := range lines {
+		tokens := count(line + "\n")
+		if i > start && used+tokens > limit {
+			groups = append(groups, [2]int{start, i})
+			start, used = i, 0
+		}
+		used += tokens
+	}
+	groups = append(groups, [2]int{start, len(lines)})
+	if len(groups) == 1 {
+		return []filesystem.FileInfo{file}
+	}
+
+	parts := make([]filesystem.FileInfo, len(groups))
+	for n, group := range groups {
+		var b strings.Builder
+		if n == 0 {
+			b.WriteString(preamble)
+		}
+		b.WriteString(partNote(n+1, len(groups), group[0]+1, group[1], len(lines)))
+		b.WriteString("\n")
+		if opening != "" {
+			b.WriteString(opening + "\n")
+		}
+		b.WriteString(strings.Join(lines[group[0]:group[1]], "\n"))
+		if closing != "" {
+			b.WriteString("\n" + closing)
+		}
+
+		parts[n] = file
+		parts[n].Code = b.String()
+		parts[n].TokenCount = count(parts[n].Code)
+	}
+	return parts
+}
+
+func partNote(part, parts, first, last, total int) string {
+	return fmt.Sprintf("Part %d of %d, lines %d-%d of %d.", part, parts, first, last, total)
+}
+
+// splitFencedCode splits content ending in a fenced code block into what comes before the
+// block, its opening and closing fence lines, and the code between them. Content that doesn't
+// end in a code block, such as with --no-codeblock, is all code.
+func splitFencedCode(content string) (preamble, opening, code, closing string) {
+	lastLine := content[strings.LastIndex(content, "\n")+1:]
+	if len(lastLine) < 3 || (strings.Trim(lastLine, "`") != "" && strings.Trim(lastLine, "~") != "") {
+		return "", "", content, ""
+	}
+
+	// The fence is longer than any run of its character in the code, so the last line starting
+	// with it opens the block
+	body := strings.TrimSuffix(content, lastLine)
+	openStart := strings.LastIndex("\n"+body, "\n"+lastLine)
+	if openStart < 0 || len(body) == 0 {
+		return "", "", content, ""
+	}
+	openEnd := strings.Index(body[openStart:], "\n")
+	if openEnd < 0 {
+		return "", "", content, ""
+	}
+	openEnd += openStart
+	return body[:openStart], body[openStart:openEnd], strings.TrimSuffix(body[openEnd+1:], "\n"), lastLine
+}
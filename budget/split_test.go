@@ -0,0 +1,72 @@
+package budget
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+
+	"github.com/sammcj/ingest/filesystem"
+)
+
+func TestSplitLines(t *testing.T) {
+	// Each word is a token
+	count := func(s string) int { return len(strings.Fields(s)) }
+
+	tests := []struct {
+		name      string
+		code      string
+		available int
+		expected  []string
+	}{
+		{
+			name:      "fits",
+			code:      "```go\na b\nc d\n```",
+			available: 100,
+			expected:  []string{"```go\na b\nc d\n```"},
+		},
+		{
+			name:      "fenced",
+			code:      "```go\na b\nc d\ne f\n```",
+			available: 14,
+			expected: []string{
+				"Part 1 of 2, lines 1-2 of 3.\n```go\na b\nc d\n```",
+				"Part 2 of 2, lines 3-3 of 3.\n```go\ne f\n```",
+			},
+		},
+		{
+			name:      "diff stays with the first part",
+			code:      "```diff\n-x\n```\n\n```go\na b\nc d\ne f\n```",
+			available: 14,
+			expected: []string{
+				"```diff\n-x\n```\n\nPart 1 of 2, lines 1-1 of 3.\n```go\na b\n```",
+				"Part 2 of 2, lines 2-3 of 3.\n```go\nc d\ne f\n```",
+			},
+		},
+		{
+			name:      "no code block",
+			code:      "a b\nc d\ne f",
+			available: 11,
+			expected: []string{
+				"Part 1 of 3, lines 1-1 of 3.\na b",
+				"Part 2 of 3, lines 2-2 of 3.\nc d",
+				"Part 3 of 3, lines 3-3 of 3.\ne f",
+			},
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			parts := SplitLines(filesystem.FileInfo{Path: "main.go", Code: tt.code}, tt.available, count)
+			var got []string
+			for _, part := range parts {
+				if part.Path != "main.go" {
+					t.Errorf("Expected each part to keep the path, got %s", part.Path)
+				}
+				got = append(got, part.Code)
+			}
+			if !reflect.DeepEqual(got, tt.expected) {
+				t.Errorf("Expected parts %q, got %q", tt.expected, got)
+			}
+		})
+	}
+}
@@ -0,0 +1,38 @@
+package cache
+
+import (
+	"path/filepath"
+	"testing"
+)
+
+func TestCache(t *testing.T) {
+	dir := filepath.Join(t.TempDir(), "ingest")
+	c, err := Open(dir)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	type entry struct{ Code string }
+	key := Key("main.go", "1700000000", "12")
+	var got entry
+	if c.Get(key, &got) {
+		t.Fatal("Expected an empty cache to miss")
+	}
+	if err := c.Put(key, entry{Code: "package main"}); err != nil {
+		t.Fatal(err)
+	}
+	if !c.Get(key, &got) || got.Code != "package main" {
+		t.Errorf("Expected the stored entry, got %+v", got)
+	}
+
+	if Key("a", "bc") == Key("ab", "c") {
+		t.Error("Expected key parts to be separated")
+	}
+
+	if err := Clear(dir); err != nil {
+		t.Fatal(err)
+	}
+	if c.Get(key, &got) {
+		t.Error("Expected a cleared cache to miss")
+	}
+}
@@ -0,0 +1,295 @@
+package config
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"unicode/utf8"
+)
+
+// ProjectConfigName is the name of the project config file, found by walking up from the
+// ingested path.
+const ProjectConfigName = ".ingest.toml"
+
+// Setting is a key and value from a project config file. Values holds the value as it would be
+// given on the command line, with one string for each element of an array.
+type Setting struct {
+	Key    string
+	Values []string
+	Array  bool
+	Line   int
+}
+
+// FindProjectConfig returns the path of the nearest project config file in dir or one of its
+// parents, the way git finds its repository, or "" if there is none.
+func FindProjectConfig(dir string) (string, error) {
+	dir, err := filepath.Abs(dir)
+	if err != nil {
+		return "", err
+	}
+	for {
+		path := filepath.Join(dir, ProjectConfigName)
+		if info, err := os.Stat(path); err == nil && !info.IsDir() {
+			return path, nil
+		}
+		parent := filepath.Dir(dir)
+		if parent == dir {
+			return "", nil
+		}
+		dir = parent
+	}
+}
+
+// LoadProjectConfig reads the settings of the project config file at path.
+func LoadProjectConfig(path string) ([]Setting, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("failed to read config file: %w", err)
+	}
+	settings, err := ParseProjectConfig(string(data))
+	if err != nil {
+		return nil, fmt.Errorf("failed to parse %s: %w", path, err)
+	}
+	return settings, nil
+}
+
+// ParseProjectConfig parses the TOML of a project config file: top-level keys set to strings,
+// numbers, booleans or arrays of them, in the order they are given. Tables, dates and
+// multi-line strings aren't needed to set flags, so aren't supported.
+func ParseProjectConfig(data string) ([]Setting, error) {
+	p := &tomlParser{data: data, line: 1}
+	seen := make(map[string]bool)
+	var settings []Setting
+	for {
+		p.skipSpace(true)
+		if p.done() {
+			return settings, nil
+		}
+		if p.peek() == '[' {
+			return nil, p.errorf("tables aren't supported, set flags at the top level")
+		}
+
+		line := p.line
+		key, err := p.key()
+		if err != nil {
+			return nil, err
+		}
+		if seen[key] {
+			return nil, p.errorf("%s is set more than once", key)
+		}
+		seen[key] = true
+
+		p.skipSpace(false)
+		if p.done() || p.peek() != '=' {
+			return nil, p.errorf("expected = after %s", key)
+		}
+		p.pos++
+		p.skipSpace(false)
+
+		setting := Setting{Key: key, Line: line}
+		if !p.done() && p.peek() == '[' {
+			setting.Array = true
+			if setting.Values, err = p.array(); err != nil {
+				return nil, err
+			}
+		} else {
+			value, err := p.value()
+			if err != nil {
+				return nil, err
+			}
+			setting.Values = []string{value}
+		}
+		if err := p.endOfLine(); err != nil {
+			return nil, err
+		}
+		settings = append(settings, setting)
+	}
+}
+
+// tomlParser reads the subset of TOML that ParseProjectConfig supports.
+type tomlParser struct {
+	data string
+	pos  int
+	line int
+}
+
+func (p *tomlParser) done() bool { return p.pos >= len(p.data) }
+func (p *tomlParser) peek() byte { return p.data[p.pos] }
+
+func (p *tomlParser) errorf(format string, args ...any) error {
+	return fmt.Errorf("line %d: %s", p.line, fmt.Sprintf(format, args...))
+}
+
+// skipSpace skips spaces and tabs, and with newlines also line breaks and comments.
+func (p *tomlParser) skipSpace(newlines bool) {
+	for !p.done() {
+		switch c := p.peek(); {
+		case c == ' ' || c == '\t' || c == '\r':
+			p.pos++
+		case newlines && c == '\n':
+			p.pos++
+			p.line++
+		case newlines && c == '#':
+			for !p.done() && p.peek() != '\n' {
+				p.pos++
+			}
+		default:
+			return
+		}
+	}
+}
+
+// endOfLine checks that nothing but a comment follows a value on its line.
+func (p *tomlParser) endOfLine() error {
+	p.skipSpace(false)
+	if !p.done() && p.peek() == '#' {
+		for !p.done() && p.peek() != '\n' {
+			p.pos++
+		}
+	}
+	if !p.done() && p.peek() != '\n' {
+		return p.errorf("unexpected %q after value", p.peek())
+	}
+	return nil
+}
+
+func (p *tomlParser) key() (string, error) {
+	if c := p.peek(); c == '"' || c == '\'' {
+		return p.quoted()
+	}
+	start := p.pos
+	for !p.done() && isBareKeyChar(p.peek()) {
+		p.pos++
+	}
+	if p.pos == start {
+		return "", p.errorf("expected a key, got %q", p.peek())
+	}
+	if !p.done() && p.peek() == '.' {
+		return "", p.errorf("dotted keys aren't supported")
+	}
+	return p.data[start:p.pos], nil
+}
+
+func isBareKeyChar(c byte) bool {
+	return c >= 'a' && c <= 'z' || c >= 'A' && c <= 'Z' || c >= '0' && c <= '9' || c == '_' || c == '-'
+}
+
+// array reads an array of values, which may span lines.
+func (p *tomlParser) array() ([]string, error) {
+	p.pos++ // [
+	values := []string{}
+	for {
+		p.skipSpace(true)
+		if p.done() {
+			return nil, p.errorf("unterminated array")
+		}
+		if p.peek() == ']' {
+			p.pos++
+			return values, nil
+		}
+		if p.peek() == '[' {
+			return nil, p.errorf("nested arrays aren't supported")
+		}
+
+		value, err := p.value()
+		if err != nil {
+			return nil, err
+		}
+		values = append(values, value)
+
+		p.skipSpace(true)
+		if p.done() {
+			return nil, p.errorf("unterminated array")
+		}
+		switch p.peek() {
+		case ',':
+			p.pos++
+		case ']':
+		default:
+			return nil, p.errorf("expected , or ] in array, got %q", p.peek())
+		}
+	}
+}
+
+// value reads a string, number or boolean, returning it as a flag value.
+func (p *tomlParser) value() (string, error) {
+	if c := p.peek(); c == '"' || c == '\'' {
+		return p.quoted()
+	}
+
+	start := p.pos
+	for !p.done() && !strings.ContainsRune(" \t\r\n,]#", rune(p.peek())) {
+		p.pos++
+	}
+	raw := p.data[start:p.pos]
+	switch {
+	case raw == "":
+		return "", p.errorf("expected a value")
+	case raw == "true" || raw == "false":
+		return raw, nil
+	}
+
+	// Underscores may separate the digits of numbers
+	number := strings.ReplaceAll(raw, "_", "")
+	if _, err := strconv.ParseFloat(number, 64); err != nil {
+		return "", p.errorf("invalid value %s, strings must be quoted", raw)
+	}
+	return strings.TrimPrefix(number, "+"), nil
+}
+
+// quoted reads a basic string in double quotes, with escapes, or a literal one in single
+// quotes, without them.
+func (p *tomlParser) quoted() (string, error) {
+	quote := p.peek()
+	if strings.HasPrefix(p.data[p.pos:], strings.Repeat(string(quote), 3)) {
+		return "", p.errorf("multi-line strings aren't supported")
+	}
+	p.pos++
+
+	var b strings.Builder
+	for {
+		if p.done() || p.peek() == '\n' {
+			return "", p.errorf("unterminated string")
+		}
+		c := p.peek()
+		p.pos++
+		switch {
+		case c == quote:
+			return b.String(), nil
+		case c == '\\' && quote == '"':
+			if err := p.escape(&b); err != nil {
+				return "", err
+			}
+		default:
+			b.WriteByte(c)
+		}
+	}
+}
+
+var escapes = map[byte]string{'b': "\b", 't': "\t", 'n': "\n", 'f': "\f", 'r': "\r", '"': "\"", '\\': "\\"}
+
+func (p *tomlParser) escape(b *strings.Builder) error {
+	if p.done() {
+		return p.errorf("unterminated string")
+	}
+	c := p.peek()
+	p.pos++
+	if s, ok := escapes[c]; ok {
+		b.WriteString(s)
+		return nil
+	}
+
+	digits := map[byte]int{'u': 4, 'U': 8}[c]
+	if digits == 0 || p.pos+digits > len(p.data) {
+		return p.errorf("invalid escape \\%c", c)
+	}
+	code, err := strconv.ParseUint(p.data[p.pos:p.pos+digits], 16, 32)
+	if err != nil || !utf8.ValidRune(rune(code)) {
+		return p.errorf("invalid escape \\%c%s", c, p.data[p.pos:p.pos+digits])
+	}
+	p.pos += digits
+	b.WriteRune(rune(code))
+	return nil
+}
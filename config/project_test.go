@@ -0,0 +1,70 @@
+package config
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestParseProjectConfig(t *testing.T) {
+	data := `# Flags for this project
+exclude = [
+	"**/testdata/**", # fixtures
+	'*.snap',
+]
+compress = true
+max_tokens = 100_000
+output = "out\tdir/context.md"
+"llm-model" = 'gpt-4o'
+`
+	settings, err := ParseProjectConfig(data)
+	if err != nil {
+		t.Fatalf("ParseProjectConfig failed: %v", err)
+	}
+	expected := []Setting{
+		{Key: "exclude", Values: []string{"**/testdata/**", "*.snap"}, Array: true, Line: 2},
+		{Key: "compress", Values: []string{"true"}, Line: 6},
+		{Key: "max_tokens", Values: []string{"100000"}, Line: 7},
+		{Key: "output", Values: []string{"out\tdir/context.md"}, Line: 8},
+		{Key: "llm-model", Values: []string{"gpt-4o"}, Line: 9},
+	}
+	if !reflect.DeepEqual(settings, expected) {
+		t.Errorf("Expected %+v, got %+v", expected, settings)
+	}
+
+	invalid := []struct{ data, want string }{
+		{"[llm]\nmodel = 'x'\n", "tables"},
+		{"a.b = 1\n", "dotted keys"},
+		{"output = context.md\n", "must be quoted"},
+		{"compress = true\ncompress = false\n", "more than once"},
+		{"exclude = [[\"a\"]]\n", "nested arrays"},
+		{"output = \"context.md\n", "unterminated string"},
+		{"compress = true false\n", "after value"},
+	}
+	for _, c := range invalid {
+		if _, err := ParseProjectConfig(c.data); err == nil || !strings.Contains(err.Error(), c.want) {
+			t.Errorf("Expected an error containing %q for %q, got %v", c.want, c.data, err)
+		}
+	}
+}
+
+func TestFindProjectConfig(t *testing.T) {
+	root := t.TempDir()
+	nested := filepath.Join(root, "src", "pkg")
+	if err := os.MkdirAll(nested, 0755); err != nil {
+		t.Fatal(err)
+	}
+	if path, err := FindProjectConfig(nested); err != nil || path != "" {
+		t.Errorf("Expected no config file, got %q, %v", path, err)
+	}
+
+	want := filepath.Join(root, ProjectConfigName)
+	if err := os.WriteFile(want, []byte("compress = true\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+	if path, err := FindProjectConfig(nested); err != nil || path != want {
+		t.Errorf("Expected %q, got %q, %v", want, path, err)
+	}
+}
@@ -0,0 +1,94 @@
+package filesystem
+
+import (
+	"strings"
+	"unicode/utf8"
+)
+
+// DefaultAnnotationMarkers are the markers listed by --annotations.
+var DefaultAnnotationMarkers = []string{"TODO", "FIXME", "XXX", "HACK", "@security"}
+
+// annotationMaxLength is the length in bytes past which an annotation's line is cut short.
+const annotationMaxLength = 200
+
+// Annotation is a line of a file marked for attention, such as a TODO comment.
+type Annotation struct {
+	Path   string `json:"path"`
+	Line   int    `json:"line"`   // The line's number in the file, from 1
+	Marker string `json:"marker"` // The first of Options.AnnotationMarkers found on the line
+	Text   string `json:"text"`   // The line without surrounding whitespace
+}
+
+// Annotations returns the annotations of files, in the order the files are listed.
+func Annotations(files []FileInfo) []Annotation {
+	var annotations []Annotation
+	for _, file := range files {
+		annotations = append(annotations, file.Annotations...)
+	}
+	return annotations
+}
+
+// findAnnotations returns the lines of source holding one of markers as a whole word. source is
+// the file before it was formatted and code is what is output for it, so lines that compression,
+// a stub or a line range left out aren't listed, such as a TODO in a stripped function body.
+func findAnnotations(path, source, code string, markers []string) []Annotation {
+	if len(markers) == 0 {
+		return nil
+	}
+
+	var annotations []Annotation
+	number := 0
+	for line := range strings.SplitSeq(source, "\n") {
+		number++
+		marker := findMarker(line, markers)
+		if marker == "" {
+			continue
+		}
+		line = strings.TrimSpace(line)
+		if !strings.Contains(code, line) {
+			continue
+		}
+		annotations = append(annotations, Annotation{Path: path, Line: number, Marker: marker, Text: truncateAnnotation(line)})
+	}
+	return annotations
+}
+
+// findMarker returns the first of markers found in line without a letter, digit or underscore
+// either side of it, so TODO doesn't match TODOS or a marker within an identifier.
+func findMarker(line string, markers []string) string {
+	for _, marker := range markers {
+		for start := 0; ; {
+			i := strings.Index(line[start:], marker)
+			if i < 0 {
+				break
+			}
+			i += start
+			end := i + len(marker)
+			if !isWordByte(line, i-1) && !isWordByte(line, end) {
+				return marker
+			}
+			start = i + 1
+		}
+	}
+	return ""
+}
+
+func isWordByte(s string, i int) bool {
+	if i < 0 || i >= len(s) {
+		return false
+	}
+	c := s[i]
+	return c >= 'a' && c <= 'z' || c >= 'A' && c <= 'Z' || c >= '0' && c <= '9' || c == '_'
+}
+
+// truncateAnnotation cuts line short at annotationMaxLength, on a rune boundary.
+func truncateAnnotation(line string) string {
+	if len(line) <= annotationMaxLength {
+		return line
+	}
+	cut := annotationMaxLength
+	for cut > 0 && !utf8.RuneStart(line[cut]) {
+		cut--
+	}
+	return line[:cut] + "…"
+}
@@ -0,0 +1,43 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"reflect"
+	"testing"
+)
+
+func TestWalkDirectoryAnnotations(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n\n// TODO: handle errors\nfunc main() {\n\tvar TODOS []string // not a marker\n\t_ = TODOS // FIXME(sam) remove\n}\n")
+	writeTestFile(t, filepath.Join(root, "auth.js"), "/**\n * @security checks the token\n */\n")
+	writeTestFile(t, filepath.Join(root, "notes.txt"), "nothing to see\n")
+
+	_, files, _, err := WalkDirectory(root, Options{AnnotationMarkers: DefaultAnnotationMarkers, LineNumber: true})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	var got []Annotation
+	for _, a := range Annotations(files) {
+		got = append(got, Annotation{Path: filepath.Base(a.Path), Line: a.Line, Marker: a.Marker, Text: a.Text})
+	}
+	expected := []Annotation{
+		{Path: "auth.js", Line: 2, Marker: "@security", Text: "* @security checks the token"},
+		{Path: "main.go", Line: 3, Marker: "TODO", Text: "// TODO: handle errors"},
+		{Path: "main.go", Line: 6, Marker: "FIXME", Text: "_ = TODOS // FIXME(sam) remove"},
+	}
+	if !reflect.DeepEqual(got, expected) {
+		t.Errorf("Expected %+v, got %+v", expected, got)
+	}
+}
+
+func TestFindAnnotationsLeavesOutStrippedLines(t *testing.T) {
+	source := "// Run runs\nfunc Run() {\n\t// TODO: retry\n}\n"
+	compressed := "// Run runs\nfunc Run() { ... }\n"
+	if annotations := findAnnotations("run.go", source, compressed, DefaultAnnotationMarkers); len(annotations) != 0 {
+		t.Errorf("Expected no annotations for a line compression removed, got %+v", annotations)
+	}
+	if annotations := findAnnotations("run.go", source, source, DefaultAnnotationMarkers); len(annotations) != 1 || annotations[0].Line != 3 {
+		t.Errorf("Expected the TODO on line 3, got %+v", annotations)
+	}
+}
@@ -0,0 +1,70 @@
+package filesystem
+
+import (
+	"errors"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestLooksBinary(t *testing.T) {
+	tests := []struct {
+		name     string
+		data     []byte
+		expected bool
+	}{
+		{"empty", nil, false},
+		{"plain text", []byte("package main\n\nfunc main() {}\n"), false},
+		{"unicode text", []byte("héllo wörld, こんにちは\n"), false},
+		{"latin-1 text", []byte("caf\xe9 na\xefve r\xe9sum\xe9 and plenty of plain ascii around it\n"), false},
+		{"nul byte", []byte("text\x00more"), true},
+		{"mostly invalid utf-8", []byte("\xff\xfe\xfd\xfc\xfb\xfa\xf9ab"), true},
+		{"control characters", []byte("\x01\x02\x03\x04\x05\x06ab"), true},
+		{"terminal escapes", []byte("\x1b[31mred\x1b[0m\n"), false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := looksBinary(tt.data); got != tt.expected {
+				t.Errorf("looksBinary(%q) = %v, expected %v", tt.data, got, tt.expected)
+			}
+		})
+	}
+}
+
+func TestLooksBinaryTruncatedRune(t *testing.T) {
+	// A multi-byte rune split by the end of the sample should not count against the file
+	data := []byte("a" + strings.Repeat("é", binarySniffSize/2))
+	if looksBinary(data[:binarySniffSize]) {
+		t.Error("Expected text with a truncated trailing rune not to be binary")
+	}
+}
+
+func TestWalkDirectoryReportsBinary(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(root, "data.bin"), "\x00\x01\x02\x03")
+
+	_, files, excluded, err := WalkDirectory(root, Options{NoDefaultExcludes: true})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	if len(files) != 1 || filepath.Base(files[0].Path) != "main.go" {
+		t.Fatalf("Expected only main.go to be ingested, got %v", files)
+	}
+	if len(excluded.Skipped) != 1 || excluded.Skipped[0].Reason != "binary" {
+		t.Fatalf("Expected data.bin to be skipped as binary, got %v", excluded.Skipped)
+	}
+}
+
+func TestProcessSingleFileReportsBinary(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "blob.bin")
+	writeTestFile(t, path, "\x00\x01\x02\x03")
+
+	_, err := ProcessSingleFile(path, Options{})
+	var skipped *SkipError
+	if !errors.As(err, &skipped) || skipped.File.Reason != "binary" {
+		t.Fatalf("Expected blob.bin to be skipped as binary, got %v", err)
+	}
+}
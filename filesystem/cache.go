@@ -0,0 +1,154 @@
+package filesystem
+
+import (
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+
+	"github.com/sammcj/ingest/cache"
+	"github.com/sammcj/ingest/token"
+)
+
+// cacheVersion is part of every cache key, so bumping it invalidates entries written by older
+// versions that formatted files differently.
+const cacheVersion = "4"
+
+// cacheEntry is the processed form of a file stored in the cache.
+type cacheEntry struct {
+	Extension     string       `json:"extension"`
+	Code          string       `json:"code"`
+	Compressed    bool         `json:"compressed"`
+	Lines         int          `json:"lines"`
+	SkipReason    string       `json:"skip_reason,omitempty"`
+	TokenCount    int          `json:"token_count"`
+	TokenEncoding string       `json:"token_encoding"`
+	NoCorrection  bool         `json:"no_correction"`
+	Redactions    int          `json:"redactions"`
+	RawTokens     int          `json:"raw_tokens"`
+	Annotations   []Annotation `json:"annotations,omitempty"`
+}
+
+// cacheKey identifies a job's processed content by its file's path, modification time and size
+// along with the options that change how it is formatted. It returns false if the file can't be
+// cached.
+//
+// Some options are deliberately left out of the key. Those that choose which files are read,
+// such as MaxFileSize, OnlyPaths and the patterns, are checked before the cache is. The paths
+// shown, which depend on PathBase and RelativePaths, are set from the job rather than cached.
+// Token counts are stored with their tokeniser settings and recounted when those change. Skip
+// reasons such as binary or unsupported only depend on the content and Charset, so a skipped
+// file stays skipped until it changes; bump cacheVersion if how files are skipped changes.
+func cacheKey(job fileJob, opts Options) (string, bool) {
+	info, err := os.Stat(job.path)
+	if err != nil {
+		return "", false
+	}
+	level := ""
+	if comp := opts.compressorFor(job.relPath); comp != nil {
+		level = comp.Level().String()
+	}
+	redactions := ""
+	if opts.Redactor != nil {
+		redactions = opts.Redactor.Key()
+	}
+	minified := ""
+	if opts.Minified != nil {
+		minified = strconv.Itoa(opts.Minified.LineLength) + "," + strconv.FormatFloat(opts.Minified.NewlineRatio, 'g', -1, 64)
+	}
+	return cache.Key(
+		cacheVersion,
+		job.path,
+		strconv.FormatInt(info.ModTime().UnixNano(), 10),
+		strconv.FormatInt(info.Size(), 10),
+		level,
+		strconv.FormatBool(opts.LineNumber),
+		strconv.FormatBool(opts.NoCodeblock),
+		opts.Fence,
+		strconv.FormatBool(opts.CompressFallback),
+		opts.Charset,
+		strings.Join(opts.CollapsePatterns, "\n"),
+		strconv.FormatBool(job.generated),
+		minified,
+		strings.Join(opts.AnnotationMarkers, "\n"),
+		redactions,
+	), true
+}
+
+// processCachedFile is processFile backed by opts.Cache. Cached token counts are only reused
+// when they were counted with the same tokeniser settings, otherwise the content is recounted.
+// Files with a diff or line ranges are never cached, as they depend on more than the file, nor
+// are files read from an FS, which may not have modification times.
+func processCachedFile(job fileJob, opts Options, counter *token.Counter) (FileInfo, string, bool) {
+	process := func() (FileInfo, string, bool) {
+		file, skipReason, ok := processFile(job, opts, counter)
+		if ok && counter != nil {
+			file.TokenCount = counter.Count(file.Code)
+		}
+		return file, skipReason, ok
+	}
+
+	if opts.Cache == nil || opts.FS != nil || opts.ExecFilter != nil || opts.Diffs[filepath.ToSlash(job.relPath)] != "" || len(opts.LineRanges[job.path]) > 0 {
+		return process()
+	}
+	key, ok := cacheKey(job, opts)
+	if !ok {
+		return process()
+	}
+
+	// Recounting a compressed file's savings needs its uncompressed content, so it is processed again
+	var entry cacheEntry
+	cached := opts.Cache.Get(key, &entry)
+	stale := counter != nil && (entry.TokenEncoding != opts.TokenEncoding || entry.NoCorrection != opts.NoCorrection)
+	if cached && !(stale && entry.Compressed) {
+		if entry.SkipReason != "" {
+			return FileInfo{}, entry.SkipReason, false
+		}
+		if stale {
+			entry.TokenCount = counter.Count(entry.Code)
+			entry.TokenEncoding, entry.NoCorrection = opts.TokenEncoding, opts.NoCorrection
+			_ = opts.Cache.Put(key, entry)
+		}
+
+		file := FileInfo{
+			Path:       opts.DisplayPath(job.path, filepath.Join(filepath.Base(job.rootPath), job.relPath)),
+			Extension:  entry.Extension,
+			Code:       entry.Code,
+			Compressed: entry.Compressed,
+			Lines:      entry.Lines,
+			RelPath:    filepath.ToSlash(job.relPath),
+			Redactions: entry.Redactions,
+		}
+		// The path in annotations depends on options that aren't part of the key
+		for _, annotation := range entry.Annotations {
+			annotation.Path = file.Path
+			file.Annotations = append(file.Annotations, annotation)
+		}
+		if counter != nil {
+			file.TokenCount, file.RawTokens = entry.TokenCount, entry.RawTokens
+		}
+		return file, "", true
+	}
+
+	file, skipReason, ok := process()
+	switch {
+	case ok:
+		entry = cacheEntry{
+			Extension:   file.Extension,
+			Code:        file.Code,
+			Compressed:  file.Compressed,
+			Lines:       file.Lines,
+			Redactions:  file.Redactions,
+			Annotations: file.Annotations,
+		}
+		if counter != nil {
+			entry.TokenCount, entry.RawTokens = file.TokenCount, file.RawTokens
+			entry.TokenEncoding, entry.NoCorrection = opts.TokenEncoding, opts.NoCorrection
+		}
+		_ = opts.Cache.Put(key, entry)
+	case skipReason != "":
+		// Files skipped for what they contain, such as binaries, stay skipped until they change
+		_ = opts.Cache.Put(key, cacheEntry{SkipReason: skipReason})
+	}
+	return file, skipReason, ok
+}
@@ -0,0 +1,117 @@
+package filesystem
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/sammcj/ingest/cache"
+	"github.com/sammcj/ingest/internal/compressor"
+	"github.com/sammcj/ingest/token"
+)
+
+func TestProcessCachedFile(t *testing.T) {
+	root := t.TempDir()
+	path := filepath.Join(root, "main.go")
+	if err := os.WriteFile(path, []byte("package aaaa\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+	modTime := time.Now().Add(-time.Hour)
+	if err := os.Chtimes(path, modTime, modTime); err != nil {
+		t.Fatal(err)
+	}
+
+	c, err := cache.Open(t.TempDir())
+	if err != nil {
+		t.Fatal(err)
+	}
+	opts := Options{Cache: c}
+	job := fileJob{path: path, relPath: "main.go", rootPath: root}
+
+	if file, _, ok := processCachedFile(job, opts, nil); !ok || !strings.Contains(file.Code, "aaaa") {
+		t.Fatalf("Expected the file to be processed, got %q", file.Code)
+	}
+
+	// Rewrite the file with the same size and modification time, so only the cache can know the
+	// old content
+	if err := os.WriteFile(path, []byte("package bbbb\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+	if err := os.Chtimes(path, modTime, modTime); err != nil {
+		t.Fatal(err)
+	}
+	file, _, ok := processCachedFile(job, opts, nil)
+	if !ok || !strings.Contains(file.Code, "aaaa") {
+		t.Errorf("Expected the cached content, got %q", file.Code)
+	}
+	if file.RelPath != "main.go" || file.Path != path {
+		t.Errorf("Expected the paths to be set from the job, got %q and %q", file.Path, file.RelPath)
+	}
+
+	// Options that change formatting use a different entry
+	opts.NoCodeblock = true
+	if file, _, _ := processCachedFile(job, opts, nil); !strings.Contains(file.Code, "bbbb") {
+		t.Errorf("Expected changed options to miss the cache, got %q", file.Code)
+	}
+}
+
+func TestProcessCachedFileRecountsCompressed(t *testing.T) {
+	root := t.TempDir()
+	path := filepath.Join(root, "main.go")
+	modTime := time.Now().Add(-time.Hour)
+	writeFunc := func(name string) {
+		t.Helper()
+		if err := os.WriteFile(path, []byte("package main\n\nfunc "+name+"() {\n\tprintln(\"running\")\n}\n"), 0644); err != nil {
+			t.Fatal(err)
+		}
+		if err := os.Chtimes(path, modTime, modTime); err != nil {
+			t.Fatal(err)
+		}
+	}
+	writeFunc("aaaa")
+
+	// Two tokenisers, so that switching from one to the other makes the cached counts stale
+	encodings := make([]string, 2)
+	for i := range encodings {
+		tokenizer := filepath.Join(t.TempDir(), "tokenizer.json")
+		config := `{"pre_tokenizer": {"type": "Metaspace", "replacement": "▁", "prepend_scheme": "always", "split": true}, "model": {"type": "BPE", "vocab": {"▁": 0}, "merges": [], "byte_fallback": true}}`
+		if err := os.WriteFile(tokenizer, []byte(config), 0644); err != nil {
+			t.Fatal(err)
+		}
+		if err := token.LoadHuggingFace(tokenizer); err != nil {
+			t.Fatal(err)
+		}
+		encodings[i] = token.HuggingFaceEncoding(tokenizer)
+	}
+
+	c, err := cache.Open(t.TempDir())
+	if err != nil {
+		t.Fatal(err)
+	}
+	opts := Options{Cache: c, Compressor: compressor.NewGenericCompressor(), TokenEncoding: encodings[0]}
+	job := fileJob{path: path, relPath: "main.go", rootPath: root}
+	if file, _, ok := processCachedFile(job, opts, token.NewCounter(encodings[0], false)); !ok || !file.Compressed || file.RawTokens == 0 {
+		t.Fatalf("Expected the file to be compressed and counted, got %+v", file)
+	}
+
+	// The file keeps its size and modification time, so only processing it again finds the new
+	// content, which recounting a compressed file's savings needs
+	writeFunc("bbbb")
+	opts.TokenEncoding = encodings[1]
+	counter := token.NewCounter(encodings[1], false)
+	file, _, ok := processCachedFile(job, opts, counter)
+	if !ok || !strings.Contains(file.Code, "bbbb") {
+		t.Fatalf("Expected the compressed file to be processed again for the new tokeniser, got %q", file.Code)
+	}
+	if file.TokenCount != counter.Count(file.Code) || file.RawTokens == 0 {
+		t.Errorf("Expected the file to be recounted, got %d tokens and %d raw tokens", file.TokenCount, file.RawTokens)
+	}
+
+	// The recount is cached for the new tokeniser
+	writeFunc("cccc")
+	if file, _, _ := processCachedFile(job, opts, counter); !strings.Contains(file.Code, "bbbb") {
+		t.Errorf("Expected the recounted entry to be cached, got %q", file.Code)
+	}
+}
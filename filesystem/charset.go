@@ -0,0 +1,147 @@
+package filesystem
+
+import (
+	"bytes"
+	"fmt"
+	"strings"
+	"unicode/utf8"
+
+	"golang.org/x/text/encoding"
+	"golang.org/x/text/encoding/charmap"
+	"golang.org/x/text/encoding/htmlindex"
+	"golang.org/x/text/encoding/japanese"
+	"golang.org/x/text/encoding/unicode"
+)
+
+// CharsetAuto detects the charset of each file rather than using a fixed one.
+const CharsetAuto = "auto"
+
+// ValidateCharset checks that name is "auto" or a charset that files can be decoded from, such
+// as shift_jis or latin1.
+func ValidateCharset(name string) error {
+	if name == CharsetAuto {
+		return nil
+	}
+	if _, err := htmlindex.Get(name); err != nil {
+		return fmt.Errorf("unknown charset %q", name)
+	}
+	return nil
+}
+
+// readTextFile reads the file at path, decoding it to UTF-8 from charset unless charset is ""
+// (the default), in which case the content is used as it is. It returns a skip reason if the
+// file is binary or can't be decoded.
+func readTextFile(fsys fileSystem, path, charset string) ([]byte, string, error) {
+	if charset == "" {
+		isBinary, err := isBinaryFile(fsys, path)
+		if err != nil {
+			return nil, "", fmt.Errorf("failed to check if file is binary: %w", err)
+		}
+		if isBinary {
+			return nil, "binary", nil
+		}
+		content, err := fsys.ReadFile(path)
+		if err != nil {
+			return nil, "", err
+		}
+		return content, "", nil
+	}
+
+	raw, err := fsys.ReadFile(path)
+	if err != nil {
+		return nil, "", err
+	}
+	content, err := decodeCharset(raw, charset)
+	if err != nil {
+		return nil, err.Error(), nil
+	}
+
+	// Files in UTF-16 are full of NUL bytes, so only check for binary content once decoded
+	if looksBinary(content[:min(len(content), binarySniffSize)]) {
+		return nil, "binary", nil
+	}
+	return content, "", nil
+}
+
+// decodeCharset converts raw from charset, or the charset detected with detectCharset if it is
+// "auto", to UTF-8. A byte order mark overrides the charset. It fails if raw isn't valid in
+// the charset.
+func decodeCharset(raw []byte, charset string) ([]byte, error) {
+	name, enc := bomCharset(raw)
+	switch {
+	case enc != nil:
+	case charset == CharsetAuto:
+		name, enc = detectCharset(raw)
+	default:
+		var err error
+		if enc, err = htmlindex.Get(charset); err != nil {
+			return nil, fmt.Errorf("unknown charset %q", charset)
+		}
+		name = charset
+	}
+
+	// UTF-8 needs no decoding, only validating
+	if enc == encoding.Nop || enc == unicode.UTF8 {
+		raw = bytes.TrimPrefix(raw, []byte("\xef\xbb\xbf"))
+		if !utf8.Valid(raw) {
+			return nil, fmt.Errorf("not valid %s", name)
+		}
+		return raw, nil
+	}
+
+	decoded, err := enc.NewDecoder().Bytes(raw)
+	if err != nil || bytes.ContainsRune(decoded, utf8.RuneError) {
+		return nil, fmt.Errorf("not valid %s", name)
+	}
+	return decoded, nil
+}
+
+// bomCharset returns the charset named by the byte order mark at the start of raw, if any.
+func bomCharset(raw []byte) (string, encoding.Encoding) {
+	switch {
+	case bytes.HasPrefix(raw, []byte("\xef\xbb\xbf")):
+		return "utf-8", unicode.UTF8
+	case bytes.HasPrefix(raw, []byte("\xff\xfe")):
+		return "utf-16le", unicode.UTF16(unicode.LittleEndian, unicode.ExpectBOM)
+	case bytes.HasPrefix(raw, []byte("\xfe\xff")):
+		return "utf-16be", unicode.UTF16(unicode.BigEndian, unicode.ExpectBOM)
+	default:
+		return "", nil
+	}
+}
+
+// detectCharset guesses the charset of raw without a byte order mark. Valid UTF-8 is taken as
+// UTF-8. Otherwise Japanese charsets are tried, only accepted when they decode cleanly to text
+// with kana in it, as Latin-1 text often decodes without errors too. Anything else is taken as
+// Windows-1252, or ISO-8859-1 where that fails, which accept almost any bytes.
+func detectCharset(raw []byte) (string, encoding.Encoding) {
+	if utf8.Valid(raw) {
+		return "utf-8", unicode.UTF8
+	}
+
+	candidates := []struct {
+		name string
+		enc  encoding.Encoding
+	}{
+		{"shift_jis", japanese.ShiftJIS},
+		{"euc-jp", japanese.EUCJP},
+	}
+	for _, candidate := range candidates {
+		decoded, err := candidate.enc.NewDecoder().Bytes(raw)
+		if err == nil && !bytes.ContainsRune(decoded, utf8.RuneError) && containsKana(decoded) {
+			return candidate.name, candidate.enc
+		}
+	}
+
+	if decoded, err := charmap.Windows1252.NewDecoder().Bytes(raw); err == nil && !bytes.ContainsRune(decoded, utf8.RuneError) {
+		return "windows-1252", charmap.Windows1252
+	}
+	return "iso-8859-1", charmap.ISO8859_1
+}
+
+// containsKana reports whether text contains hiragana or katakana.
+func containsKana(text []byte) bool {
+	return strings.ContainsFunc(string(text), func(r rune) bool {
+		return r >= 0x3040 && r <= 0x30ff
+	})
+}
@@ -0,0 +1,52 @@
+package filesystem
+
+import "testing"
+
+func TestDecodeCharset(t *testing.T) {
+	tests := []struct {
+		name     string
+		raw      string
+		charset  string
+		expected string
+		wantErr  bool
+	}{
+		{"utf-8 as is", "héllo", "utf-8", "héllo", false},
+		{"utf-8 bom stripped", "\xef\xbb\xbfhello", "utf-8", "hello", false},
+		{"latin1", "caf\xe9", "latin1", "café", false},
+		{"shift_jis", "\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd", "shift_jis", "こんにちは", false},
+		{"auto shift_jis", "\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd", CharsetAuto, "こんにちは", false},
+		{"auto windows-1252", "caf\xe9", CharsetAuto, "café", false},
+		{"auto utf-8", "こんにちは", CharsetAuto, "こんにちは", false},
+		{"utf-16 bom overrides charset", "\xff\xfeh\x00i\x00", "latin1", "hi", false},
+		{"invalid utf-8", "caf\xe9", "utf-8", "", true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			decoded, err := decodeCharset([]byte(tt.raw), tt.charset)
+			if tt.wantErr {
+				if err == nil {
+					t.Fatalf("expected an error, got %q", decoded)
+				}
+				return
+			}
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if string(decoded) != tt.expected {
+				t.Errorf("got %q, want %q", decoded, tt.expected)
+			}
+		})
+	}
+}
+
+func TestValidateCharset(t *testing.T) {
+	for _, name := range []string{"auto", "shift_jis", "latin1", "utf-16le"} {
+		if err := ValidateCharset(name); err != nil {
+			t.Errorf("ValidateCharset(%q) = %v", name, err)
+		}
+	}
+	if err := ValidateCharset("klingon"); err == nil {
+		t.Error("expected an error for an unknown charset")
+	}
+}
@@ -0,0 +1,32 @@
+package filesystem
+
+import (
+	"github.com/sammcj/ingest/internal/compressor"
+	"github.com/sammcj/ingest/utils"
+)
+
+// CompressGlob overrides whether the files matching Pattern are compressed. Like --order, a
+// pattern matches any trailing part of the path.
+type CompressGlob struct {
+	Pattern  string
+	Compress bool
+}
+
+// compressorFor returns the compressor for the file at the relative path, or nil if it isn't
+// to be compressed. Files are compressed when there is a Compressor, unless CompressGlobsOnly
+// is set, and the last of the CompressGlobs to match the file overrides that.
+func (o Options) compressorFor(relPath string) *compressor.GenericCompressor {
+	if o.Compressor == nil {
+		return nil
+	}
+	compress := !o.CompressGlobsOnly
+	for _, glob := range o.CompressGlobs {
+		if utils.MatchPathSuffix(glob.Pattern, relPath) {
+			compress = glob.Compress
+		}
+	}
+	if !compress {
+		return nil
+	}
+	return o.Compressor
+}
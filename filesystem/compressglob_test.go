@@ -0,0 +1,34 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"reflect"
+	"testing"
+
+	"github.com/sammcj/ingest/internal/compressor"
+)
+
+func TestWalkDirectoryCompressGlobs(t *testing.T) {
+	root := t.TempDir()
+	for _, path := range []string{"main.go", "api/schema.go", "api/client.go"} {
+		writeTestFile(t, filepath.Join(root, path), "package main\n\nfunc run() {\n\tprintln(\"running\")\n}\n")
+	}
+
+	globs := []CompressGlob{
+		{Pattern: "api/**", Compress: false},
+		{Pattern: "client.go", Compress: true},
+	}
+	_, files, _, err := WalkDirectory(root, Options{Compressor: compressor.NewGenericCompressor(), CompressGlobs: globs, Concurrency: 1})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	got := make(map[string]bool)
+	for _, file := range files {
+		got[file.RelPath] = file.Compressed
+	}
+	expected := map[string]bool{"main.go": true, "api/schema.go": false, "api/client.go": true}
+	if !reflect.DeepEqual(got, expected) {
+		t.Errorf("Expected compressed files %v, got %v", expected, got)
+	}
+}
@@ -0,0 +1,40 @@
+package filesystem
+
+import (
+	"crypto/sha256"
+	"fmt"
+)
+
+// Dedupe replaces the code of each file identical to an earlier one in files with a note naming
+// the earlier file, so copies such as vendored licences and configs are only shown once. Files
+// are compared by a hash of their formatted code, and the first copy in the list keeps its code,
+// so the result only depends on the order of files. Copies have their token counts and
+// annotations cleared, so the note is counted in their place. It returns how many files were
+// copies.
+func Dedupe(files []FileInfo) int {
+	first := make(map[[sha256.Size]byte]int)
+	copies := 0
+	for i := range files {
+		if files[i].Code == "" {
+			continue
+		}
+		sum := sha256.Sum256([]byte(files[i].Code))
+		original, seen := first[sum]
+		if !seen {
+			first[sum] = i
+			continue
+		}
+
+		files[i].Code = duplicateStub(files[original].Path)
+		files[i].TokenCount = 0
+		files[i].RawTokens = 0
+		files[i].Annotations = nil
+		copies++
+	}
+	return copies
+}
+
+// duplicateStub is the one line that replaces the content of a file identical to the one at path.
+func duplicateStub(path string) string {
+	return fmt.Sprintf("<identical to %s>", path)
+}
@@ -0,0 +1,26 @@
+package filesystem
+
+import "testing"
+
+func TestDedupe(t *testing.T) {
+	files := []FileInfo{
+		{Path: "a/LICENSE", Code: "MIT", TokenCount: 10},
+		{Path: "main.go", Code: "package main"},
+		{Path: "b/LICENSE", Code: "MIT", TokenCount: 10},
+		{Path: "c/LICENSE", Code: "MIT", TokenCount: 10},
+		{Path: "empty.txt"},
+		{Path: "empty2.txt"},
+	}
+
+	if copies := Dedupe(files); copies != 2 {
+		t.Errorf("Expected 2 copies, got %d", copies)
+	}
+	for _, i := range []int{2, 3} {
+		if files[i].Code != "<identical to a/LICENSE>" || files[i].TokenCount != 0 {
+			t.Errorf("Expected %s to refer to a/LICENSE and be recounted, got %q with %d tokens", files[i].Path, files[i].Code, files[i].TokenCount)
+		}
+	}
+	if files[0].Code != "MIT" || files[1].Code != "package main" || files[5].Code != "" {
+		t.Errorf("Expected the first copies and empty files to be unchanged, got %+v", files)
+	}
+}
@@ -93,7 +93,6 @@ const defaultGlobContent = `
 **/*.dll
 **/*.dmg
 **/*.doc
-**/*.docx
 **/*.DS_Store
 **/*.eot
 **/*.excalidrawlib
@@ -118,6 +117,7 @@ const defaultGlobContent = `
 **/*.key
 **/*.lock
 **/*.log*
+**/*.map
 **/*.mp3
 **/*.mp4
 **/*.msi
@@ -125,7 +125,6 @@ const defaultGlobContent = `
 **/*.obj
 **/*.odf
 **/*.otf
-**/*.pdf
 **/*.partial
 **/*.pem
 **/*.png
@@ -154,7 +153,6 @@ const defaultGlobContent = `
 **/*.woff2
 **/*.xd
 **/*.xls
-**/*.xlsx
 **/*.zip
 **/terraform.tfstate.*
 **/test/*
@@ -180,6 +178,8 @@ const defaultGlobContent = `
 **/.gitlab-ci.yml
 **/.gitmodules
 **/.gitpod.yml
+**/.ignore
+**/.ingestignore
 **/.npmrc
 **/.nvmrc
 **/.pre-commit-config.yaml
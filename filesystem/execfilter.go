@@ -0,0 +1,90 @@
+package filesystem
+
+import (
+	"bytes"
+	"fmt"
+	"os"
+	"os/exec"
+	"path/filepath"
+	"runtime"
+	"strings"
+	"sync"
+)
+
+// ExecFilter pipes the content of each file through a shell command, which reads it on stdin
+// and writes the transformed content to stdout, before the file is formatted and counted. The
+// command can tell files apart from the INGEST_FILE_PATH and INGEST_FILE_REL_PATH environment
+// variables. It is safe for concurrent use.
+type ExecFilter struct {
+	Command   string
+	KeepGoing bool // Skip the files the command fails on, rather than failing the walk
+
+	mu  sync.Mutex
+	err error // The first failure, if not KeepGoing
+}
+
+// NewExecFilter returns an ExecFilter that runs command.
+func NewExecFilter(command string, keepGoing bool) *ExecFilter {
+	return &ExecFilter{Command: command, KeepGoing: keepGoing}
+}
+
+// Err returns the failure that stopped the walk, or nil if the command never failed or
+// KeepGoing is set.
+func (f *ExecFilter) Err() error {
+	if f == nil {
+		return nil
+	}
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	return f.err
+}
+
+// Run returns content after piping it through the command for the file at path, relPath
+// within the walk root. It fails if the command exits with a non-zero status.
+func (f *ExecFilter) Run(path, relPath string, content []byte) ([]byte, error) {
+	var cmd *exec.Cmd
+	if runtime.GOOS == "windows" {
+		cmd = exec.Command("cmd", "/C", f.Command)
+	} else {
+		cmd = exec.Command("sh", "-c", f.Command)
+	}
+	cmd.Env = append(os.Environ(), "INGEST_FILE_PATH="+path, "INGEST_FILE_REL_PATH="+filepath.ToSlash(relPath))
+	cmd.Stdin = bytes.NewReader(content)
+	var stdout, stderr bytes.Buffer
+	cmd.Stdout = &stdout
+	cmd.Stderr = &stderr
+
+	if err := cmd.Run(); err != nil {
+		if msg := strings.TrimSpace(stderr.String()); msg != "" {
+			return nil, fmt.Errorf("%w: %s", err, msg)
+		}
+		return nil, err
+	}
+	return stdout.Bytes(), nil
+}
+
+// filter runs the command on content, returning false along with a skip reason if it fails.
+// Without KeepGoing the first failure is kept for Err, and later files are skipped without
+// running the command.
+func (f *ExecFilter) filter(path, relPath string, content []byte) ([]byte, string, bool) {
+	if f == nil {
+		return content, "", true
+	}
+	if !f.KeepGoing && f.Err() != nil {
+		return nil, "", false
+	}
+
+	filtered, err := f.Run(path, relPath, content)
+	if err == nil {
+		return filtered, "", true
+	}
+	if f.KeepGoing {
+		return nil, "exec filter failed: " + err.Error(), false
+	}
+	f.mu.Lock()
+	if f.err == nil {
+		f.err = fmt.Errorf("--exec-filter failed for %s: %w", path, err)
+	}
+	f.mu.Unlock()
+	return nil, "", false
+}
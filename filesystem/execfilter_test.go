@@ -0,0 +1,59 @@
+package filesystem
+
+import (
+	"errors"
+	"path/filepath"
+	"runtime"
+	"strings"
+	"testing"
+)
+
+func TestWalkDirectoryExecFilter(t *testing.T) {
+	if runtime.GOOS == "windows" {
+		t.Skip("the filter commands need sh")
+	}
+
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "a.txt"), "hello\n")
+	writeTestFile(t, filepath.Join(root, "bad.txt"), "fails\n")
+
+	// Upper-cases every file except bad.txt, which the filter fails on
+	command := `if [ "$INGEST_FILE_REL_PATH" = bad.txt ]; then echo broken >&2; exit 3; fi; tr a-z A-Z`
+
+	_, files, excluded, err := WalkDirectory(root, Options{NoCodeblock: true, ExecFilter: NewExecFilter(command, true)})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	if len(files) != 1 || !strings.Contains(files[0].Code, "HELLO") {
+		t.Errorf("Expected only a.txt, filtered to HELLO, got %+v", files)
+	}
+	if len(excluded.Skipped) != 1 || !strings.Contains(excluded.Skipped[0].Reason, "broken") {
+		t.Errorf("Expected bad.txt to be skipped with the filter's error, got %+v", excluded.Skipped)
+	}
+
+	_, _, _, err = WalkDirectory(root, Options{NoCodeblock: true, ExecFilter: NewExecFilter(command, false)})
+	if err == nil || !strings.Contains(err.Error(), "bad.txt") {
+		t.Errorf("Expected the walk to fail on bad.txt, got %v", err)
+	}
+}
+
+func TestProcessSingleFileExecFilterKeepGoing(t *testing.T) {
+	if runtime.GOOS == "windows" {
+		t.Skip("the filter commands need sh")
+	}
+
+	path := filepath.Join(t.TempDir(), "bad.txt")
+	writeTestFile(t, path, "fails\n")
+	command := "echo broken >&2; exit 3"
+
+	_, err := ProcessSingleFile(path, Options{ExecFilter: NewExecFilter(command, true)})
+	var skipped *SkipError
+	if !errors.As(err, &skipped) || !strings.Contains(skipped.File.Reason, "broken") {
+		t.Errorf("Expected bad.txt to be skipped with the filter's error, got %v", err)
+	}
+
+	_, err = ProcessSingleFile(path, Options{ExecFilter: NewExecFilter(command, false)})
+	if err == nil || errors.As(err, &skipped) || !strings.Contains(err.Error(), "bad.txt") {
+		t.Errorf("Expected processing to fail on bad.txt, got %v", err)
+	}
+}
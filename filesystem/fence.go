@@ -0,0 +1,28 @@
+package filesystem
+
+import "strings"
+
+// FenceStyles are the accepted values of --fence.
+var FenceStyles = []string{"backtick", "tilde"}
+
+// Fence returns the code fence to wrap content in with style, backtick or tilde (backtick if
+// empty). It is at least three characters, and one longer than the longest run of its
+// character in content, so that fences within content, such as in a README's examples, can't
+// close it early.
+func Fence(content, style string) string {
+	char := byte('`')
+	if style == "tilde" {
+		char = '~'
+	}
+
+	longest, run := 0, 0
+	for i := 0; i < len(content); i++ {
+		if content[i] == char {
+			run++
+			longest = max(longest, run)
+		} else {
+			run = 0
+		}
+	}
+	return strings.Repeat(string(char), max(3, longest+1))
+}
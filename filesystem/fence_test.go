@@ -0,0 +1,41 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestFence(t *testing.T) {
+	tests := []struct {
+		content string
+		style   string
+		want    string
+	}{
+		{"package main\n", "", "```"},
+		{"Example:\n```go\nfmt.Println()\n```\n", "", "````"},
+		{"Nested:\n`````\n", "backtick", "``````"},
+		{"Example:\n```go\n```\n", "tilde", "~~~"},
+		{"~~~~\n", "tilde", "~~~~~"},
+	}
+
+	for _, tt := range tests {
+		if got := Fence(tt.content, tt.style); got != tt.want {
+			t.Errorf("Fence(%q, %q) = %q, want %q", tt.content, tt.style, got, tt.want)
+		}
+	}
+}
+
+func TestProcessFileNestedFence(t *testing.T) {
+	root := t.TempDir()
+	path := filepath.Join(root, "README.md")
+	writeTestFile(t, path, "# Usage\n\n```shell\ningest .\n```\n")
+
+	file, err := ProcessSingleFile(path, Options{})
+	if err != nil {
+		t.Fatalf("ProcessSingleFile failed: %v", err)
+	}
+	if !strings.HasPrefix(file.Code, "````markdown\n") || !strings.HasSuffix(file.Code, "\n````") {
+		t.Errorf("Expected the README to be fenced with four backticks, got:\n%s", file.Code)
+	}
+}
@@ -2,29 +2,45 @@ package filesystem
 
 import (
 	"bufio"
+	"bytes"
+	"errors"
 	"fmt"
 	"io"
 	"io/fs"
-	"net/http"
 	"os"
 	"path/filepath"
-	"sort"
+	"runtime"
+	"slices"
 	"strings"
 	"sync"
+	"sync/atomic"
+	"time"
+	"unicode/utf8"
 
 	"github.com/bmatcuk/doublestar/v4"
 	"github.com/fatih/color"
 	"github.com/mitchellh/go-homedir"
-	ignore "github.com/sabhiram/go-gitignore"
+	"github.com/sammcj/ingest/cache"
 	"github.com/sammcj/ingest/internal/compressor"
+	"github.com/sammcj/ingest/office"
 	"github.com/sammcj/ingest/pdf"
+	"github.com/sammcj/ingest/redact"
+	"github.com/sammcj/ingest/token"
 	"github.com/sammcj/ingest/utils"
 )
 
 type FileInfo struct {
-	Path      string `json:"path"`
-	Extension string `json:"extension"`
-	Code      string `json:"code"`
+	Path        string        `json:"path"`
+	Extension   string        `json:"extension"`
+	Code        string        `json:"code"`
+	TokenCount  int           `json:"token_count"`           // Tokens in Code, only set when per-file counts are requested
+	Compressed  bool          `json:"compressed"`            // Code was compressed with Tree-sitter, or had its comments stripped by the fallback
+	RawTokens   int           `json:"raw_tokens"`            // Tokens in the file before compression, only set for compressed files counted offline
+	Lines       int           `json:"lines"`                 // Lines in the file before any formatting or compression
+	RelPath     string        `json:"rel_path"`              // Slash separated path relative to the ingested directory
+	Redactions  int           `json:"redactions"`            // Secrets replaced by Options.Redactor
+	Metadata    *FileMetadata `json:"metadata,omitempty"`    // Size, modification time and mode, only set with Options.FileMetadata
+	Annotations []Annotation  `json:"annotations,omitempty"` // Lines with one of Options.AnnotationMarkers
 }
 
 // New type to track excluded files and directories
@@ -33,6 +49,30 @@ type ExcludedInfo struct {
 	Extensions  map[string]int // File extension -> count of excluded files
 	TotalFiles  int            // Total number of excluded files
 	Files       []string       // List of excluded files (if total ≤ 20)
+	Skipped     []SkippedFile  // Files that matched the patterns but could not be ingested
+	TooLarge    []SkippedFile  // Files that matched the patterns but exceeded the maximum file size
+	External    []string       // Symlinks followed to content outside the root, as "link -> target"
+	NotModified int            // Files that matched the patterns but weren't modified since Options.Since
+}
+
+// SkippedFile is a file that was selected for ingestion but skipped, such as an image-only PDF
+// or a corrupt office document.
+type SkippedFile struct {
+	Path   string
+	Reason string
+	Size   int64
+}
+
+// SkipError is returned by ProcessSingleFile for a file that was skipped rather than failing.
+// Excluded lists the file, to be reported along with the files skipped by walks, unless it
+// was left out by OnlyPaths, which walks don't report either.
+type SkipError struct {
+	File     SkippedFile
+	Excluded *ExcludedInfo
+}
+
+func (e *SkipError) Error() string {
+	return fmt.Sprintf("skipped %s: %s", e.File.Path, e.File.Reason)
 }
 
 type treeNode struct {
@@ -40,6 +80,7 @@ type treeNode struct {
 	children []*treeNode
 	isDir    bool
 	excluded bool
+	rank     int // Position of the earliest --order glob matching a file in this node
 }
 
 func ReadExcludePatterns(patternExclude string, noDefaultExcludes bool) ([]string, error) {
@@ -150,10 +191,167 @@ func trackExcludedDirectory(excluded *ExcludedInfo, path string, mu *sync.Mutex)
 	excluded.Directories[path] = 0 // Initialize directory count
 }
 
-func WalkDirectory(rootPath string, includePatterns, excludePatterns []string, patternExclude string, includePriority, lineNumber, relativePaths, excludeFromTree, noCodeblock, noDefaultExcludes, followSymlinks bool, comp *compressor.GenericCompressor) (string, []FileInfo, *ExcludedInfo, error) {
-	var files []FileInfo
+// Options controls how WalkDirectory and ProcessSingleFile select and format files.
+type Options struct {
+	IncludePatterns   []string
+	ExcludePatterns   []string
+	PatternExclude    string // Path to a specific .glob file of exclude patterns
+	IncludePriority   bool
+	LineRanges        map[string][]LineRange // Only these lines of the files at these absolute paths are ingested
+	LineNumber        bool
+	RelativePaths     bool
+	PathBase          string // If set, paths are shown relative to this absolute directory
+	ExcludeFromTree   bool
+	NoCodeblock       bool
+	Fence             string // Code fence style, "backtick" (the default) or "tilde"
+	NoDefaultExcludes bool
+	NoGlobalIgnore    bool          // Don't apply the user's global git excludes file
+	NoGitAttributes   bool          // Don't skip export-ignore or collapse linguist-generated paths from .gitattributes files
+	Charset           string        // Charset files are decoded from, "auto" to detect it, or "" to read them as they are
+	Symlinks          SymlinkPolicy // How symlinks are handled, defaults to skipping them
+	Compressor        *compressor.GenericCompressor
+	CompressGlobs     []CompressGlob      // Override whether matching files are compressed with Compressor, the last match winning
+	CompressGlobsOnly bool                // Only compress the files a CompressGlob says to, rather than every file
+	Concurrency       int                 // Maximum number of files processed at once, defaults to GOMAXPROCS
+	TokenEncoding     string              // If set, count the tokens of each file with this encoding
+	NoCorrection      bool                // Disable the offline tokeniser correction factor
+	MaxFileSize       int64               // Files larger than this many bytes are skipped, 0 for no limit
+	OnlyPaths         map[string]bool     // If set, only these paths (slash separated, relative to the root) are processed
+	Diffs             map[string]string   // Unified diffs shown above file contents, by slash separated path relative to the root
+	Order             []string            // Globs whose matching files are listed first, in the order given
+	ReadmeFirst       bool                // List each directory's READMEs and other docs before its other files, unless Order is set
+	TreeStats         bool                // Annotate the tree with line counts, languages and directory token subtotals
+	Cache             *cache.Cache        // If set, reuse processed files that haven't changed since the last run
+	CollapsePatterns  []string            // Files matching these globs are shown with a one-line stub instead of their content
+	Minified          *MinifiedThresholds // If set, files that look minified are shown with a one-line stub instead of their content
+	Since             time.Time           // If set, only files modified after this time are processed
+	ListOnly          bool                // Only find and filter the files, returning their paths without reading them
+	CompressFallback  bool                // Strip comment lines from files the compressor has no grammar for
+	Redactor          *redact.Redactor    // If set, secrets are replaced with a placeholder before files are formatted
+	ExecFilter        *ExecFilter         // If set, each file's content is piped through a command before it is redacted
+	FileMetadata      bool                // Set each file's Metadata
+	NoModTime         bool                // Leave the modification time out of Metadata, so the output is reproducible
+	AnnotationMarkers []string            // If set, lines with these markers, such as TODO, are listed in each file's Annotations
+	ExcludeTests      bool                // Leave out test files found by the walk, see TestPatterns
+	OnlyTests         bool                // Leave out the files found by the walk that aren't tests
+	Manifest          *Manifest           // If set, whether each path found is included, or why not, is recorded in it
+
+	// FS, if set, is read instead of the OS, with paths relative to its root such as "." or
+	// "cmd/main.go". Symlinks in it are always skipped, PDFs and Office documents are treated
+	// as binary files, and git's exclude files and the cache aren't used.
+	FS fs.FS
+
+	// Progress, if set, is called with the number of files found so far during the walk, with
+	// a total of 0, then with the number read out of the total. It may be called concurrently.
+	Progress func(done, total int)
+}
+
+func (o Options) workers() int {
+	if o.Concurrency > 0 {
+		return o.Concurrency
+	}
+	return runtime.GOMAXPROCS(0)
+}
+
+// redact returns content with its secrets replaced if there is a Redactor, and how many were.
+func (o Options) redact(content string) (string, int) {
+	if o.Redactor == nil {
+		return content, 0
+	}
+	return o.Redactor.Redact(content)
+}
+
+// progress reports progress to the Progress callback, if there is one.
+func (o Options) progress(done, total int) {
+	if o.Progress != nil {
+		o.Progress(done, total)
+	}
+}
+
+// Prefetcher is an Options.FS that reads files fastest together, such as a compressed tar
+// archive, which can only be read from its start. WalkDirectory calls Prefetch with the paths
+// of the files it is about to read.
+type Prefetcher interface {
+	Prefetch(paths []string) error
+}
+
+// fileJob is a file found by the walk that is waiting to be processed.
+type fileJob struct {
+	path      string
+	relPath   string
+	rootPath  string
+	generated bool // Marked linguist-generated in a .gitattributes file, so collapsed
+}
+
+// displayPath returns the path the job's file is shown with.
+func (j fileJob) displayPath(opts Options) string {
+	return opts.DisplayPath(j.path, filepath.Join(filepath.Base(j.rootPath), j.relPath))
+}
+
+// walkResult is a walked root whose files are waiting to be read.
+type walkResult struct {
+	isDir           bool
+	tree            string     // The tree of a root that is a single file
+	files           []FileInfo // The converted content of a root that is a PDF, in which case there are no jobs
+	jobs            []fileJob
+	dirs            []string // The directories the walk descended into, for watching
+	excluded        *ExcludedInfo
+	excludePatterns []string
+	collapsed       map[string]bool
+}
+
+func WalkDirectory(rootPath string, opts Options) (string, []FileInfo, *ExcludedInfo, error) {
+	walk, err := walkRoot(rootPath, opts)
+	if err != nil {
+		return "", nil, walk.excluded, err
+	}
+	if walk.files != nil {
+		return walk.tree, walk.files, walk.excluded, nil
+	}
+	if prefetcher, ok := opts.FS.(Prefetcher); ok && !opts.ListOnly {
+		paths := make([]string, len(walk.jobs))
+		for i, job := range walk.jobs {
+			paths[i] = job.path
+		}
+		if err := prefetcher.Prefetch(paths); err != nil {
+			return "", nil, walk.excluded, err
+		}
+	}
+
+	files, relPaths, skipped := processFiles(walk.jobs, opts)
+	walk.excluded.Skipped = append(walk.excluded.Skipped, skipped...)
+	if err := opts.ExecFilter.Err(); err != nil {
+		return "", nil, walk.excluded, err
+	}
+
+	if !walk.isDir {
+		return walk.tree, files, walk.excluded, nil
+	}
+
+	// The tree is generated last so it can show the stats of the processed files
+	var stats map[string]fileStats
+	if opts.TreeStats {
+		stats = make(map[string]fileStats, len(files))
+		for i, file := range files {
+			stats[filepath.ToSlash(relPaths[i])] = fileStats{lines: file.Lines, language: languageName(relPaths[i]), tokens: file.TokenCount}
+		}
+	}
+
+	tree, err := generateTreeString(opts.fsys(), rootPath, walk.excludePatterns, walk.collapsed, opts.symlinks(), opts.Order, stats)
+	if err != nil {
+		return "", nil, nil, fmt.Errorf("failed to generate directory tree: %w", err)
+	}
+	return tree, files, walk.excluded, nil
+}
+
+// walkRoot finds the files to process below rootPath, or rootPath itself if it is a file, in
+// the order they are listed.
+func walkRoot(rootPath string, opts Options) (walkResult, error) {
+	var jobs []fileJob
+	var dirs []string
 	var mu sync.Mutex
-	var wg sync.WaitGroup
+
+	fsys := opts.fsys()
 
 	excluded := &ExcludedInfo{
 		Directories: make(map[string]int),
@@ -162,48 +360,59 @@ func WalkDirectory(rootPath string, includePatterns, excludePatterns []string, p
 	}
 
 	// Read exclude patterns
-	defaultExcludes, err := ReadExcludePatterns(patternExclude, noDefaultExcludes)
+	defaultExcludes, err := ReadExcludePatterns(opts.PatternExclude, opts.NoDefaultExcludes)
 	if err != nil {
-		return "", nil, nil, fmt.Errorf("failed to read exclude patterns: %w", err)
+		return walkResult{}, fmt.Errorf("failed to read exclude patterns: %w", err)
+	}
+
+	// The default excludes leave out test directories, which are what OnlyTests is after
+	if opts.OnlyTests {
+		defaultExcludes = slices.DeleteFunc(defaultExcludes, IsTestFile)
 	}
 
 	// Combine user-provided exclude patterns with default excludes (if not disabled)
-	allExcludePatterns := append(excludePatterns, defaultExcludes...)
+	allExcludePatterns := append(opts.ExcludePatterns, defaultExcludes...)
 
 	// Always exclude .git directories
 	allExcludePatterns = append(allExcludePatterns, "**/.git/**")
 
-	// Read .gitignore if it exists
-	gitignore, err := readGitignore(rootPath)
-	if err != nil {
-		return "", nil, nil, fmt.Errorf("failed to read .gitignore: %w", err)
+	// Collapsed files are kept even though the default excludes usually drop lockfiles, so they
+	// still appear in the tree. Explicit --exclude patterns still apply to them.
+	collapseExcludes := append(slices.Clip(opts.ExcludePatterns), "**/.git/**")
+	collapsed := make(map[string]bool)
+	excludesFor := func(relPath string, isDir bool) []string {
+		if !isDir && isCollapsed(relPath, opts.CollapsePatterns) {
+			return collapseExcludes
+		}
+		return allExcludePatterns
+	}
+	record := func(path, relPath, status, reason string, isDir bool) {
+		displayPath := opts.DisplayPath(path, filepath.Join(filepath.Base(rootPath), relPath))
+		opts.Manifest.Add(ManifestEntry{Path: displayPath, Status: status, Reason: reason, Directory: isDir})
 	}
 
 	// Check if rootPath is a file or directory
-	fileInfo, err := os.Stat(rootPath)
+	fileInfo, err := fsys.Stat(rootPath)
 	if err != nil {
-		return "", nil, nil, fmt.Errorf("failed to get file info: %w", err)
+		return walkResult{}, fmt.Errorf("failed to get file info: %w", err)
 	}
 
-	// Check if rootPath is a single PDF file
-	if !fileInfo.IsDir() {
-		isPDF, err := pdf.IsPDF(rootPath)
+	// Read the .gitignore, .ignore and .ingestignore files of the directory tree, along with
+	// the repository's exclude files
+	var gitignore *ignoreMatcher
+	var attributes *attributeMatcher
+	if fileInfo.IsDir() {
+		gitignore, err = newIgnoreMatcher(fsys, rootPath)
 		if err != nil {
-			return "", nil, nil, fmt.Errorf("failed to check if file is PDF: %w", err)
+			return walkResult{}, fmt.Errorf("failed to read ignore files: %w", err)
 		}
-
-		if isPDF {
-			// Process single PDF file directly
-			content, err := pdf.ConvertPDFToMarkdown(rootPath, false)
-			if err != nil {
-				return "", nil, nil, fmt.Errorf("failed to convert PDF: %w", err)
+		if opts.FS == nil {
+			if err := gitignore.addRepoExcludes(!opts.NoGlobalIgnore); err != nil {
+				return walkResult{}, fmt.Errorf("failed to read git exclude files: %w", err)
 			}
-
-			return fmt.Sprintf("File: %s", rootPath), []FileInfo{{
-				Path:      rootPath,
-				Extension: ".md",
-				Code:      content,
-			}}, excluded, nil
+		}
+		if !opts.NoGitAttributes {
+			attributes = newAttributeMatcher(fsys, rootPath)
 		}
 	}
 
@@ -211,36 +420,55 @@ func WalkDirectory(rootPath string, includePatterns, excludePatterns []string, p
 
 	if !fileInfo.IsDir() {
 		// Check if the single file is a symlink
-		if !followSymlinks {
-			linkInfo, err := os.Lstat(rootPath)
+		if !opts.symlinks().follows() {
+			linkInfo, err := fsys.Lstat(rootPath)
 			if err != nil {
-				return "", nil, nil, fmt.Errorf("failed to get symlink info: %w", err)
+				return walkResult{}, fmt.Errorf("failed to get symlink info: %w", err)
 			}
 			if linkInfo.Mode()&os.ModeSymlink != 0 {
 				utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Skipping symlinked file: %s", rootPath), color.FgCyan)
-				return fmt.Sprintf("File: %s (symlink, skipped)", rootPath), []FileInfo{}, excluded, nil
+				return walkResult{tree: fmt.Sprintf("File: %s (symlink, skipped)", rootPath), files: []FileInfo{}, excluded: excluded}, nil
 			}
 		}
 
 		// Handle single file
 		relPath := filepath.Base(rootPath)
-		if shouldIncludeFile(relPath, includePatterns, allExcludePatterns, gitignore, includePriority) {
-			wg.Go(func() {
-				processFile(rootPath, relPath, filepath.Dir(rootPath), lineNumber, relativePaths, noCodeblock, &mu, &files, comp)
-			})
-		} else {
+		job := fileJob{path: rootPath, relPath: relPath, rootPath: filepath.Dir(rootPath)}
+		switch {
+		case !shouldIncludeFile(relPath, opts.IncludePatterns, excludesFor(relPath, false), gitignore, opts.IncludePriority):
 			trackExcludedFile(excluded, rootPath, &mu)
+			status := StatusNotIncluded
+			if shouldExcludePath(relPath, false, excludesFor(relPath, false), gitignore) {
+				status = exclusionStatus(relPath, excludesFor(relPath, false))
+			}
+			opts.Manifest.Add(ManifestEntry{Path: job.displayPath(opts), Status: status})
+		case opts.MaxFileSize > 0 && fileInfo.Size() > opts.MaxFileSize:
+			excluded.TooLarge = append(excluded.TooLarge, SkippedFile{Path: rootPath, Reason: "too large", Size: fileInfo.Size()})
+			opts.Manifest.Add(ManifestEntry{Path: job.displayPath(opts), Status: StatusTooLarge})
+		default:
+			jobs = append(jobs, job)
 		}
 		treeString = fmt.Sprintf("File: %s", rootPath)
 	} else {
-		// Generate the tree representation for directory
-		treeString, err = generateTreeString(rootPath, allExcludePatterns)
-		if err != nil {
-			return "", nil, nil, fmt.Errorf("failed to generate directory tree: %w", err)
+		// Symlinks that aren't followed are listed as skipped, unless they'd be excluded anyway
+		skipSymlink := func(path, reason string) {
+			relPath, err := filepath.Rel(rootPath, path)
+			if err != nil {
+				return
+			}
+			if shouldExcludePath(relPath, false, excludesFor(relPath, false), gitignore) {
+				record(path, relPath, exclusionStatus(relPath, excludesFor(relPath, false)), "", false)
+				return
+			}
+			excluded.Skipped = append(excluded.Skipped, SkippedFile{Path: path, Reason: reason})
+			record(path, relPath, StatusSymlink, reason, false)
+		}
+		externalSymlink := func(path, target string) {
+			excluded.External = append(excluded.External, fmt.Sprintf("%s -> %s", path, target))
 		}
 
-		// Process files in directory
-		err = filepath.Walk(rootPath, func(path string, info os.FileInfo, err error) error {
+		// Find the files in the directory, they are processed once the walk is complete
+		err = walkTree(fsys, rootPath, opts.symlinks(), skipSymlink, externalSymlink, func(path string, info os.FileInfo, err error) error {
 			if err != nil {
 				return err
 			}
@@ -250,24 +478,15 @@ func WalkDirectory(rootPath string, includePatterns, excludePatterns []string, p
 				return err
 			}
 
-			// Check if the path is a symlink
-			if !followSymlinks {
-				linkInfo, err := os.Lstat(path)
-				if err != nil {
-					return err
-				}
-				if linkInfo.Mode()&os.ModeSymlink != 0 {
-					if linkInfo.IsDir() || (info != nil && info.IsDir()) {
-						utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Skipping symlinked directory: %s", path), color.FgCyan)
-						return filepath.SkipDir
-					}
-					utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Skipping symlinked file: %s", path), color.FgCyan)
-					return nil
-				}
-			}
-
 			// Check if the current path (file or directory) should be excluded
-			if shouldExcludePath(relPath, allExcludePatterns, gitignore) {
+			excludePatterns := excludesFor(relPath, info.IsDir())
+			exportIgnore, generated := attributes.attributes(relPath, info.IsDir())
+			if exportIgnore || shouldExcludePath(relPath, info.IsDir(), excludePatterns, gitignore) {
+				status := StatusExportIgnore
+				if !exportIgnore {
+					status = exclusionStatus(relPath, excludePatterns)
+				}
+				record(path, relPath, status, "", info.IsDir())
 				if info.IsDir() {
 					trackExcludedDirectory(excluded, path, &mu)
 					return filepath.SkipDir
@@ -275,49 +494,161 @@ func WalkDirectory(rootPath string, includePatterns, excludePatterns []string, p
 				trackExcludedFile(excluded, path, &mu)
 				return nil
 			}
+			if info.IsDir() {
+				dirs = append(dirs, path)
+			}
 
-			if !info.IsDir() && !shouldIncludeFile(relPath, includePatterns, allExcludePatterns, gitignore, includePriority) {
+			if !info.IsDir() && !shouldIncludeFile(relPath, opts.IncludePatterns, excludePatterns, gitignore, opts.IncludePriority) {
+				trackExcludedFile(excluded, path, &mu)
+				record(path, relPath, StatusNotIncluded, "", false)
+				return nil
+			}
+			if !info.IsDir() && opts.dropsTestFile(rootPath, relPath) {
 				trackExcludedFile(excluded, path, &mu)
+				record(path, relPath, StatusTests, "", false)
+				return nil
+			}
+
+			if !info.IsDir() && opts.OnlyPaths != nil && !opts.OnlyPaths[filepath.ToSlash(relPath)] {
+				record(path, relPath, StatusNotSelected, "", false)
+				return nil
+			}
+
+			if !info.IsDir() && !opts.Since.IsZero() && !info.ModTime().After(opts.Since) {
+				excluded.NotModified++
+				record(path, relPath, StatusNotModified, "", false)
+				return nil
+			}
+
+			// Check the size before reading so huge files are never loaded
+			if !info.IsDir() && opts.MaxFileSize > 0 && info.Size() > opts.MaxFileSize {
+				excluded.TooLarge = append(excluded.TooLarge, SkippedFile{Path: path, Reason: "too large", Size: info.Size()})
+				record(path, relPath, StatusTooLarge, "", false)
 				return nil
 			}
 
-			if !info.IsDir() && shouldIncludeFile(relPath, includePatterns, allExcludePatterns, gitignore, includePriority) {
-				wg.Add(1)
-				go func(path, relPath string) {
-					defer wg.Done()
-					processFile(path, relPath, rootPath, lineNumber, relativePaths, noCodeblock, &mu, &files, comp)
-				}(path, relPath)
+			if !info.IsDir() {
+				jobs = append(jobs, fileJob{path: path, relPath: relPath, rootPath: rootPath, generated: generated})
+				if generated || isCollapsed(relPath, opts.CollapsePatterns) {
+					collapsed[relPath] = true
+				}
+				opts.progress(len(jobs), 0)
 			}
 
 			return nil
 		})
 	}
 
-	wg.Wait()
-
 	if err != nil {
-		return "", nil, excluded, err
+		return walkResult{excluded: excluded}, err
+	}
+
+	if len(opts.Order) > 0 {
+		sortJobs(jobs, opts.Order)
+	} else if opts.ReadmeFirst {
+		readmeFirst(jobs)
 	}
 
-	return treeString, files, excluded, nil
+	return walkResult{
+		isDir:           fileInfo.IsDir(),
+		tree:            treeString,
+		jobs:            jobs,
+		dirs:            dirs,
+		excluded:        excluded,
+		excludePatterns: allExcludePatterns,
+		collapsed:       collapsed,
+	}, nil
+}
+
+// processFiles processes the jobs with a bounded pool of workers. Results keep the order of
+// the jobs (the walk order) so the output is identical to processing them sequentially. The
+// path of each file relative to its root is returned alongside it.
+func processFiles(jobs []fileJob, opts Options) ([]FileInfo, []string, []SkippedFile) {
+	if opts.ListOnly {
+		files := make([]FileInfo, len(jobs))
+		relPaths := make([]string, len(jobs))
+		for i, job := range jobs {
+			files[i] = FileInfo{
+				Path:      opts.DisplayPath(job.path, filepath.Join(filepath.Base(job.rootPath), job.relPath)),
+				Extension: filepath.Ext(job.path),
+				RelPath:   filepath.ToSlash(job.relPath),
+			}
+			relPaths[i] = job.relPath
+		}
+		return files, relPaths, nil
+	}
+
+	results := make([]*FileInfo, len(jobs))
+	skipReasons := make([]string, len(jobs))
+	next := make(chan int)
+
+	var done atomic.Int64
+	var wg sync.WaitGroup
+	for range min(opts.workers(), len(jobs)) {
+		wg.Go(func() {
+			// Tokenisers are not safe for concurrent use, so each worker has its own
+			var counter *token.Counter
+			if opts.TokenEncoding != "" {
+				counter = token.NewCounter(opts.TokenEncoding, opts.NoCorrection)
+			}
+
+			for i := range next {
+				job := jobs[i]
+				file, skipReason, ok := processCachedFile(job, opts, counter)
+				opts.progress(int(done.Add(1)), len(jobs))
+				if !ok {
+					skipReasons[i] = skipReason
+					continue
+				}
+				file.Metadata = opts.fileMetadata(job.path)
+				results[i] = &file
+			}
+		})
+	}
+
+	for i := range jobs {
+		next <- i
+	}
+	close(next)
+	wg.Wait()
+
+	files := make([]FileInfo, 0, len(jobs))
+	relPaths := make([]string, 0, len(jobs))
+	var skipped []SkippedFile
+	for i, file := range results {
+		job := jobs[i]
+		switch {
+		case file != nil:
+			files = append(files, *file)
+			relPaths = append(relPaths, job.relPath)
+			opts.Manifest.Add(ManifestEntry{Path: file.Path, Status: StatusIncluded, Tokens: file.TokenCount})
+		case skipReasons[i] != "":
+			skipped = append(skipped, SkippedFile{Path: job.path, Reason: skipReasons[i]})
+			opts.Manifest.Add(ManifestEntry{Path: job.displayPath(opts), Status: skipStatus(skipReasons[i]), Reason: skipReasons[i]})
+		default:
+			// The error was reported when the file failed to read
+			opts.Manifest.Add(ManifestEntry{Path: job.displayPath(opts), Status: StatusUnreadable})
+		}
+	}
+	return files, relPaths, skipped
 }
 
 // New helper function to check if a path should be excluded
-func shouldExcludePath(path string, excludePatterns []string, gitignore *ignore.GitIgnore) bool {
+func shouldExcludePath(path string, isDir bool, excludePatterns []string, gitignore *ignoreMatcher) bool {
 	for _, pattern := range excludePatterns {
 		if match, _ := doublestar.Match(pattern, path); match {
 			return true
 		}
 	}
-	return gitignore != nil && gitignore.MatchesPath(path)
+	return gitignore.Matches(path, isDir)
 }
 
-func shouldIncludeFile(path string, includePatterns, excludePatterns []string, gitignore *ignore.GitIgnore, includePriority bool) bool {
+func shouldIncludeFile(path string, includePatterns, excludePatterns []string, gitignore *ignoreMatcher, includePriority bool) bool {
 	// Check if the file is explicitly included
 	included := len(includePatterns) == 0 || matchesAny(path, includePatterns)
 
 	// Check if the file is explicitly excluded
-	excluded := isExcluded(path, excludePatterns) || (gitignore != nil && gitignore.MatchesPath(path))
+	excluded := isExcluded(path, excludePatterns) || gitignore.Matches(path, false)
 
 	if included && excluded {
 		return includePriority
@@ -334,58 +665,86 @@ func matchesAny(path string, patterns []string) bool {
 	return false
 }
 
-func readGitignore(rootPath string) (*ignore.GitIgnore, error) {
-	gitignorePath := filepath.Join(rootPath, ".gitignore")
-	if _, err := os.Stat(gitignorePath); os.IsNotExist(err) {
-		return nil, nil
+// prependDiff shows a file's unified diff in a diff code block above its content.
+func prependDiff(code, diff, fence string) string {
+	if diff == "" {
+		return code
 	}
-
-	return ignore.CompileIgnoreFile(gitignorePath)
+	return wrapCodeBlock(strings.TrimSuffix(diff, "\n"), "diff", fence) + "\n\n" + code
 }
 
-func addLineNumbers(code string) string {
-	lines := strings.Split(code, "\n")
-	for i := range lines {
-		lines[i] = fmt.Sprintf("%4d | %s", i+1, lines[i])
+// compressFallback strips the comment lines of a file the compressor has no grammar for, when
+// --compress-fallback is set. It returns false if the fallback is off or doesn't know the file's
+// comment syntax.
+func compressFallback(path string, content []byte, opts Options) (string, bool) {
+	if !opts.CompressFallback {
+		return "", false
 	}
-	return strings.Join(lines, "\n")
+	return compressor.StripCommentLines(path, content)
 }
 
-func wrapCodeBlock(code, extension string) string {
-	if extension == "" {
-		return fmt.Sprintf("```\n%s\n```", code)
-	}
-	return fmt.Sprintf("```%s\n%s\n```", extension[1:], code)
+// wrapCodeBlock wraps code in a markdown code fence of the style fence, tagged with language,
+// or a bare fence if the language is unknown.
+func wrapCodeBlock(code, language, fence string) string {
+	marker := Fence(code, fence)
+	return fmt.Sprintf("%s%s\n%s\n%s", marker, language, code, marker)
 }
 
-func isBinaryFile(filePath string) (bool, error) {
-	// First check if it's a PDF
-	isPDF, err := pdf.IsPDF(filePath)
+// isBinaryFile reports whether the file at filePath looks binary. PDFs must already have been
+// handled, as they look binary too.
+func isBinaryFile(fsys fileSystem, filePath string) (bool, error) {
+	file, err := fsys.Open(filePath)
 	if err != nil {
 		return false, err
 	}
-	if isPDF {
-		return false, nil // Don't treat PDFs as binary files
-	}
+	defer file.Close()
 
-	file, err := os.Open(filePath)
-	if err != nil {
+	buffer := make([]byte, binarySniffSize)
+	n, err := io.ReadFull(file, buffer)
+	if err != nil && err != io.EOF && err != io.ErrUnexpectedEOF {
 		return false, err
 	}
-	defer file.Close()
 
-	// Read the first 512 bytes of the file
-	buffer := make([]byte, 512)
-	n, err := file.Read(buffer)
-	if err != nil && err != io.EOF {
-		return false, err
+	return looksBinary(buffer[:n]), nil
+}
+
+// binarySniffSize is how much of a file is read to decide whether it is binary.
+const binarySniffSize = 8 * 1024
+
+// BinaryThreshold is the fraction of invalid UTF-8 or non-printable bytes above which a file is
+// treated as binary. It is deliberately high so text in other encodings is still included.
+const BinaryThreshold = 0.3
+
+// looksBinary reports whether data is from a binary file: it contains a NUL byte, or more than
+// BinaryThreshold of it is invalid UTF-8 or control characters other than whitespace.
+func looksBinary(data []byte) bool {
+	if len(data) == 0 {
+		return false
+	}
+	if bytes.IndexByte(data, 0) >= 0 {
+		return true
 	}
 
-	// Use http.DetectContentType to determine the content type
-	contentType := http.DetectContentType(buffer[:n])
+	suspicious := 0
+	for i := 0; i < len(data); {
+		r, size := utf8.DecodeRune(data[i:])
+		switch {
+		case r == utf8.RuneError && size == 1:
+			// A rune cut off by the end of the sample is not evidence of binary content
+			if len(data) == binarySniffSize && len(data)-i < utf8.UTFMax && !utf8.FullRune(data[i:]) {
+				i = len(data)
+				continue
+			}
+			suspicious++
+		case r < 0x20 && r != '\n' && r != '\r' && r != '\t' && r != '\f' && r != '\b' && r != 0x1b:
+			suspicious++
+		case r == 0x7f:
+			suspicious++
+		}
+		i += size
+	}
 
-	// Allow PDFs and text files
-	return !strings.HasPrefix(contentType, "text/") && contentType != "application/pdf", nil
+	return float64(suspicious)/float64(len(data)) > BinaryThreshold
 }
 
 func PrintDefaultExcludes() {
@@ -397,112 +756,183 @@ func PrintDefaultExcludes() {
 	fmt.Println(strings.Join(excludes, "\n"))
 }
 
-func processFile(path, relPath string, rootPath string, lineNumber, relativePaths, noCodeblock bool, mu *sync.Mutex, files *[]FileInfo, comp *compressor.GenericCompressor) {
-	// Check if it's the root path being processed (explicitly provided file)
-	isExplicitFile := path == rootPath
-
-	// Check if file is a PDF
-	isPDF, err := pdf.IsPDF(path)
-	if err != nil {
-		utils.PrintColouredMessage("!", fmt.Sprintf("Failed to check if file is PDF %s: %v", path, err), color.FgRed)
-		return
+// processFile reads, converts and formats a single file. It returns false if the file was
+// skipped, along with the reason if the skip should be reported.
+func processFile(job fileJob, opts Options, counter *token.Counter) (FileInfo, string, bool) {
+	path, relPath, rootPath := job.path, job.relPath, job.rootPath
+
+	// PDFs and Office documents are converted from files on disk, so not from an FS
+	isPDF := false
+	if opts.FS == nil {
+		var err error
+		if isPDF, err = pdf.IsPDF(path); err != nil {
+			utils.PrintColouredMessage("!", fmt.Sprintf("Failed to check if file is PDF %s: %v", path, err), color.FgRed)
+			return FileInfo{}, "", false
+		}
 	}
 
 	if isPDF {
-		if !isExplicitFile {
-			// Skip PDFs during directory traversal
-			return
+		content, err := pdf.ExtractText(path)
+		if errors.Is(err, pdf.ErrNoText) {
+			return FileInfo{}, err.Error(), false
 		}
-
-		utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Converting PDF to markdown: %s", path), color.FgBlue)
-		content, err := pdf.ConvertPDFToMarkdown(path, false)
 		if err != nil {
-			utils.PrintColouredMessage("!", fmt.Sprintf("Failed to convert PDF %s: %v", path, err), color.FgRed)
-			return
+			utils.PrintColouredMessage("!", fmt.Sprintf("Failed to extract text from PDF %s: %v", path, err), color.FgRed)
+			return FileInfo{}, "", false
 		}
-
-		filePath := path
-		if relativePaths {
-			filePath = filepath.Join(filepath.Base(rootPath), relPath)
+		filtered, skipReason, ok := opts.ExecFilter.filter(path, relPath, []byte(content))
+		if !ok {
+			return FileInfo{}, skipReason, false
 		}
 
-		mu.Lock()
-		*files = append(*files, FileInfo{
-			Path:      filePath,
-			Extension: ".md",
-			Code:      content,
-		})
-		mu.Unlock()
-		return
+		code, redactions := opts.redact(string(filtered))
+		return FileInfo{
+			Path:       opts.DisplayPath(path, filepath.Join(filepath.Base(rootPath), relPath)),
+			Extension:  ".md",
+			Code:       code,
+			Lines:      countLines(string(filtered)),
+			RelPath:    filepath.ToSlash(relPath),
+			Redactions: redactions,
+		}, "", true
 	}
 
-	// Check if the file is binary
-	isBinary, err := isBinaryFile(path)
-	if err != nil {
-		utils.PrintColouredMessage("!", fmt.Sprintf("Failed to check if file is binary %s: %v", path, err), color.FgRed)
-		return
-	}
+	// Office documents are zip archives, so extract them before checking for binary files
+	if opts.FS == nil && office.IsOfficeDocument(filepath.Ext(path)) {
+		content, err := office.ExtractText(path)
+		if err != nil {
+			return FileInfo{}, err.Error(), false
+		}
+		filtered, skipReason, ok := opts.ExecFilter.filter(path, relPath, []byte(content))
+		if !ok {
+			return FileInfo{}, skipReason, false
+		}
 
-	if isBinary {
-		return // Skip binary files
+		code, redactions := opts.redact(string(filtered))
+		return FileInfo{
+			Path:       opts.DisplayPath(path, filepath.Join(filepath.Base(rootPath), relPath)),
+			Extension:  filepath.Ext(path),
+			Code:       code,
+			Lines:      countLines(string(filtered)),
+			RelPath:    filepath.ToSlash(relPath),
+			Redactions: redactions,
+		}, "", true
 	}
 
-	content, err := os.ReadFile(path)
+	// Binary files and those that aren't valid in the charset of --encoding are skipped
+	content, skipReason, err := readTextFile(opts.fsys(), path, opts.Charset)
 	if err != nil {
 		utils.PrintColouredMessage("!", fmt.Sprintf("Failed to read file %s: %v", path, err), color.FgRed)
-		return
+		return FileInfo{}, "", false
+	}
+	if skipReason != "" {
+		return FileInfo{}, skipReason, false
+	}
+	content, skipReason, ok := opts.ExecFilter.filter(path, relPath, content)
+	if !ok {
+		return FileInfo{}, skipReason, false
 	}
 
-	code := string(content)
+	// Secrets are redacted before anything formats or compresses the content
+	code, redactions := opts.redact(string(content))
+	if redactions > 0 {
+		content = []byte(code)
+	}
+	compressed := false
+	rawTokens := 0
+	lines := countLines(code)
+	source := code
+
+	// Collapsed files keep their line count, but not their content
+	if job.generated || isCollapsed(relPath, opts.CollapsePatterns) {
+		code = collapsedStub(relPath, lines)
+		goto skipFormatting
+	}
+	if isMinified(code, opts.Minified) {
+		code = minifiedStub(relPath, len(content))
+		goto skipFormatting
+	}
 
-	// Attempt compression if compressor is provided and it's not a PDF
-	if comp != nil && !isPDF {
+	// Extracts of files are left uncompressed, as they are unlikely to parse on their own
+	if ranges := opts.LineRanges[path]; len(ranges) > 0 {
+		code, lines = extractLines(code, ranges, fenceLanguage(path, code), opts)
+		goto skipFormatting
+	}
+
+	// Attempt compression if the file is to be compressed, PDFs have already returned
+	if comp := opts.compressorFor(relPath); comp != nil {
 		langID, err := compressor.IdentifyLanguage(path)
 		if err == nil { // Language identified
-			compressedCode, err := comp.Compress(content, langID)
+			compressedCode, originalTokens, err := compressContent(comp, content, langID, counter)
 			if err == nil {
 				code = compressedCode
-				// If compressed, we might not want to add line numbers or wrap in a generic code block
-				// as the compressor might handle formatting. For now, let's assume compressed output
-				// is final for this file's content.
-				// We'll skip line numbering and code block wrapping for compressed content.
+				compressed = true
+				rawTokens = originalTokens
+				// Compressed output isn't wrapped in a code block, and keeps the original line numbers
+				if opts.LineNumber {
+					code = addSourceLineNumbers(code, string(content))
+				}
 				goto skipFormatting
 			} else {
 				utils.PrintColouredMessage("⚠️", fmt.Sprintf("Compression failed for %s: %v. Using original content.", path, err), color.FgYellow)
 			}
+		} else if stripped, ok := compressFallback(path, content, opts); ok {
+			code = stripped
+			compressed = true
+			if counter != nil {
+				rawTokens = counter.Count(string(content))
+			}
 		} else {
 			// Language not identified for compression, use original content
 			utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Language not identified for compression for %s. Using original content.", path), color.FgBlue)
 		}
 	}
 
-	if lineNumber {
+	if opts.LineNumber && compressed {
+		code = addSourceLineNumbers(code, string(content))
+	} else if opts.LineNumber {
 		code = addLineNumbers(code)
 	}
-	if !noCodeblock {
-		code = wrapCodeBlock(code, filepath.Ext(path))
+	if !opts.NoCodeblock {
+		code = wrapCodeBlock(code, fenceLanguage(path, string(content)), opts.Fence)
 	}
 
 skipFormatting:
-	filePath := path
-	if relativePaths {
-		filePath = filepath.Join(filepath.Base(rootPath), relPath)
-	}
+	// Annotations are found in the content as it is output, before a diff is added above it
+	displayPath := opts.DisplayPath(path, filepath.Join(filepath.Base(rootPath), relPath))
+	annotations := findAnnotations(displayPath, source, code, opts.AnnotationMarkers)
+	code = prependDiff(code, opts.Diffs[filepath.ToSlash(relPath)], opts.Fence)
 
-	mu.Lock()
-	*files = append(*files, FileInfo{
-		Path:      filePath,
-		Extension: filepath.Ext(path),
-		Code:      code,
-	})
-	mu.Unlock()
+	return FileInfo{
+		Path:        displayPath,
+		Extension:   filepath.Ext(path),
+		Code:        code,
+		Compressed:  compressed,
+		RawTokens:   rawTokens,
+		Lines:       lines,
+		RelPath:     filepath.ToSlash(relPath),
+		Redactions:  redactions,
+		Annotations: annotations,
+	}, "", true
+}
+
+// compressContent compresses content with c, also returning its token count before compression
+// if counter is set, so the tokens compression saved can be reported.
+func compressContent(c *compressor.GenericCompressor, content []byte, langID string, counter *token.Counter) (string, int, error) {
+	if counter == nil {
+		code, err := c.Compress(content, langID)
+		return code, 0, err
+	}
+	code, stats, err := c.CompressWithStats(content, langID, counter.Count)
+	return code, stats.OriginalTokens, err
 }
 
-func generateTreeString(rootPath string, excludePatterns []string) (string, error) {
+// generateTreeString prints the tree of rootPath, marking paths matching excludePatterns as
+// excluded unless they are in keep.
+func generateTreeString(fsys fileSystem, rootPath string, excludePatterns []string, keep map[string]bool, symlinks SymlinkPolicy, order []string, stats map[string]fileStats) (string, error) {
 	root := &treeNode{name: filepath.Base(rootPath), isDir: true}
 	hasExclusions := false
 
-	err := filepath.Walk(rootPath, func(path string, info fs.FileInfo, err error) error {
+	err := walkTree(fsys, rootPath, symlinks, nil, nil, func(path string, info fs.FileInfo, err error) error {
 		if err != nil {
 			return err
 		}
@@ -518,7 +948,7 @@ func generateTreeString(rootPath string, excludePatterns []string) (string, erro
 		}
 
 		// Check if the path should be excluded
-		excluded := isExcluded(relPath, excludePatterns)
+		excluded := !keep[relPath] && isExcluded(relPath, excludePatterns)
 		if excluded {
 			hasExclusions = true
 			if info.IsDir() {
@@ -603,45 +1033,66 @@ func generateTreeString(rootPath string, excludePatterns []string) (string, erro
 		return "", err
 	}
 
+	return renderTree(root, hasExclusions, order, stats), nil
+}
+
+// renderTree prints the tree below root, sorted by --order when it is given and annotated
+// with stats when they are given.
+func renderTree(root *treeNode, hasExclusions bool, order []string, stats map[string]fileStats) string {
 	var output strings.Builder
 	if hasExclusions {
 		output.WriteString("(Files/directories marked with ❌ are excluded or not included here)\n\n")
 	}
-	output.WriteString(root.name + "/\n")
+	if len(order) > 0 {
+		rankTree(root, "", order)
+		sortTreeNodes(root.children)
+	}
+	lines := []treeLine{{text: root.name + "/", node: root}}
 	for i, child := range root.children {
-		printTree(child, "", i == len(root.children)-1, &output)
+		printTree(child, "", child.name, i == len(root.children)-1, &lines)
+	}
+
+	if stats != nil {
+		output.WriteString(annotateTree(lines, stats))
+	} else {
+		for _, line := range lines {
+			output.WriteString(line.text + "\n")
+		}
 	}
 
-	return strings.TrimSuffix(output.String(), "\n"), nil
+	return strings.TrimSuffix(output.String(), "\n")
+}
+
+// treeLine is a single printed line of the tree along with the node it represents.
+type treeLine struct {
+	text string
+	node *treeNode
+	path string // Slash separated path relative to the root
 }
 
-func printTree(node *treeNode, prefix string, isLast bool, output *strings.Builder) {
-	output.WriteString(prefix)
+func printTree(node *treeNode, prefix string, path string, isLast bool, lines *[]treeLine) {
+	var line strings.Builder
+	line.WriteString(prefix)
 	if isLast {
-		output.WriteString("└── ")
+		line.WriteString("└── ")
 		prefix += "    "
 	} else {
-		output.WriteString("├── ")
+		line.WriteString("├── ")
 		prefix += "│   "
 	}
-	output.WriteString(node.name)
+	line.WriteString(node.name)
 	if node.isDir {
-		output.WriteString("/")
+		line.WriteString("/")
 	}
 	if node.excluded {
-		output.WriteString(" ❌")
+		line.WriteString(" ❌")
 	}
-	output.WriteString("\n")
+	*lines = append(*lines, treeLine{text: line.String(), node: node, path: path})
 
-	sort.Slice(node.children, func(i, j int) bool {
-		if node.children[i].isDir != node.children[j].isDir {
-			return node.children[i].isDir
-		}
-		return node.children[i].name < node.children[j].name
-	})
+	sortTreeNodes(node.children)
 
 	for i, child := range node.children {
-		printTree(child, prefix, i == len(node.children)-1, output)
+		printTree(child, prefix, path+"/"+child.name, i == len(node.children)-1, lines)
 	}
 }
 
@@ -657,79 +1108,51 @@ func isExcluded(path string, patterns []string) bool {
 	return false
 }
 
-func ProcessSingleFile(path string, lineNumber, relativePaths, noCodeblock, followSymlinks bool, comp *compressor.GenericCompressor) (FileInfo, error) {
+// ProcessSingleFile ingests a file given on its own, through the same pipeline as the files of
+// a directory walk. A file that is skipped rather than failing, such as a binary file or an
+// image-only PDF, is returned as a *SkipError.
+func ProcessSingleFile(path string, opts Options) (FileInfo, error) {
+	fsys := opts.fsys()
+
 	// Check if the file is a symlink
-	if !followSymlinks {
-		linkInfo, err := os.Lstat(path)
+	if !opts.symlinks().follows() {
+		linkInfo, err := fsys.Lstat(path)
 		if err != nil {
 			return FileInfo{}, fmt.Errorf("failed to get symlink info: %w", err)
 		}
 		if linkInfo.Mode()&os.ModeSymlink != 0 {
 			utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Skipping symlinked file: %s", path), color.FgCyan)
-			return FileInfo{}, fmt.Errorf("file is a symlink and --follow-symlinks is not set")
+			return FileInfo{}, fmt.Errorf("file is a symlink and --symlinks is skip")
 		}
 	}
 
-	// Check if it's a PDF first
-	isPDF, err := pdf.IsPDF(path)
-	if err != nil {
-		return FileInfo{}, fmt.Errorf("failed to check if file is PDF: %w", err)
+	job := fileJob{path: path, relPath: filepath.Base(path), rootPath: filepath.Dir(path)}
+	if opts.OnlyPaths != nil && !opts.OnlyPaths[filepath.ToSlash(job.relPath)] {
+		opts.Manifest.Add(ManifestEntry{Path: job.displayPath(opts), Status: StatusNotSelected})
+		return FileInfo{}, &SkipError{File: SkippedFile{Path: path, Reason: "not selected"}, Excluded: &ExcludedInfo{}}
 	}
 
-	if isPDF {
-		content, err := pdf.ConvertPDFToMarkdown(path, false)
-		if err != nil {
-			return FileInfo{}, fmt.Errorf("failed to convert PDF: %w", err)
-		}
-
-		return FileInfo{
-			Path:      path,
-			Extension: ".md",
-			Code:      content,
-		}, nil
-	}
-
-	// Handle non-PDF files
-	content, err := os.ReadFile(path)
+	// Check the size before reading so huge files are never loaded
+	info, err := fsys.Stat(path)
 	if err != nil {
-		return FileInfo{}, fmt.Errorf("failed to read file: %w", err)
+		return FileInfo{}, fmt.Errorf("failed to get file info: %w", err)
 	}
-
-	code := string(content)
-
-	// Attempt compression if compressor is provided and it's not a PDF
-	if comp != nil && !isPDF {
-		langID, err := compressor.IdentifyLanguage(path)
-		if err == nil { // Language identified
-			compressedCode, err := comp.Compress(content, langID)
-			if err == nil {
-				code = compressedCode
-				// Skip standard formatting for compressed content
-				goto skipSingleFileFormatting
-			} else {
-				utils.PrintColouredMessage("⚠️", fmt.Sprintf("Compression failed for %s: %v. Using original content.", path, err), color.FgYellow)
-			}
-		} else {
-			utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Language not identified for compression for %s. Using original content.", path), color.FgBlue)
-		}
+	if opts.MaxFileSize > 0 && info.Size() > opts.MaxFileSize {
+		tooLarge := SkippedFile{Path: path, Reason: "too large", Size: info.Size()}
+		opts.Manifest.Add(ManifestEntry{Path: job.displayPath(opts), Status: StatusTooLarge})
+		return FileInfo{}, &SkipError{File: tooLarge, Excluded: &ExcludedInfo{TooLarge: []SkippedFile{tooLarge}}}
 	}
 
-	if lineNumber {
-		code = addLineNumbers(code)
+	files, _, skipped := processFiles([]fileJob{job}, opts)
+	if err := opts.ExecFilter.Err(); err != nil {
+		return FileInfo{}, err
 	}
-	if !noCodeblock {
-		code = wrapCodeBlock(code, filepath.Ext(path))
+	if len(skipped) > 0 {
+		return FileInfo{}, &SkipError{File: skipped[0], Excluded: &ExcludedInfo{Skipped: skipped}}
 	}
-
-skipSingleFileFormatting:
-	filePath := path
-	if relativePaths {
-		filePath = filepath.Base(path)
+	if len(files) == 0 {
+		// The error was reported when the file failed to read
+		return FileInfo{}, fmt.Errorf("failed to read file")
 	}
-
-	return FileInfo{
-		Path:      filePath,
-		Extension: filepath.Ext(path),
-		Code:      code,
-	}, nil
+	return files[0], nil
 }
@@ -0,0 +1,62 @@
+package filesystem
+
+import (
+	"errors"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestProcessSingleFileCorruptOffice(t *testing.T) {
+	root := t.TempDir()
+	path := filepath.Join(root, "design.docx")
+	writeTestFile(t, path, "not a zip archive")
+
+	_, err := ProcessSingleFile(path, Options{})
+	var skipped *SkipError
+	if !errors.As(err, &skipped) {
+		t.Fatalf("Expected the corrupt document to be skipped, got %v", err)
+	}
+	if len(skipped.Excluded.Skipped) != 1 || skipped.Excluded.Skipped[0].Path != path {
+		t.Errorf("Expected design.docx to be listed as skipped, got %v", skipped.Excluded.Skipped)
+	}
+}
+
+func TestMaxFileSizeSingleFile(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "dump.sql")
+	writeTestFile(t, path, strings.Repeat("INSERT INTO t VALUES (1);\n", 10))
+	opts := Options{NoDefaultExcludes: true, MaxFileSize: 50}
+
+	_, err := ProcessSingleFile(path, opts)
+	var skipped *SkipError
+	if !errors.As(err, &skipped) || len(skipped.Excluded.TooLarge) != 1 {
+		t.Fatalf("Expected dump.sql to be skipped as too large, got %v", err)
+	}
+
+	_, files, excluded, err := WalkDirectory(path, opts)
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	if len(files) != 0 || len(excluded.TooLarge) != 1 || excluded.TooLarge[0].Size != 260 {
+		t.Errorf("Expected dump.sql to be listed as too large, got files %v and %v", files, excluded.TooLarge)
+	}
+}
+
+func TestProcessSingleFileOnlyPaths(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "unchanged.go")
+	writeTestFile(t, path, "package main\n")
+	manifest := NewManifest()
+
+	_, err := ProcessSingleFile(path, Options{OnlyPaths: map[string]bool{"changed.go": true}, Manifest: manifest})
+	var skipped *SkipError
+	if !errors.As(err, &skipped) {
+		t.Fatalf("Expected unchanged.go to be skipped, got %v", err)
+	}
+	if entries := manifest.Entries(); len(entries) != 1 || entries[0].Status != StatusNotSelected {
+		t.Errorf("Expected unchanged.go to be recorded as not selected, got %v", entries)
+	}
+
+	if _, err := ProcessSingleFile(path, Options{OnlyPaths: map[string]bool{"unchanged.go": true}}); err != nil {
+		t.Errorf("Expected unchanged.go to be ingested once selected, got %v", err)
+	}
+}
@@ -0,0 +1,79 @@
+package filesystem
+
+import (
+	"io/fs"
+	"os"
+	"path"
+	"path/filepath"
+)
+
+// fileSystem is where files are read from: the OS, or the fs.FS given in Options.FS.
+type fileSystem interface {
+	Stat(name string) (fs.FileInfo, error)
+	Lstat(name string) (fs.FileInfo, error)
+	ReadDir(name string) ([]fs.DirEntry, error)
+	ReadFile(name string) ([]byte, error)
+	Open(name string) (fs.File, error)
+	// RealPath returns name with symlinks resolved, as an absolute path where there is one
+	RealPath(name string) (string, error)
+}
+
+// fsys returns the file system files are read from.
+func (o Options) fsys() fileSystem {
+	if o.FS != nil {
+		return ioFS{o.FS}
+	}
+	return osFS{}
+}
+
+// Stat returns the info of the file or directory at path, within FS if it is set.
+func (o Options) Stat(path string) (fs.FileInfo, error) {
+	return o.fsys().Stat(path)
+}
+
+// symlinks returns how symlinks are handled. They are always skipped within an FS, as their
+// targets can't be resolved safely.
+func (o Options) symlinks() SymlinkPolicy {
+	if o.FS != nil {
+		return SymlinksSkip
+	}
+	return o.Symlinks
+}
+
+// osFS reads files from the OS.
+type osFS struct{}
+
+func (osFS) Stat(name string) (fs.FileInfo, error)      { return os.Stat(name) }
+func (osFS) Lstat(name string) (fs.FileInfo, error)     { return os.Lstat(name) }
+func (osFS) ReadDir(name string) ([]fs.DirEntry, error) { return os.ReadDir(name) }
+func (osFS) ReadFile(name string) ([]byte, error)       { return os.ReadFile(name) }
+func (osFS) Open(name string) (fs.File, error)          { return os.Open(name) }
+
+func (osFS) RealPath(name string) (string, error) {
+	resolved, err := filepath.EvalSymlinks(name)
+	if err != nil {
+		return "", err
+	}
+	return filepath.Abs(resolved)
+}
+
+// ioFS reads files from an fs.FS. Paths are relative to its root, and are converted to the
+// slash separated form it expects, so they can be built with filepath.Join like OS paths.
+type ioFS struct {
+	fsys fs.FS
+}
+
+func (f ioFS) name(name string) string {
+	return path.Clean(filepath.ToSlash(name))
+}
+
+func (f ioFS) Stat(name string) (fs.FileInfo, error)      { return fs.Stat(f.fsys, f.name(name)) }
+func (f ioFS) Lstat(name string) (fs.FileInfo, error)     { return fs.Lstat(f.fsys, f.name(name)) }
+func (f ioFS) ReadDir(name string) ([]fs.DirEntry, error) { return fs.ReadDir(f.fsys, f.name(name)) }
+func (f ioFS) ReadFile(name string) ([]byte, error)       { return fs.ReadFile(f.fsys, f.name(name)) }
+func (f ioFS) Open(name string) (fs.File, error)          { return f.fsys.Open(f.name(name)) }
+
+// RealPath returns the cleaned name, as symlinks within an fs.FS are never followed.
+func (f ioFS) RealPath(name string) (string, error) {
+	return f.name(name), nil
+}
@@ -0,0 +1,50 @@
+package filesystem
+
+import (
+	"fmt"
+	"path/filepath"
+
+	"github.com/sammcj/ingest/utils"
+)
+
+// GeneratedPatterns are the lockfiles and generated files collapsed by --collapse-generated.
+// They are usually large, and tell an LLM little beyond the fact that they exist.
+var GeneratedPatterns = []string{
+	"Cargo.lock",
+	"Gemfile.lock",
+	"Package.resolved",
+	"Pipfile.lock",
+	"Podfile.lock",
+	"composer.lock",
+	"flake.lock",
+	"go.sum",
+	"mix.lock",
+	"npm-shrinkwrap.json",
+	"package-lock.json",
+	"packages.lock.json",
+	"pnpm-lock.yaml",
+	"poetry.lock",
+	"pubspec.lock",
+	"uv.lock",
+	"yarn.lock",
+	"*.pb.go",
+	"*_pb2.py",
+	"*.g.dart",
+}
+
+// isCollapsed reports whether the file at the relative path matches one of the collapse
+// patterns. Like --order, a pattern matches any trailing part of the path.
+func isCollapsed(relPath string, patterns []string) bool {
+	slashPath := filepath.ToSlash(relPath)
+	for _, pattern := range patterns {
+		if utils.MatchPathSuffix(pattern, slashPath) {
+			return true
+		}
+	}
+	return false
+}
+
+// collapsedStub is the one line that replaces the content of a collapsed file.
+func collapsedStub(relPath string, lines int) string {
+	return fmt.Sprintf("<generated: %s, %d lines, omitted>", filepath.ToSlash(relPath), lines)
+}
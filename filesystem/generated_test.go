@@ -0,0 +1,50 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestWalkDirectoryCollapseGenerated(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(root, "web", "package-lock.json"), "{\n  \"lockfileVersion\": 3\n}\n")
+	excludes := filepath.Join(t.TempDir(), "excludes.glob")
+	writeTestFile(t, excludes, "**/package-lock.json\n")
+
+	// The lockfile is excluded by the exclude file, as it is by the default excludes
+	tree, files, _, err := WalkDirectory(root, Options{PatternExclude: excludes, CollapsePatterns: GeneratedPatterns})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	if len(files) != 2 {
+		t.Fatalf("Expected the lockfile to be kept, got %d files", len(files))
+	}
+	lockfile := files[1]
+	if lockfile.RelPath != "web/package-lock.json" {
+		t.Fatalf("Expected the lockfile second, got %s", lockfile.RelPath)
+	}
+	if want := "<generated: web/package-lock.json, 3 lines, omitted>"; lockfile.Code != want {
+		t.Errorf("Expected %q, got %q", want, lockfile.Code)
+	}
+	if !strings.Contains(files[0].Code, "package main") {
+		t.Errorf("Expected other files to be unchanged, got %q", files[0].Code)
+	}
+	if strings.Contains(tree, "❌") {
+		t.Errorf("Expected the collapsed file not to be marked excluded:\n%s", tree)
+	}
+
+	// Explicit excludes still apply
+	_, files, _, err = WalkDirectory(root, Options{
+		PatternExclude:   excludes,
+		ExcludePatterns:  []string{"web/**"},
+		CollapsePatterns: GeneratedPatterns,
+	})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	if len(files) != 1 {
+		t.Errorf("Expected --exclude to drop the lockfile, got %d files", len(files))
+	}
+}
@@ -0,0 +1,153 @@
+package filesystem
+
+import (
+	"bufio"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+
+	"github.com/fatih/color"
+	"github.com/sammcj/ingest/utils"
+)
+
+// attributesFileName is the per-directory file git reads path attributes from.
+const attributesFileName = ".gitattributes"
+
+// attributeState is what a .gitattributes line does to an attribute. Attributes a line
+// doesn't mention are left as earlier lines had them.
+type attributeState int
+
+const (
+	attributeUnmentioned attributeState = iota
+	attributeSet
+	attributeUnset
+	attributeUnspecified // Reset with !attr, as if no line had mentioned it
+)
+
+// attributeRule is a line of a .gitattributes file, reduced to the attributes ingest honours.
+type attributeRule struct {
+	pattern      ignoreRule
+	exportIgnore attributeState // Paths git archive leaves out, which are skipped
+	generated    attributeState // linguist-generated, files GitHub treats as generated, which are collapsed
+}
+
+// attributeMatcher applies .gitattributes files the way git does: lines accumulate from the walk
+// root down to the directory of a path, and for each attribute the last line that sets or
+// unsets it wins. Unlike ignore files, a pattern matching a directory says nothing of the files
+// within it, though a directory that is export-ignore is skipped along with its contents, as git
+// archive does. It is not safe for concurrent use.
+type attributeMatcher struct {
+	fsys  fileSystem
+	root  string
+	rules map[string][]attributeRule // Relative directory -> rules from its .gitattributes
+}
+
+func newAttributeMatcher(fsys fileSystem, root string) *attributeMatcher {
+	return &attributeMatcher{fsys: fsys, root: root, rules: make(map[string][]attributeRule)}
+}
+
+// attributes reports whether relPath, relative to the walk root, is export-ignore and whether
+// it is linguist-generated.
+func (m *attributeMatcher) attributes(relPath string, isDir bool) (exportIgnore, generated bool) {
+	if m == nil {
+		return false, false
+	}
+
+	rel := filepath.ToSlash(relPath)
+	if rel == "." || rel == "" {
+		return false, false
+	}
+
+	var exportState, generatedState attributeState
+	for _, dir := range ancestorDirs(rel) {
+		for _, rule := range m.rulesFor(dir) {
+			if !rule.pattern.matches(rel, isDir) {
+				continue
+			}
+			if rule.exportIgnore != attributeUnmentioned {
+				exportState = rule.exportIgnore
+			}
+			if rule.generated != attributeUnmentioned {
+				generatedState = rule.generated
+			}
+		}
+	}
+	return exportState == attributeSet, generatedState == attributeSet && !isDir
+}
+
+func (m *attributeMatcher) rulesFor(dir string) []attributeRule {
+	if rules, ok := m.rules[dir]; ok {
+		return rules
+	}
+
+	filename := filepath.Join(m.root, filepath.FromSlash(dir), attributesFileName)
+	rules, err := readAttributesFile(m.fsys, filename, dir)
+	if err != nil {
+		utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to read %s: %v", filename, err), color.FgYellow)
+	}
+	m.rules[dir] = rules
+	return rules
+}
+
+func readAttributesFile(fsys fileSystem, filename, base string) ([]attributeRule, error) {
+	file, err := fsys.Open(filename)
+	if os.IsNotExist(err) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	defer file.Close()
+
+	var rules []attributeRule
+	scanner := bufio.NewScanner(file)
+	for scanner.Scan() {
+		if rule, ok := parseAttributesLine(scanner.Text(), base); ok {
+			rules = append(rules, rule)
+		}
+	}
+	return rules, scanner.Err()
+}
+
+// parseAttributesLine converts a line of a .gitattributes file into a rule, returning false if
+// it sets neither export-ignore nor linguist-generated. Patterns follow the gitignore format,
+// except that negative patterns aren't allowed.
+func parseAttributesLine(line, base string) (attributeRule, bool) {
+	fields := strings.Fields(line)
+	if len(fields) < 2 || strings.HasPrefix(fields[0], "#") || strings.HasPrefix(fields[0], "!") {
+		return attributeRule{}, false
+	}
+
+	var rule attributeRule
+	for _, attr := range fields[1:] {
+		name, value, hasValue := strings.Cut(attr, "=")
+		state := attributeSet
+		switch {
+		case strings.HasPrefix(name, "-"):
+			name, state = name[1:], attributeUnset
+		case strings.HasPrefix(name, "!"):
+			name, state = name[1:], attributeUnspecified
+		case hasValue && value == "false":
+			state = attributeUnset
+		}
+
+		switch name {
+		case "export-ignore":
+			rule.exportIgnore = state
+		case "linguist-generated":
+			rule.generated = state
+		}
+	}
+	if rule.exportIgnore == attributeUnmentioned && rule.generated == attributeUnmentioned {
+		return attributeRule{}, false
+	}
+
+	pattern, ok := parseIgnoreLine(fields[0], base)
+	if !ok || pattern.dirOnly {
+		// Git doesn't match patterns ending in a slash against anything in .gitattributes
+		return attributeRule{}, false
+	}
+	rule.pattern = pattern
+	return rule, true
+}
@@ -0,0 +1,59 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestWalkDirectoryGitAttributes(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, ".gitattributes"), "# Not in releases\ndocs/internal export-ignore\n*.gen.go linguist-generated\nkeep.gen.go -linguist-generated\n")
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(root, "api.gen.go"), "package api\nvar X = 1\n")
+	writeTestFile(t, filepath.Join(root, "keep.gen.go"), "package api\n")
+	writeTestFile(t, filepath.Join(root, "docs", "readme.md"), "# Docs\n")
+	writeTestFile(t, filepath.Join(root, "docs", "internal", "notes.md"), "# Notes\n")
+	writeTestFile(t, filepath.Join(root, "sub", ".gitattributes"), "secret.txt export-ignore\n")
+	writeTestFile(t, filepath.Join(root, "sub", "secret.txt"), "secret\n")
+	writeTestFile(t, filepath.Join(root, "sub", "ok.txt"), "ok\n")
+
+	codes := func(opts Options) map[string]string {
+		t.Helper()
+		_, files, _, err := WalkDirectory(root, opts)
+		if err != nil {
+			t.Fatalf("WalkDirectory failed: %v", err)
+		}
+		codes := make(map[string]string, len(files))
+		for _, file := range files {
+			codes[file.RelPath] = file.Code
+		}
+		return codes
+	}
+
+	got := codes(Options{NoDefaultExcludes: true, NoCodeblock: true})
+	for _, path := range []string{"docs/internal/notes.md", "sub/secret.txt"} {
+		if _, ok := got[path]; ok {
+			t.Errorf("Expected export-ignore path %s to be skipped", path)
+		}
+	}
+	for _, path := range []string{"main.go", "docs/readme.md", "sub/ok.txt"} {
+		if _, ok := got[path]; !ok {
+			t.Errorf("Expected %s to be ingested", path)
+		}
+	}
+	if want := "<generated: api.gen.go, 2 lines, omitted>"; got["api.gen.go"] != want {
+		t.Errorf("Expected the linguist-generated file to be collapsed to %q, got %q", want, got["api.gen.go"])
+	}
+	if !strings.Contains(got["keep.gen.go"], "package api") {
+		t.Errorf("Expected the file with linguist-generated unset to be kept, got %q", got["keep.gen.go"])
+	}
+
+	got = codes(Options{NoDefaultExcludes: true, NoCodeblock: true, NoGitAttributes: true})
+	if _, ok := got["sub/secret.txt"]; !ok {
+		t.Error("Expected export-ignore to be ignored with NoGitAttributes")
+	}
+	if !strings.Contains(got["api.gen.go"], "var X = 1") {
+		t.Errorf("Expected linguist-generated to be ignored with NoGitAttributes, got %q", got["api.gen.go"])
+	}
+}
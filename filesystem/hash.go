@@ -0,0 +1,37 @@
+package filesystem
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"sort"
+)
+
+// ContentHash returns a hex SHA-256 over the relative paths and contents of files. Files are
+// sorted before hashing so the result doesn't depend on walk order or concurrency, and absolute
+// paths are left out so the same files hash the same wherever they are checked out.
+func ContentHash(files []FileInfo) string {
+	type entry struct{ key, content string }
+	entries := make([]entry, len(files))
+	for i, file := range files {
+		key := file.RelPath
+		if key == "" {
+			// Web pages are keyed by their URL
+			key = file.Path
+		}
+		entries[i] = entry{key: key, content: file.Code}
+	}
+	sort.Slice(entries, func(i, j int) bool {
+		if entries[i].key != entries[j].key {
+			return entries[i].key < entries[j].key
+		}
+		return entries[i].content < entries[j].content
+	})
+
+	// Length prefixes keep the boundaries between paths and contents unambiguous
+	h := sha256.New()
+	for _, e := range entries {
+		fmt.Fprintf(h, "%d:%s%d:%s", len(e.key), e.key, len(e.content), e.content)
+	}
+	return hex.EncodeToString(h.Sum(nil))
+}
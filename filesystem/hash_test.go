@@ -0,0 +1,38 @@
+package filesystem
+
+import "testing"
+
+func TestContentHash(t *testing.T) {
+	a := FileInfo{Path: "/home/me/repo/main.go", RelPath: "main.go", Code: "package main"}
+	b := FileInfo{Path: "/home/me/repo/util/util.go", RelPath: "util/util.go", Code: "package util"}
+
+	hash := ContentHash([]FileInfo{a, b})
+	if len(hash) != 64 {
+		t.Fatalf("Expected a hex SHA-256, got %q", hash)
+	}
+
+	if got := ContentHash([]FileInfo{b, a}); got != hash {
+		t.Error("Expected the hash not to depend on file order")
+	}
+
+	// The same files checked out elsewhere hash the same
+	movedA, movedB := a, b
+	movedA.Path = "/ci/build/main.go"
+	movedB.Path = "/ci/build/util/util.go"
+	if got := ContentHash([]FileInfo{movedA, movedB}); got != hash {
+		t.Error("Expected the hash not to depend on absolute paths")
+	}
+
+	changed := b
+	changed.Code = "package util2"
+	if got := ContentHash([]FileInfo{a, changed}); got == hash {
+		t.Error("Expected the hash to change with file contents")
+	}
+
+	// Moving text between the path and content changes the hash
+	x := FileInfo{RelPath: "ab", Code: "c"}
+	y := FileInfo{RelPath: "a", Code: "bc"}
+	if ContentHash([]FileInfo{x}) == ContentHash([]FileInfo{y}) {
+		t.Error("Expected path and content boundaries to be part of the hash")
+	}
+}
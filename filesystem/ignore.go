@@ -0,0 +1,226 @@
+package filesystem
+
+import (
+	"bufio"
+	"fmt"
+	"os"
+	"path"
+	"path/filepath"
+	"strings"
+
+	"github.com/bmatcuk/doublestar/v4"
+	"github.com/fatih/color"
+	"github.com/sammcj/ingest/git"
+	"github.com/sammcj/ingest/utils"
+)
+
+// ignoreFileNames are the per-directory ignore files that are honoured, in increasing order of
+// precedence.
+var ignoreFileNames = []string{".gitignore", ".ignore", ".ingestignore"}
+
+// ignoreRule is a single pattern from an ignore file.
+type ignoreRule struct {
+	base    string // Directory containing the ignore file, relative to the walk root ("" for the root)
+	glob    string // Pattern relative to base
+	negate  bool
+	dirOnly bool
+}
+
+// ignoreMatcher applies ignore files the way git does: rules accumulate from the walk root down
+// to the directory of a path, and the last matching rule wins, so deeper files override
+// shallower ones and negations can re-include paths. Rules from the repository's exclude files
+// come before all of them. It is not safe for concurrent use.
+type ignoreMatcher struct {
+	fsys       fileSystem
+	root       string
+	rules      map[string][]ignoreRule // Relative directory -> rules from its ignore files
+	repoRules  []ignoreRule            // Rules from the repository's exclude files, relative to its top level
+	repoPrefix string                  // Slash separated path of the walk root below the repository's top level, with a trailing slash
+}
+
+// newIgnoreMatcher returns a matcher for the directory tree at root. Ignore files in the root
+// are read immediately, those in subdirectories when a path beneath them is first matched.
+func newIgnoreMatcher(fsys fileSystem, root string) (*ignoreMatcher, error) {
+	m := &ignoreMatcher{fsys: fsys, root: root, rules: make(map[string][]ignoreRule)}
+	rules, err := readIgnoreRules(fsys, root, "")
+	if err != nil {
+		return nil, err
+	}
+	m.rules[""] = rules
+	return m, nil
+}
+
+// Matches reports whether relPath, relative to the walk root, is ignored.
+func (m *ignoreMatcher) Matches(relPath string, isDir bool) bool {
+	if m == nil {
+		return false
+	}
+
+	rel := filepath.ToSlash(relPath)
+	if rel == "." || rel == "" {
+		return false
+	}
+
+	ignored := false
+	for _, rule := range m.repoRules {
+		if rule.matches(m.repoPrefix+rel, isDir) {
+			ignored = !rule.negate
+		}
+	}
+	for _, dir := range ancestorDirs(rel) {
+		for _, rule := range m.rulesFor(dir) {
+			if rule.matches(rel, isDir) {
+				ignored = !rule.negate
+			}
+		}
+	}
+	return ignored
+}
+
+// addRepoExcludes applies git's exclude files for the repository containing the walk root:
+// its info/exclude and, if global is set, the user's global excludes file. They are skipped when
+// the root isn't inside a git work tree.
+func (m *ignoreMatcher) addRepoExcludes(global bool) error {
+	top, files, ok := git.ExcludeFiles(m.root, global)
+	if !ok {
+		return nil
+	}
+
+	// Git reports the top level with symlinks resolved, such as /private/tmp on macOS
+	root := m.root
+	if resolved, err := filepath.EvalSymlinks(root); err == nil {
+		root = resolved
+	}
+	rel, err := filepath.Rel(top, root)
+	if err != nil || !IsWithin(top, root) {
+		return nil
+	}
+	if rel != "." {
+		m.repoPrefix = filepath.ToSlash(rel) + "/"
+	}
+
+	for _, file := range files {
+		rules, err := readIgnoreFile(osFS{}, file, "")
+		if err != nil {
+			return fmt.Errorf("failed to read %s: %w", file, err)
+		}
+		m.repoRules = append(m.repoRules, rules...)
+	}
+	return nil
+}
+
+func (m *ignoreMatcher) rulesFor(dir string) []ignoreRule {
+	if rules, ok := m.rules[dir]; ok {
+		return rules
+	}
+
+	rules, err := readIgnoreRules(m.fsys, filepath.Join(m.root, filepath.FromSlash(dir)), dir)
+	if err != nil {
+		utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to read ignore files in %s: %v", dir, err), color.FgYellow)
+	}
+	m.rules[dir] = rules
+	return rules
+}
+
+func (r ignoreRule) matches(rel string, isDir bool) bool {
+	if r.dirOnly && !isDir {
+		return false
+	}
+
+	target := rel
+	if r.base != "" {
+		var ok bool
+		target, ok = strings.CutPrefix(rel, r.base+"/")
+		if !ok {
+			return false
+		}
+	}
+
+	match, _ := doublestar.Match(r.glob, target)
+	return match
+}
+
+// ancestorDirs returns the directories containing rel, from the root ("") down to its parent.
+func ancestorDirs(rel string) []string {
+	dirs := []string{""}
+	parent := path.Dir(rel)
+	if parent == "." {
+		return dirs
+	}
+
+	parts := strings.Split(parent, "/")
+	for i := range parts {
+		dirs = append(dirs, strings.Join(parts[:i+1], "/"))
+	}
+	return dirs
+}
+
+// readIgnoreRules reads the ignore files in dir, whose path relative to the walk root is base.
+func readIgnoreRules(fsys fileSystem, dir, base string) ([]ignoreRule, error) {
+	var rules []ignoreRule
+	for _, name := range ignoreFileNames {
+		fileRules, err := readIgnoreFile(fsys, filepath.Join(dir, name), base)
+		if err != nil {
+			return rules, fmt.Errorf("failed to read %s: %w", name, err)
+		}
+		rules = append(rules, fileRules...)
+	}
+	return rules, nil
+}
+
+func readIgnoreFile(fsys fileSystem, filename, base string) ([]ignoreRule, error) {
+	file, err := fsys.Open(filename)
+	if os.IsNotExist(err) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	defer file.Close()
+
+	var rules []ignoreRule
+	scanner := bufio.NewScanner(file)
+	for scanner.Scan() {
+		if rule, ok := parseIgnoreLine(scanner.Text(), base); ok {
+			rules = append(rules, rule)
+		}
+	}
+	return rules, scanner.Err()
+}
+
+// parseIgnoreLine converts a line of an ignore file into a rule, following the gitignore
+// pattern format.
+func parseIgnoreLine(line, base string) (ignoreRule, bool) {
+	line = strings.TrimRight(line, "\r")
+	if !strings.HasSuffix(line, "\\ ") {
+		line = strings.TrimRight(line, " \t")
+	}
+	if line == "" || strings.HasPrefix(line, "#") {
+		return ignoreRule{}, false
+	}
+
+	rule := ignoreRule{base: base}
+	if strings.HasPrefix(line, "!") {
+		rule.negate = true
+		line = line[1:]
+	} else if strings.HasPrefix(line, `\#`) || strings.HasPrefix(line, `\!`) {
+		line = line[1:]
+	}
+
+	if strings.HasSuffix(line, "/") {
+		rule.dirOnly = true
+		line = strings.TrimSuffix(line, "/")
+	}
+	if line == "" {
+		return ignoreRule{}, false
+	}
+
+	// A pattern containing a slash is anchored to the ignore file's directory, otherwise it
+	// matches at any depth below it
+	if strings.Contains(line, "/") {
+		rule.glob = strings.TrimPrefix(line, "/")
+	} else {
+		rule.glob = "**/" + line
+	}
+	return rule, true
+}
@@ -0,0 +1,134 @@
+package filesystem
+
+import (
+	"os"
+	"os/exec"
+	"path/filepath"
+	"testing"
+)
+
+func writeTestFile(t *testing.T, path, content string) {
+	t.Helper()
+	if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+		t.Fatalf("Failed to create directory for %s: %v", path, err)
+	}
+	if err := os.WriteFile(path, []byte(content), 0644); err != nil {
+		t.Fatalf("Failed to write %s: %v", path, err)
+	}
+}
+
+func TestIgnoreMatcherNested(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, ".gitignore"), "*.log\n/build/\n")
+	writeTestFile(t, filepath.Join(root, "frontend", ".gitignore"), "dist/\n!keep.log\n")
+	writeTestFile(t, filepath.Join(root, "frontend", "src", ".ignore"), "generated.ts\n")
+	writeTestFile(t, filepath.Join(root, "frontend", "src", ".ingestignore"), "!generated.ts\nsecret.ts\n")
+
+	m, err := newIgnoreMatcher(osFS{}, root)
+	if err != nil {
+		t.Fatalf("newIgnoreMatcher failed: %v", err)
+	}
+
+	tests := []struct {
+		path    string
+		isDir   bool
+		ignored bool
+	}{
+		{"debug.log", false, true},
+		{"build", true, true},
+		{"frontend/build", true, false}, // Anchored to the root
+		{"frontend/dist", true, true},
+		{"dist", true, false}, // Only ignored beneath frontend
+		{"frontend/app.log", false, true},
+		{"frontend/keep.log", false, false}, // Re-included by the child negation
+		{"frontend/src/keep.log", false, false},
+		{"frontend/src/generated.ts", false, false}, // .ingestignore overrides .ignore
+		{"frontend/src/secret.ts", false, true},
+		{"frontend/src/main.ts", false, false},
+	}
+
+	for _, tt := range tests {
+		if got := m.Matches(filepath.FromSlash(tt.path), tt.isDir); got != tt.ignored {
+			t.Errorf("Matches(%s): expected %v, got %v", tt.path, tt.ignored, got)
+		}
+	}
+}
+
+func TestWalkDirectoryNestedGitignore(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, ".gitignore"), "*.log\n")
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(root, "app.log"), "log\n")
+	writeTestFile(t, filepath.Join(root, "frontend", ".gitignore"), "dist/\n!keep.log\n")
+	writeTestFile(t, filepath.Join(root, "frontend", "dist", "bundle.js"), "bundle\n")
+	writeTestFile(t, filepath.Join(root, "frontend", "keep.log"), "keep\n")
+
+	_, files, _, err := WalkDirectory(root, Options{NoDefaultExcludes: true, RelativePaths: true})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	var paths []string
+	for _, file := range files {
+		paths = append(paths, filepath.ToSlash(file.Path))
+	}
+
+	base := filepath.Base(root)
+	expected := []string{base + "/.gitignore", base + "/frontend/.gitignore", base + "/frontend/keep.log", base + "/main.go"}
+	if len(paths) != len(expected) {
+		t.Fatalf("Expected files %v, got %v", expected, paths)
+	}
+	for i := range expected {
+		if paths[i] != expected[i] {
+			t.Errorf("Expected file %d to be %s, got %s", i, expected[i], paths[i])
+		}
+	}
+}
+
+func TestIgnoreMatcherRepoExcludes(t *testing.T) {
+	if _, err := exec.LookPath("git"); err != nil {
+		t.Skip("git is not installed")
+	}
+
+	// Keep the user's own git config out of the test
+	configHome := t.TempDir()
+	t.Setenv("XDG_CONFIG_HOME", configHome)
+	t.Setenv("GIT_CONFIG_GLOBAL", filepath.Join(configHome, "gitconfig"))
+	t.Setenv("GIT_CONFIG_NOSYSTEM", "1")
+	writeTestFile(t, filepath.Join(configHome, "git", "ignore"), "*.swp\n*.bak\n")
+
+	repo := t.TempDir()
+	if output, err := exec.Command("git", "-C", repo, "init", "-q").CombinedOutput(); err != nil {
+		t.Fatalf("git init failed: %v: %s", err, output)
+	}
+	writeTestFile(t, filepath.Join(repo, ".git", "info", "exclude"), "/src/scratch/\n*.bak\n")
+	writeTestFile(t, filepath.Join(repo, "src", ".gitignore"), "!keep.bak\n")
+	root := filepath.Join(repo, "src")
+
+	tests := []struct {
+		path    string
+		isDir   bool
+		global  bool
+		ignored bool
+	}{
+		{"main.go", false, true, false},
+		{"main.go.swp", false, true, true}, // Global excludes file
+		{"main.go.swp", false, false, false},
+		{"scratch", true, true, true}, // Anchored to the repository's top level
+		{"old.bak", false, false, true},
+		{"keep.bak", false, true, false}, // .gitignore overrides info/exclude
+	}
+
+	for _, tt := range tests {
+		m, err := newIgnoreMatcher(osFS{}, root)
+		if err != nil {
+			t.Fatalf("newIgnoreMatcher failed: %v", err)
+		}
+		if err := m.addRepoExcludes(tt.global); err != nil {
+			t.Fatalf("addRepoExcludes failed: %v", err)
+		}
+		if got := m.Matches(filepath.FromSlash(tt.path), tt.isDir); got != tt.ignored {
+			t.Errorf("Matches(%s) with global %v: expected %v, got %v", tt.path, tt.global, tt.ignored, got)
+		}
+	}
+}
@@ -0,0 +1,254 @@
+package filesystem
+
+import (
+	"bufio"
+	"os"
+	"path/filepath"
+	"strings"
+
+	"github.com/sammcj/ingest/internal/compressor"
+)
+
+// jsExtensions are tried in order when resolving a JavaScript or TypeScript import that
+// leaves out the extension.
+var jsExtensions = []string{".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs", ".mts", ".cts"}
+
+// jsCompiledExtensions maps the extensions TypeScript lets imports use to the source files
+// they are compiled from.
+var jsCompiledExtensions = map[string][]string{
+	".js":  {".ts", ".tsx"},
+	".jsx": {".tsx"},
+	".mjs": {".mts"},
+	".cjs": {".cts"},
+}
+
+// FollowImports returns the files below root that the files at paths import, directly or
+// through the files they import, in the order they are found. Go, JavaScript, TypeScript and
+// Rust imports are followed: Go packages of the module in the closest go.mod, relative
+// JavaScript and TypeScript paths, and Rust crate, self and super paths and mod declarations.
+// Imports of other packages and the standard library are ignored, as are files that can't
+// be read. The files at paths aren't included.
+func FollowImports(paths []string, root string) []string {
+	seen := make(map[string]bool, len(paths))
+	queue := make([]string, 0, len(paths))
+	for _, path := range paths {
+		if !seen[path] {
+			seen[path] = true
+			queue = append(queue, path)
+		}
+	}
+
+	r := importResolver{root: root, modules: make(map[string]goModule)}
+	var imported []string
+	for len(queue) > 0 {
+		path := queue[0]
+		queue = queue[1:]
+		for _, dep := range r.imports(path) {
+			if !seen[dep] && IsWithin(root, dep) {
+				seen[dep] = true
+				imported = append(imported, dep)
+				queue = append(queue, dep)
+			}
+		}
+	}
+	return imported
+}
+
+// importResolver resolves the imports of files to the files below root they refer to.
+type importResolver struct {
+	root    string
+	modules map[string]goModule // The module of each directory holding Go files
+}
+
+// goModule is a Go module, found from its go.mod.
+type goModule struct {
+	path string // Module path, empty if there is no go.mod below the root
+	dir  string
+}
+
+// imports returns the files that the file at path imports.
+func (r importResolver) imports(path string) []string {
+	lang, err := compressor.IdentifyLanguage(path)
+	if err != nil || !compressor.FollowsImports(lang) {
+		return nil
+	}
+	content, err := os.ReadFile(path)
+	if err != nil {
+		return nil
+	}
+	imports, err := compressor.Imports(content, lang)
+	if err != nil {
+		return nil
+	}
+
+	var files []string
+	for _, spec := range imports {
+		switch lang {
+		case "go":
+			files = append(files, r.goPackage(path, spec)...)
+		case "rust":
+			files = append(files, r.rustModule(path, spec)...)
+		default:
+			if file := resolveJSImport(path, spec); file != "" {
+				files = append(files, file)
+			}
+		}
+	}
+	return files
+}
+
+// goPackage returns the non-test Go files of the package imported as spec, if it is in the
+// same module as the file at from.
+func (r importResolver) goPackage(from, spec string) []string {
+	module := r.goModule(filepath.Dir(from))
+	if module.path == "" || (spec != module.path && !strings.HasPrefix(spec, module.path+"/")) {
+		return nil
+	}
+
+	dir := filepath.Join(module.dir, filepath.FromSlash(strings.TrimPrefix(spec, module.path)))
+	entries, err := os.ReadDir(dir)
+	if err != nil {
+		return nil
+	}
+	var files []string
+	for _, entry := range entries {
+		name := entry.Name()
+		if entry.Type().IsRegular() && strings.HasSuffix(name, ".go") && !strings.HasSuffix(name, "_test.go") {
+			files = append(files, filepath.Join(dir, name))
+		}
+	}
+	return files
+}
+
+// goModule returns the module of the closest go.mod in or above dir, stopping at the root.
+func (r importResolver) goModule(dir string) goModule {
+	if module, ok := r.modules[dir]; ok {
+		return module
+	}
+
+	var module goModule
+	if path := readModulePath(filepath.Join(dir, "go.mod")); path != "" {
+		module = goModule{path: path, dir: dir}
+	} else if parent := filepath.Dir(dir); dir != r.root && parent != dir && IsWithin(r.root, parent) {
+		module = r.goModule(parent)
+	}
+	r.modules[dir] = module
+	return module
+}
+
+// readModulePath returns the module path declared by the go.mod at path, or "" if there is none.
+func readModulePath(path string) string {
+	file, err := os.Open(path)
+	if err != nil {
+		return ""
+	}
+	defer file.Close()
+
+	scanner := bufio.NewScanner(file)
+	for scanner.Scan() {
+		if rest, ok := strings.CutPrefix(strings.TrimSpace(scanner.Text()), "module"); ok && rest != "" && (rest[0] == ' ' || rest[0] == '\t') {
+			return strings.Trim(strings.TrimSpace(rest), `"`)
+		}
+	}
+	return ""
+}
+
+// resolveJSImport returns the file a relative JavaScript or TypeScript import from the file at
+// from refers to, trying the extensions and index files Node and TypeScript do, or "" if it
+// isn't relative or can't be found.
+func resolveJSImport(from, spec string) string {
+	if !strings.HasPrefix(spec, "./") && !strings.HasPrefix(spec, "../") {
+		return ""
+	}
+	base := filepath.Join(filepath.Dir(from), filepath.FromSlash(spec))
+
+	candidates := []string{base}
+	ext := filepath.Ext(base)
+	for _, source := range jsCompiledExtensions[ext] {
+		candidates = append(candidates, strings.TrimSuffix(base, ext)+source)
+	}
+	for _, ext := range jsExtensions {
+		candidates = append(candidates, base+ext)
+	}
+	for _, ext := range jsExtensions {
+		candidates = append(candidates, filepath.Join(base, "index"+ext))
+	}
+
+	for _, candidate := range candidates {
+		if isRegularFile(candidate) {
+			return candidate
+		}
+	}
+	return ""
+}
+
+// rustModule returns the files of the modules along the Rust use path spec from the file at
+// from, such as src/a.rs and src/a/b.rs for crate::a::b::Item. Paths of other crates are
+// ignored.
+func (r importResolver) rustModule(from, spec string) []string {
+	segments := strings.Split(spec, "::")
+	var dir string
+	switch segments[0] {
+	case "crate":
+		dir = rustCrateDir(filepath.Dir(from), r.root)
+		segments = segments[1:]
+	case "self", "super":
+		dir = rustModuleDir(from)
+		if segments[0] == "self" {
+			segments = segments[1:]
+		}
+		for len(segments) > 0 && segments[0] == "super" {
+			dir = filepath.Dir(dir)
+			segments = segments[1:]
+		}
+	}
+	if dir == "" {
+		return nil
+	}
+
+	// Each segment is a module until one isn't found, which is an item of the previous module
+	var files []string
+	for _, segment := range segments {
+		file := filepath.Join(dir, segment+".rs")
+		if !isRegularFile(file) {
+			file = filepath.Join(dir, segment, "mod.rs")
+			if !isRegularFile(file) {
+				break
+			}
+		}
+		files = append(files, file)
+		dir = filepath.Join(dir, segment)
+	}
+	return files
+}
+
+// rustModuleDir returns the directory holding the submodules of the Rust file at path: its own
+// directory for crate roots and mod.rs files, otherwise a directory named after it.
+func rustModuleDir(path string) string {
+	switch filepath.Base(path) {
+	case "main.rs", "lib.rs", "mod.rs":
+		return filepath.Dir(path)
+	}
+	return strings.TrimSuffix(path, ".rs")
+}
+
+// rustCrateDir returns the src directory of the crate whose Cargo.toml is closest in or above
+// dir, stopping at the root, or "" if there is none.
+func rustCrateDir(dir, root string) string {
+	for {
+		if isRegularFile(filepath.Join(dir, "Cargo.toml")) {
+			return filepath.Join(dir, "src")
+		}
+		parent := filepath.Dir(dir)
+		if dir == root || parent == dir || !IsWithin(root, parent) {
+			return ""
+		}
+		dir = parent
+	}
+}
+
+// isRegularFile reports whether path is a regular file, following symlinks.
+func isRegularFile(path string) bool {
+	info, err := os.Stat(path)
+	return err == nil && info.Mode().IsRegular()
+}
@@ -0,0 +1,73 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"slices"
+	"testing"
+)
+
+func TestFollowImports(t *testing.T) {
+	root := t.TempDir()
+
+	tests := []struct {
+		name  string
+		start string
+		files map[string]string
+		want  []string
+	}{
+		{
+			name:  "go packages of the module",
+			start: "go/main.go",
+			files: map[string]string{
+				"go/go.mod":          "module example.com/app\n\ngo 1.22\n",
+				"go/main.go":         "package main\n\nimport (\n\t\"fmt\"\n\t\"example.com/app/pkg\"\n\t\"github.com/other/pkg\"\n)\n",
+				"go/pkg/pkg.go":      "package pkg\n\nimport \"example.com/app/pkg/inner\"\n",
+				"go/pkg/pkg_test.go": "package pkg\n",
+				"go/pkg/inner/a.go":  "package inner\n\nimport \"example.com/app/pkg\"\n",
+			},
+			want: []string{"go/pkg/pkg.go", "go/pkg/inner/a.go"},
+		},
+		{
+			name:  "relative javascript and typescript imports",
+			start: "js/index.ts",
+			files: map[string]string{
+				"js/index.ts":        "import { a } from './a.js';\nimport React from 'react';\nexport * from \"./lib\";\n",
+				"js/a.ts":            "const b = require('../js/b');\n",
+				"js/b.js":            "import('./index.ts');\n",
+				"js/lib/index.tsx":   "export const lib = 1;\n",
+				"js/unused/other.ts": "export {};\n",
+			},
+			want: []string{"js/a.ts", "js/lib/index.tsx", "js/b.js"},
+		},
+		{
+			name:  "rust modules and crate paths",
+			start: "rs/src/main.rs",
+			files: map[string]string{
+				"rs/Cargo.toml":        "[package]\nname = \"app\"\n",
+				"rs/src/main.rs":       "mod config;\nuse crate::net::{client::Client, self};\nuse std::io;\n",
+				"rs/src/config.rs":     "use super::net::client::Client as C;\n",
+				"rs/src/net/mod.rs":    "pub mod client;\n",
+				"rs/src/net/client.rs": "use crate::config;\n",
+				"rs/src/net/unused.rs": "",
+			},
+			want: []string{"rs/src/config.rs", "rs/src/net/mod.rs", "rs/src/net/client.rs"},
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			for path, content := range tt.files {
+				writeTestFile(t, filepath.Join(root, path), content)
+			}
+
+			got := FollowImports([]string{filepath.Join(root, tt.start)}, root)
+			want := make([]string, len(tt.want))
+			for i, path := range tt.want {
+				want[i] = filepath.Join(root, path)
+			}
+			if !slices.Equal(got, want) {
+				t.Errorf("FollowImports(%s) = %v, want %v", tt.start, got, want)
+			}
+		})
+	}
+}
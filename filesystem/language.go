@@ -0,0 +1,297 @@
+package filesystem
+
+import (
+	"cmp"
+	"path/filepath"
+	"slices"
+	"strings"
+)
+
+// languageNames maps file extensions to the name of their language.
+var languageNames = map[string]string{
+	".bash":       "Shell",
+	".c":          "C",
+	".cc":         "C++",
+	".clj":        "Clojure",
+	".cpp":        "C++",
+	".cs":         "C#",
+	".css":        "CSS",
+	".csv":        "CSV",
+	".dart":       "Dart",
+	".dockerfile": "Dockerfile",
+	".docx":       "Word",
+	".ex":         "Elixir",
+	".exs":        "Elixir",
+	".go":         "Go",
+	".gradle":     "Gradle",
+	".graphql":    "GraphQL",
+	".h":          "C",
+	".hcl":        "HCL",
+	".hpp":        "C++",
+	".hs":         "Haskell",
+	".html":       "HTML",
+	".ini":        "INI",
+	".java":       "Java",
+	".js":         "JavaScript",
+	".json":       "JSON",
+	".jsx":        "JavaScript",
+	".kt":         "Kotlin",
+	".lua":        "Lua",
+	".m":          "Objective-C",
+	".md":         "Markdown",
+	".mjs":        "JavaScript",
+	".pdf":        "PDF",
+	".php":        "PHP",
+	".pl":         "Perl",
+	".proto":      "Protobuf",
+	".ps1":        "PowerShell",
+	".py":         "Python",
+	".r":          "R",
+	".rb":         "Ruby",
+	".rs":         "Rust",
+	".scala":      "Scala",
+	".scss":       "SCSS",
+	".sh":         "Shell",
+	".sql":        "SQL",
+	".svelte":     "Svelte",
+	".swift":      "Swift",
+	".tf":         "Terraform",
+	".tmpl":       "Go Template",
+	".toml":       "TOML",
+	".ts":         "TypeScript",
+	".tsx":        "TypeScript",
+	".txt":        "Text",
+	".vue":        "Vue",
+	".xlsx":       "Excel",
+	".xml":        "XML",
+	".yaml":       "YAML",
+	".yml":        "YAML",
+	".zig":        "Zig",
+	".zsh":        "Shell",
+}
+
+// languageFileNames maps well known file names without a telling extension to their language.
+var languageFileNames = map[string]string{
+	"dockerfile":  "Dockerfile",
+	"makefile":    "Makefile",
+	"gemfile":     "Ruby",
+	"rakefile":    "Ruby",
+	"jenkinsfile": "Groovy",
+}
+
+// languageName returns the language of the file at path based on its name, or "" if unknown.
+func languageName(path string) string {
+	base := strings.ToLower(filepath.Base(path))
+	if name, ok := languageFileNames[base]; ok {
+		return name
+	}
+	return languageNames[strings.ToLower(filepath.Ext(base))]
+}
+
+// LanguageGroup is the files of one language, as grouped by GroupByLanguage.
+type LanguageGroup struct {
+	Language string // Language name, or "other" for files of no known language
+	Tokens   int    // Total token count of Files
+	Files    []FileInfo
+}
+
+// GroupByLanguage buckets files by their language, keeping their order within each language.
+// The languages with the most tokens come first, so files are expected to have their token
+// counts set.
+func GroupByLanguage(files []FileInfo) []LanguageGroup {
+	index := make(map[string]int)
+	var groups []LanguageGroup
+	for _, file := range files {
+		language := languageName(file.Path)
+		if language == "" {
+			language = "other"
+		}
+		i, ok := index[language]
+		if !ok {
+			i = len(groups)
+			index[language] = i
+			groups = append(groups, LanguageGroup{Language: language})
+		}
+		groups[i].Tokens += file.TokenCount
+		groups[i].Files = append(groups[i].Files, file)
+	}
+
+	slices.SortFunc(groups, func(a, b LanguageGroup) int {
+		if a.Tokens != b.Tokens {
+			return cmp.Compare(b.Tokens, a.Tokens)
+		}
+		return strings.Compare(a.Language, b.Language)
+	})
+	return groups
+}
+
+// fenceLanguages maps file extensions to the language tag of their markdown code fence.
+var fenceLanguages = map[string]string{
+	".bash":       "bash",
+	".bat":        "batch",
+	".c":          "c",
+	".cc":         "cpp",
+	".cfg":        "ini",
+	".cjs":        "javascript",
+	".clj":        "clojure",
+	".cmake":      "cmake",
+	".conf":       "ini",
+	".cpp":        "cpp",
+	".cs":         "csharp",
+	".css":        "css",
+	".csv":        "csv",
+	".dart":       "dart",
+	".diff":       "diff",
+	".dockerfile": "dockerfile",
+	".elm":        "elm",
+	".erl":        "erlang",
+	".ex":         "elixir",
+	".exs":        "elixir",
+	".fish":       "fish",
+	".fs":         "fsharp",
+	".go":         "go",
+	".gradle":     "groovy",
+	".graphql":    "graphql",
+	".groovy":     "groovy",
+	".h":          "c",
+	".hcl":        "hcl",
+	".hpp":        "cpp",
+	".hs":         "haskell",
+	".html":       "html",
+	".ini":        "ini",
+	".java":       "java",
+	".jl":         "julia",
+	".js":         "javascript",
+	".json":       "json",
+	".jsx":        "jsx",
+	".kt":         "kotlin",
+	".kts":        "kotlin",
+	".less":       "less",
+	".lua":        "lua",
+	".m":          "objectivec",
+	".md":         "markdown",
+	".mjs":        "javascript",
+	".ml":         "ocaml",
+	".nix":        "nix",
+	".patch":      "diff",
+	".php":        "php",
+	".pl":         "perl",
+	".proto":      "protobuf",
+	".ps1":        "powershell",
+	".py":         "python",
+	".r":          "r",
+	".rb":         "ruby",
+	".rs":         "rust",
+	".sass":       "sass",
+	".scala":      "scala",
+	".scss":       "scss",
+	".sh":         "bash",
+	".sql":        "sql",
+	".svelte":     "svelte",
+	".swift":      "swift",
+	".tf":         "hcl",
+	".tmpl":       "gotemplate",
+	".toml":       "toml",
+	".ts":         "typescript",
+	".tsx":        "tsx",
+	".txt":        "text",
+	".vue":        "vue",
+	".xml":        "xml",
+	".yaml":       "yaml",
+	".yml":        "yaml",
+	".zig":        "zig",
+	".zsh":        "zsh",
+}
+
+// fenceFileNames maps well known file names to the language tag of their code fence.
+var fenceFileNames = map[string]string{
+	".bashrc":        "bash",
+	".dockerignore":  "gitignore",
+	".editorconfig":  "ini",
+	".gitignore":     "gitignore",
+	".profile":       "bash",
+	".zshrc":         "zsh",
+	"cmakelists.txt": "cmake",
+	"containerfile":  "dockerfile",
+	"dockerfile":     "dockerfile",
+	"gemfile":        "ruby",
+	"gnumakefile":    "makefile",
+	"jenkinsfile":    "groovy",
+	"justfile":       "just",
+	"makefile":       "makefile",
+	"rakefile":       "ruby",
+	"vagrantfile":    "ruby",
+}
+
+// shebangLanguages maps script interpreters to the language tag of their code fence.
+var shebangLanguages = map[string]string{
+	"ash":     "sh",
+	"awk":     "awk",
+	"bash":    "bash",
+	"dash":    "sh",
+	"deno":    "typescript",
+	"fish":    "fish",
+	"ksh":     "bash",
+	"lua":     "lua",
+	"node":    "javascript",
+	"perl":    "perl",
+	"php":     "php",
+	"pwsh":    "powershell",
+	"python":  "python",
+	"rscript": "r",
+	"ruby":    "ruby",
+	"sh":      "sh",
+	"zsh":     "zsh",
+}
+
+// fenceLanguage returns the code fence language tag for the file at path, from its name or
+// extension, or from the shebang of an extensionless script. Unknown files return "" so they
+// get a bare fence rather than a wrong language.
+func fenceLanguage(path, content string) string {
+	base := strings.ToLower(filepath.Base(path))
+	if language, ok := fenceFileNames[base]; ok {
+		return language
+	}
+	if base == ".env" || strings.HasPrefix(base, ".env.") {
+		return "dotenv"
+	}
+	if strings.HasPrefix(base, "dockerfile.") || strings.HasSuffix(base, ".dockerfile") {
+		return "dockerfile"
+	}
+
+	ext := filepath.Ext(base)
+	if ext == "" {
+		return shebangLanguage(content)
+	}
+	return fenceLanguages[ext]
+}
+
+// shebangLanguage returns the language of a script from its #! line, such as python for
+// "#!/usr/bin/env python3", or "" if it has none or the interpreter is unknown.
+func shebangLanguage(content string) string {
+	line, _, _ := strings.Cut(content, "\n")
+	line, ok := strings.CutPrefix(strings.TrimSpace(line), "#!")
+	if !ok {
+		return ""
+	}
+
+	fields := strings.Fields(line)
+	if len(fields) == 0 {
+		return ""
+	}
+	interpreter := filepath.Base(fields[0])
+	if interpreter == "env" {
+		// Skip env's own options, such as -S
+		interpreter = ""
+		for _, field := range fields[1:] {
+			if !strings.HasPrefix(field, "-") {
+				interpreter = field
+				break
+			}
+		}
+	}
+
+	// Drop version suffixes, so python3 and python3.12 are both python
+	interpreter = strings.TrimRight(strings.ToLower(interpreter), "0123456789.")
+	return shebangLanguages[interpreter]
+}
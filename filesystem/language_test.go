@@ -0,0 +1,62 @@
+package filesystem
+
+import "testing"
+
+func TestFenceLanguage(t *testing.T) {
+	tests := []struct {
+		path     string
+		content  string
+		expected string
+	}{
+		{"main.go", "package main", "go"},
+		{"src/App.TSX", "", "tsx"},
+		{"Dockerfile", "FROM alpine", "dockerfile"},
+		{"build/Dockerfile.prod", "FROM alpine", "dockerfile"},
+		{"Makefile", "all:", "makefile"},
+		{".env", "KEY=value", "dotenv"},
+		{".env.local", "KEY=value", "dotenv"},
+		{"bin/tool", "#!/usr/bin/env python3\nprint()", "python"},
+		{"bin/run", "#!/usr/bin/env -S deno run\n", "typescript"},
+		{"bin/setup", "#!/bin/bash\nset -e", "bash"},
+		{"bin/data", "no shebang here", ""},
+		{"notes.xyz", "", ""},
+	}
+
+	for _, tt := range tests {
+		if got := fenceLanguage(tt.path, tt.content); got != tt.expected {
+			t.Errorf("fenceLanguage(%q): expected %q, got %q", tt.path, tt.expected, got)
+		}
+	}
+}
+
+func TestGroupByLanguage(t *testing.T) {
+	files := []FileInfo{
+		{Path: "main.go", TokenCount: 10},
+		{Path: "schema.sql", TokenCount: 30},
+		{Path: "notes.xyz", TokenCount: 5},
+		{Path: "util.go", TokenCount: 25},
+	}
+
+	groups := GroupByLanguage(files)
+	expected := []struct {
+		language string
+		tokens   int
+		files    int
+	}{
+		{"Go", 35, 2},
+		{"SQL", 30, 1},
+		{"other", 5, 1},
+	}
+	if len(groups) != len(expected) {
+		t.Fatalf("Expected %d groups, got %d", len(expected), len(groups))
+	}
+	for i, want := range expected {
+		got := groups[i]
+		if got.Language != want.language || got.Tokens != want.tokens || len(got.Files) != want.files {
+			t.Errorf("Expected group %d to be %s with %d tokens in %d files, got %s with %d in %d", i, want.language, want.tokens, want.files, got.Language, got.Tokens, len(got.Files))
+		}
+	}
+	if groups[0].Files[0].Path != "main.go" {
+		t.Errorf("Expected files to keep their order within a language, got %s first", groups[0].Files[0].Path)
+	}
+}
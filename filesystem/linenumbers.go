@@ -0,0 +1,85 @@
+package filesystem
+
+import (
+	"fmt"
+	"sort"
+	"strconv"
+	"strings"
+)
+
+// elision is how the compressor marks a body it removed, such as "func main() { ... }".
+const elision = "{ ... }"
+
+// lineNumberWidth is the width line numbers up to last are right-aligned to, so the separators
+// line up through a file.
+func lineNumberWidth(last int) int {
+	return max(4, len(strconv.Itoa(last)))
+}
+
+// numberLine prefixes line with number, or with blanks if number is 0.
+func numberLine(line string, number, width int) string {
+	if number == 0 {
+		return fmt.Sprintf("%*s | %s", width, "", line)
+	}
+	return fmt.Sprintf("%*d | %s", width, number, line)
+}
+
+// addLineNumbers prefixes each line of code with its line number.
+func addLineNumbers(code string) string {
+	lines := strings.Split(code, "\n")
+	width := lineNumberWidth(len(lines))
+	for i := range lines {
+		lines[i] = numberLine(lines[i], i+1, width)
+	}
+	return strings.Join(lines, "\n")
+}
+
+// addSourceLineNumbers numbers the lines of code, a compressed view of source, with the numbers
+// of the lines of source they came from rather than renumbering them, so references to a line
+// stay accurate and the gaps show where lines were left out. Lines are matched to source in
+// order by their text, and a signature whose body was elided to the line the body started on.
+// Lines compression added, such as the "// -----" between chunks, are left unnumbered.
+func addSourceLineNumbers(code, source string) string {
+	sourceLines := strings.Split(source, "\n")
+	positions := make(map[string][]int)
+	for i, line := range sourceLines {
+		line = strings.TrimSpace(line)
+		sourceLines[i] = line
+		positions[line] = append(positions[line], i)
+	}
+
+	lines := strings.Split(code, "\n")
+	width := lineNumberWidth(len(sourceLines))
+	next := 0
+	for i, line := range lines {
+		number := matchSourceLine(strings.TrimSpace(line), sourceLines, positions, next)
+		if number > 0 {
+			next = number
+		}
+		lines[i] = numberLine(line, number, width)
+	}
+	return strings.Join(lines, "\n")
+}
+
+// matchSourceLine returns the number of the first line of source from index next that line
+// came from, or 0 if none did. positions holds the indexes of each line of source by its text.
+func matchSourceLine(line string, source []string, positions map[string][]int, next int) int {
+	if line == "" {
+		return 0
+	}
+	if i := strings.Index(line, elision); i > 0 {
+		signature := strings.TrimSpace(line[:i])
+		for n := next; n < len(source); n++ {
+			if strings.HasPrefix(source[n], signature) {
+				return n + 1
+			}
+		}
+		return 0
+	}
+
+	indexes := positions[line]
+	if i := sort.SearchInts(indexes, next); i < len(indexes) {
+		return indexes[i] + 1
+	}
+	return 0
+}
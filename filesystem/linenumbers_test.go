@@ -0,0 +1,30 @@
+package filesystem
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestAddSourceLineNumbers(t *testing.T) {
+	source := "package main\n\n// Run runs\nfunc Run(\n\tname string,\n) error {\n\treturn nil\n}\n\ntype T struct {\n\tA int\n}\n"
+	compressed := "// Run runs\nfunc Run(\n\tname string,\n) error { ... } // Body removed\n// -----\ntype T struct {\n\tA int\n}"
+
+	expected := "   3 | // Run runs\n" +
+		"   4 | func Run(\n" +
+		"   5 | \tname string,\n" +
+		"   6 | ) error { ... } // Body removed\n" +
+		"     | // -----\n" +
+		"  10 | type T struct {\n" +
+		"  11 | \tA int\n" +
+		"  12 | }"
+	if got := addSourceLineNumbers(compressed, source); got != expected {
+		t.Errorf("Expected:\n%s\ngot:\n%s", expected, got)
+	}
+}
+
+func TestAddLineNumbersWidth(t *testing.T) {
+	lines := addLineNumbers(strings.Repeat("x\n", 10000))
+	if got := lines[:10]; got != "    1 | x\n" {
+		t.Errorf("Expected numbers aligned to the widest, got %q", got)
+	}
+}
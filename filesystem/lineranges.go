@@ -0,0 +1,109 @@
+package filesystem
+
+import (
+	"fmt"
+	"slices"
+	"strconv"
+	"strings"
+)
+
+// LineRange is an inclusive range of lines, numbered from 1.
+type LineRange struct {
+	Start int
+	End   int
+}
+
+func (r LineRange) String() string {
+	if r.Start == r.End {
+		return strconv.Itoa(r.Start)
+	}
+	return fmt.Sprintf("%d-%d", r.Start, r.End)
+}
+
+// SplitLineRanges splits a path with a line range suffix, such as src/main.rs:40-120 or
+// src/main.rs:10-20,50-60, into the path and its ranges. It reports false if arg has no valid
+// suffix. A single number selects a single line.
+func SplitLineRanges(arg string) (string, []LineRange, bool) {
+	i := strings.LastIndex(arg, ":")
+	if i <= 0 {
+		return "", nil, false
+	}
+
+	var ranges []LineRange
+	for _, part := range strings.Split(arg[i+1:], ",") {
+		startText, endText, isRange := strings.Cut(part, "-")
+		if !isRange {
+			endText = startText
+		}
+		start, err := strconv.Atoi(startText)
+		if err != nil || start < 1 {
+			return "", nil, false
+		}
+		end, err := strconv.Atoi(endText)
+		if err != nil || end < start {
+			return "", nil, false
+		}
+		ranges = append(ranges, LineRange{Start: start, End: end})
+	}
+	return arg[:i], ranges, true
+}
+
+// mergeLineRanges sorts the ranges, clamps them to the file's line count and joins those that
+// overlap or touch.
+func mergeLineRanges(ranges []LineRange, total int) []LineRange {
+	sorted := slices.Clone(ranges)
+	slices.SortFunc(sorted, func(a, b LineRange) int { return a.Start - b.Start })
+
+	var merged []LineRange
+	for _, r := range sorted {
+		if r.Start > total {
+			continue
+		}
+		r.End = min(r.End, total)
+		if n := len(merged); n > 0 && r.Start <= merged[n-1].End+1 {
+			merged[n-1].End = max(merged[n-1].End, r.End)
+			continue
+		}
+		merged = append(merged, r)
+	}
+	return merged
+}
+
+// extractLines formats only the given lines of content, with a note of which lines they are
+// such as "(lines 40-120 of 900)". Line numbers, when enabled, are those of the original file
+// and separate ranges are divided by a "..." line. It returns the formatted code and the
+// number of lines included.
+func extractLines(content string, ranges []LineRange, language string, opts Options) (string, int) {
+	lines := strings.Split(strings.TrimSuffix(content, "\n"), "\n")
+	total := countLines(content)
+	ranges = mergeLineRanges(ranges, total)
+
+	var selected []string
+	var included int
+	width := lineNumberWidth(total)
+	labels := make([]string, len(ranges))
+	for i, r := range ranges {
+		labels[i] = r.String()
+		if i > 0 {
+			selected = append(selected, "...")
+		}
+		for n := r.Start; n <= r.End; n++ {
+			line := lines[n-1]
+			if opts.LineNumber {
+				line = numberLine(line, n, width)
+			}
+			selected = append(selected, line)
+			included++
+		}
+	}
+
+	code := strings.Join(selected, "\n")
+	if !opts.NoCodeblock {
+		code = wrapCodeBlock(code, language, opts.Fence)
+	}
+	note := fmt.Sprintf("(lines %s of %d)", strings.Join(labels, ", "), total)
+	if len(ranges) == 0 {
+		note = fmt.Sprintf("(no lines in range, the file has %d)", total)
+	}
+	return note + "\n" + code, included
+}
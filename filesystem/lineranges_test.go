@@ -0,0 +1,51 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestSplitLineRanges(t *testing.T) {
+	path, ranges, ok := SplitLineRanges("src/main.rs:10-20,50-60,99")
+	if !ok || path != "src/main.rs" {
+		t.Fatalf("Expected src/main.rs with ranges, got %q (%v)", path, ok)
+	}
+	if want := []LineRange{{10, 20}, {50, 60}, {99, 99}}; !reflect.DeepEqual(ranges, want) {
+		t.Errorf("Expected %v, got %v", want, ranges)
+	}
+
+	for _, arg := range []string{"src/main.rs", "src/main.rs:", "src/main.rs:20-10", "src/main.rs:0-5", `C:\src\main.rs`, ":10-20"} {
+		if _, _, ok := SplitLineRanges(arg); ok {
+			t.Errorf("Expected %q not to have a line range", arg)
+		}
+	}
+}
+
+func TestProcessPathsLineRanges(t *testing.T) {
+	root := t.TempDir()
+	path := filepath.Join(root, "main.go")
+	var content strings.Builder
+	for i := range 10 {
+		content.WriteString("line" + string(rune('0'+i)) + "\n")
+	}
+	writeTestFile(t, path, content.String())
+
+	opts := Options{
+		LineNumber: true,
+		LineRanges: map[string][]LineRange{path: {{8, 20}, {2, 3}, {3, 4}}},
+	}
+	_, files, _, err := ProcessPaths([]string{path}, opts)
+	if err != nil {
+		t.Fatalf("ProcessPaths failed: %v", err)
+	}
+
+	expected := "(lines 2-4, 8-10 of 10)\n```go\n   2 | line1\n   3 | line2\n   4 | line3\n...\n   8 | line7\n   9 | line8\n  10 | line9\n```"
+	if files[0].Code != expected {
+		t.Errorf("Expected:\n%s\ngot:\n%s", expected, files[0].Code)
+	}
+	if files[0].Lines != 6 {
+		t.Errorf("Expected 6 lines, got %d", files[0].Lines)
+	}
+}
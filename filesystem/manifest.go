@@ -0,0 +1,120 @@
+package filesystem
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"slices"
+	"strings"
+	"sync"
+)
+
+// The statuses of the paths in a Manifest.
+const (
+	StatusIncluded     = "included"
+	StatusUnread       = "unread" // Found by a dry run, which doesn't read files, so it may yet be skipped
+	StatusBinary       = "skipped:binary"
+	StatusTooLarge     = "skipped:too-large"
+	StatusGitignore    = "skipped:gitignore"     // A .gitignore, .ignore or .ingestignore file, or git's exclude files
+	StatusExcluded     = "skipped:excluded"      // An exclude pattern, including the default excludes
+	StatusNotIncluded  = "skipped:not-included"  // Include patterns were given and none match
+	StatusExportIgnore = "skipped:export-ignore" // Marked export-ignore in a .gitattributes file
+	StatusTests        = "skipped:tests"         // Left out by ExcludeTests or OnlyTests
+	StatusNotModified  = "skipped:not-modified"  // Not modified since Options.Since
+	StatusNotSelected  = "skipped:not-selected"  // Not one of Options.OnlyPaths, such as an unchanged file with --diff
+	StatusSymlink      = "skipped:symlink"
+	StatusDirectory    = "skipped:directory" // A directory given where a file was expected
+	StatusExecFilter   = "skipped:exec-filter"
+	StatusUnsupported  = "skipped:unsupported" // Content that can't be converted, such as a PDF without text
+	StatusBudget       = "skipped:budget"      // Dropped to fit a token budget
+	StatusNotFound     = "error:not-found"
+	StatusUnreadable   = "error:unreadable"
+)
+
+// ManifestEntry is a path and what was decided about it.
+type ManifestEntry struct {
+	Path      string `json:"path"`
+	Status    string `json:"status"`
+	Reason    string `json:"reason,omitempty"`    // The detail of a skip, such as the symlink problem
+	Directory bool   `json:"directory,omitempty"` // A directory left out whole, so its contents weren't walked
+	Tokens    int    `json:"tokens,omitempty"`    // The tokens of an included or budget-dropped file
+}
+
+// Manifest records every path walks come across and whether it was included, or why not. It
+// is safe for concurrent use, and a nil Manifest records nothing.
+type Manifest struct {
+	mu      sync.Mutex
+	entries map[string]ManifestEntry
+}
+
+// NewManifest returns an empty Manifest.
+func NewManifest() *Manifest {
+	return &Manifest{entries: make(map[string]ManifestEntry)}
+}
+
+// Add records entry, replacing any entry for the same path, as when a file that was read is
+// later dropped to fit a token budget.
+func (m *Manifest) Add(entry ManifestEntry) {
+	if m == nil {
+		return
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.entries[entry.Path] = entry
+}
+
+// Entries returns the recorded entries, sorted by path.
+func (m *Manifest) Entries() []ManifestEntry {
+	if m == nil {
+		return nil
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	entries := make([]ManifestEntry, 0, len(m.entries))
+	for _, entry := range m.entries {
+		entries = append(entries, entry)
+	}
+	slices.SortFunc(entries, func(a, b ManifestEntry) int { return strings.Compare(a.Path, b.Path) })
+	return entries
+}
+
+// WriteFile writes the manifest to path as a JSON document of its entries and how many there
+// are of each status.
+func (m *Manifest) WriteFile(path string) error {
+	entries := m.Entries()
+	counts := make(map[string]int)
+	for _, entry := range entries {
+		counts[entry.Status]++
+	}
+	data, err := json.MarshalIndent(struct {
+		Files  []ManifestEntry `json:"files"`
+		Counts map[string]int  `json:"counts"`
+	}{entries, counts}, "", "  ")
+	if err != nil {
+		return fmt.Errorf("failed to encode manifest: %w", err)
+	}
+	if err := os.WriteFile(path, append(data, '\n'), 0644); err != nil {
+		return fmt.Errorf("failed to write manifest: %w", err)
+	}
+	return nil
+}
+
+// skipStatus returns the status of a file skipped once it was read, for the reason it was.
+func skipStatus(reason string) string {
+	switch {
+	case reason == "binary":
+		return StatusBinary
+	case strings.HasPrefix(reason, "exec filter failed"):
+		return StatusExecFilter
+	default:
+		return StatusUnsupported
+	}
+}
+
+// exclusionStatus returns the status of a path shouldExcludePath excluded.
+func exclusionStatus(path string, excludePatterns []string) string {
+	if matchesAny(path, excludePatterns) {
+		return StatusExcluded
+	}
+	return StatusGitignore
+}
@@ -0,0 +1,48 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestWalkDirectoryManifest(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, ".gitignore"), "ignored.txt\n")
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(root, "ignored.txt"), "ignored\n")
+	writeTestFile(t, filepath.Join(root, "image.bin"), "\x00\x01\x02")
+	writeTestFile(t, filepath.Join(root, "big.txt"), strings.Repeat("x", 100))
+	writeTestFile(t, filepath.Join(root, "build", "out.js"), "x\n")
+
+	manifest := NewManifest()
+	_, _, _, err := WalkDirectory(root, Options{
+		ExcludePatterns:   []string{"build"},
+		NoDefaultExcludes: true,
+		RelativePaths:     true,
+		MaxFileSize:       50,
+		Manifest:          manifest,
+	})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	base := filepath.Base(root)
+	var got []ManifestEntry
+	for _, entry := range manifest.Entries() {
+		entry.Path, _ = filepath.Rel(base, entry.Path)
+		got = append(got, entry)
+	}
+	expected := []ManifestEntry{
+		{Path: ".gitignore", Status: StatusIncluded},
+		{Path: "big.txt", Status: StatusTooLarge},
+		{Path: "build", Status: StatusExcluded, Directory: true},
+		{Path: "ignored.txt", Status: StatusGitignore},
+		{Path: "image.bin", Status: StatusBinary, Reason: "binary"},
+		{Path: "main.go", Status: StatusIncluded},
+	}
+	if !reflect.DeepEqual(got, expected) {
+		t.Errorf("Expected %+v, got %+v", expected, got)
+	}
+}
@@ -0,0 +1,41 @@
+package filesystem
+
+import (
+	"fmt"
+	"time"
+)
+
+// FileMetadata is the provenance of a file, shown on a line of its own above its contents.
+type FileMetadata struct {
+	Size     int64  `json:"size"`               // Bytes on disk, before any formatting or compression
+	Modified string `json:"modified,omitempty"` // Last modification time in RFC 3339, in UTC
+	Mode     string `json:"mode"`               // Permission bits in octal, such as 0644
+}
+
+// String formats the metadata as one line of key=value pairs, such as
+// size=1024 modified=2025-01-02T03:04:05Z mode=0644.
+func (m FileMetadata) String() string {
+	line := fmt.Sprintf("size=%d", m.Size)
+	if m.Modified != "" {
+		line += " modified=" + m.Modified
+	}
+	return line + " mode=" + m.Mode
+}
+
+// fileMetadata returns the metadata of the file at path if FileMetadata is set, or nil if it
+// isn't or the file can't be read.
+func (o Options) fileMetadata(path string) *FileMetadata {
+	if !o.FileMetadata {
+		return nil
+	}
+	info, err := o.fsys().Stat(path)
+	if err != nil {
+		return nil
+	}
+
+	meta := &FileMetadata{Size: info.Size(), Mode: fmt.Sprintf("%04o", info.Mode().Perm())}
+	if !o.NoModTime && !info.ModTime().IsZero() {
+		meta.Modified = info.ModTime().UTC().Format(time.RFC3339)
+	}
+	return meta
+}
@@ -0,0 +1,48 @@
+package filesystem
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+)
+
+func TestFileMetadata(t *testing.T) {
+	root := t.TempDir()
+	path := filepath.Join(root, "main.go")
+	writeTestFile(t, path, "package main\n")
+	if err := os.Chmod(path, 0640); err != nil {
+		t.Fatalf("Failed to chmod: %v", err)
+	}
+	modified := time.Date(2025, 1, 2, 3, 4, 5, 0, time.UTC)
+	if err := os.Chtimes(path, modified, modified); err != nil {
+		t.Fatalf("Failed to set modification time: %v", err)
+	}
+
+	file, err := ProcessSingleFile(path, Options{FileMetadata: true})
+	if err != nil {
+		t.Fatalf("ProcessSingleFile failed: %v", err)
+	}
+	if file.Metadata == nil {
+		t.Fatal("Expected metadata to be set")
+	}
+	if got, want := file.Metadata.String(), "size=13 modified=2025-01-02T03:04:05Z mode=0640"; got != want {
+		t.Errorf("Expected metadata %q, got %q", want, got)
+	}
+
+	file, err = ProcessSingleFile(path, Options{FileMetadata: true, NoModTime: true})
+	if err != nil {
+		t.Fatalf("ProcessSingleFile failed: %v", err)
+	}
+	if got, want := file.Metadata.String(), "size=13 mode=0640"; got != want {
+		t.Errorf("Expected metadata %q with --no-mtime, got %q", want, got)
+	}
+
+	file, err = ProcessSingleFile(path, Options{})
+	if err != nil {
+		t.Fatalf("ProcessSingleFile failed: %v", err)
+	}
+	if file.Metadata != nil {
+		t.Errorf("Expected no metadata unless it is asked for, got %v", file.Metadata)
+	}
+}
@@ -0,0 +1,49 @@
+package filesystem
+
+import (
+	"fmt"
+	"path/filepath"
+	"strings"
+)
+
+// MinifiedThresholds are the limits past which a file is taken to be minified, like a bundled
+// JavaScript or CSS file. Its content is then replaced with a one-line stub, as its few
+// enormous lines use up a token budget while giving an LLM nothing it can read. A file must
+// pass both limits, and files smaller than minifiedMinSize never do.
+type MinifiedThresholds struct {
+	LineLength   int     // Least average length of the file's non-blank lines, in bytes
+	NewlineRatio float64 // Greatest share of the file's bytes that are newlines
+}
+
+// DefaultMinifiedThresholds are the thresholds used by --collapse-minified.
+var DefaultMinifiedThresholds = MinifiedThresholds{LineLength: 300, NewlineRatio: 0.01}
+
+// minifiedMinSize is the size in bytes below which a file isn't treated as minified, so a short
+// file with a single long line is left alone.
+const minifiedMinSize = 2048
+
+// isMinified reports whether content looks minified under the thresholds.
+func isMinified(content string, t *MinifiedThresholds) bool {
+	if t == nil || len(content) < minifiedMinSize {
+		return false
+	}
+
+	newlines := strings.Count(content, "\n")
+	if float64(newlines)/float64(len(content)) > t.NewlineRatio {
+		return false
+	}
+
+	lines, size := 0, 0
+	for line := range strings.SplitSeq(content, "\n") {
+		if line = strings.TrimSpace(line); line != "" {
+			lines++
+			size += len(line)
+		}
+	}
+	return lines > 0 && size/lines >= t.LineLength
+}
+
+// minifiedStub is the one line that replaces the content of a minified file.
+func minifiedStub(relPath string, size int) string {
+	return fmt.Sprintf("<minified: %s, %d bytes, omitted>", filepath.ToSlash(relPath), size)
+}
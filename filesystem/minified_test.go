@@ -0,0 +1,43 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestWalkDirectoryMinified(t *testing.T) {
+	root := t.TempDir()
+	bundle := strings.Repeat("var a=function(){return 1};", 200) + "\n"
+	writeTestFile(t, filepath.Join(root, "app.min.js"), bundle)
+	writeTestFile(t, filepath.Join(root, "app.js"), strings.Repeat("function a() {\n\treturn 1\n}\n\n", 200))
+	writeTestFile(t, filepath.Join(root, "short.js"), strings.Repeat("x", 400)+"\n")
+
+	_, files, _, err := WalkDirectory(root, Options{NoCodeblock: true, Minified: &DefaultMinifiedThresholds})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	codes := make(map[string]string)
+	for _, file := range files {
+		codes[file.RelPath] = file.Code
+	}
+	if want := "<minified: app.min.js, 5401 bytes, omitted>"; codes["app.min.js"] != want {
+		t.Errorf("Expected %q, got %q", want, codes["app.min.js"])
+	}
+	for _, path := range []string{"app.js", "short.js"} {
+		if strings.HasPrefix(codes[path], "<minified") {
+			t.Errorf("Expected %s not to be treated as minified", path)
+		}
+	}
+
+	// Raising the threshold keeps the bundle
+	_, files, _, err = WalkDirectory(root, Options{NoCodeblock: true, Minified: &MinifiedThresholds{LineLength: 10000, NewlineRatio: 0.01}})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	for _, file := range files {
+		if strings.HasPrefix(file.Code, "<minified") {
+			t.Errorf("Expected no minified files with a higher threshold, got %s", file.RelPath)
+		}
+	}
+}
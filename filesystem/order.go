@@ -0,0 +1,158 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"sort"
+	"strings"
+
+	"github.com/sammcj/ingest/utils"
+)
+
+// orderRank returns the index of the first --order glob matching the slash separated path, or
+// len(order) if none do.
+func orderRank(path string, order []string) int {
+	for i, pattern := range order {
+		if utils.MatchPathSuffix(pattern, path) {
+			return i
+		}
+	}
+	return len(order)
+}
+
+// sortJobs orders jobs the same way the tree is printed when --order is set: at each level the
+// entry containing the earliest matching file comes first, then directories before files, then
+// by name. This keeps the file contents in the same order as the tree.
+func sortJobs(jobs []fileJob, order []string) {
+	// The rank of a directory is the best rank of any file beneath it
+	ranks := make(map[string]int)
+	paths := make([][]string, len(jobs))
+	for i, job := range jobs {
+		slashPath := filepath.ToSlash(job.relPath)
+		paths[i] = strings.Split(slashPath, "/")
+		rank := orderRank(slashPath, order)
+		for j := range paths[i] {
+			prefix := strings.Join(paths[i][:j+1], "/")
+			if existing, ok := ranks[prefix]; !ok || rank < existing {
+				ranks[prefix] = rank
+			}
+		}
+	}
+
+	sorted := make([]int, len(jobs))
+	for i := range sorted {
+		sorted[i] = i
+	}
+	sort.SliceStable(sorted, func(a, b int) bool {
+		pa, pb := paths[sorted[a]], paths[sorted[b]]
+		for level := range min(len(pa), len(pb)) {
+			if pa[level] == pb[level] {
+				continue
+			}
+			ra := ranks[strings.Join(pa[:level+1], "/")]
+			rb := ranks[strings.Join(pb[:level+1], "/")]
+			if ra != rb {
+				return ra < rb
+			}
+			aDir, bDir := level < len(pa)-1, level < len(pb)-1
+			if aDir != bDir {
+				return aDir
+			}
+			return pa[level] < pb[level]
+		}
+		return len(pa) < len(pb)
+	})
+
+	reordered := make([]fileJob, len(jobs))
+	for i, j := range sorted {
+		reordered[i] = jobs[j]
+	}
+	copy(jobs, reordered)
+}
+
+// rankTree sets the rank of each node to the best --order rank of the files beneath it, so the
+// tree is printed in the same order as the file contents. Excluded files don't affect the rank.
+func rankTree(node *treeNode, path string, order []string) int {
+	if !node.isDir {
+		node.rank = len(order)
+		if !node.excluded {
+			node.rank = orderRank(path, order)
+		}
+		return node.rank
+	}
+
+	node.rank = len(order)
+	for _, child := range node.children {
+		childPath := child.name
+		if path != "" {
+			childPath = path + "/" + child.name
+		}
+		node.rank = min(node.rank, rankTree(child, childPath, order))
+	}
+	return node.rank
+}
+
+// sortTreeNodes orders sibling nodes by rank, then directories before files, then by name.
+func sortTreeNodes(nodes []*treeNode) {
+	sort.Slice(nodes, func(i, j int) bool {
+		if nodes[i].rank != nodes[j].rank {
+			return nodes[i].rank < nodes[j].rank
+		}
+		if nodes[i].isDir != nodes[j].isDir {
+			return nodes[i].isDir
+		}
+		return nodes[i].name < nodes[j].name
+	})
+}
+
+// docRank returns where a documentation file is listed among the others in its directory with
+// readme first: READMEs, then CONTRIBUTING files, then other Markdown files. It returns false
+// if the file isn't documentation.
+func docRank(name string) (int, bool) {
+	upper := strings.ToUpper(name)
+	switch {
+	case strings.HasPrefix(upper, "README"):
+		return 0, true
+	case strings.HasPrefix(upper, "CONTRIBUTING"):
+		return 1, true
+	case strings.EqualFold(filepath.Ext(name), ".md"):
+		return 2, true
+	default:
+		return 0, false
+	}
+}
+
+// readmeFirst moves the documentation files of each directory ahead of its other files, where
+// the first file of the directory was. Files otherwise keep their order, and the tree isn't
+// affected.
+func readmeFirst(jobs []fileJob) {
+	docs := make(map[string][]fileJob)
+	for _, job := range jobs {
+		if _, ok := docRank(filepath.Base(job.relPath)); ok {
+			dir := filepath.Dir(job.relPath)
+			docs[dir] = append(docs[dir], job)
+		}
+	}
+	if len(docs) == 0 {
+		return
+	}
+
+	reordered := make([]fileJob, 0, len(jobs))
+	placed := make(map[string]bool)
+	for _, job := range jobs {
+		dir := filepath.Dir(job.relPath)
+		if !placed[dir] {
+			placed[dir] = true
+			dirDocs := docs[dir]
+			sort.SliceStable(dirDocs, func(i, j int) bool {
+				ri, _ := docRank(filepath.Base(dirDocs[i].relPath))
+				rj, _ := docRank(filepath.Base(dirDocs[j].relPath))
+				return ri < rj
+			})
+			reordered = append(reordered, dirDocs...)
+		}
+		if _, ok := docRank(filepath.Base(job.relPath)); !ok {
+			reordered = append(reordered, job)
+		}
+	}
+	copy(jobs, reordered)
+}
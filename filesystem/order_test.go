@@ -0,0 +1,81 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestWalkDirectoryOrder(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "Cargo.toml"), "[package]\n")
+	writeTestFile(t, filepath.Join(root, "README.md"), "# readme\n")
+	writeTestFile(t, filepath.Join(root, "docs", "guide.md"), "guide\n")
+	writeTestFile(t, filepath.Join(root, "src", "main.rs"), "fn main() {}\n")
+	writeTestFile(t, filepath.Join(root, "src", "lib.rs"), "pub fn lib() {}\n")
+
+	tree, files, _, err := WalkDirectory(root, Options{
+		NoDefaultExcludes: true,
+		RelativePaths:     true,
+		Order:             []string{"README*", "src/**", "*.toml"},
+	})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	base := filepath.Base(root)
+	expected := []string{
+		base + "/README.md",
+		base + "/src/lib.rs",
+		base + "/src/main.rs",
+		base + "/Cargo.toml",
+		base + "/docs/guide.md",
+	}
+	if len(files) != len(expected) {
+		t.Fatalf("Expected %d files, got %d", len(expected), len(files))
+	}
+	for i := range expected {
+		if got := filepath.ToSlash(files[i].Path); got != expected[i] {
+			t.Errorf("Expected file %d to be %s, got %s", i, expected[i], got)
+		}
+	}
+
+	// The tree lists the top level entries in the same order
+	var positions []int
+	for _, name := range []string{"README.md", "src/", "Cargo.toml", "docs/"} {
+		pos := strings.Index(tree, name)
+		if pos < 0 {
+			t.Fatalf("Expected %s in tree:\n%s", name, tree)
+		}
+		positions = append(positions, pos)
+	}
+	for i := 1; i < len(positions); i++ {
+		if positions[i] < positions[i-1] {
+			t.Errorf("Expected the tree to follow --order, got:\n%s", tree)
+			break
+		}
+	}
+}
+
+func TestWalkDirectoryReadmeFirst(t *testing.T) {
+	root := t.TempDir()
+	for _, name := range []string{"CONTRIBUTING.md", "README.md", "a.go", "notes.md", "pkg/a.go", "pkg/guide.md", "z.go"} {
+		writeTestFile(t, filepath.Join(root, filepath.FromSlash(name)), name+"\n")
+	}
+
+	_, files, _, err := WalkDirectory(root, Options{NoDefaultExcludes: true, RelativePaths: true, ReadmeFirst: true})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	base := filepath.Base(root)
+	expected := []string{"README.md", "CONTRIBUTING.md", "notes.md", "a.go", "pkg/guide.md", "pkg/a.go", "z.go"}
+	if len(files) != len(expected) {
+		t.Fatalf("Expected %d files, got %d", len(expected), len(files))
+	}
+	for i := range expected {
+		if got := filepath.ToSlash(files[i].Path); got != base+"/"+expected[i] {
+			t.Errorf("Expected file %d to be %s, got %s", i, expected[i], got)
+		}
+	}
+}
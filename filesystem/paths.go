@@ -0,0 +1,158 @@
+package filesystem
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// ProcessPaths ingests an explicit list of files, such as paths piped in with --stdin, and
+// builds a tree of just those files below their closest common directory. Relative paths are
+// resolved against the working directory. Paths that don't exist or aren't files are reported
+// in the returned ExcludedInfo's Skipped list rather than failing the run. The returned tree
+// includes its root directory as a header.
+func ProcessPaths(paths []string, opts Options) (string, []FileInfo, *ExcludedInfo, error) {
+	excluded := &ExcludedInfo{
+		Directories: make(map[string]int),
+		Extensions:  make(map[string]int),
+		Files:       make([]string, 0),
+	}
+
+	cwd, err := os.Getwd()
+	if err != nil {
+		return "", nil, nil, fmt.Errorf("failed to get current directory: %w", err)
+	}
+
+	var absPaths []string
+	seen := make(map[string]bool)
+	for _, path := range paths {
+		absPath := filepath.Clean(path)
+		if !filepath.IsAbs(absPath) {
+			absPath = filepath.Join(cwd, absPath)
+		}
+		if seen[absPath] {
+			continue
+		}
+		seen[absPath] = true
+		if opts.PathBase != "" && !IsWithin(opts.PathBase, absPath) {
+			return "", nil, nil, fmt.Errorf("%s is not below the directory paths are shown relative to, %s", path, opts.PathBase)
+		}
+
+		info, err := os.Stat(absPath)
+		record := func(status string) {
+			opts.Manifest.Add(ManifestEntry{Path: opts.DisplayPath(absPath, path), Status: status})
+		}
+		switch {
+		case err != nil:
+			excluded.Skipped = append(excluded.Skipped, SkippedFile{Path: path, Reason: "not found"})
+			record(StatusNotFound)
+		case info.IsDir():
+			excluded.Skipped = append(excluded.Skipped, SkippedFile{Path: path, Reason: "directory"})
+			record(StatusDirectory)
+		case !opts.Since.IsZero() && !info.ModTime().After(opts.Since):
+			excluded.NotModified++
+			record(StatusNotModified)
+		case opts.MaxFileSize > 0 && info.Size() > opts.MaxFileSize:
+			excluded.TooLarge = append(excluded.TooLarge, SkippedFile{Path: absPath, Reason: "too large", Size: info.Size()})
+			record(StatusTooLarge)
+		default:
+			absPaths = append(absPaths, absPath)
+		}
+	}
+
+	if len(absPaths) == 0 {
+		return "", nil, excluded, nil
+	}
+
+	rootPath := commonDir(absPaths)
+	jobs := make([]fileJob, len(absPaths))
+	root := &treeNode{name: filepath.Base(rootPath), isDir: true}
+	for i, absPath := range absPaths {
+		relPath, err := filepath.Rel(rootPath, absPath)
+		if err != nil {
+			return "", nil, nil, err
+		}
+		jobs[i] = fileJob{path: absPath, relPath: relPath, rootPath: rootPath}
+		addTreePath(root, strings.Split(filepath.ToSlash(relPath), "/"))
+	}
+
+	// List the files in tree order rather than the order they were given in
+	sortJobs(jobs, opts.Order)
+	if len(opts.Order) == 0 && opts.ReadmeFirst {
+		readmeFirst(jobs)
+	}
+	sortTreeNodes(root.children)
+
+	files, relPaths, skipped := processFiles(jobs, opts)
+	excluded.Skipped = append(excluded.Skipped, skipped...)
+	if err := opts.ExecFilter.Err(); err != nil {
+		return "", nil, excluded, err
+	}
+
+	var stats map[string]fileStats
+	if opts.TreeStats {
+		stats = make(map[string]fileStats, len(files))
+		for i, file := range files {
+			stats[filepath.ToSlash(relPaths[i])] = fileStats{lines: file.Lines, language: languageName(relPaths[i]), tokens: file.TokenCount}
+		}
+	}
+
+	tree := fmt.Sprintf("%s:\n%s", opts.DisplayPath(rootPath, filepath.Base(rootPath)), renderTree(root, false, opts.Order, stats))
+
+	return tree, files, excluded, nil
+}
+
+// addTreePath adds the file at the slash separated parts below root.
+func addTreePath(root *treeNode, parts []string) {
+	current := root
+	for i, part := range parts {
+		var next *treeNode
+		for _, child := range current.children {
+			if child.name == part {
+				next = child
+				break
+			}
+		}
+		if next == nil {
+			next = &treeNode{name: part, isDir: i < len(parts)-1}
+			current.children = append(current.children, next)
+		}
+		current = next
+	}
+}
+
+// commonDir returns the deepest directory containing all of the absolute file paths.
+func commonDir(paths []string) string {
+	dir := filepath.Dir(paths[0])
+	for _, path := range paths[1:] {
+		for !IsWithin(dir, path) {
+			parent := filepath.Dir(dir)
+			if parent == dir {
+				break
+			}
+			dir = parent
+		}
+	}
+	return dir
+}
+
+// IsWithin reports whether the absolute path is root or below it.
+func IsWithin(root, path string) bool {
+	rel, err := filepath.Rel(root, path)
+	return err == nil && rel != ".." && !strings.HasPrefix(rel, ".."+string(filepath.Separator))
+}
+
+// DisplayPath returns how the absolute path is shown in the output: relative to PathBase if it
+// is set, as relative (the path shown with RelativePaths) if that is set, otherwise as is.
+func (o Options) DisplayPath(path, relative string) string {
+	if o.PathBase != "" {
+		if rel, err := filepath.Rel(o.PathBase, path); err == nil {
+			return rel
+		}
+	}
+	if o.RelativePaths {
+		return relative
+	}
+	return path
+}
@@ -0,0 +1,84 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestProcessPaths(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "src", "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(root, "src", "util", "strings.go"), "package util\n")
+	writeTestFile(t, filepath.Join(root, "README.md"), "# readme\n")
+	writeTestFile(t, filepath.Join(root, "other.go"), "package other\n")
+
+	paths := []string{
+		filepath.Join(root, "src", "util", "strings.go"),
+		filepath.Join(root, "README.md"),
+		filepath.Join(root, "missing.go"),
+		filepath.Join(root, "src", "main.go"),
+	}
+	tree, files, excluded, err := ProcessPaths(paths, Options{RelativePaths: true})
+	if err != nil {
+		t.Fatalf("ProcessPaths failed: %v", err)
+	}
+
+	base := filepath.Base(root)
+	expected := []string{base + "/src/util/strings.go", base + "/src/main.go", base + "/README.md"}
+	if len(files) != len(expected) {
+		t.Fatalf("Expected %d files, got %d", len(expected), len(files))
+	}
+	for i := range expected {
+		if got := filepath.ToSlash(files[i].Path); got != expected[i] {
+			t.Errorf("Expected file %d to be %s, got %s", i, expected[i], got)
+		}
+	}
+
+	if strings.Contains(tree, "other.go") {
+		t.Errorf("Expected the tree to only contain the given paths, got:\n%s", tree)
+	}
+	if !strings.HasPrefix(tree, base+":\n") {
+		t.Errorf("Expected the tree to start with the common directory, got:\n%s", tree)
+	}
+
+	if len(excluded.Skipped) != 1 || excluded.Skipped[0].Reason != "not found" {
+		t.Errorf("Expected missing.go to be reported as not found, got %v", excluded.Skipped)
+	}
+}
+
+func TestCommonDir(t *testing.T) {
+	root := filepath.FromSlash("/repo")
+	paths := []string{
+		filepath.Join(root, "src", "a.go"),
+		filepath.Join(root, "src", "lib", "b.go"),
+		filepath.Join(root, "docs", "c.md"),
+	}
+	if got := commonDir(paths); got != root {
+		t.Errorf("Expected %s, got %s", root, got)
+	}
+	if got := commonDir(paths[:2]); got != filepath.Join(root, "src") {
+		t.Errorf("Expected %s, got %s", filepath.Join(root, "src"), got)
+	}
+}
+
+func TestWalkDirectoryRelativeTo(t *testing.T) {
+	project := t.TempDir()
+	src := filepath.Join(project, "src")
+	writeTestFile(t, filepath.Join(src, "pkg", "lib.go"), "package pkg\n")
+
+	_, files, _, err := WalkDirectory(src, Options{NoDefaultExcludes: true, PathBase: project})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	if len(files) != 1 || filepath.ToSlash(files[0].Path) != "src/pkg/lib.go" {
+		t.Errorf("Expected the path relative to the project, got %+v", files)
+	}
+	if files[0].RelPath != "pkg/lib.go" {
+		t.Errorf("Expected RelPath to stay relative to the ingested directory, got %s", files[0].RelPath)
+	}
+
+	if _, _, _, err := ProcessPaths([]string{filepath.Join(src, "pkg", "lib.go")}, Options{PathBase: filepath.Join(project, "other")}); err == nil {
+		t.Error("Expected an error for paths outside the base")
+	}
+}
@@ -0,0 +1,32 @@
+package filesystem
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestReadPatternFile(t *testing.T) {
+	path := filepath.Join(t.TempDir(), ".ingest-excludes")
+	content := "# Build output\ndist/**  \n\n   \n*.min.js\t\n  # indented comment\nvendor/**\n"
+	if err := os.WriteFile(path, []byte(content), 0644); err != nil {
+		t.Fatal(err)
+	}
+
+	patterns, err := ReadPatternFile(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if want := []string{"dist/**", "*.min.js", "vendor/**"}; !reflect.DeepEqual(patterns, want) {
+		t.Errorf("Expected %v, got %v", want, patterns)
+	}
+
+	if err := os.WriteFile(path, []byte("*.go\nsrc/[\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+	if _, err := ReadPatternFile(path); err == nil || !strings.Contains(err.Error(), ":2:") {
+		t.Errorf("Expected an error naming line 2, got %v", err)
+	}
+}
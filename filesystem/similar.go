@@ -0,0 +1,159 @@
+package filesystem
+
+import (
+	"fmt"
+	"hash/fnv"
+	"math"
+	"regexp"
+	"slices"
+)
+
+// DefaultSimilarityThreshold is the estimated share of a file's content that must match an
+// earlier file's for CollapseSimilar to collapse it.
+const DefaultSimilarityThreshold = 0.9
+
+const (
+	// similarShingleSize is the number of tokens in each shingle files are compared by.
+	similarShingleSize = 5
+
+	// similarMinTokens is the fewest tokens a file needs to be compared, as smaller files are
+	// cheap to keep and too short to estimate the similarity of.
+	similarMinTokens = 50
+
+	// A signature is split into bands of rows, and only files that share a band are compared.
+	// With 32 bands of 4 rows, files that are 60% the same share one 99% of the time, and files
+	// that are half the same 87% of the time, which is why thresholds start at 0.5.
+	similarBands = 32
+	similarRows  = 4
+)
+
+// similarTokenRegex matches the tokens files are compared by: words, numbers and single
+// punctuation characters, so that whitespace and formatting differences are ignored.
+var similarTokenRegex = regexp.MustCompile(`[A-Za-z_][A-Za-z0-9_]*|[0-9]+|[^\sA-Za-z0-9_]`)
+
+// signature is a MinHash signature of a file's shingles. The share of positions two signatures
+// have the same value at estimates the Jaccard similarity of the files' sets of shingles.
+type signature [similarBands * similarRows]uint64
+
+// similarSeeds are the seeds of the signature's hash functions, the same on every run.
+var similarSeeds = func() signature {
+	var seeds signature
+	state := uint64(0x9e3779b97f4a7c15)
+	for i := range seeds {
+		state += 0x9e3779b97f4a7c15
+		seeds[i] = mix64(state)
+	}
+	return seeds
+}()
+
+// CollapseSimilar replaces the code of each file that is at least threshold similar to an
+// earlier file in files with a note naming that file, so near-copies such as generated mocks
+// and protobuf outputs are only shown once. Similarity is estimated with MinHash over
+// shingles of each file's tokens, with numbers treated as the same, and each file is compared
+// with the earlier files that kept their code, so the first file of a group is the one shown
+// and the result only depends on the order of files. Files too small to compare are left as
+// they are. Collapsed files have their token counts and annotations cleared, so the note is
+// counted in their place. It returns how many files were collapsed.
+func CollapseSimilar(files []FileInfo, threshold float64) int {
+	signatures := make(map[int]*signature)
+	buckets := make([]map[[similarRows]uint64][]int, similarBands)
+	for band := range buckets {
+		buckets[band] = make(map[[similarRows]uint64][]int)
+	}
+
+	collapsed := 0
+	for i := range files {
+		sig, ok := fileSignature(files[i].Code)
+		if !ok {
+			continue
+		}
+
+		// The files sharing any band with this one are the candidates, compared in order
+		var candidates []int
+		for band := range buckets {
+			candidates = append(candidates, buckets[band][sig.band(band)]...)
+		}
+		slices.Sort(candidates)
+		best, bestSimilarity := -1, 0.0
+		for _, candidate := range slices.Compact(candidates) {
+			if similarity := sig.similarity(signatures[candidate]); similarity > bestSimilarity {
+				best, bestSimilarity = candidate, similarity
+			}
+		}
+
+		if best >= 0 && bestSimilarity >= threshold {
+			files[i].Code = similarStub(files[best].Path, bestSimilarity)
+			files[i].TokenCount = 0
+			files[i].RawTokens = 0
+			files[i].Annotations = nil
+			collapsed++
+			continue
+		}
+
+		signatures[i] = sig
+		for band := range buckets {
+			key := sig.band(band)
+			buckets[band][key] = append(buckets[band][key], i)
+		}
+	}
+	return collapsed
+}
+
+// fileSignature returns the signature of code, or false if it has too few tokens to compare.
+func fileSignature(code string) (*signature, bool) {
+	tokens := similarTokenRegex.FindAllString(code, -1)
+	if len(tokens) < similarMinTokens {
+		return nil, false
+	}
+	for i, token := range tokens {
+		if token[0] >= '0' && token[0] <= '9' {
+			tokens[i] = "0"
+		}
+	}
+
+	sig := new(signature)
+	for i := range sig {
+		sig[i] = math.MaxUint64
+	}
+	h := fnv.New64a()
+	for start := 0; start+similarShingleSize <= len(tokens); start++ {
+		h.Reset()
+		for _, token := range tokens[start : start+similarShingleSize] {
+			h.Write([]byte(token))
+			h.Write([]byte{0})
+		}
+		shingle := h.Sum64()
+		for i, seed := range similarSeeds {
+			sig[i] = min(sig[i], mix64(shingle^seed))
+		}
+	}
+	return sig, true
+}
+
+// band returns the rows of the signature in the band.
+func (s *signature) band(band int) [similarRows]uint64 {
+	return [similarRows]uint64(s[band*similarRows : (band+1)*similarRows])
+}
+
+// similarity returns the share of positions the signatures have the same value at.
+func (s *signature) similarity(other *signature) float64 {
+	same := 0
+	for i := range s {
+		if s[i] == other[i] {
+			same++
+		}
+	}
+	return float64(same) / float64(len(s))
+}
+
+// mix64 is the finaliser of SplitMix64, which spreads the bits of x over the result.
+func mix64(x uint64) uint64 {
+	x = (x ^ (x >> 30)) * 0xbf58476d1ce4e5b9
+	x = (x ^ (x >> 27)) * 0x94d049bb133111eb
+	return x ^ (x >> 31)
+}
+
+// similarStub is the one line that replaces the content of a file similar to the one at path.
+func similarStub(path string, similarity float64) string {
+	return fmt.Sprintf("<similar to %s (~%d%% match)>", path, int(math.Round(similarity*100)))
+}
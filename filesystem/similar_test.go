@@ -0,0 +1,44 @@
+package filesystem
+
+import (
+	"fmt"
+	"strings"
+	"testing"
+)
+
+func TestCollapseSimilar(t *testing.T) {
+	mock := func(name string, version int) string {
+		var code strings.Builder
+		fmt.Fprintf(&code, "// Code generated by MockGen, version %d. DO NOT EDIT.\npackage mocks\n\n", version)
+		for _, method := range []string{"Get", "List", "Create", "Update", "Delete", "Count", "Exists", "Find"} {
+			fmt.Fprintf(&code, "func (m *Mock%s) %s(ctx context.Context, id int) (*%s, error) {\n", name, method, name)
+			fmt.Fprintf(&code, "\tret := m.ctrl.Call(m, %q, ctx, id)\n\tret0, _ := ret[0].(*%s)\n\treturn ret0, nil\n}\n\n", method, name)
+		}
+		return code.String()
+	}
+	files := []FileInfo{
+		{Path: "mocks/user.go", Code: mock("User", 1), TokenCount: 400},
+		{Path: "README.md", Code: strings.Repeat("Ingest turns a directory of code into a prompt for a model, with a tree and every file. ", 5)},
+		{Path: "mocks/user_v2.go", Code: mock("User", 2), TokenCount: 400},
+		{Path: "mocks/order.go", Code: mock("Order", 1), TokenCount: 400},
+		{Path: "small.go", Code: "package mocks"},
+		{Path: "small_copy.go", Code: "package mocks"},
+	}
+
+	if collapsed := CollapseSimilar(files, DefaultSimilarityThreshold); collapsed != 1 {
+		t.Errorf("Expected 1 file to be collapsed, got %d", collapsed)
+	}
+	if code := files[2].Code; code != "<similar to mocks/user.go (~100% match)>" || files[2].TokenCount != 0 {
+		t.Errorf("Expected mocks/user_v2.go to refer to mocks/user.go and be recounted, got %q with %d tokens", code, files[2].TokenCount)
+	}
+	for _, i := range []int{0, 1, 3, 4, 5} {
+		if strings.HasPrefix(files[i].Code, "<similar") {
+			t.Errorf("Expected %s to keep its code, got %q", files[i].Path, files[i].Code)
+		}
+	}
+
+	// A lower threshold groups the mocks of different types too
+	if collapsed := CollapseSimilar(files[:4], 0.5); collapsed != 1 || files[3].Code != "<similar to mocks/user.go (~59% match)>" {
+		t.Errorf("Expected mocks/order.go to be collapsed at a threshold of 0.5, got %d collapsed and %q", collapsed, files[3].Code)
+	}
+}
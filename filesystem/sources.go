@@ -0,0 +1,64 @@
+package filesystem
+
+import (
+	"fmt"
+	"path"
+)
+
+// Source is the files ingested from one input, such as a directory or URL.
+type Source struct {
+	Name  string // Short name for the input, such as the directory's base name
+	Files []FileInfo
+}
+
+// MergeSources combines the files of several inputs into one list. Files whose paths clash
+// with a file from another input, such as the same relative path in two sibling repositories,
+// have their paths prefixed with their source's name so neither is mistaken for the other.
+// Sources with the same name are numbered to keep them apart.
+func MergeSources(sources []Source) []FileInfo {
+	var files []FileInfo
+	if len(sources) == 1 {
+		return append(files, sources[0].Files...)
+	}
+
+	names := make([]string, len(sources))
+	used := make(map[string]int)
+	for i, source := range sources {
+		used[source.Name]++
+		names[i] = source.Name
+		if n := used[source.Name]; n > 1 {
+			names[i] = fmt.Sprintf("%s-%d", source.Name, n)
+		}
+	}
+
+	// Count the sources each path appears in, a path repeated within a source isn't a clash
+	relOwners := make(map[string]map[int]bool)
+	pathOwners := make(map[string]map[int]bool)
+	own := func(owners map[string]map[int]bool, key string, source int) {
+		if owners[key] == nil {
+			owners[key] = make(map[int]bool)
+		}
+		owners[key][source] = true
+	}
+	for i, source := range sources {
+		for _, file := range source.Files {
+			if file.RelPath != "" {
+				own(relOwners, file.RelPath, i)
+			}
+			own(pathOwners, file.Path, i)
+		}
+	}
+
+	for i, source := range sources {
+		for _, file := range source.Files {
+			if len(relOwners[file.RelPath]) > 1 {
+				file.RelPath = path.Join(names[i], file.RelPath)
+			}
+			if len(pathOwners[file.Path]) > 1 {
+				file.Path = names[i] + "/" + file.Path
+			}
+			files = append(files, file)
+		}
+	}
+	return files
+}
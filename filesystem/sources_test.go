@@ -0,0 +1,34 @@
+package filesystem
+
+import "testing"
+
+func TestMergeSources(t *testing.T) {
+	files := MergeSources([]Source{
+		{Name: "api", Files: []FileInfo{
+			{Path: "api/main.go", RelPath: "main.go"},
+			{Path: "api/server.go", RelPath: "server.go"},
+		}},
+		{Name: "web", Files: []FileInfo{{Path: "web/main.go", RelPath: "main.go"}}},
+		{Name: "api", Files: []FileInfo{{Path: "api/main.go", RelPath: "main.go"}}},
+	})
+
+	expected := []struct{ path, relPath string }{
+		{"api/api/main.go", "api/main.go"},
+		{"api/server.go", "server.go"},
+		{"web/main.go", "web/main.go"},
+		{"api-2/api/main.go", "api-2/main.go"},
+	}
+	if len(files) != len(expected) {
+		t.Fatalf("Expected %d files, got %d", len(expected), len(files))
+	}
+	for i, e := range expected {
+		if files[i].Path != e.path || files[i].RelPath != e.relPath {
+			t.Errorf("File %d: expected %s (%s), got %s (%s)", i, e.path, e.relPath, files[i].Path, files[i].RelPath)
+		}
+	}
+
+	single := MergeSources([]Source{{Name: "api", Files: []FileInfo{{Path: "main.go", RelPath: "main.go"}}}})
+	if single[0].RelPath != "main.go" {
+		t.Errorf("Expected a single source to be unchanged, got %s", single[0].RelPath)
+	}
+}
@@ -0,0 +1,57 @@
+package filesystem
+
+import "fmt"
+
+// streamBatch is how many files each worker reads ahead of those being written when streaming.
+const streamBatch = 4
+
+// DirectoryStream is a walked directory whose files are read a batch at a time by Each, so
+// they are never all held in memory.
+type DirectoryStream struct {
+	Tree     string        // The directory tree, without stats
+	Excluded *ExcludedInfo // What was left out, to which Each adds files that can't be read
+	jobs     []fileJob
+	opts     Options
+}
+
+// StreamDirectory walks the directory at rootPath like WalkDirectory, but returns its tree
+// before any of its files are read. TreeStats is ignored, as the stats come from the files.
+func StreamDirectory(rootPath string, opts Options) (*DirectoryStream, error) {
+	walk, err := walkRoot(rootPath, opts)
+	if err != nil {
+		return nil, err
+	}
+	if !walk.isDir {
+		return nil, fmt.Errorf("%s is not a directory", rootPath)
+	}
+
+	tree, err := generateTreeString(opts.fsys(), rootPath, walk.excludePatterns, walk.collapsed, opts.symlinks(), opts.Order, nil)
+	if err != nil {
+		return nil, fmt.Errorf("failed to generate directory tree: %w", err)
+	}
+	return &DirectoryStream{Tree: tree, Excluded: walk.excluded, jobs: walk.jobs, opts: opts}, nil
+}
+
+// Each reads the files in the order they are listed and calls fn with each, stopping at the
+// first error it returns. Files are read concurrently, a batch at a time.
+func (s *DirectoryStream) Each(fn func(FileInfo) error) error {
+	opts := s.opts
+	opts.Progress = nil
+	batch := opts.workers() * streamBatch
+
+	for start := 0; start < len(s.jobs); start += batch {
+		end := min(start+batch, len(s.jobs))
+		files, _, skipped := processFiles(s.jobs[start:end], opts)
+		s.Excluded.Skipped = append(s.Excluded.Skipped, skipped...)
+		if err := opts.ExecFilter.Err(); err != nil {
+			return err
+		}
+		for _, file := range files {
+			if err := fn(file); err != nil {
+				return err
+			}
+		}
+		s.opts.progress(end, len(s.jobs))
+	}
+	return nil
+}
@@ -0,0 +1,46 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"testing"
+)
+
+func TestStreamDirectoryMatchesWalk(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "README.md"), "# readme\n")
+	writeTestFile(t, filepath.Join(root, "go.mod"), "module example\n")
+	for _, name := range []string{"a.go", "b.go", "c.go", "d.go", "e.go"} {
+		writeTestFile(t, filepath.Join(root, "pkg", name), "package pkg\n")
+	}
+	opts := Options{NoDefaultExcludes: true, RelativePaths: true, ReadmeFirst: true, Concurrency: 1}
+
+	_, files, _, err := WalkDirectory(root, opts)
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+
+	stream, err := StreamDirectory(root, opts)
+	if err != nil {
+		t.Fatalf("StreamDirectory failed: %v", err)
+	}
+	if stream.Tree == "" {
+		t.Error("Expected a tree before any files are read")
+	}
+
+	var streamed []FileInfo
+	if err := stream.Each(func(file FileInfo) error {
+		streamed = append(streamed, file)
+		return nil
+	}); err != nil {
+		t.Fatalf("Each failed: %v", err)
+	}
+
+	if len(streamed) != len(files) {
+		t.Fatalf("Expected %d files, got %d", len(files), len(streamed))
+	}
+	for i := range files {
+		if streamed[i].Path != files[i].Path || streamed[i].Code != files[i].Code {
+			t.Errorf("Expected file %d to be %s, got %s", i, files[i].Path, streamed[i].Path)
+		}
+	}
+}
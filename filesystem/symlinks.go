@@ -0,0 +1,162 @@
+package filesystem
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+)
+
+// SymlinkPolicy controls how symlinks found while walking a directory are handled.
+type SymlinkPolicy string
+
+const (
+	// SymlinksSkip leaves symlinks out, listing them as skipped. This is the default.
+	SymlinksSkip SymlinkPolicy = "skip"
+	// SymlinksFollow ingests whatever symlinks point to, including content outside the root.
+	SymlinksFollow SymlinkPolicy = "follow"
+	// SymlinksFollowSafe follows symlinks that stay within the root.
+	SymlinksFollowSafe SymlinkPolicy = "follow-safe"
+)
+
+// ParseSymlinkPolicy parses a --symlinks value.
+func ParseSymlinkPolicy(value string) (SymlinkPolicy, error) {
+	switch policy := SymlinkPolicy(value); policy {
+	case SymlinksSkip, SymlinksFollow, SymlinksFollowSafe:
+		return policy, nil
+	}
+	return "", fmt.Errorf("invalid symlink policy %q, must be skip, follow or follow-safe", value)
+}
+
+func (p SymlinkPolicy) follows() bool {
+	return p == SymlinksFollow || p == SymlinksFollowSafe
+}
+
+// symlinkWalker walks a directory tree like filepath.Walk, but resolves symlinks according to
+// its policy. Paths passed to the walk function are below the root as given, even when they
+// are reached through a symlink, and followed symlinks are passed with the info of their
+// target. Directories are tracked by their resolved path, so a symlink back to a directory
+// that has already been walked is skipped rather than looping forever.
+type symlinkWalker struct {
+	fsys     fileSystem
+	policy   SymlinkPolicy
+	realRoot string
+	visited  map[string]bool
+	skip     func(path, reason string) // Called for symlinks that aren't followed
+	external func(path, target string) // Called for symlinks followed outside the root
+	fn       func(path string, info os.FileInfo, err error) error
+}
+
+// walkTree walks the tree at root, calling fn for each file and directory in lexical order.
+// skip and external may be nil.
+func walkTree(fsys fileSystem, root string, policy SymlinkPolicy, skip func(path, reason string), external func(path, target string), fn filepath.WalkFunc) error {
+	info, err := fsys.Stat(root)
+	if err != nil {
+		return fn(root, nil, err)
+	}
+	realRoot, err := fsys.RealPath(root)
+	if err != nil {
+		return fn(root, nil, err)
+	}
+
+	w := &symlinkWalker{
+		fsys:     fsys,
+		policy:   policy,
+		realRoot: realRoot,
+		visited:  make(map[string]bool),
+		skip:     skip,
+		external: external,
+		fn:       fn,
+	}
+	err = w.walk(root, realRoot, info)
+	if errors.Is(err, filepath.SkipDir) || errors.Is(err, filepath.SkipAll) {
+		return nil
+	}
+	return err
+}
+
+func (w *symlinkWalker) walk(path, realPath string, info os.FileInfo) error {
+	if !info.IsDir() {
+		return w.fn(path, info, nil)
+	}
+
+	if err := w.fn(path, info, nil); err != nil {
+		if errors.Is(err, filepath.SkipDir) {
+			return nil
+		}
+		return err
+	}
+	w.visited[realPath] = true
+
+	entries, err := w.fsys.ReadDir(path)
+	if err != nil {
+		return w.fn(path, info, err)
+	}
+	for _, entry := range entries {
+		child := filepath.Join(path, entry.Name())
+		childInfo, childReal, err := w.resolve(child, filepath.Join(realPath, entry.Name()), entry)
+		if err != nil {
+			if err := w.fn(child, nil, err); err != nil {
+				return err
+			}
+			continue
+		}
+		if childInfo == nil {
+			continue
+		}
+		if err := w.walk(child, childReal, childInfo); err != nil {
+			// As with filepath.Walk, SkipDir from a file skips the rest of its directory
+			if errors.Is(err, filepath.SkipDir) {
+				return nil
+			}
+			return err
+		}
+	}
+	return nil
+}
+
+// resolve returns the info and resolved path of a directory entry, following it if it is a
+// symlink the policy allows. It returns nil info for symlinks that are skipped.
+func (w *symlinkWalker) resolve(path, realPath string, entry os.DirEntry) (os.FileInfo, string, error) {
+	if entry.Type()&os.ModeSymlink == 0 {
+		info, err := entry.Info()
+		return info, realPath, err
+	}
+
+	if !w.policy.follows() {
+		w.skipped(path, "symlink")
+		return nil, "", nil
+	}
+
+	target, err := w.fsys.RealPath(path)
+	if err != nil {
+		w.skipped(path, "broken symlink")
+		return nil, "", nil
+	}
+	info, err := w.fsys.Stat(target)
+	if err != nil {
+		w.skipped(path, "broken symlink")
+		return nil, "", nil
+	}
+
+	if !IsWithin(w.realRoot, target) {
+		if w.policy == SymlinksFollowSafe {
+			w.skipped(path, "symlink outside root")
+			return nil, "", nil
+		}
+		if w.external != nil {
+			w.external(path, target)
+		}
+	}
+	if info.IsDir() && w.visited[target] {
+		w.skipped(path, "symlink to a directory already walked")
+		return nil, "", nil
+	}
+	return info, target, nil
+}
+
+func (w *symlinkWalker) skipped(path, reason string) {
+	if w.skip != nil {
+		w.skip(path, reason)
+	}
+}
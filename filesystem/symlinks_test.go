@@ -0,0 +1,65 @@
+package filesystem
+
+import (
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"testing"
+)
+
+func TestWalkDirectorySymlinks(t *testing.T) {
+	base := t.TempDir()
+	root := filepath.Join(base, "project")
+	outside := filepath.Join(base, "outside")
+	writeTestFile(t, filepath.Join(root, "src", "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(outside, "shared.go"), "package shared\n")
+	links := [][2]string{
+		{filepath.Join(root, "src"), filepath.Join(root, "linked")},
+		{outside, filepath.Join(root, "shared")},
+		{root, filepath.Join(root, "src", "loop")},
+	}
+	for _, link := range links {
+		if err := os.Symlink(link[0], link[1]); err != nil {
+			t.Skipf("Symlinks not supported: %v", err)
+		}
+	}
+
+	walk := func(policy SymlinkPolicy) ([]string, *ExcludedInfo) {
+		t.Helper()
+		_, files, excluded, err := WalkDirectory(root, Options{NoDefaultExcludes: true, Symlinks: policy})
+		if err != nil {
+			t.Fatalf("WalkDirectory failed: %v", err)
+		}
+		var paths []string
+		for _, file := range files {
+			paths = append(paths, file.RelPath)
+		}
+		sort.Strings(paths)
+		return paths, excluded
+	}
+
+	paths, excluded := walk(SymlinksSkip)
+	if strings.Join(paths, ",") != "src/main.go" {
+		t.Errorf("Expected symlinks to be skipped, got %v", paths)
+	}
+	if len(excluded.Skipped) != 3 {
+		t.Errorf("Expected the 3 symlinks to be listed as skipped, got %v", excluded.Skipped)
+	}
+
+	paths, excluded = walk(SymlinksFollowSafe)
+	if strings.Join(paths, ",") != "linked/main.go,src/main.go" {
+		t.Errorf("Expected only links within the root to be followed, got %v", paths)
+	}
+	if len(excluded.External) != 0 {
+		t.Errorf("Expected nothing outside the root, got %v", excluded.External)
+	}
+
+	paths, excluded = walk(SymlinksFollow)
+	if strings.Join(paths, ",") != "linked/main.go,shared/shared.go,src/main.go" {
+		t.Errorf("Expected links outside the root to be followed, got %v", paths)
+	}
+	if len(excluded.External) != 1 || !strings.HasPrefix(excluded.External[0], filepath.Join(root, "shared")+" -> ") {
+		t.Errorf("Expected the external link to be reported, got %v", excluded.External)
+	}
+}
@@ -0,0 +1,47 @@
+package filesystem
+
+import (
+	"path/filepath"
+
+	"github.com/bmatcuk/doublestar/v4"
+)
+
+// TestPatterns are the globs of the test files --exclude-tests and --only-tests recognise.
+var TestPatterns = []string{
+	"**/*_test.go",
+	"**/test_*.py",
+	"**/*_test.py",
+	"**/*.test.*",
+	"**/*.spec.*",
+	"**/test/**",
+	"**/tests/**",
+	"**/__tests__/**",
+}
+
+// IsTestFile reports whether the slash separated path matches one of TestPatterns.
+func IsTestFile(path string) bool {
+	return matchesAny(path, TestPatterns)
+}
+
+// dropsTestFile reports whether ExcludeTests or OnlyTests leaves out the file at relPath, under
+// the walked directory rootPath. The name of the root is part of the path matched, so the files
+// of a walked tests directory are tests.
+//
+// Explicit patterns win: ExcludeTests keeps a test file matching an include pattern that is
+// itself a test path, such as "pkg/foo_test.go" or "**/*.spec.ts" but not "**/*.go", and
+// OnlyTests keeps only the tests among the files the include patterns match.
+func (o Options) dropsTestFile(rootPath, relPath string) bool {
+	if !o.ExcludeTests && !o.OnlyTests {
+		return false
+	}
+	isTest := IsTestFile(filepath.ToSlash(filepath.Join(filepath.Base(rootPath), relPath)))
+	if o.OnlyTests {
+		return !isTest
+	}
+	for _, pattern := range o.IncludePatterns {
+		if match, _ := doublestar.Match(pattern, relPath); match && IsTestFile(pattern) {
+			return false
+		}
+	}
+	return isTest
+}
@@ -0,0 +1,70 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"reflect"
+	"slices"
+	"testing"
+)
+
+func TestWalkDirectoryTests(t *testing.T) {
+	root := t.TempDir()
+	for _, path := range []string{
+		"main.go",
+		"main_test.go",
+		"app.py",
+		"test_app.py",
+		"web/app.ts",
+		"web/app.test.ts",
+		"web/app.spec.js",
+		"web/__tests__/render.js",
+		"tests/fixtures/data.json",
+		"tests/helpers.py",
+	} {
+		writeTestFile(t, filepath.Join(root, path), "x\n")
+	}
+
+	walk := func(opts Options) []string {
+		_, files, _, err := WalkDirectory(root, opts)
+		if err != nil {
+			t.Fatalf("WalkDirectory failed: %v", err)
+		}
+		var paths []string
+		for _, file := range files {
+			rel, _ := filepath.Rel(root, file.Path)
+			paths = append(paths, filepath.ToSlash(rel))
+		}
+		slices.Sort(paths)
+		return paths
+	}
+
+	tests := []struct {
+		name     string
+		opts     Options
+		expected []string
+	}{
+		{"exclude", Options{ExcludeTests: true, NoDefaultExcludes: true}, []string{"app.py", "main.go", "web/app.ts"}},
+		{"only", Options{OnlyTests: true, IncludePatterns: []string{"**/*.py"}}, []string{"test_app.py", "tests/helpers.py"}},
+		{"only with an exclude", Options{OnlyTests: true, ExcludePatterns: []string{"web/**"}}, []string{"main_test.go", "test_app.py", "tests/fixtures/data.json", "tests/helpers.py"}},
+		{"exclude with a test include", Options{ExcludeTests: true, NoDefaultExcludes: true, IncludePatterns: []string{"**/*.go", "**/*.spec.js"}}, []string{"main.go", "web/app.spec.js"}},
+	}
+	for _, tt := range tests {
+		tt.opts.Concurrency = 1
+		if got := walk(tt.opts); !reflect.DeepEqual(got, tt.expected) {
+			t.Errorf("%s: expected %v, got %v", tt.name, tt.expected, got)
+		}
+	}
+}
+
+func TestWalkDirectoryTestsRoot(t *testing.T) {
+	root := filepath.Join(t.TempDir(), "tests")
+	writeTestFile(t, filepath.Join(root, "helpers.py"), "x\n")
+
+	_, files, _, err := WalkDirectory(root, Options{OnlyTests: true})
+	if err != nil {
+		t.Fatalf("WalkDirectory failed: %v", err)
+	}
+	if len(files) != 1 {
+		t.Errorf("Expected the files of a walked tests directory to be tests, got %d files", len(files))
+	}
+}
@@ -0,0 +1,77 @@
+package filesystem
+
+import (
+	"fmt"
+	"strings"
+
+	"github.com/mattn/go-runewidth"
+	"github.com/sammcj/ingest/utils"
+)
+
+// fileStats are the details shown beside a file in the tree with --tree-stats.
+type fileStats struct {
+	lines    int
+	language string
+	tokens   int
+}
+
+// countLines returns the number of lines in content, counting a final line without a newline.
+func countLines(content string) int {
+	if content == "" {
+		return 0
+	}
+	lines := strings.Count(content, "\n")
+	if !strings.HasSuffix(content, "\n") {
+		lines++
+	}
+	return lines
+}
+
+// annotateTree appends aligned columns to the tree lines: the line count and language of each
+// ingested file, and the token subtotal of each directory. Columns are aligned by display
+// width so wide unicode file names don't push them out of line.
+func annotateTree(lines []treeLine, stats map[string]fileStats) string {
+	// Roll file tokens up into every directory above them, the root being ""
+	dirTokens := make(map[string]int)
+	for path, stat := range stats {
+		dirTokens[""] += stat.tokens
+		for i := range len(path) {
+			if path[i] == '/' {
+				dirTokens[path[:i]] += stat.tokens
+			}
+		}
+	}
+
+	columns := make([][3]string, len(lines))
+	var nameWidth int
+	var widths [3]int
+	for i, line := range lines {
+		nameWidth = max(nameWidth, runewidth.StringWidth(line.text))
+		switch {
+		case line.node.excluded:
+			continue
+		case line.node.isDir:
+			columns[i][2] = fmt.Sprintf("%s tokens", utils.FormatNumber(dirTokens[line.path]))
+		default:
+			stat, ok := stats[line.path]
+			if !ok {
+				continue
+			}
+			columns[i][0] = fmt.Sprintf("%s lines", utils.FormatNumber(stat.lines))
+			columns[i][1] = stat.language
+		}
+		for c := range columns[i] {
+			widths[c] = max(widths[c], runewidth.StringWidth(columns[i][c]))
+		}
+	}
+
+	var output strings.Builder
+	for i, line := range lines {
+		row := runewidth.FillRight(line.text, nameWidth) +
+			"  " + runewidth.FillLeft(columns[i][0], widths[0]) +
+			"  " + runewidth.FillRight(columns[i][1], widths[1]) +
+			"  " + runewidth.FillLeft(columns[i][2], widths[2])
+		output.WriteString(strings.TrimRight(row, " ") + "\n")
+	}
+	return output.String()
+}
@@ -0,0 +1,60 @@
+package filesystem
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/mattn/go-runewidth"
+)
+
+func TestCountLines(t *testing.T) {
+	tests := map[string]int{
+		"":          0,
+		"one":       1,
+		"one\n":     1,
+		"one\ntwo":  2,
+		"one\n\n":   2,
+		"a\nb\nc\n": 3,
+	}
+	for content, expected := range tests {
+		if got := countLines(content); got != expected {
+			t.Errorf("countLines(%q) = %d, expected %d", content, got, expected)
+		}
+	}
+}
+
+func TestAnnotateTreeAlignment(t *testing.T) {
+	root := &treeNode{name: "repo", isDir: true}
+	src := &treeNode{name: "src", isDir: true}
+	deep := &treeNode{name: "データ.go"}
+	main := &treeNode{name: "main.go"}
+	lines := []treeLine{
+		{text: "repo/", node: root, path: ""},
+		{text: "├── src/", node: src, path: "src"},
+		{text: "│   └── データ.go", node: deep, path: "src/データ.go"},
+		{text: "└── main.go", node: main, path: "main.go"},
+	}
+	stats := map[string]fileStats{
+		"src/データ.go": {lines: 1200, language: "Go", tokens: 900},
+		"main.go":     {lines: 5, language: "Go", tokens: 100},
+	}
+
+	output := strings.Split(strings.TrimSuffix(annotateTree(lines, stats), "\n"), "\n")
+	if len(output) != len(lines) {
+		t.Fatalf("Expected %d lines, got %d", len(lines), len(output))
+	}
+
+	if !strings.HasSuffix(output[0], "1,000 tokens") {
+		t.Errorf("Expected the root subtotal to be 1,000 tokens, got %q", output[0])
+	}
+	if !strings.HasSuffix(output[1], "900 tokens") {
+		t.Errorf("Expected the src subtotal to be 900 tokens, got %q", output[1])
+	}
+
+	// The lines columns of both files end at the same display width
+	deepEnd := runewidth.StringWidth(output[2][:strings.Index(output[2], "lines")])
+	mainEnd := runewidth.StringWidth(output[3][:strings.Index(output[3], "lines")])
+	if deepEnd != mainEnd {
+		t.Errorf("Expected aligned columns, got:\n%s", strings.Join(output, "\n"))
+	}
+}
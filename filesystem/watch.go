@@ -0,0 +1,157 @@
+package filesystem
+
+import (
+	"errors"
+	"fmt"
+	"maps"
+	"path/filepath"
+	"slices"
+	"time"
+
+	"github.com/fsnotify/fsnotify"
+)
+
+// Watcher waits for changes to the files that walking its roots would ingest. Only the
+// directories the walk descends into are watched, so churn in excluded or ignored directories
+// such as node_modules doesn't count as a change.
+type Watcher struct {
+	watcher *fsnotify.Watcher
+	roots   []string
+	ignored map[string]bool
+	opts    Options
+	dirs    map[string]bool // Directories being watched
+	files   map[string]bool // Files the roots would ingest
+}
+
+// NewWatcher watches the files and directories at roots, which are absolute paths, selecting
+// files with opts like WalkDirectory. Changes to the ignored paths, such as the output file,
+// never count.
+func NewWatcher(roots, ignored []string, opts Options) (*Watcher, error) {
+	watcher, err := fsnotify.NewWatcher()
+	if err != nil {
+		return nil, fmt.Errorf("failed to start watching for changes: %w", err)
+	}
+
+	// Every file that could be ingested is watched, as whether a file is modified, changed
+	// since a time or small enough depends on what it is changed to
+	opts.Progress = nil
+	opts.OnlyPaths = nil
+	opts.Since = time.Time{}
+	opts.MaxFileSize = 0
+	w := &Watcher{
+		watcher: watcher,
+		roots:   roots,
+		ignored: make(map[string]bool),
+		opts:    opts,
+		dirs:    make(map[string]bool),
+	}
+	for _, path := range ignored {
+		w.ignored[path] = true
+	}
+	if _, err := w.refresh(); err != nil {
+		watcher.Close()
+		return nil, err
+	}
+	return w, nil
+}
+
+// Close stops watching.
+func (w *Watcher) Close() error {
+	return w.watcher.Close()
+}
+
+// Wait blocks until a file that would be ingested is written, added, removed or renamed, and
+// no further change follows within debounce, so a burst of changes such as a save that touches
+// several files returns once.
+func (w *Watcher) Wait(debounce time.Duration) error {
+	var settled <-chan time.Time
+	changed, rescan := false, false
+	for {
+		select {
+		case event, ok := <-w.watcher.Events:
+			if !ok {
+				return errors.New("stopped watching for changes")
+			}
+			if w.ignored[event.Name] {
+				continue
+			}
+			switch {
+			case w.files[event.Name] && !event.Has(fsnotify.Chmod):
+				// Editors that save by replacing a file remove and create it, which is a change too
+				changed = true
+			case event.Has(fsnotify.Create) || event.Has(fsnotify.Remove) || event.Has(fsnotify.Rename):
+				// Whether a new or removed path matters depends on the ignore rules
+				rescan = true
+			case event.Has(fsnotify.Write) && slices.Contains(ignoreFileNames, filepath.Base(event.Name)):
+				rescan = true
+			case event.Has(fsnotify.Write) && filepath.Base(event.Name) == attributesFileName:
+				// Generated files are collapsed, so the output can change even if the files don't
+				changed, rescan = true, true
+			default:
+				continue
+			}
+			settled = time.After(debounce)
+
+		case err, ok := <-w.watcher.Errors:
+			if !ok {
+				return errors.New("stopped watching for changes")
+			}
+			return fmt.Errorf("failed watching for changes: %w", err)
+
+		case <-settled:
+			settled = nil
+			if rescan {
+				rescan = false
+				filesChanged, err := w.refresh()
+				if err != nil {
+					return err
+				}
+				changed = changed || filesChanged
+			}
+			if changed {
+				return nil
+			}
+		}
+	}
+}
+
+// refresh walks the roots again to find the files they would ingest, watching any directories
+// that weren't already, and reports whether the files are different from before.
+func (w *Watcher) refresh() (bool, error) {
+	files := make(map[string]bool)
+	dirs := make(map[string]bool)
+	for _, root := range w.roots {
+		walk, err := walkRoot(root, w.opts)
+		if err != nil {
+			return false, fmt.Errorf("failed to walk %s: %w", root, err)
+		}
+		if !walk.isDir {
+			// Files are replaced on save by some editors, which can only be seen from their directory
+			dirs[filepath.Dir(root)] = true
+		}
+		for _, dir := range walk.dirs {
+			dirs[dir] = true
+		}
+		for _, job := range walk.jobs {
+			files[job.path] = true
+		}
+	}
+
+	for dir := range dirs {
+		if !w.dirs[dir] {
+			if err := w.watcher.Add(dir); err != nil {
+				return false, fmt.Errorf("failed to watch %s: %w", dir, err)
+			}
+		}
+	}
+	for dir := range w.dirs {
+		if !dirs[dir] {
+			// Removed directories stop being watched on their own
+			_ = w.watcher.Remove(dir)
+		}
+	}
+
+	changed := w.files != nil && !maps.Equal(files, w.files)
+	w.dirs, w.files = dirs, files
+	return changed, nil
+}
@@ -0,0 +1,39 @@
+package filesystem
+
+import (
+	"path/filepath"
+	"testing"
+	"time"
+)
+
+func TestWatcherWaitsForIngestedFiles(t *testing.T) {
+	root := t.TempDir()
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n")
+	writeTestFile(t, filepath.Join(root, "node_modules", "dep.js"), "one\n")
+
+	w, err := NewWatcher([]string{root}, nil, Options{NoDefaultExcludes: true, ExcludePatterns: []string{"**/node_modules/**"}})
+	if err != nil {
+		t.Fatalf("NewWatcher failed: %v", err)
+	}
+	defer w.Close()
+
+	done := make(chan error, 1)
+	go func() { done <- w.Wait(50 * time.Millisecond) }()
+
+	writeTestFile(t, filepath.Join(root, "node_modules", "dep.js"), "two\n")
+	select {
+	case err := <-done:
+		t.Fatalf("Expected a change in node_modules to be ignored, got %v", err)
+	case <-time.After(300 * time.Millisecond):
+	}
+
+	writeTestFile(t, filepath.Join(root, "main.go"), "package main\n\nfunc main() {}\n")
+	select {
+	case err := <-done:
+		if err != nil {
+			t.Fatalf("Wait failed: %v", err)
+		}
+	case <-time.After(5 * time.Second):
+		t.Fatal("Expected a change to main.go to end the wait")
+	}
+}
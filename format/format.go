@@ -0,0 +1,183 @@
+package format
+
+import (
+	"encoding/json"
+	"fmt"
+	"io"
+	"strings"
+
+	"github.com/sammcj/ingest/filesystem"
+)
+
+// Format is an output format for the ingested result.
+type Format string
+
+const (
+	Markdown Format = "markdown"
+	JSON     Format = "json"
+	XML      Format = "xml"
+	HTML     Format = "html"
+
+	// JSONLines is one JSON object per file, one per line. It is only streamed, with
+	// --json-lines, so it isn't one of the Formats.
+	JSONLines Format = "jsonl"
+)
+
+// Formats lists the supported output formats.
+var Formats = []Format{Markdown, JSON, XML, HTML}
+
+// Parse returns the Format for the given name.
+func Parse(name string) (Format, error) {
+	for _, f := range Formats {
+		if strings.EqualFold(name, string(f)) {
+			return f, nil
+		}
+	}
+
+	names := make([]string, len(Formats))
+	for i, f := range Formats {
+		names[i] = string(f)
+	}
+	return "", fmt.Errorf("invalid format %q, must be one of: %s", name, strings.Join(names, ", "))
+}
+
+// Output is the structured form of an ingested result. New fields should only ever be
+// added, so that consumers of the serialised output keep working.
+type Output struct {
+	Tree        string                  `json:"tree"`
+	TokenCount  int                     `json:"token_count"`
+	Files       []File                  `json:"files"`
+	ContentHash string                  `json:"content_hash"`          // SHA-256 over the relative paths and contents of the files
+	Languages   []Language              `json:"languages,omitempty"`   // Token subtotals of each language when grouped by language
+	Annotations []filesystem.Annotation `json:"annotations,omitempty"` // Lines marked TODO and the like, with --annotations
+}
+
+// File is a single ingested file within an Output.
+type File struct {
+	Path       string                   `json:"path"`
+	Content    string                   `json:"content"`
+	TokenCount int                      `json:"token_count"`
+	Compressed bool                     `json:"compressed"`
+	Metadata   *filesystem.FileMetadata `json:"metadata,omitempty"` // Set with --file-metadata
+}
+
+// Language summarises the files of one language, in the order the files are listed.
+type Language struct {
+	Language   string `json:"language"`
+	TokenCount int    `json:"token_count"`
+	Files      int    `json:"files"`
+}
+
+// NewLanguages converts language groups to their structured form.
+func NewLanguages(groups []filesystem.LanguageGroup) []Language {
+	out := make([]Language, 0, len(groups))
+	for _, group := range groups {
+		out = append(out, Language{Language: group.Language, TokenCount: group.Tokens, Files: len(group.Files)})
+	}
+	return out
+}
+
+// NewOutput builds an Output from the gathered files. The files are expected to have their
+// token counts set and their contents not wrapped in code blocks.
+func NewOutput(tree string, tokenCount int, files []filesystem.FileInfo) Output {
+	return Output{
+		Tree:       tree,
+		TokenCount: tokenCount,
+		Files:      NewFiles(files),
+	}
+}
+
+// NewFiles converts the gathered files to their structured form.
+func NewFiles(files []filesystem.FileInfo) []File {
+	out := make([]File, 0, len(files))
+	for _, file := range files {
+		out = append(out, File{
+			Path:       file.Path,
+			Content:    file.Code,
+			TokenCount: file.TokenCount,
+			Compressed: file.Compressed,
+			Metadata:   file.Metadata,
+		})
+	}
+	return out
+}
+
+// Render serialises the Output in the given format. Markdown is rendered by the template
+// package rather than here.
+func Render(out Output, f Format) (string, error) {
+	switch f {
+	case JSON:
+		return renderJSON(out)
+	case XML:
+		return renderXML(out)
+	case HTML:
+		return renderHTML(out)
+	default:
+		return "", fmt.Errorf("format %s is not rendered by the format package", f)
+	}
+}
+
+func renderJSON(out Output) (string, error) {
+	jsonBytes, err := json.MarshalIndent(out, "", "  ")
+	if err != nil {
+		return "", fmt.Errorf("failed to marshal JSON: %w", err)
+	}
+	return string(jsonBytes) + "\n", nil
+}
+
+// xmlEscaper escapes the characters that would otherwise produce malformed XML. Newlines and
+// quotes are left as-is so that file contents remain readable.
+var xmlEscaper = strings.NewReplacer("&", "&amp;", "<", "&lt;", ">", "&gt;")
+
+// renderXML follows Anthropic's long context prompting convention of one indexed document
+// element per file, with the directory tree in its own element ahead of them.
+func renderXML(out Output) (string, error) {
+	var b strings.Builder
+
+	b.WriteString("<documents>\n")
+	if out.ContentHash != "" {
+		fmt.Fprintf(&b, "<content_hash>%s</content_hash>\n", out.ContentHash)
+	}
+	if out.Tree != "" {
+		b.WriteString("<tree>\n")
+		b.WriteString(xmlEscaper.Replace(out.Tree))
+		b.WriteString("\n</tree>\n")
+	}
+	if len(out.Languages) > 0 {
+		b.WriteString("<languages>\n")
+		for _, language := range out.Languages {
+			fmt.Fprintf(&b, "<language name=\"%s\" token_count=\"%d\" files=\"%d\"/>\n", xmlEscaper.Replace(language.Language), language.TokenCount, language.Files)
+		}
+		b.WriteString("</languages>\n")
+	}
+	if len(out.Annotations) > 0 {
+		b.WriteString("<annotations>\n")
+		for _, a := range out.Annotations {
+			fmt.Fprintf(&b, "<annotation source=\"%s\" line=\"%d\" marker=\"%s\">%s</annotation>\n", xmlEscaper.Replace(a.Path), a.Line, xmlEscaper.Replace(a.Marker), xmlEscaper.Replace(a.Text))
+		}
+		b.WriteString("</annotations>\n")
+	}
+
+	for i, file := range out.Files {
+		fmt.Fprintf(&b, "<document index=\"%d\">\n", i+1)
+		fmt.Fprintf(&b, "<source>%s</source>\n", xmlEscaper.Replace(file.Path))
+		writeXMLMetadata(&b, file.Metadata)
+		fmt.Fprintf(&b, "<document_contents>\n%s\n</document_contents>\n", xmlEscaper.Replace(file.Content))
+		b.WriteString("</document>\n")
+	}
+
+	b.WriteString("</documents>\n")
+	return b.String(), nil
+}
+
+// writeXMLMetadata writes a file's metadata element, if it has metadata.
+func writeXMLMetadata(w io.Writer, meta *filesystem.FileMetadata) {
+	if meta == nil {
+		return
+	}
+	fmt.Fprintf(w, "<metadata size=\"%d\"", meta.Size)
+	if meta.Modified != "" {
+		fmt.Fprintf(w, " modified=\"%s\"", meta.Modified)
+	}
+	fmt.Fprintf(w, " mode=\"%s\"/>\n", meta.Mode)
+}
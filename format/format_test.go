@@ -0,0 +1,234 @@
+package format
+
+import (
+	"encoding/json"
+	"encoding/xml"
+	"strings"
+	"testing"
+
+	"github.com/sammcj/ingest/filesystem"
+)
+
+func TestRenderJSON(t *testing.T) {
+	files := []filesystem.FileInfo{
+		{Path: "main.go", Extension: ".go", Code: "package main\n", TokenCount: 3},
+		{Path: "lib.go", Extension: ".go", Code: "func f()", TokenCount: 2, Compressed: true},
+	}
+
+	rendered, err := Render(NewOutput("root/\n└── main.go", 10, files), JSON)
+	if err != nil {
+		t.Fatalf("Render failed: %v", err)
+	}
+
+	var decoded Output
+	if err := json.Unmarshal([]byte(rendered), &decoded); err != nil {
+		t.Fatalf("Rendered output is not valid JSON: %v", err)
+	}
+
+	if decoded.Tree != "root/\n└── main.go" || decoded.TokenCount != 10 {
+		t.Errorf("Unexpected top-level fields: %+v", decoded)
+	}
+	if len(decoded.Files) != 2 {
+		t.Fatalf("Expected 2 files, got %d", len(decoded.Files))
+	}
+	if decoded.Files[0].Content != "package main\n" || decoded.Files[0].TokenCount != 3 || decoded.Files[0].Compressed {
+		t.Errorf("Unexpected first file: %+v", decoded.Files[0])
+	}
+	if !decoded.Files[1].Compressed {
+		t.Errorf("Expected second file to be marked as compressed")
+	}
+}
+
+func TestRenderXML(t *testing.T) {
+	files := []filesystem.FileInfo{
+		{Path: "a&b.go", Code: "if a < b && b > c {\n}"},
+		{Path: "README.md", Code: "# Title"},
+	}
+
+	rendered, err := Render(NewOutput("root/", 0, files), XML)
+	if err != nil {
+		t.Fatalf("Render failed: %v", err)
+	}
+
+	var decoded struct {
+		Tree      string `xml:"tree"`
+		Documents []struct {
+			Index    int    `xml:"index,attr"`
+			Source   string `xml:"source"`
+			Contents string `xml:"document_contents"`
+		} `xml:"document"`
+	}
+	if err := xml.Unmarshal([]byte(rendered), &decoded); err != nil {
+		t.Fatalf("Rendered output is not well-formed XML: %v\n%s", err, rendered)
+	}
+
+	if strings.TrimSpace(decoded.Tree) != "root/" {
+		t.Errorf("Expected tree 'root/', got %q", decoded.Tree)
+	}
+	if len(decoded.Documents) != 2 {
+		t.Fatalf("Expected 2 documents, got %d", len(decoded.Documents))
+	}
+	first := decoded.Documents[0]
+	if first.Index != 1 || first.Source != "a&b.go" || strings.TrimSpace(first.Contents) != "if a < b && b > c {\n}" {
+		t.Errorf("Unexpected first document: %+v", first)
+	}
+	if decoded.Documents[1].Index != 2 {
+		t.Errorf("Expected second document index 2, got %d", decoded.Documents[1].Index)
+	}
+}
+
+func TestRenderHTML(t *testing.T) {
+	files := []filesystem.FileInfo{
+		{Path: "repo/src/main.go", Code: "package main\n", TokenCount: 3},
+		{Path: "repo/<img src=x onerror=alert(1)>.html", Code: "</code></pre><script>alert(1)</script>", TokenCount: 5},
+	}
+
+	rendered, err := Render(NewOutput("repo/", 8, files), HTML)
+	if err != nil {
+		t.Fatalf("Render failed: %v", err)
+	}
+
+	if strings.Contains(rendered, "<script>alert(1)</script>") || strings.Contains(rendered, "<img src=x") {
+		t.Errorf("Expected paths and contents to be escaped, got:\n%s", rendered)
+	}
+	for _, want := range []string{`<details class="file" id="file-1">`, `<a href="#file-2">`, "<summary>src/</summary>", "8 tokens"} {
+		if !strings.Contains(rendered, want) {
+			t.Errorf("Expected the report to contain %q, got:\n%s", want, rendered)
+		}
+	}
+}
+
+func TestRenderContentHash(t *testing.T) {
+	out := NewOutput("root/", 0, nil)
+	out.ContentHash = "abc123"
+
+	rendered, err := Render(out, JSON)
+	if err != nil {
+		t.Fatalf("Render failed: %v", err)
+	}
+	if !strings.Contains(rendered, `"content_hash": "abc123"`) {
+		t.Errorf("Expected the JSON output to include the content hash, got:\n%s", rendered)
+	}
+
+	rendered, err = Render(out, XML)
+	if err != nil {
+		t.Fatalf("Render failed: %v", err)
+	}
+	if !strings.Contains(rendered, "<content_hash>abc123</content_hash>") {
+		t.Errorf("Expected the XML output to include the content hash, got:\n%s", rendered)
+	}
+}
+
+func TestParse(t *testing.T) {
+	for _, name := range []string{"markdown", "JSON"} {
+		if _, err := Parse(name); err != nil {
+			t.Errorf("Parse(%q): unexpected error: %v", name, err)
+		}
+	}
+	if _, err := Parse("yaml"); err == nil {
+		t.Errorf("Parse(\"yaml\"): expected error, got nil")
+	}
+}
+
+func TestSummary(t *testing.T) {
+	files := []filesystem.FileInfo{
+		{Path: "main.go", Code: "package main\n", Lines: 1, TokenCount: 3},
+		{Path: "a|b.go", Code: "package ab\n", Lines: 1200, TokenCount: 4000},
+	}
+
+	summary := Summary("root/\n└── main.go\n", files, 4100)
+	for _, expected := range []string{"└── main.go\n```", "| main.go | 1 | 3 |", `| a\|b.go | 1,200 | 4,000 |`, "Total: 4,100 tokens across 2 files"} {
+		if !strings.Contains(summary, expected) {
+			t.Errorf("Expected summary to contain %q, got:\n%s", expected, summary)
+		}
+	}
+	if strings.Contains(summary, "package main") {
+		t.Error("Expected file contents to be left out")
+	}
+}
+
+func TestStreamJSON(t *testing.T) {
+	var out strings.Builder
+	stream, err := NewStream(&out, JSON, nil, "root/", 1, nil)
+	if err != nil {
+		t.Fatalf("NewStream failed: %v", err)
+	}
+	for _, file := range []filesystem.FileInfo{
+		{Path: "main.go", Code: "package main\n", TokenCount: 3},
+		{Path: "lib.go", Code: "func f()", TokenCount: 2},
+	} {
+		if err := stream.WriteFile(file); err != nil {
+			t.Fatalf("WriteFile failed: %v", err)
+		}
+	}
+	if err := stream.Close(); err != nil {
+		t.Fatalf("Close failed: %v", err)
+	}
+
+	var decoded Output
+	if err := json.Unmarshal([]byte(out.String()), &decoded); err != nil {
+		t.Fatalf("Streamed output is not valid JSON: %v\n%s", err, out.String())
+	}
+	if decoded.Tree != "root/" || decoded.TokenCount != 6 || len(decoded.Files) != 2 || decoded.Files[1].Path != "lib.go" {
+		t.Errorf("Unexpected streamed output: %+v", decoded)
+	}
+}
+
+func TestStreamXMLMatchesRender(t *testing.T) {
+	files := []filesystem.FileInfo{{Path: "a<b>.go", Code: "x && y"}, {Path: "c.go", Code: "z"}}
+	rendered, err := Render(NewOutput("root/", 0, files), XML)
+	if err != nil {
+		t.Fatalf("Render failed: %v", err)
+	}
+
+	var out strings.Builder
+	stream, err := NewStream(&out, XML, nil, "root/", 0, nil)
+	if err != nil {
+		t.Fatalf("NewStream failed: %v", err)
+	}
+	for _, file := range files {
+		if err := stream.WriteFile(file); err != nil {
+			t.Fatalf("WriteFile failed: %v", err)
+		}
+	}
+	if err := stream.Close(); err != nil {
+		t.Fatalf("Close failed: %v", err)
+	}
+	if out.String() != rendered {
+		t.Errorf("Expected the stream to match the rendered output:\n%s\n%s", out.String(), rendered)
+	}
+}
+
+func TestStreamJSONLines(t *testing.T) {
+	var out strings.Builder
+	stream, err := NewStream(&out, JSONLines, nil, "root/", 1, nil)
+	if err != nil {
+		t.Fatalf("NewStream failed: %v", err)
+	}
+	files := []filesystem.FileInfo{
+		{Path: "main.go", Code: "package main\n\nfunc main() {}\n", TokenCount: 8},
+		{Path: "odd.txt", Code: "tab\there\x00\x1b[0m \"quoted\"", TokenCount: 5},
+	}
+	for _, file := range files {
+		if err := stream.WriteFile(file); err != nil {
+			t.Fatalf("WriteFile failed: %v", err)
+		}
+	}
+	if err := stream.Close(); err != nil {
+		t.Fatalf("Close failed: %v", err)
+	}
+
+	lines := strings.Split(strings.TrimSuffix(out.String(), "\n"), "\n")
+	if len(lines) != len(files) {
+		t.Fatalf("Expected one line per file and no tree, got %q", out.String())
+	}
+	for i, line := range lines {
+		var decoded File
+		if err := json.Unmarshal([]byte(line), &decoded); err != nil {
+			t.Fatalf("Line %d is not valid JSON: %v\n%s", i+1, err, line)
+		}
+		if decoded.Path != files[i].Path || decoded.Content != files[i].Code || decoded.TokenCount != files[i].TokenCount {
+			t.Errorf("Expected line %d to be %+v, got %+v", i+1, files[i], decoded)
+		}
+	}
+}
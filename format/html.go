@@ -0,0 +1,141 @@
+package format
+
+import (
+	_ "embed"
+	"fmt"
+	"html/template"
+	"path/filepath"
+	"strings"
+
+	"github.com/alecthomas/chroma/v2"
+	chromahtml "github.com/alecthomas/chroma/v2/formatters/html"
+	"github.com/alecthomas/chroma/v2/lexers"
+	"github.com/alecthomas/chroma/v2/styles"
+
+	"github.com/sammcj/ingest/utils"
+)
+
+//go:embed html.tmpl
+var htmlTemplate string
+
+// htmlReport is the page of the HTML format. html/template escapes every value from the output,
+// so paths and file contents can't inject markup or scripts into the report.
+var htmlReport = template.Must(template.New("report").Funcs(template.FuncMap{"number": utils.FormatNumber}).Parse(htmlTemplate))
+
+// htmlStyle is the Chroma style files are highlighted with.
+const htmlStyle = "github"
+
+// htmlFile is a file in the HTML report, with its content highlighted.
+type htmlFile struct {
+	File
+	ID   string        // The anchor of the file's section
+	Code template.HTML // The highlighted content, escaped by Chroma
+}
+
+// htmlNode is a directory or file in the report's tree of files.
+type htmlNode struct {
+	Name     string
+	ID       string // The anchor of a file's section, empty for a directory
+	Children []*htmlNode
+}
+
+// renderHTML renders a self-contained page for people rather than models: a clickable tree of
+// the files, the token counts, and each file highlighted in a section that starts collapsed.
+// The styles and the script that opens a file's section when it is linked to are inlined, so
+// the page is a single file that works offline.
+func renderHTML(out Output) (string, error) {
+	style := styles.Get(htmlStyle)
+	formatter := chromahtml.New(chromahtml.WithClasses(true), chromahtml.PreventSurroundingPre(true))
+	var css strings.Builder
+	if err := formatter.WriteCSS(&css, style); err != nil {
+		return "", fmt.Errorf("failed to write the highlighting styles: %w", err)
+	}
+
+	files := make([]htmlFile, len(out.Files))
+	for i, file := range out.Files {
+		code, err := highlight(formatter, style, file)
+		if err != nil {
+			return "", err
+		}
+		files[i] = htmlFile{File: file, ID: fmt.Sprintf("file-%d", i+1), Code: code}
+	}
+
+	var b strings.Builder
+	err := htmlReport.Execute(&b, map[string]any{
+		"Output": out,
+		"Files":  files,
+		"Tree":   htmlTree(files),
+		"CSS":    template.CSS(css.String()),
+	})
+	if err != nil {
+		return "", fmt.Errorf("failed to render HTML: %w", err)
+	}
+	return b.String() + "\n", nil
+}
+
+// highlight returns the content of file as HTML, highlighted for the language of its name, or
+// as plain text if Chroma doesn't know it.
+func highlight(formatter *chromahtml.Formatter, style *chroma.Style, file File) (template.HTML, error) {
+	lexer := lexers.Match(filepath.Base(file.Path))
+	if lexer == nil {
+		lexer = lexers.Fallback
+	}
+	iterator, err := chroma.Coalesce(lexer).Tokenise(nil, file.Content)
+	if err != nil {
+		return "", fmt.Errorf("failed to highlight %s: %w", file.Path, err)
+	}
+
+	var b strings.Builder
+	if err := formatter.Format(&b, style, iterator); err != nil {
+		return "", fmt.Errorf("failed to highlight %s: %w", file.Path, err)
+	}
+	return template.HTML(b.String()), nil
+}
+
+// htmlTree arranges files by their directories, in the order the files are listed, leaving out
+// the directories they all share, so the tree starts where their paths differ.
+func htmlTree(files []htmlFile) []*htmlNode {
+	paths := make([][]string, len(files))
+	for i, file := range files {
+		paths[i] = strings.Split(filepath.ToSlash(file.Path), "/")
+	}
+	shared := sharedDirs(paths)
+
+	root := &htmlNode{}
+	for i, parts := range paths {
+		node := root
+		for _, dir := range parts[shared : len(parts)-1] {
+			node = node.dir(dir)
+		}
+		node.Children = append(node.Children, &htmlNode{Name: parts[len(parts)-1], ID: files[i].ID})
+	}
+	return root.Children
+}
+
+// dir returns the directory called name in n, adding it if there isn't one.
+func (n *htmlNode) dir(name string) *htmlNode {
+	for _, child := range n.Children {
+		if child.ID == "" && child.Name == name {
+			return child
+		}
+	}
+	child := &htmlNode{Name: name}
+	n.Children = append(n.Children, child)
+	return child
+}
+
+// sharedDirs returns how many leading directories every path has in common.
+func sharedDirs(paths [][]string) int {
+	if len(paths) == 0 {
+		return 0
+	}
+	shared := len(paths[0]) - 1
+	for _, parts := range paths[1:] {
+		n := 0
+		for n < shared && n < len(parts)-1 && parts[n] == paths[0][n] {
+			n++
+		}
+		shared = n
+	}
+	return shared
+}
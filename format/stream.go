@@ -0,0 +1,127 @@
+package format
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"io"
+	"maps"
+	gotemplate "text/template"
+
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/template"
+)
+
+// Stream writes the output a file at a time as the files are read, rather than building it in
+// memory. Markdown is rendered with the header, file and footer templates of the template. The
+// content hash is left out, as it can only be computed once every file has been read, and JSON
+// gives its token_count after the files. JSON lines are only the files, each a File on a line of
+// its own, with no tree or total.
+type Stream struct {
+	w      io.Writer
+	format Format
+	tmpl   *gotemplate.Template
+	data   map[string]any
+	tokens int // Token count of the tree and the files written so far
+	files  int
+	buf    bytes.Buffer
+}
+
+// NewStream writes the start of the output to w, up to where the files go. treeTokens is the
+// token count of tree, and data holds more values for the template, such as git_data.
+func NewStream(w io.Writer, f Format, tmpl *gotemplate.Template, tree string, treeTokens int, data map[string]any) (*Stream, error) {
+	if f == Markdown && !template.Streamable(tmpl) {
+		return nil, fmt.Errorf("the template must define header, file and footer templates to be streamed")
+	}
+
+	s := &Stream{w: w, format: f, tmpl: tmpl, data: make(map[string]any, len(data)+2), tokens: treeTokens}
+	maps.Copy(s.data, data)
+	s.data["source_trees"] = tree
+	s.data["Tree"] = tree
+
+	switch f {
+	case Markdown:
+		if err := tmpl.ExecuteTemplate(&s.buf, "header", s.data); err != nil {
+			return nil, fmt.Errorf("failed to render template: %w", err)
+		}
+	case JSON:
+		treeJSON, err := json.Marshal(tree)
+		if err != nil {
+			return nil, fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Fprintf(&s.buf, "{\n  \"tree\": %s,\n  \"files\": [", treeJSON)
+	case JSONLines:
+		// Nothing comes before the files
+	case XML:
+		s.buf.WriteString("<documents>\n")
+		if tree != "" {
+			fmt.Fprintf(&s.buf, "<tree>\n%s\n</tree>\n", xmlEscaper.Replace(tree))
+		}
+	default:
+		return nil, fmt.Errorf("format %s can't be streamed", f)
+	}
+	return s, s.flush()
+}
+
+// WriteFile writes a file to the output. Its token count is expected to be set for JSON and
+// JSON lines.
+func (s *Stream) WriteFile(file filesystem.FileInfo) error {
+	s.files++
+	s.tokens += file.TokenCount
+
+	switch s.format {
+	case Markdown:
+		if err := s.tmpl.ExecuteTemplate(&s.buf, "file", file); err != nil {
+			return fmt.Errorf("failed to render template: %w", err)
+		}
+	case JSON:
+		fileJSON, err := json.MarshalIndent(NewFiles([]filesystem.FileInfo{file})[0], "    ", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		if s.files > 1 {
+			s.buf.WriteString(",")
+		}
+		fmt.Fprintf(&s.buf, "\n    %s", fileJSON)
+	case JSONLines:
+		// Marshal escapes newlines and control characters, so each file stays on one line
+		fileJSON, err := json.Marshal(NewFiles([]filesystem.FileInfo{file})[0])
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		s.buf.Write(fileJSON)
+		s.buf.WriteString("\n")
+	case XML:
+		fmt.Fprintf(&s.buf, "<document index=\"%d\">\n", s.files)
+		fmt.Fprintf(&s.buf, "<source>%s</source>\n", xmlEscaper.Replace(file.Path))
+		writeXMLMetadata(&s.buf, file.Metadata)
+		fmt.Fprintf(&s.buf, "<document_contents>\n%s\n</document_contents>\n", xmlEscaper.Replace(file.Code))
+		s.buf.WriteString("</document>\n")
+	}
+	return s.flush()
+}
+
+// Close writes the end of the output, after the files.
+func (s *Stream) Close() error {
+	switch s.format {
+	case Markdown:
+		if err := s.tmpl.ExecuteTemplate(&s.buf, "footer", s.data); err != nil {
+			return fmt.Errorf("failed to render template: %w", err)
+		}
+	case JSON:
+		if s.files > 0 {
+			s.buf.WriteString("\n  ")
+		}
+		fmt.Fprintf(&s.buf, "],\n  \"token_count\": %d\n}\n", s.tokens)
+	case XML:
+		s.buf.WriteString("</documents>\n")
+	}
+	return s.flush()
+}
+
+// flush writes what has been rendered to the output in one go.
+func (s *Stream) flush() error {
+	_, err := s.w.Write(s.buf.Bytes())
+	s.buf.Reset()
+	return err
+}
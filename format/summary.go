@@ -0,0 +1,33 @@
+package format
+
+import (
+	"fmt"
+	"strings"
+
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/utils"
+)
+
+// Summary renders the markdown for --summary-only: the source tree and a table of each file's
+// line and token counts, followed by the total tokens a full run would produce.
+func Summary(tree string, files []filesystem.FileInfo, totalTokens int) string {
+	var b strings.Builder
+	fmt.Fprintf(&b, "Source Trees:\n\n```\n%s\n```\n\n", strings.TrimRight(tree, "\n"))
+
+	if len(files) > 0 {
+		b.WriteString("| File | Lines | Tokens |\n")
+		b.WriteString("| --- | ---: | ---: |\n")
+		for _, file := range files {
+			path := strings.ReplaceAll(file.Path, "|", `\|`)
+			fmt.Fprintf(&b, "| %s | %s | %s |\n", path, utils.FormatNumber(file.Lines), utils.FormatNumber(file.TokenCount))
+		}
+		b.WriteString("\n")
+	}
+
+	noun := "files"
+	if len(files) == 1 {
+		noun = "file"
+	}
+	fmt.Fprintf(&b, "Total: %s tokens across %d %s\n", utils.FormatNumber(totalTokens), len(files), noun)
+	return b.String()
+}
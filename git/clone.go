@@ -0,0 +1,88 @@
+package git
+
+import (
+	"fmt"
+	"net/url"
+	"os/exec"
+	"regexp"
+	"strconv"
+	"strings"
+)
+
+// hostingServices are the hosts whose https://host/owner/repo URLs are repositories to clone
+// rather than pages to crawl.
+var hostingServices = map[string]bool{
+	"bitbucket.org": true,
+	"codeberg.org":  true,
+	"github.com":    true,
+	"gitlab.com":    true,
+}
+
+// scpURL matches scp-like remotes such as git@github.com:owner/repo.git.
+var scpURL = regexp.MustCompile(`^[\w.-]+@[\w.-]+:[^/\\]`)
+
+// IsRemoteURL reports whether arg is the URL of a remote git repository: an scp-like or ssh://
+// or git:// remote, an http(s) URL ending in .git, or the https://host/owner/repo URL of a
+// repository on a well known hosting service.
+func IsRemoteURL(arg string) bool {
+	if scpURL.MatchString(arg) {
+		return true
+	}
+	u, err := url.Parse(arg)
+	if err != nil || u.Host == "" {
+		return false
+	}
+
+	switch u.Scheme {
+	case "ssh", "git", "git+ssh":
+		return true
+	case "http", "https":
+		if strings.HasSuffix(u.Path, ".git") {
+			return true
+		}
+		host := strings.TrimPrefix(strings.ToLower(u.Hostname()), "www.")
+		segments := strings.Split(strings.Trim(u.Path, "/"), "/")
+		return hostingServices[host] && len(segments) == 2 && u.RawQuery == "" && u.Fragment == ""
+	}
+	return false
+}
+
+// RepositoryName returns the name of the repository at a remote URL, such as "repo" for
+// https://github.com/owner/repo.git.
+func RepositoryName(remote string) string {
+	name := strings.TrimSuffix(strings.TrimRight(remote, "/"), ".git")
+	if i := strings.LastIndexAny(name, "/:"); i >= 0 {
+		name = name[i+1:]
+	}
+	if name == "" {
+		return "repo"
+	}
+	return name
+}
+
+// Clone clones the repository at remote into dir, checking out branch if it isn't empty, with
+// depth commits of history, or all of it if depth is 0. Credentials come from the user's git
+// setup, such as a credential helper or SSH agent. If the clone fails, the error holds git's
+// own message.
+func Clone(remote, dir, branch string, depth int) error {
+	args := []string{"clone", "--quiet"}
+	if depth > 0 {
+		args = append(args, "--depth", strconv.Itoa(depth))
+	}
+	if branch != "" {
+		args = append(args, "--branch", branch)
+	}
+	args = append(args, "--", remote, dir)
+
+	var stderr strings.Builder
+	cmd := exec.Command("git", args...)
+	cmd.Stderr = &stderr
+	if err := cmd.Run(); err != nil {
+		message := strings.TrimSpace(stderr.String())
+		if message == "" {
+			message = err.Error()
+		}
+		return fmt.Errorf("failed to clone %s: %s", remote, message)
+	}
+	return nil
+}
@@ -0,0 +1,43 @@
+package git
+
+import "testing"
+
+func TestIsRemoteURL(t *testing.T) {
+	tests := []struct {
+		arg      string
+		expected bool
+	}{
+		{"https://github.com/owner/repo", true},
+		{"https://github.com/owner/repo/", true},
+		{"https://www.github.com/owner/repo", true},
+		{"https://example.com/team/repo.git", true},
+		{"git@github.com:owner/repo.git", true},
+		{"ssh://git@example.com/repo.git", true},
+		{"https://github.com/owner/repo/blob/main/README.md", false},
+		{"https://github.com/owner", false},
+		{"https://example.com/docs/guide", false},
+		{"./src", false},
+		{"main.go:10-20", false},
+	}
+
+	for _, tt := range tests {
+		if got := IsRemoteURL(tt.arg); got != tt.expected {
+			t.Errorf("IsRemoteURL(%q): expected %v, got %v", tt.arg, tt.expected, got)
+		}
+	}
+}
+
+func TestRepositoryName(t *testing.T) {
+	tests := map[string]string{
+		"https://github.com/owner/repo":     "repo",
+		"https://github.com/owner/repo.git": "repo",
+		"git@github.com:owner/tool.git":     "tool",
+		"git@example.com:project":           "project",
+	}
+
+	for remote, expected := range tests {
+		if got := RepositoryName(remote); got != expected {
+			t.Errorf("RepositoryName(%q): expected %q, got %q", remote, expected, got)
+		}
+	}
+}
@@ -2,7 +2,11 @@ package git
 
 import (
 	"fmt"
+	"os"
 	"os/exec"
+	"path/filepath"
+	"strconv"
+	"strings"
 )
 
 func GetGitDiff(repoPath string) (string, error) {
@@ -14,6 +18,131 @@ func GetGitDiff(repoPath string) (string, error) {
 	return string(output), nil
 }
 
+// IsRepository reports whether path is inside a git work tree.
+func IsRepository(path string) bool {
+	cmd := exec.Command("git", "-C", path, "rev-parse", "--is-inside-work-tree")
+	output, err := cmd.Output()
+	return err == nil && strings.TrimSpace(string(output)) == "true"
+}
+
+// TopLevel returns the top level directory of the git work tree containing path. ok is false
+// if path isn't inside a work tree.
+func TopLevel(path string) (top string, ok bool) {
+	cmd := exec.Command("git", "-C", path, "rev-parse", "--show-toplevel")
+	output, err := cmd.Output()
+	if err != nil {
+		return "", false
+	}
+	return strings.TrimSpace(string(output)), true
+}
+
+// GetChangedFiles returns the files with unstaged changes in the working tree, or with staged
+// changes in the index if staged is set. Paths are relative to, and limited to, repoPath.
+func GetChangedFiles(repoPath string, staged bool) ([]string, error) {
+	args := []string{"-C", repoPath, "diff", "--name-only", "--relative", "-z"}
+	if staged {
+		args = append(args, "--cached")
+	}
+
+	output, err := exec.Command("git", args...).Output()
+	if err != nil {
+		return nil, fmt.Errorf("failed to get changed files: %w", err)
+	}
+
+	var files []string
+	for file := range strings.SplitSeq(string(output), "\x00") {
+		if file != "" {
+			files = append(files, file)
+		}
+	}
+	return files, nil
+}
+
+// GetFileDiff returns the unified diff of a single file in the working tree, or in the index
+// if staged is set. The path is relative to repoPath.
+func GetFileDiff(repoPath, path string, staged bool) (string, error) {
+	args := []string{"-C", repoPath, "diff", "--relative"}
+	if staged {
+		args = append(args, "--cached")
+	}
+	args = append(args, "--", path)
+
+	output, err := exec.Command("git", args...).Output()
+	if err != nil {
+		return "", fmt.Errorf("failed to get git diff for %s: %w", path, err)
+	}
+	return string(output), nil
+}
+
+// ChangedFile is a file changed across a commit range.
+type ChangedFile struct {
+	Status  string // git status letter: A, M, D, R, C or T
+	Path    string // Current path, or the removed path for deletions
+	OldPath string // Previous path of a renamed or copied file
+}
+
+// Deleted reports whether the file no longer exists at the end of the range.
+func (f ChangedFile) Deleted() bool {
+	return f.Status == "D"
+}
+
+func (f ChangedFile) String() string {
+	switch f.Status {
+	case "A":
+		return "added: " + f.Path
+	case "D":
+		return "deleted: " + f.Path
+	case "R":
+		return "renamed: " + f.OldPath + " -> " + f.Path
+	case "C":
+		return "copied: " + f.OldPath + " -> " + f.Path
+	default:
+		return "modified: " + f.Path
+	}
+}
+
+// GetChangedFilesInRange returns the files changed across a revision range such as main..HEAD,
+// with renames detected. Paths are relative to, and limited to, repoPath.
+func GetChangedFilesInRange(repoPath, revRange string) ([]ChangedFile, error) {
+	cmd := exec.Command("git", "-C", repoPath, "diff", "--name-status", "-M", "--relative", "-z", revRange, "--")
+	output, err := cmd.Output()
+	if err != nil {
+		return nil, fmt.Errorf("failed to get files changed in %s: %w", revRange, err)
+	}
+
+	// Entries are NUL separated: a status, then one path (or two for renames and copies)
+	fields := strings.Split(strings.TrimSuffix(string(output), "\x00"), "\x00")
+	var files []ChangedFile
+	for i := 0; i < len(fields) && fields[i] != ""; {
+		status := fields[i][:1]
+		if (status == "R" || status == "C") && i+2 < len(fields) {
+			files = append(files, ChangedFile{Status: status, OldPath: fields[i+1], Path: fields[i+2]})
+			i += 3
+			continue
+		}
+		if i+1 >= len(fields) {
+			break
+		}
+		files = append(files, ChangedFile{Status: status, Path: fields[i+1]})
+		i += 2
+	}
+	return files, nil
+}
+
+// CountCommits returns the number of commits in a revision range.
+func CountCommits(repoPath, revRange string) (int, error) {
+	output, err := exec.Command("git", "-C", repoPath, "rev-list", "--count", revRange).Output()
+	if err != nil {
+		return 0, fmt.Errorf("failed to count commits in %s: %w", revRange, err)
+	}
+
+	count, err := strconv.Atoi(strings.TrimSpace(string(output)))
+	if err != nil {
+		return 0, fmt.Errorf("failed to parse commit count: %w", err)
+	}
+	return count, nil
+}
+
 func GetGitDiffBetweenBranches(repoPath, branch1, branch2 string) (string, error) {
 	cmd := exec.Command("git", "-C", repoPath, "diff", branch1+".."+branch2)
 	output, err := cmd.Output()
@@ -46,3 +175,51 @@ func BranchExists(repoPath, branchName string) (bool, error) {
 	}
 	return true, nil
 }
+
+// ExcludeFiles returns the top level directory of the work tree containing path, and the
+// exclude files git applies there besides .gitignore files, in increasing order of precedence:
+// the user's global excludes file (core.excludesFile, defaulting to $XDG_CONFIG_HOME/git/ignore)
+// if global is set, then the repository's info/exclude. The files might not exist. ok is false
+// if path isn't inside a work tree.
+func ExcludeFiles(path string, global bool) (top string, files []string, ok bool) {
+	cmd := exec.Command("git", "-C", path, "rev-parse", "--show-toplevel", "--git-path", "info/exclude")
+	output, err := cmd.Output()
+	if err != nil {
+		return "", nil, false
+	}
+	lines := strings.Split(strings.TrimSpace(string(output)), "\n")
+	if len(lines) != 2 {
+		return "", nil, false
+	}
+	top = lines[0]
+	infoExclude := lines[1]
+	if !filepath.IsAbs(infoExclude) {
+		infoExclude = filepath.Join(path, infoExclude)
+	}
+
+	if global {
+		if excludesFile := globalExcludesFile(path); excludesFile != "" {
+			files = append(files, excludesFile)
+		}
+	}
+	return top, append(files, infoExclude), true
+}
+
+// globalExcludesFile returns the path of the user's global excludes file, or "" if it can't
+// be found.
+func globalExcludesFile(path string) string {
+	cmd := exec.Command("git", "-C", path, "config", "--path", "--get", "core.excludesFile")
+	if output, err := cmd.Output(); err == nil && strings.TrimSpace(string(output)) != "" {
+		return strings.TrimSpace(string(output))
+	}
+
+	// Git's default when core.excludesFile isn't set
+	if configHome := os.Getenv("XDG_CONFIG_HOME"); configHome != "" {
+		return filepath.Join(configHome, "git", "ignore")
+	}
+	home, err := os.UserHomeDir()
+	if err != nil {
+		return ""
+	}
+	return filepath.Join(home, ".config", "git", "ignore")
+}
@@ -0,0 +1,152 @@
+package ingest
+
+import (
+	"fmt"
+
+	"github.com/sammcj/ingest/budget"
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/format"
+	"github.com/sammcj/ingest/token"
+	"github.com/sammcj/ingest/utils"
+)
+
+// fitBudget drops files so the rendered output fits within MaxTokens. Files are taken in
+// priority order while their estimated cost, including the template around each file, fits
+// alongside the tree and headers, then the output is rendered and counted to confirm it is
+// within the budget, dropping further files if the estimate was short. It returns the kept
+// and dropped files in their original order along with the rendered output.
+func (r *runner) fitBudget(files []filesystem.FileInfo, render func([]filesystem.FileInfo) (string, error)) ([]filesystem.FileInfo, []filesystem.FileInfo, string, error) {
+	baseTokens, cost, err := r.budgetCosts(render)
+	if err != nil {
+		return nil, nil, "", err
+	}
+	selected := budget.Select(files, r.opts.Priority.Order(files), r.opts.MaxTokens-baseTokens, cost)
+
+	for {
+		included := make(map[int]bool, len(selected))
+		for _, i := range selected {
+			included[i] = true
+		}
+
+		var kept, dropped []filesystem.FileInfo
+		for i, file := range files {
+			if included[i] {
+				kept = append(kept, file)
+			} else {
+				dropped = append(dropped, file)
+			}
+		}
+
+		rendered, err := render(kept)
+		if err != nil {
+			return nil, nil, "", err
+		}
+		if len(selected) == 0 || r.opts.Tokenizer.Count(rendered) <= r.opts.MaxTokens {
+			return kept, dropped, rendered, nil
+		}
+
+		// The estimate was short, drop the lowest priority file and try again
+		selected = selected[:len(selected)-1]
+	}
+}
+
+// budgetCosts counts the tokens of the output without files, which must fit within MaxTokens,
+// and returns them with a function estimating the tokens a file adds to the output, including
+// the template around it.
+func (r *runner) budgetCosts(render func([]filesystem.FileInfo) (string, error)) (int, func(filesystem.FileInfo) int, error) {
+	count := r.opts.Tokenizer.Count
+	empty, err := render(nil)
+	if err != nil {
+		return 0, nil, err
+	}
+	baseTokens := count(empty)
+	if baseTokens > r.opts.MaxTokens {
+		return 0, nil, fmt.Errorf("the source tree and template alone use %s tokens, over %s", utils.FormatNumber(baseTokens), r.budgetName())
+	}
+
+	header, err := render([]filesystem.FileInfo{{}})
+	if err != nil {
+		return 0, nil, err
+	}
+	perFile := max(count(header)-baseTokens, 0)
+
+	return baseTokens, func(file filesystem.FileInfo) int {
+		return file.TokenCount + count(file.Path) + perFile
+	}, nil
+}
+
+// budgetName describes MaxTokens in errors.
+func (r *runner) budgetName() string {
+	if r.opts.BudgetName != "" {
+		return r.opts.BudgetName
+	}
+	return "--max-tokens " + utils.FormatNumber(r.opts.MaxTokens)
+}
+
+// split divides the files into parts, each holding as many files as fit within MaxTokens, in
+// priority order. Files are never split between parts unless SplitLines splits one too large
+// for a part of its own at line boundaries. Every part repeats the source tree, so each can be
+// read on its own.
+func (r *runner) split(files []filesystem.FileInfo, render func([]filesystem.FileInfo) (string, error)) ([]Part, error) {
+	baseTokens, cost, err := r.budgetCosts(render)
+	if err != nil {
+		return nil, err
+	}
+	available := r.opts.MaxTokens - baseTokens
+
+	// Lines are counted offline, as there are too many to send to an API
+	lineCounter := token.NewCounter(r.opts.Tokenizer.encoding(), r.opts.Tokenizer.NoCorrection)
+	var pending []filesystem.FileInfo
+	for _, i := range r.opts.Priority.Order(files) {
+		file := files[i]
+		if r.opts.SplitLines && cost(file) > available {
+			pending = append(pending, budget.SplitLines(file, available-(cost(file)-file.TokenCount), lineCounter.Count)...)
+			continue
+		}
+		pending = append(pending, file)
+	}
+
+	order := make([]int, len(pending))
+	for i := range order {
+		order[i] = i
+	}
+
+	var parts []Part
+	for len(parts) == 0 || len(pending) > 0 {
+		// Take the files estimated to fit, then check the rendered output does, taking fewer if not
+		n := min(max(len(budget.Select(pending, order[:len(pending)], available, cost)), 1), len(pending))
+		var part Part
+		for {
+			if part.Rendered, err = render(pending[:n]); err != nil {
+				return nil, err
+			}
+			part.Tokens = r.opts.Tokenizer.Count(part.Rendered)
+			if n <= 1 || part.Tokens <= r.opts.MaxTokens {
+				break
+			}
+			n--
+		}
+		part.Files = pending[:n]
+		parts = append(parts, part)
+		pending = pending[n:]
+	}
+	return parts, nil
+}
+
+// renderSummary renders the SummaryOnly output: the tree and per-file counts of the files
+// without their contents, with the token total of the full output.
+func renderSummary(outputFormat format.Format, tree string, files []filesystem.FileInfo, totalTokens int) (string, error) {
+	if outputFormat == format.Markdown {
+		return format.Summary(tree, files, totalTokens), nil
+	}
+
+	out := format.NewOutput(tree, totalTokens, files)
+	for i := range out.Files {
+		out.Files[i].Content = ""
+	}
+	rendered, err := format.Render(out, outputFormat)
+	if err != nil {
+		return "", fmt.Errorf("failed to render %s output: %w", outputFormat, err)
+	}
+	return rendered, nil
+}
@@ -0,0 +1,454 @@
+// Package ingest is the library behind the ingest command. Run finds and reads files, optionally
+// compresses them, renders them into a prompt and counts its tokens, returning the lot as a
+// Result so tools can embed ingest rather than shelling out to it.
+package ingest
+
+import (
+	"errors"
+	"fmt"
+	"io"
+	"maps"
+	"path/filepath"
+	"strings"
+	gotemplate "text/template"
+
+	"github.com/sammcj/ingest/budget"
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/format"
+	"github.com/sammcj/ingest/internal/compressor"
+	"github.com/sammcj/ingest/template"
+	"github.com/sammcj/ingest/token"
+	"github.com/sammcj/ingest/web"
+)
+
+// ReadmeFirstOrder describes the file order to templates when ReadmeFirst is in effect.
+const ReadmeFirstOrder = "README, CONTRIBUTING and other Markdown files are listed before the other files in each directory."
+
+// LanguageOrder describes the file order to templates when files are grouped by language.
+const LanguageOrder = "Files are grouped by language, with the languages with the most tokens first."
+
+// Options configures Run and Stream. The embedded filesystem.Options control how files are
+// found and formatted, such as the include and exclude patterns. Set its FS to ingest an fs.FS,
+// such as an embed.FS or fstest.MapFS, instead of the OS. Its Compressor, CompressGlobsOnly,
+// TokenEncoding and NoCorrection are set by Run from the fields below, and each path is walked
+// with its own copy of its ExecFilter.
+type Options struct {
+	filesystem.Options
+
+	Paths            []string        // Files, directories, archives, URLs and git URLs to ingest, within FS if it is set. Defaults to "."
+	Stdin            io.Reader       // If set, newline-delimited file paths to ingest instead of Paths, such as the output of rg -l
+	Compress         bool            // Compress code with Tree-sitter, otherwise only the files CompressGlobs say to
+	CompressionLevel string          // signatures, no-bodies (the default) or no-comments, implies Compress
+	Template         string          // Path of the template to render Markdown with, "" for the default
+	Format           format.Format   // Output format, Markdown if empty. JSONLines can only be streamed
+	GroupByLanguage  bool            // List files in one section per language rather than by directory
+	Dedupe           bool            // Replace files identical to an earlier one with a note naming it
+	CollapseSimilar  float64         // If set, replace files at least this similar to an earlier one with a note naming it
+	NoTree           bool            // Leave the tree out of Rendered, though Result.Tree is still set
+	TreeOnly         bool            // Only render the tree, without reading the files unless TreeStats needs them
+	DryRun           bool            // Only find the files, returning them without reading or rendering them
+	SummaryOnly      bool            // Render the tree and each file's token count instead of their contents
+	PromptSuffix     string          // Appended to Markdown output, such as an instruction for the model
+	MaxTokens        int             // If set, files are dropped in Priority order until Rendered fits within this many tokens
+	BudgetName       string          // Describes MaxTokens in errors, "--max-tokens" and its value if empty
+	Priority         budget.Priority // The order files are kept in to fit within MaxTokens
+	Split            bool            // Split the files into Result.Parts of up to MaxTokens each, rather than dropping them
+	SplitLines       bool            // With Split, split a file too large for a part of its own at line boundaries
+	NoFileTokens     bool            // Don't count each file's tokens unless the output needs them
+	NoTotal          bool            // Leave TotalTokens unset, saving a count of the output
+	Tokenizer        Tokenizer
+
+	FollowImports bool   // Also ingest the local files that files in Paths import, and the files those import in turn
+	DiffMode      string // working or staged to only ingest the files changed in git, each with its diff
+	DiffRange     string // If set, only ingest the files changed across this git revision range
+	GitDiffBranch string // Two branches separated by a comma, whose diff is passed to templates in git_data
+	GitLogBranch  string // Two branches separated by a comma, whose log is passed to templates in git_data
+	Branch        string // Branch or tag to check out when cloning a git URL in Paths
+	CloneDepth    int    // Commits of history to clone from a git URL, 0 for all of them
+
+	Web        web.CrawlOptions // How URLs in Paths are crawled
+	WebOnly    bool             // Crawl every path, which must be a URL, rather than cloning git URLs
+	CrawlPaths bool             // Crawl from a URL with a path to Web.MaxDepth too, rather than only fetching that page
+
+	// Phase, if set, is called as each step after reading the files starts, such as "Rendering".
+	Phase func(name string)
+}
+
+// Result is everything Run produced.
+type Result struct {
+	Tree        string                     // The tree of each path, separated by blank lines
+	Files       []filesystem.FileInfo      // The ingested files, with their token counts
+	Dropped     []filesystem.FileInfo      // The files left out to fit within MaxTokens
+	Excluded    []*filesystem.ExcludedInfo // What was left out of each directory, and why
+	Rendered    string                     // The prompt, or the serialised result for structured formats
+	Parts       []Part                     // With Split, the outputs the files were split into, instead of Rendered
+	ContentHash string                     // SHA-256 over the relative paths and contents of the files
+	TotalTokens int                        // Tokens in Rendered or the Parts, or in the full output with SummaryOnly
+	Deduped     int                        // Files replaced with a note by Dedupe
+	Collapsed   int                        // Files replaced with a note by CollapseSimilar
+}
+
+// Part is one of the outputs the files are split into with Split.
+type Part struct {
+	Rendered string
+	Tokens   int
+	Files    []filesystem.FileInfo // The files in the part, or pieces of them if SplitLines split them
+}
+
+// Run ingests the paths in opts, renders them and counts the tokens of the result.
+func Run(opts Options) (Result, error) {
+	r, err := newRunner(opts)
+	if err != nil {
+		return Result{}, err
+	}
+	if r.format == format.JSONLines {
+		return Result{}, errors.New("JSON lines can only be streamed, with Stream")
+	}
+	paths, cloned, removeClones, err := r.cloneRemote(r.paths())
+	if err != nil {
+		return Result{}, err
+	}
+	defer removeClones()
+
+	in, err := r.gather(paths, cloned)
+	if err != nil {
+		return Result{}, err
+	}
+
+	// Each path keeps its own tree section, while their files are merged into one list
+	result := Result{Tree: strings.Join(in.trees, "\n\n"), Files: filesystem.MergeSources(in.sources), Excluded: in.excluded}
+	if opts.DryRun {
+		if opts.Manifest != nil {
+			// The files may yet be skipped once they're read
+			for _, file := range result.Files {
+				opts.Manifest.Add(filesystem.ManifestEntry{Path: file.Path, Status: filesystem.StatusUnread})
+			}
+		}
+		return result, nil
+	}
+
+	// The tree was generated during the walk, so the files have served their purpose
+	if opts.TreeOnly {
+		result.Files = nil
+	}
+
+	// Identical and near-identical files are compared once every path is merged, so copies
+	// across paths are found. Exact copies are replaced first, so they say they're identical
+	if opts.Dedupe {
+		result.Deduped = filesystem.Dedupe(result.Files)
+	}
+	if opts.CollapseSimilar > 0 {
+		result.Collapsed = filesystem.CollapseSimilar(result.Files, opts.CollapseSimilar)
+	}
+
+	if r.fileTokens {
+		r.phase("Counting tokens")
+		opts.Tokenizer.CountFiles(result.Files)
+	}
+	r.phase("Rendering")
+
+	var excluded any
+	if len(result.Excluded) > 0 {
+		excluded = result.Excluded[0]
+	}
+	data := map[string]any{"git_data": in.gitData, "excluded": excluded}
+	if opts.ReadmeFirst && len(opts.Order) == 0 {
+		data["FileOrder"] = ReadmeFirstOrder
+	}
+
+	tree := result.Tree
+	if opts.NoTree {
+		tree = ""
+	}
+	renderer := Renderer{Template: r.tmpl, Format: r.format, Tree: tree, Data: data, GroupByLanguage: opts.GroupByLanguage, Tokenizer: opts.Tokenizer}
+	render := func(files []filesystem.FileInfo) (string, error) {
+		rendered, hash, err := renderer.Render(files)
+		if err != nil {
+			return "", err
+		}
+		result.ContentHash = hash
+		if opts.PromptSuffix != "" && r.format == format.Markdown {
+			rendered += "\n" + opts.PromptSuffix
+		}
+		return rendered, nil
+	}
+
+	if opts.Split {
+		if result.Parts, err = r.split(result.Files, render); err != nil {
+			return Result{}, err
+		}
+		for _, part := range result.Parts {
+			result.TotalTokens += part.Tokens
+		}
+		r.recordManifest(result.Files, nil)
+		return result, nil
+	}
+
+	if opts.MaxTokens > 0 {
+		result.Files, result.Dropped, result.Rendered, err = r.fitBudget(result.Files, render)
+	} else {
+		result.Rendered, err = render(result.Files)
+	}
+	if err != nil {
+		return Result{}, err
+	}
+	r.recordManifest(result.Files, result.Dropped)
+
+	// A summary reports the total of the full output it replaces
+	if opts.SummaryOnly {
+		result.TotalTokens = opts.Tokenizer.Count(result.Rendered)
+		if result.Rendered, err = renderSummary(r.format, tree, result.Files, result.TotalTokens); err != nil {
+			return Result{}, err
+		}
+	} else if !opts.NoTotal {
+		result.TotalTokens = opts.Tokenizer.Count(result.Rendered)
+	}
+	return result, nil
+}
+
+// runner holds what Run and Stream work out from the Options before ingesting anything.
+type runner struct {
+	opts         Options
+	format       format.Format
+	tmpl         *gotemplate.Template
+	level        compressor.CompressionLevel
+	compress     bool
+	fileTokens   bool   // Whether each file's tokens are counted
+	walkEncoding string // The encoding files are counted with as they are read, if any
+}
+
+func newRunner(opts Options) (*runner, error) {
+	r := &runner{opts: opts, format: opts.Format}
+	if r.format == "" {
+		r.format = format.Markdown
+	}
+	if r.format != format.JSONLines {
+		if _, err := format.Parse(string(r.format)); err != nil {
+			return nil, err
+		}
+	}
+	if r.format != format.Markdown {
+		// Structured formats carry file contents verbatim
+		r.opts.NoCodeblock = true
+	}
+
+	var err error
+	if r.level, err = compressor.ParseCompressionLevel(opts.CompressionLevel); err != nil {
+		return nil, err
+	}
+	r.compress = opts.Compress || opts.CompressionLevel != "" || opts.CompressFallback
+
+	if r.tmpl, err = template.SetupTemplate(opts.Template); err != nil {
+		return nil, fmt.Errorf("failed to set up template: %w", err)
+	}
+
+	// Per-file token counts are needed for the token budget, tree stats, summaries, language
+	// subtotals, compression savings, templates that show them, structured formats and the
+	// manifest. Offline counts are done while reading files, API counts are batched afterwards.
+	// Tree stats are built during the walk so always use the offline tokeniser.
+	templateTokens := r.format == format.Markdown && template.UsesField(r.tmpl, "TokenCount", "TotalTokens")
+	r.fileTokens = !opts.NoFileTokens || opts.MaxTokens > 0 || opts.TreeStats || opts.SummaryOnly || opts.GroupByLanguage ||
+		r.compress || len(opts.CompressGlobs) > 0 || templateTokens || r.format != format.Markdown || opts.Manifest != nil
+	if r.fileTokens && (!opts.Tokenizer.Anthropic || opts.TreeStats) {
+		r.walkEncoding = opts.Tokenizer.encoding()
+	}
+	return r, nil
+}
+
+// paths returns the paths to ingest, the current directory if there are none.
+func (r *runner) paths() []string {
+	if len(r.opts.Paths) == 0 {
+		return []string{"."}
+	}
+	return r.opts.Paths
+}
+
+// walkOptions returns the options to walk a single path with. Each path gets its own
+// compressor, and its own exec filter, as a filter keeps the failure that stops its walk.
+func (r *runner) walkOptions() filesystem.Options {
+	opts := r.opts.Options
+	opts.ListOnly = opts.ListOnly || r.opts.DryRun || r.opts.TreeOnly && !opts.TreeStats
+	opts.Compressor = nil
+	// Without Compress only the files the CompressGlobs say to are compressed
+	if r.compress || len(opts.CompressGlobs) > 0 {
+		opts.Compressor = compressor.NewGenericCompressorWithLevel(r.level)
+		opts.CompressGlobsOnly = !r.compress
+	}
+	if opts.ExecFilter != nil {
+		opts.ExecFilter = filesystem.NewExecFilter(opts.ExecFilter.Command, opts.ExecFilter.KeepGoing)
+	}
+	opts.TokenEncoding = r.walkEncoding
+	opts.NoCorrection = r.opts.Tokenizer.NoCorrection
+	return opts
+}
+
+// phase reports the start of a step to the Phase callback, if there is one.
+func (r *runner) phase(name string) {
+	if r.opts.Phase != nil {
+		r.opts.Phase(name)
+	}
+}
+
+// recordManifest marks the files that are output as included in the Manifest, if there is
+// one, with their final token counts, and those dropped to fit within MaxTokens.
+func (r *runner) recordManifest(kept, dropped []filesystem.FileInfo) {
+	if r.opts.Manifest == nil {
+		return
+	}
+	for _, file := range kept {
+		r.opts.Manifest.Add(filesystem.ManifestEntry{Path: file.Path, Status: filesystem.StatusIncluded, Tokens: file.TokenCount})
+	}
+	for _, file := range dropped {
+		r.opts.Manifest.Add(filesystem.ManifestEntry{Path: file.Path, Status: filesystem.StatusBudget, Tokens: file.TokenCount})
+	}
+}
+
+// WalkPath ingests the file or directory at path, returning its section of the tree, its files
+// and, for a directory, what was left out of it.
+func WalkPath(path string, opts filesystem.Options) (string, []filesystem.FileInfo, *filesystem.ExcludedInfo, error) {
+	info, err := opts.Stat(path)
+	if err != nil {
+		return "", nil, nil, fmt.Errorf("failed to get file info for %s: %w", path, err)
+	}
+
+	if info.IsDir() {
+		tree, files, excluded, err := filesystem.WalkDirectory(path, opts)
+		if err != nil {
+			return "", nil, nil, fmt.Errorf("failed to process directory %s: %w", path, err)
+		}
+		return fmt.Sprintf("%s:\n%s", opts.DisplayPath(path, filepath.Base(path)), tree), files, excluded, nil
+	}
+
+	tree := fmt.Sprintf("File: %s", opts.DisplayPath(path, filepath.Base(path)))
+	file, err := filesystem.ProcessSingleFile(path, opts)
+	var skipped *filesystem.SkipError
+	if errors.As(err, &skipped) {
+		return tree, nil, skipped.Excluded, nil
+	}
+	if err != nil {
+		return "", nil, nil, fmt.Errorf("failed to process file %s: %w", path, err)
+	}
+	return tree, []filesystem.FileInfo{file}, nil, nil
+}
+
+// Renderer turns ingested files into the output: a prompt rendered with a template for
+// Markdown, or the serialised result for structured formats.
+type Renderer struct {
+	Template  *gotemplate.Template // Template for Markdown, from template.SetupTemplate
+	Format    format.Format
+	Tree      string         // The tree of the ingested paths
+	Data      map[string]any // More values for the template, such as git_data
+	Tokenizer Tokenizer      // Counts the total for structured formats and TotalTokens
+
+	// GroupByLanguage lists the files in one section per language, those with the most tokens
+	// first, passing the sections to templates as LanguageGroups.
+	GroupByLanguage bool
+}
+
+// Render returns the output for files, along with their content hash. Files are expected to
+// have their token counts set.
+func (r Renderer) Render(files []filesystem.FileInfo) (string, string, error) {
+	contentHash := filesystem.ContentHash(files)
+
+	var groups []filesystem.LanguageGroup
+	if r.GroupByLanguage {
+		groups = filesystem.GroupByLanguage(files)
+		files = make([]filesystem.FileInfo, 0, len(files))
+		for _, group := range groups {
+			files = append(files, group.Files...)
+		}
+	}
+
+	if r.Format == "" || r.Format == format.Markdown {
+		// The lower case keys predate Tree, Files and TotalTokens and are kept for existing templates
+		data := maps.Clone(r.Data)
+		if data == nil {
+			data = make(map[string]any)
+		}
+		if r.GroupByLanguage {
+			data["LanguageGroups"] = groups
+			data["FileOrder"] = LanguageOrder
+		}
+		data["source_trees"] = r.Tree
+		data["Tree"] = r.Tree
+		data["files"] = files
+		data["Files"] = format.NewFiles(files)
+		data["ContentHash"] = contentHash
+		if annotations := filesystem.Annotations(files); len(annotations) > 0 {
+			data["Annotations"] = annotations
+		}
+		if template.UsesField(r.Template, "TotalTokens") {
+			data["TotalTokens"] = r.Tokenizer.Total(r.Tree, files)
+		}
+		rendered, err := template.RenderTemplate(r.Template, data)
+		if err != nil {
+			return "", "", fmt.Errorf("failed to render template: %w", err)
+		}
+		return rendered, contentHash, nil
+	}
+
+	out := format.NewOutput(r.Tree, r.Tokenizer.Total(r.Tree, files), files)
+	out.ContentHash = contentHash
+	out.Annotations = filesystem.Annotations(files)
+	if r.GroupByLanguage {
+		out.Languages = format.NewLanguages(groups)
+	}
+	rendered, err := format.Render(out, r.Format)
+	if err != nil {
+		return "", "", fmt.Errorf("failed to render %s output: %w", r.Format, err)
+	}
+	return rendered, contentHash, nil
+}
+
+// Tokenizer counts tokens offline with a tiktoken encoding or a Hugging Face tokeniser, or with
+// the Anthropic API.
+type Tokenizer struct {
+	Encoding     string // Offline encoding such as o200k_base, or from token.HuggingFaceEncoding, o200k_base if empty
+	Anthropic    bool   // Count with the Anthropic API, which needs ANTHROPIC_API_KEY or ANTHROPIC_TOKEN
+	NoCorrection bool   // Disable the correction factor applied to offline counts
+}
+
+func (t Tokenizer) encoding() string {
+	if t.Encoding == "" {
+		return "o200k_base"
+	}
+	return t.Encoding
+}
+
+// Label names the tokeniser, to label the counts it produces.
+func (t Tokenizer) Label() string {
+	return token.Label(t.encoding(), t.Anthropic)
+}
+
+// Count returns the number of tokens in content.
+func (t Tokenizer) Count(content string) int {
+	return token.CountTokens(content, t.encoding(), t.Anthropic, t.NoCorrection)
+}
+
+// CountFiles sets the token count of each file not already counted while walking, such as web
+// pages, or every file when using the Anthropic API.
+func (t Tokenizer) CountFiles(files []filesystem.FileInfo) {
+	var pending []int
+	var contents []string
+	for i := range files {
+		if files[i].TokenCount == 0 && files[i].Code != "" {
+			pending = append(pending, i)
+			contents = append(contents, files[i].Code)
+		}
+	}
+
+	counts := token.CountTokensBatch(contents, t.encoding(), t.Anthropic, t.NoCorrection)
+	for j, i := range pending {
+		files[i].TokenCount = counts[j]
+	}
+}
+
+// Total returns the token count of the tree and the contents of files, which have their token
+// counts set. It is the total reported by structured formats and templates that show TotalTokens.
+func (t Tokenizer) Total(tree string, files []filesystem.FileInfo) int {
+	total := t.Count(tree)
+	for _, file := range files {
+		total += file.TokenCount
+	}
+	return total
+}
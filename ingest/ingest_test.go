@@ -0,0 +1,190 @@
+package ingest
+
+import (
+	"encoding/json"
+	"strings"
+	"testing"
+	"testing/fstest"
+
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/format"
+)
+
+func TestRunFS(t *testing.T) {
+	fsys := fstest.MapFS{
+		".gitignore":    {Data: []byte("secret.txt\n")},
+		"main.go":       {Data: []byte("package main\n")},
+		"docs/guide.md": {Data: []byte("# Guide\n")},
+		"secret.txt":    {Data: []byte("hunter2\n")},
+	}
+
+	result, err := Run(Options{Options: filesystem.Options{FS: fsys}})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+
+	paths := make(map[string]bool)
+	for _, file := range result.Files {
+		paths[file.RelPath] = true
+	}
+	if !paths["main.go"] || !paths["docs/guide.md"] {
+		t.Errorf("expected main.go and docs/guide.md to be ingested, got %v", paths)
+	}
+	if paths["secret.txt"] {
+		t.Error("expected secret.txt to be left out by .gitignore")
+	}
+	if !strings.Contains(result.Rendered, "package main") {
+		t.Errorf("expected the rendered prompt to contain main.go, got:\n%s", result.Rendered)
+	}
+	if !strings.Contains(result.Tree, "guide.md") || result.ContentHash == "" {
+		t.Errorf("expected a tree and content hash, got %q and %q", result.Tree, result.ContentHash)
+	}
+}
+
+func TestRunFSJSON(t *testing.T) {
+	fsys := fstest.MapFS{
+		"src/main.go": {Data: []byte("package main\n")},
+	}
+
+	result, err := Run(Options{
+		Options: filesystem.Options{FS: fsys},
+		Paths:   []string{"src/main.go"},
+		Format:  format.JSON,
+	})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+
+	var out format.Output
+	if err := json.Unmarshal([]byte(result.Rendered), &out); err != nil {
+		t.Fatalf("expected JSON output: %v", err)
+	}
+	if len(out.Files) != 1 || out.Files[0].Content != "package main\n" {
+		t.Errorf("expected main.go without a code block, got %+v", out.Files)
+	}
+}
+
+func TestRunGroupByLanguage(t *testing.T) {
+	fsys := fstest.MapFS{
+		"a/main.go":     {Data: []byte("package main\n")},
+		"a/schema.sql":  {Data: []byte(strings.Repeat("CREATE TABLE users (id INTEGER PRIMARY KEY);\n", 20))},
+		"b/util.go":     {Data: []byte("package b\n")},
+		"b/notes.xyz":   {Data: []byte("notes\n")},
+		"b/queries.sql": {Data: []byte("SELECT 1;\n")},
+	}
+
+	result, err := Run(Options{Options: filesystem.Options{FS: fsys}, GroupByLanguage: true})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+
+	rendered := result.Rendered
+	sql, goSection := strings.Index(rendered, "## SQL ("), strings.Index(rendered, "## Go (")
+	if sql < 0 || goSection < 0 || !strings.Contains(rendered, "## other (") {
+		t.Fatalf("expected SQL, Go and other sections, got:\n%s", rendered)
+	}
+	if sql > goSection {
+		t.Error("expected the SQL section, with the most tokens, to come first")
+	}
+
+	goFiles := rendered[goSection:]
+	if end := strings.Index(goFiles[1:], "## "); end >= 0 {
+		goFiles = goFiles[:end+1]
+	}
+	if !strings.Contains(goFiles, "package main") || !strings.Contains(goFiles, "package b") {
+		t.Errorf("expected both Go files in the Go section, got:\n%s", goFiles)
+	}
+}
+
+func TestRunNoTreeAndTreeOnly(t *testing.T) {
+	fsys := fstest.MapFS{
+		"src/main.go": {Data: []byte("package main\n")},
+	}
+
+	result, err := Run(Options{Options: filesystem.Options{FS: fsys}, NoTree: true})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+	if strings.Contains(result.Rendered, "Source Trees:") || !strings.Contains(result.Rendered, "src/main.go:`") {
+		t.Errorf("expected the file with its path header but no tree, got:\n%s", result.Rendered)
+	}
+
+	result, err = Run(Options{Options: filesystem.Options{FS: fsys}, TreeOnly: true})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+	if !strings.Contains(result.Rendered, "main.go") || strings.Contains(result.Rendered, "package main") || len(result.Files) != 0 {
+		t.Errorf("expected only the tree, got %d files and:\n%s", len(result.Files), result.Rendered)
+	}
+}
+
+func TestRunCompressGlobs(t *testing.T) {
+	code := []byte("package main\n\nfunc run() {\n\tprintln(\"running\")\n}\n")
+	fsys := fstest.MapFS{
+		"main.go":       {Data: code},
+		"api/client.go": {Data: code},
+	}
+
+	// Without Compress, only the files matching a glob are compressed
+	globs := []filesystem.CompressGlob{{Pattern: "api/**", Compress: true}}
+	result, err := Run(Options{Options: filesystem.Options{FS: fsys, CompressGlobs: globs}})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+
+	compressed := make(map[string]bool)
+	for _, file := range result.Files {
+		compressed[file.RelPath] = file.Compressed
+	}
+	if !compressed["api/client.go"] || compressed["main.go"] {
+		t.Errorf("expected only api/client.go to be compressed, got %v", compressed)
+	}
+}
+
+func TestRunMaxTokens(t *testing.T) {
+	fsys := fstest.MapFS{
+		"a.go": {Data: []byte("package a\n")},
+		"b.go": {Data: []byte(strings.Repeat("// padding to go over the budget\n", 200))},
+	}
+
+	result, err := Run(Options{Options: filesystem.Options{FS: fsys}, MaxTokens: 500})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+	if len(result.Files) != 1 || result.Files[0].RelPath != "a.go" {
+		t.Errorf("expected only a.go to fit, got %+v", result.Files)
+	}
+	if len(result.Dropped) != 1 || result.Dropped[0].RelPath != "b.go" {
+		t.Errorf("expected b.go to be dropped, got %+v", result.Dropped)
+	}
+	if result.TotalTokens == 0 || result.TotalTokens > 500 {
+		t.Errorf("expected the output to be counted within the budget, got %d tokens", result.TotalTokens)
+	}
+}
+
+func TestRunSplit(t *testing.T) {
+	fsys := fstest.MapFS{
+		"a.go": {Data: []byte(strings.Repeat("// first\n", 100))},
+		"b.go": {Data: []byte(strings.Repeat("// second\n", 100))},
+		"c.go": {Data: []byte(strings.Repeat("// third\n", 100))},
+	}
+
+	result, err := Run(Options{Options: filesystem.Options{FS: fsys}, MaxTokens: 500, Split: true})
+	if err != nil {
+		t.Fatalf("Run failed: %v", err)
+	}
+	if len(result.Parts) < 2 || result.Rendered != "" {
+		t.Fatalf("expected the files to be split into parts, got %d parts", len(result.Parts))
+	}
+	files, total := 0, 0
+	for _, part := range result.Parts {
+		if part.Tokens > 500 {
+			t.Errorf("expected each part within the budget, got %d tokens", part.Tokens)
+		}
+		files += len(part.Files)
+		total += part.Tokens
+	}
+	if files != 3 || total != result.TotalTokens {
+		t.Errorf("expected 3 files across the parts and their tokens in the total, got %d files and %d of %d tokens", files, total, result.TotalTokens)
+	}
+}
@@ -0,0 +1,471 @@
+package ingest
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"io/fs"
+	"maps"
+	"net/url"
+	"os"
+	"path/filepath"
+	"slices"
+	"strings"
+
+	"github.com/fatih/color"
+	"github.com/sammcj/ingest/archive"
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/git"
+	"github.com/sammcj/ingest/utils"
+	"github.com/sammcj/ingest/web"
+)
+
+// GitData is the git information about a local path passed to templates as git_data.
+type GitData struct {
+	Path          string
+	GitDiff       string
+	GitDiffBranch string
+	GitLogBranch  string
+	DiffRange     string            // Revision range given with DiffRange
+	CommitCount   int               // Number of commits in DiffRange
+	Changes       []git.ChangedFile // Files changed across DiffRange, including deletions
+}
+
+// inputs are the ingested paths, each with its own section of the tree.
+type inputs struct {
+	sources  []filesystem.Source
+	trees    []string
+	excluded []*filesystem.ExcludedInfo
+	gitData  []GitData
+}
+
+func (in *inputs) add(name, tree string, files []filesystem.FileInfo, excluded *filesystem.ExcludedInfo) {
+	in.sources = append(in.sources, filesystem.Source{Name: name, Files: files})
+	in.trees = append(in.trees, tree)
+	if excluded != nil {
+		in.excluded = append(in.excluded, excluded)
+	}
+}
+
+// gather ingests the paths, or the paths read from Stdin, and then the files they import if
+// FollowImports is set. cloned is the set of paths that are clones of git URLs.
+func (r *runner) gather(paths []string, cloned map[string]bool) (*inputs, error) {
+	in := &inputs{}
+	if r.opts.Stdin != nil {
+		tree, files, excluded, err := processStdinPaths(r.opts.Stdin, r.walkOptions())
+		if err != nil {
+			return nil, err
+		}
+		in.add("stdin", tree, files, excluded)
+		paths = nil
+	}
+
+	// With FollowImports, imports are followed from the files given once every path is read
+	var importStarts, localPaths []string
+	for _, arg := range paths {
+		if r.opts.WebOnly || IsURL(arg) {
+			if err := r.addWeb(in, arg); err != nil {
+				return nil, err
+			}
+			continue
+		}
+
+		path, info, lineRanges, err := r.resolve(arg)
+		if err != nil {
+			return nil, err
+		}
+
+		// Archives are walked like directories, reading their entries without extracting them
+		if r.opts.FS == nil && !info.IsDir() && archive.IsArchive(path) {
+			if len(lineRanges) > 0 {
+				return nil, fmt.Errorf("line ranges can only be given for files, but %s is an archive", arg)
+			}
+			tree, files, excluded, err := processArchive(path, r.walkOptions())
+			if err != nil {
+				return nil, err
+			}
+			in.add(filepath.Base(path), tree, files, excluded)
+			continue
+		}
+
+		opts, err := r.localOptions(path, info, lineRanges, cloned[path])
+		if err != nil {
+			return nil, err
+		}
+		localPaths = append(localPaths, path)
+		if r.opts.FollowImports && !info.IsDir() {
+			importStarts = append(importStarts, path)
+		}
+
+		var rangeData GitData
+		if r.opts.DiffRange != "" {
+			if !info.IsDir() {
+				return nil, fmt.Errorf("--diff-range requires a directory, but %s is a file", arg)
+			}
+			if rangeData, err = applyDiffRange(&opts, path, r.opts.DiffRange); err != nil {
+				return nil, err
+			}
+		}
+
+		tree, files, excluded, err := WalkPath(path, opts)
+		if err != nil {
+			return nil, err
+		}
+		in.add(filepath.Base(path), tree, files, excluded)
+		if !r.opts.DryRun {
+			in.gitData = append(in.gitData, r.gitData(path, rangeData))
+		}
+	}
+
+	if len(importStarts) > 0 {
+		tree, files, excluded, err := followImports(importStarts, localPaths, r.opts.PathBase, r.walkOptions())
+		if err != nil {
+			return nil, err
+		}
+		if len(files) > 0 {
+			in.sources = append(in.sources, filesystem.Source{Name: "imports", Files: files})
+			in.trees = append(in.trees, tree)
+		}
+		if excluded != nil {
+			in.excluded = append(in.excluded, excluded)
+		}
+	}
+	return in, nil
+}
+
+// resolve splits the local path arg from any line ranges, such as main.go:10-20, and returns
+// it, absolute unless it is within FS, with its info.
+func (r *runner) resolve(arg string) (string, fs.FileInfo, []filesystem.LineRange, error) {
+	path, lineRanges := splitLineRangeArg(arg, r.opts.Options)
+	if r.opts.FS == nil {
+		absPath, err := filepath.Abs(path)
+		if err != nil {
+			return "", nil, nil, fmt.Errorf("failed to get absolute path for %s: %w", arg, err)
+		}
+		path = absPath
+	}
+
+	info, err := r.opts.Stat(path)
+	if err != nil {
+		return "", nil, nil, fmt.Errorf("failed to get file info for %s: %w", arg, err)
+	}
+	return path, info, lineRanges, nil
+}
+
+// localOptions returns the options to walk the local file or directory at path with, limited to
+// its lineRanges and, with DiffMode, to the files changed in git. A clone of a git URL shows its
+// paths from the repository's name.
+func (r *runner) localOptions(path string, info fs.FileInfo, lineRanges []filesystem.LineRange, cloned bool) (filesystem.Options, error) {
+	opts := r.walkOptions()
+	if cloned {
+		// Show paths from the repository's name rather than the temporary directory
+		opts.PathBase = filepath.Dir(path)
+	} else if opts.PathBase != "" && !filesystem.IsWithin(opts.PathBase, path) {
+		return opts, fmt.Errorf("--relative-to %s is not an ancestor of %s", opts.PathBase, path)
+	}
+
+	if len(lineRanges) > 0 {
+		if info.IsDir() {
+			return opts, fmt.Errorf("line ranges can only be given for files, but %s is a directory", path)
+		}
+		opts.LineRanges = map[string][]filesystem.LineRange{path: lineRanges}
+	}
+
+	if r.opts.DiffMode != "" {
+		if err := applyDiffMode(&opts, path, info.IsDir(), r.opts.DiffMode); err != nil {
+			return opts, err
+		}
+	}
+	return opts, nil
+}
+
+// gitData returns the git_data of the local path: the diff and log between the GitDiffBranch
+// and GitLogBranch branches, along with the DiffRange summary in rangeData.
+func (r *runner) gitData(path string, rangeData GitData) GitData {
+	data := rangeData
+	data.Path = path
+
+	if branches := strings.Split(r.opts.GitDiffBranch, ","); len(branches) == 2 {
+		diff, err := git.GetGitDiffBetweenBranches(path, branches[0], branches[1])
+		if err != nil {
+			utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to get git diff between branches for %s: %v", path, err), color.FgYellow)
+		}
+		data.GitDiffBranch = diff
+	}
+
+	if branches := strings.Split(r.opts.GitLogBranch, ","); len(branches) == 2 {
+		log, err := git.GetGitLog(path, branches[0], branches[1])
+		if err != nil {
+			utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to get git log for %s: %v", path, err), color.FgYellow)
+		}
+		data.GitLogBranch = log
+	}
+	return data
+}
+
+// addWeb crawls the URL arg, redacting its pages like files. A URL with a path is fetched on
+// its own unless CrawlPaths is set.
+func (r *runner) addWeb(in *inputs, arg string) error {
+	if !IsURL(arg) {
+		return fmt.Errorf("web crawling is enabled but the argument '%s' is not a URL", arg)
+	}
+	if r.opts.DryRun {
+		return fmt.Errorf("--dry-run only lists local files, but %s is a URL", arg)
+	}
+
+	utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Processing URL: %s", arg), color.FgBlue)
+
+	crawl := r.opts.Web
+	u, err := url.Parse(arg)
+	if err == nil && !r.opts.CrawlPaths && u.Path != "" && u.Path != "/" {
+		crawl.MaxDepth = 0
+	}
+	result, err := web.ProcessWebURL(arg, crawl, r.opts.ExcludePatterns)
+	if err != nil {
+		return fmt.Errorf("failed to process web URL %s: %w", arg, err)
+	}
+
+	if r.opts.Redactor != nil {
+		for i := range result.Files {
+			result.Files[i].Code, result.Files[i].Redactions = r.opts.Redactor.Redact(result.Files[i].Code)
+		}
+	}
+
+	name := arg
+	if u != nil && u.Host != "" {
+		name = u.Host
+	}
+	in.sources = append(in.sources, filesystem.Source{Name: name, Files: result.Files})
+	in.trees = append(in.trees, result.TreeString)
+	return nil
+}
+
+// cloneRemote clones the paths that are remote git repositories, unless WebOnly is set or the
+// paths are within FS, into temporary directories. It returns the paths with each replaced by
+// its clone, the set of cloned directories, and a function that removes them.
+func (r *runner) cloneRemote(paths []string) ([]string, map[string]bool, func(), error) {
+	var tempDirs []string
+	remove := func() {
+		for _, dir := range tempDirs {
+			os.RemoveAll(dir)
+		}
+	}
+
+	cloned := make(map[string]bool)
+	result := make([]string, len(paths))
+	for i, path := range paths {
+		if r.opts.WebOnly || r.opts.FS != nil || !git.IsRemoteURL(path) {
+			result[i] = path
+			continue
+		}
+
+		tempDir, err := os.MkdirTemp("", "ingest-clone-")
+		if err != nil {
+			remove()
+			return nil, nil, nil, fmt.Errorf("failed to create a directory to clone %s into: %w", path, err)
+		}
+		tempDirs = append(tempDirs, tempDir)
+
+		// Cloning into a directory named after the repository keeps its name in the output
+		dir := filepath.Join(tempDir, git.RepositoryName(path))
+		utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Cloning %s", path), color.FgBlue)
+		if err := git.Clone(path, dir, r.opts.Branch, r.opts.CloneDepth); err != nil {
+			remove()
+			return nil, nil, nil, err
+		}
+		result[i] = dir
+		cloned[dir] = true
+	}
+	return result, cloned, remove, nil
+}
+
+// applyDiffMode restricts opts to the files changed in the git working tree, or the index if
+// mode is staged, at path, and attaches each file's diff so it is shown above the file's content.
+func applyDiffMode(opts *filesystem.Options, path string, isDir bool, mode string) error {
+	repoDir := path
+	if !isDir {
+		repoDir = filepath.Dir(path)
+	}
+
+	if !git.IsRepository(repoDir) {
+		return fmt.Errorf("--diff requires a git repository, but %s is not inside one", path)
+	}
+
+	staged := mode == "staged"
+	changed, err := git.GetChangedFiles(repoDir, staged)
+	if err != nil {
+		return err
+	}
+
+	opts.OnlyPaths = make(map[string]bool, len(changed))
+	opts.Diffs = make(map[string]string, len(changed))
+	for _, file := range changed {
+		if !isDir && file != filepath.Base(path) {
+			continue
+		}
+
+		diff, err := git.GetFileDiff(repoDir, file, staged)
+		if err != nil {
+			return err
+		}
+		opts.OnlyPaths[file] = true
+		opts.Diffs[file] = diff
+	}
+
+	if len(opts.OnlyPaths) == 0 {
+		utils.PrintColouredMessage("ℹ️", fmt.Sprintf("No %s changes found in %s", mode, path), color.FgBlue)
+	}
+	return nil
+}
+
+// applyDiffRange restricts opts to the files that exist after being changed across the
+// revision range at path, and returns the range summary for the template.
+func applyDiffRange(opts *filesystem.Options, path, revisions string) (GitData, error) {
+	if !git.IsRepository(path) {
+		return GitData{}, fmt.Errorf("--diff-range requires a git repository, but %s is not inside one", path)
+	}
+
+	changes, err := git.GetChangedFilesInRange(path, revisions)
+	if err != nil {
+		return GitData{}, err
+	}
+	commitCount, err := git.CountCommits(path, revisions)
+	if err != nil {
+		return GitData{}, err
+	}
+
+	opts.OnlyPaths = make(map[string]bool, len(changes))
+	for _, change := range changes {
+		if !change.Deleted() {
+			opts.OnlyPaths[change.Path] = true
+		}
+	}
+
+	return GitData{DiffRange: revisions, CommitCount: commitCount, Changes: changes}, nil
+}
+
+// followImports ingests the local files imported by the files at paths, directly or through
+// other imports, leaving out those already ingested as part of inputs. It adds how many files
+// were pulled in by following imports to the messages.
+func followImports(paths, inputs []string, pathBase string, opts filesystem.Options) (string, []filesystem.FileInfo, *filesystem.ExcludedInfo, error) {
+	var imported []string
+	seen := make(map[string]bool)
+	for _, path := range paths {
+		for _, file := range filesystem.FollowImports([]string{path}, importRoot(path, pathBase)) {
+			ingested := slices.ContainsFunc(inputs, func(input string) bool { return filesystem.IsWithin(input, file) })
+			if !seen[file] && !ingested {
+				seen[file] = true
+				imported = append(imported, file)
+			}
+		}
+	}
+	if len(imported) == 0 {
+		return "", nil, nil, nil
+	}
+
+	tree, files, excluded, err := filesystem.ProcessPaths(imported, opts)
+	if err != nil {
+		return "", nil, nil, fmt.Errorf("failed to ingest imported files: %w", err)
+	}
+	utils.AddMessage("🔗", fmt.Sprintf("Included %s files by following the imports of the %s given explicitly.", utils.FormatNumber(len(files)), utils.FormatNumber(len(paths))), color.FgCyan, 30)
+	return tree, files, excluded, nil
+}
+
+// importRoot returns the directory whose files the imports of the file at path are followed to:
+// pathBase if set, otherwise the git work tree or working directory holding the file, otherwise
+// the file's own directory.
+func importRoot(path, pathBase string) string {
+	if pathBase != "" {
+		return pathBase
+	}
+	if top, ok := git.TopLevel(filepath.Dir(path)); ok {
+		return top
+	}
+	if cwd, err := os.Getwd(); err == nil && filesystem.IsWithin(cwd, path) {
+		return cwd
+	}
+	return filepath.Dir(path)
+}
+
+// processArchive ingests the zip or tar archive at path like a directory named after it, with
+// the same filtering and formatting, reading its entries from the archive as they are needed.
+// Nested archives are skipped with a note.
+func processArchive(path string, opts filesystem.Options) (string, []filesystem.FileInfo, *filesystem.ExcludedInfo, error) {
+	fsys, err := archive.Open(path)
+	if err != nil {
+		return "", nil, nil, err
+	}
+	defer fsys.Close()
+
+	if len(fsys.Nested) > 0 {
+		utils.AddMessage("ℹ️", fmt.Sprintf("Skipped %d nested archives in %s: %s", len(fsys.Nested), filepath.Base(path), strings.Join(fsys.Nested, ", ")), color.FgCyan, 30)
+	}
+
+	// Paths are shown from the archive's name, as its entries aren't on disk
+	opts.FS = fsys
+	opts.PathBase = ""
+	return WalkPath(filepath.Base(path), opts)
+}
+
+// processStdinPaths ingests the newline-delimited file paths read from r, such as the output of
+// rg -l or fzf.
+func processStdinPaths(r io.Reader, opts filesystem.Options) (string, []filesystem.FileInfo, *filesystem.ExcludedInfo, error) {
+	var paths []string
+	opts.LineRanges = maps.Clone(opts.LineRanges)
+	scanner := bufio.NewScanner(r)
+	for scanner.Scan() {
+		path := strings.TrimSpace(scanner.Text())
+		if path == "" {
+			continue
+		}
+		path, ranges := SplitLineRangeArg(path)
+		paths = append(paths, path)
+
+		// The same file can be listed more than once with different ranges
+		if len(ranges) > 0 {
+			absPath, err := filepath.Abs(path)
+			if err != nil {
+				return "", nil, nil, fmt.Errorf("failed to get absolute path for %s: %w", path, err)
+			}
+			if opts.LineRanges == nil {
+				opts.LineRanges = make(map[string][]filesystem.LineRange)
+			}
+			opts.LineRanges[absPath] = append(opts.LineRanges[absPath], ranges...)
+		}
+	}
+	if err := scanner.Err(); err != nil {
+		return "", nil, nil, fmt.Errorf("failed to read paths from stdin: %w", err)
+	}
+	if len(paths) == 0 {
+		return "", nil, nil, fmt.Errorf("no paths were read from stdin")
+	}
+
+	tree, files, excluded, err := filesystem.ProcessPaths(paths, opts)
+	if err != nil {
+		return "", nil, nil, fmt.Errorf("failed to process paths from stdin: %w", err)
+	}
+	return tree, files, excluded, nil
+}
+
+// SplitLineRangeArg splits a path given as path:40-120 into the path and its line ranges. A
+// path that exists as given is never split, so file names containing colons still work.
+func SplitLineRangeArg(arg string) (string, []filesystem.LineRange) {
+	return splitLineRangeArg(arg, filesystem.Options{})
+}
+
+// splitLineRangeArg is SplitLineRangeArg for a path within opts.FS, if it is set.
+func splitLineRangeArg(arg string, opts filesystem.Options) (string, []filesystem.LineRange) {
+	if _, err := opts.Stat(arg); err == nil {
+		return arg, nil
+	}
+	if path, ranges, ok := filesystem.SplitLineRanges(arg); ok {
+		return path, ranges
+	}
+	return arg, nil
+}
+
+// IsURL reports whether str is an absolute URL, such as a web page to crawl.
+func IsURL(str string) bool {
+	u, err := url.Parse(str)
+	return err == nil && u.Scheme != "" && u.Host != ""
+}
@@ -0,0 +1,160 @@
+package ingest
+
+import (
+	"errors"
+	"fmt"
+	"io"
+	"path/filepath"
+	"strings"
+
+	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/format"
+	"github.com/sammcj/ingest/template"
+	"github.com/sammcj/ingest/token"
+)
+
+// Stream ingests the local files and directories in Paths like Run, but writes the output to w
+// a file at a time as the files are read, so neither the files nor the output are ever all held
+// in memory. Every path is walked first so their trees can head the output. The options that
+// need every file before writing any, such as MaxTokens and Dedupe, are ignored, as are Stdin,
+// DiffRange and the options for URLs.
+//
+// The Result has no Rendered output, and its Files only hold the path, token count and
+// redactions of each file written. TotalTokens is counted with the offline tokeniser as the
+// output is written, or summed from the files for JSONLines, which has no total of its own.
+func Stream(opts Options, w io.Writer) (Result, error) {
+	r, err := newRunner(opts)
+	if err != nil {
+		return Result{}, err
+	}
+	if r.format == format.Markdown && !template.Streamable(r.tmpl) {
+		return Result{}, errors.New("--stream needs a template that defines header, file and footer templates, as the default template does")
+	}
+	paths, cloned, removeClones, err := r.cloneRemote(r.paths())
+	if err != nil {
+		return Result{}, err
+	}
+	defer removeClones()
+
+	// streamInput is a walked path whose files are still to be read
+	type streamInput struct {
+		path   string
+		opts   filesystem.Options
+		stream *filesystem.DirectoryStream // nil for a single file
+	}
+
+	var result Result
+	var inputs []streamInput
+	var trees []string
+	for _, arg := range paths {
+		path, info, lineRanges, err := r.resolve(arg)
+		if err != nil {
+			return Result{}, err
+		}
+		walkOpts, err := r.localOptions(path, info, lineRanges, cloned[path])
+		if err != nil {
+			return Result{}, err
+		}
+
+		input := streamInput{path: path, opts: walkOpts}
+		if info.IsDir() {
+			if input.stream, err = filesystem.StreamDirectory(path, walkOpts); err != nil {
+				return Result{}, fmt.Errorf("failed to process directory %s: %w", path, err)
+			}
+			trees = append(trees, fmt.Sprintf("%s:\n%s", walkOpts.DisplayPath(path, filepath.Base(path)), input.stream.Tree))
+			result.Excluded = append(result.Excluded, input.stream.Excluded)
+		} else {
+			trees = append(trees, fmt.Sprintf("File: %s", walkOpts.DisplayPath(path, filepath.Base(path))))
+		}
+		inputs = append(inputs, input)
+	}
+	result.Tree = strings.Join(trees, "\n\n")
+
+	counted := &tokenWriter{w: w}
+	// The output is counted as it's written, which needs the offline tokeniser
+	if !opts.NoTotal && r.format != format.JSONLines {
+		counted.count = token.NewCounter(opts.Tokenizer.encoding(), opts.Tokenizer.NoCorrection).Count
+	}
+
+	var excluded any
+	if len(result.Excluded) > 0 {
+		excluded = result.Excluded[0]
+	}
+	data := map[string]any{"excluded": excluded}
+	if opts.ReadmeFirst && len(opts.Order) == 0 {
+		data["FileOrder"] = ReadmeFirstOrder
+	}
+
+	// JSON reports the token count of the tree and files after them
+	tree := result.Tree
+	if opts.NoTree {
+		tree = ""
+	}
+	treeTokens := 0
+	if r.format == format.JSON {
+		treeTokens = opts.Tokenizer.Count(tree)
+	}
+
+	stream, err := format.NewStream(counted, r.format, r.tmpl, tree, treeTokens, data)
+	if err != nil {
+		return Result{}, err
+	}
+	fileTokens := 0
+	write := func(file filesystem.FileInfo) error {
+		if r.format == format.JSON || r.format == format.JSONLines {
+			files := []filesystem.FileInfo{file}
+			opts.Tokenizer.CountFiles(files)
+			file = files[0]
+			fileTokens += file.TokenCount
+		}
+		if err := stream.WriteFile(file); err != nil {
+			return err
+		}
+		result.Files = append(result.Files, filesystem.FileInfo{Path: file.Path, TokenCount: file.TokenCount, Redactions: file.Redactions})
+		return nil
+	}
+
+	for _, input := range inputs {
+		if input.stream != nil {
+			if err := input.stream.Each(write); err != nil {
+				return Result{}, err
+			}
+			continue
+		}
+		file, err := filesystem.ProcessSingleFile(input.path, input.opts)
+		var skipped *filesystem.SkipError
+		if errors.As(err, &skipped) {
+			result.Excluded = append(result.Excluded, skipped.Excluded)
+			continue
+		}
+		if err != nil {
+			return Result{}, fmt.Errorf("failed to process file %s: %w", input.path, err)
+		}
+		if err := write(file); err != nil {
+			return Result{}, err
+		}
+	}
+	if err := stream.Close(); err != nil {
+		return Result{}, err
+	}
+
+	result.TotalTokens = counted.tokens
+	if r.format == format.JSONLines {
+		result.TotalTokens = fileTokens
+	}
+	return result, nil
+}
+
+// tokenWriter adds up the token count of everything written through it with count, if set.
+type tokenWriter struct {
+	w      io.Writer
+	count  func(string) int
+	tokens int
+}
+
+func (t *tokenWriter) Write(p []byte) (int, error) {
+	if t.count != nil {
+		t.tokens += t.count(string(p))
+	}
+	return t.w.Write(p)
+}
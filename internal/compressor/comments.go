@@ -0,0 +1,146 @@
+package compressor
+
+import (
+	"fmt"
+	"regexp"
+	"sort"
+	"strings"
+)
+
+var htmlCommentRegex = regexp.MustCompile(`<!--[\s\S]*?-->`)
+
+// stripComments removes comments and blank lines from the source while leaving all code
+// in place. It backs the NoComments compression level.
+func (gc *GenericCompressor) stripComments(content []byte, languageIdentifier string) (string, error) {
+	var stripped string
+	switch languageIdentifier {
+	case "html":
+		stripped = htmlCommentRegex.ReplaceAllString(string(content), "")
+	case "rust", "java", "swift":
+		// These languages are handled with regular expressions rather than a grammar
+		stripped = stripCStyleComments(string(content))
+	default:
+		var err error
+		stripped, err = stripSyntaxComments(content, languageIdentifier)
+		if err != nil {
+			return "", err
+		}
+	}
+	return removeBlankLines(stripped), nil
+}
+
+// stripSyntaxComments uses the Tree-sitter grammar for the language to find and remove
+// comment nodes. A leading shebang line is kept.
+func stripSyntaxComments(content []byte, languageIdentifier string) (string, error) {
+	lang, err := GetLanguage(languageIdentifier)
+	if err != nil {
+		return "", fmt.Errorf("could not get language for '%s': %w", languageIdentifier, err)
+	}
+
+	tree, err := ParseSource(content, lang)
+	if err != nil {
+		return "", fmt.Errorf("could not parse source for '%s': %w", languageIdentifier, err)
+	}
+	defer tree.Close()
+
+	query, err := CompileQuery("(comment) @comment", lang)
+	if err != nil {
+		return "", fmt.Errorf("could not compile comment query for '%s': %w", languageIdentifier, err)
+	}
+	defer query.Close()
+
+	matches, err := ExecuteQuery(tree, query, content)
+	if err != nil {
+		return "", fmt.Errorf("could not execute comment query for '%s': %w", languageIdentifier, err)
+	}
+
+	type byteRange struct{ start, end uint32 }
+	var ranges []byteRange
+	for _, match := range matches {
+		for _, capture := range match.Captures {
+			start, end := capture.Node.StartByte(), capture.Node.EndByte()
+			if start == 0 && strings.HasPrefix(string(content), "#!") {
+				continue
+			}
+			ranges = append(ranges, byteRange{start, end})
+		}
+	}
+	sort.Slice(ranges, func(i, j int) bool { return ranges[i].start < ranges[j].start })
+
+	var result strings.Builder
+	last := uint32(0)
+	for _, r := range ranges {
+		if r.start < last {
+			continue
+		}
+		result.Write(content[last:r.start])
+		last = r.end
+	}
+	result.Write(content[last:])
+	return result.String(), nil
+}
+
+// stripCStyleComments removes // and /* */ comments, skipping over double quoted strings so
+// comment markers inside string literals are kept. Line breaks inside block comments are
+// preserved so the surrounding lines stay separate.
+func stripCStyleComments(src string) string {
+	var result strings.Builder
+	inString := false
+	for i := 0; i < len(src); i++ {
+		c := src[i]
+		if inString {
+			result.WriteByte(c)
+			if c == '\\' && i+1 < len(src) {
+				i++
+				result.WriteByte(src[i])
+				continue
+			}
+			if c == '"' || c == '\n' {
+				inString = false
+			}
+			continue
+		}
+
+		switch {
+		case c == '"':
+			inString = true
+			result.WriteByte(c)
+		case c == '/' && i+1 < len(src) && src[i+1] == '/':
+			for i < len(src) && src[i] != '\n' {
+				i++
+			}
+			if i < len(src) {
+				result.WriteByte('\n')
+			}
+		case c == '/' && i+1 < len(src) && src[i+1] == '*':
+			end := strings.Index(src[i+2:], "*/")
+			if end < 0 {
+				// Unterminated block comment, keep the rest as is
+				result.WriteString(src[i:])
+				return result.String()
+			}
+			result.WriteString(strings.Repeat("\n", strings.Count(src[i:i+end+4], "\n")))
+			i += end + 3
+		default:
+			result.WriteByte(c)
+		}
+	}
+	return result.String()
+}
+
+// removeBlankLines drops whitespace-only lines and trailing whitespace left behind after
+// comments are removed.
+func removeBlankLines(src string) string {
+	var lines []string
+	for line := range strings.SplitSeq(src, "\n") {
+		line = strings.TrimRight(line, " \t\r")
+		if strings.TrimSpace(line) == "" {
+			continue
+		}
+		lines = append(lines, line)
+	}
+	if len(lines) == 0 {
+		return ""
+	}
+	return strings.Join(lines, "\n") + "\n"
+}
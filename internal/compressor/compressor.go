@@ -13,6 +13,8 @@ import (
 	"github.com/smacker/go-tree-sitter/golang"
 	"github.com/smacker/go-tree-sitter/javascript"
 	"github.com/smacker/go-tree-sitter/python"
+	"github.com/smacker/go-tree-sitter/typescript/tsx"
+	"github.com/smacker/go-tree-sitter/typescript/typescript"
 )
 
 // LanguageMap maps language identifiers to their Tree-sitter Language.
@@ -20,6 +22,8 @@ var LanguageMap = map[string]*sitter.Language{
 	"go":         golang.GetLanguage(),
 	"python":     python.GetLanguage(),
 	"javascript": javascript.GetLanguage(),
+	"typescript": typescript.GetLanguage(),
+	"tsx":        tsx.GetLanguage(),
 	"bash":       bash.GetLanguage(),
 	"c":          c.GetLanguage(),
 	"css":        css.GetLanguage(),
@@ -37,8 +41,12 @@ func IdentifyLanguage(filePath string) (string, error) {
 		return "go", nil
 	case ".py":
 		return "python", nil
-	case ".js", ".jsx", ".mjs", ".cjs", ".ts", ".tsx":
+	case ".js", ".jsx", ".mjs", ".cjs":
 		return "javascript", nil
+	case ".ts", ".mts", ".cts":
+		return "typescript", nil
+	case ".tsx":
+		return "tsx", nil
 	case ".sh", ".bash":
 		return "bash", nil
 	case ".c", ".h":
@@ -53,6 +61,8 @@ func IdentifyLanguage(filePath string) (string, error) {
 		return "java", nil
 	case ".swift":
 		return "swift", nil
+	case ".md", ".markdown":
+		return "markdown", nil
 	// Add more extensions and languages here
 	default:
 		return "", fmt.Errorf("unsupported file extension: %s", ext)
@@ -154,6 +164,8 @@ var QueryMap = map[string]string{
         ((identifier) @identifier)
         ((property_identifier) @property)
         `,
+	"typescript": typeScriptQuery,
+	"tsx":        typeScriptQuery,
 	"javascript": `
         (import_statement) @import
         (comment) @comment
@@ -254,6 +266,25 @@ var QueryMap = map[string]string{
         `,
 }
 
+// typeScriptQuery is shared by the TypeScript and TSX grammars. Declarations are matched
+// directly, exported declarations are widened to their export statement when processed.
+const typeScriptQuery = `
+        (import_statement) @import
+        (comment) @comment
+        (interface_declaration) @definition.interface
+        (type_alias_declaration) @definition.type
+        (enum_declaration) @definition.enum
+        (function_declaration) @definition.function.full
+        (generator_function_declaration) @definition.function.full
+        (class_declaration) @definition.class.full
+        (abstract_class_declaration) @definition.class.full
+        (method_definition) @definition.method.full
+
+        ; Top-level variables, arrow functions assigned to them have their bodies stripped
+        (program (lexical_declaration) @definition.variable)
+        (program (export_statement (lexical_declaration) @definition.variable))
+        `
+
 // GetQuery retrieves a Tree-sitter query for a given language identifier.
 func GetQuery(languageIdentifier string) (string, error) {
 	query, ok := QueryMap[strings.ToLower(languageIdentifier)]
@@ -315,6 +346,12 @@ type CodeChunk struct {
 	StartByte    uint32
 	EndByte      uint32
 	OriginalLine int
+	Capture      string // Name of the query capture that produced the chunk
+
+	// BodyStart and BodyEnd hold the byte range of a function or method body that was
+	// replaced with a placeholder, they are both zero when nothing was elided.
+	BodyStart uint32
+	BodyEnd   uint32
 }
 
 func isNamedDeclarationType(n *sitter.Node) bool {
@@ -324,12 +361,61 @@ func isNamedDeclarationType(n *sitter.Node) bool {
 	switch n.Type() {
 	case "function_declaration", "generator_function_declaration", "class_declaration":
 		nameNode := n.ChildByFieldName("name")
-		return nameNode != nil && nameNode.Type() == "identifier"
+		return nameNode != nil && (nameNode.Type() == "identifier" || nameNode.Type() == "type_identifier")
 	default:
 		return false
 	}
 }
 
+func isTypeScript(languageIdentifier string) bool {
+	return languageIdentifier == "typescript" || languageIdentifier == "tsx"
+}
+
+// exportedNode returns the enclosing export statement of a declaration, or the node itself
+// when it isn't exported.
+func exportedNode(n *sitter.Node) *sitter.Node {
+	if parent := n.Parent(); parent != nil && parent.Type() == "export_statement" {
+		return parent
+	}
+	return n
+}
+
+// typeScriptBody returns the body of a TypeScript declaration. For variable declarations
+// it looks for an arrow function or function expression value and returns its body,
+// which may be an expression rather than a statement block.
+func typeScriptBody(decl *sitter.Node) *sitter.Node {
+	switch decl.Type() {
+	case "lexical_declaration", "variable_declaration":
+		for i := 0; i < int(decl.NamedChildCount()); i++ {
+			declarator := decl.NamedChild(i)
+			if declarator == nil || declarator.Type() != "variable_declarator" {
+				continue
+			}
+			value := declarator.ChildByFieldName("value")
+			if value == nil {
+				continue
+			}
+			switch value.Type() {
+			case "arrow_function", "function_expression", "function":
+				return value.ChildByFieldName("body")
+			}
+		}
+		return nil
+	default:
+		return decl.ChildByFieldName("body")
+	}
+}
+
+// hasCapture reports whether a match contains a capture with the given name.
+func hasCapture(match *sitter.QueryMatch, query *sitter.Query, captureName string) bool {
+	for _, c := range match.Captures {
+		if query.CaptureNameForId(c.Index) == captureName {
+			return true
+		}
+	}
+	return false
+}
+
 func LogCaptures(matches []*sitter.QueryMatch, query *sitter.Query, source []byte) {
 	for _, match := range matches {
 		for _, capture := range match.Captures {
@@ -1,11 +1,15 @@
 package compressor
 
 import (
+	"flag"
 	"os"
+	"path/filepath"
 	"strings"
 	"testing"
 )
 
+var updateGolden = flag.Bool("update", false, "update golden files in testdata/golden")
+
 func TestGenericCompressor_Compress_Go(t *testing.T) {
 	compressor := NewGenericCompressor()
 	goCode := `
@@ -493,6 +497,43 @@ func TestGenericCompressor_Compress_Swift(t *testing.T) {
 	t.Log("Swift compression completed without errors")
 }
 
+func TestGenericCompressor_Compress_Markdown(t *testing.T) {
+	markdown := "# Project\n\n" +
+		"[![CI](https://github.com/o/r/actions/workflows/ci.yml/badge.svg)](https://github.com/o/r/actions) [![Go Report](https://goreportcard.com/badge/github.com/o/r)](https://goreportcard.com/report/github.com/o/r)\n" +
+		"<img src=\"https://img.shields.io/badge/licence-MIT-blue\" alt=\"Licence\">\n\n" +
+		"<!-- This comment\nspans lines -->\n\n" +
+		"A tool that does things. ![diagram](docs/diagram.png)\n\n" +
+		"## Table of Contents\n\n" +
+		"- [Install](#install)\n- [Usage](#usage)\n  - [Flags](#flags)\n\n" +
+		"## Install\n\n" +
+		"```html\n<!-- kept in code -->\n```\n\n" +
+		"- [Usage](#usage) and more\n"
+
+	expected := "# Project\n\n" +
+		"A tool that does things. ![diagram](docs/diagram.png)\n\n" +
+		"## Table of Contents\n\n" +
+		"(Table of contents of 3 links removed)\n\n" +
+		"## Install\n\n" +
+		"```html\n<!-- kept in code -->\n```\n\n" +
+		"- [Usage](#usage) and more\n"
+	compressed, err := NewGenericCompressor().Compress([]byte(markdown), "markdown")
+	if err != nil {
+		t.Fatalf("Compress failed for Markdown: %v", err)
+	}
+	if compressed != expected {
+		t.Errorf("Expected:\n%s\ngot:\n%s", expected, compressed)
+	}
+
+	// Only comments are removed at the no-comments level
+	compressed, err = NewGenericCompressorWithLevel(NoComments).Compress([]byte(markdown), "markdown")
+	if err != nil {
+		t.Fatalf("Compress failed for Markdown: %v", err)
+	}
+	if strings.Contains(compressed, "spans lines") || !strings.Contains(compressed, "badge.svg") || !strings.Contains(compressed, "- [Install](#install)") {
+		t.Errorf("Expected only the comment removed at the no-comments level, got:\n%s", compressed)
+	}
+}
+
 func TestIdentifyLanguage(t *testing.T) {
 	tests := []struct {
 		filePath      string
@@ -502,6 +543,8 @@ func TestIdentifyLanguage(t *testing.T) {
 		{"main.go", "go", false},
 		{"script.py", "python", false},
 		{"app.js", "javascript", false},
+		{"app.ts", "typescript", false},
+		{"component.tsx", "tsx", false},
 		{"style.css", "css", false},
 		{"script.sh", "bash", false},
 		{"header.h", "c", false},
@@ -511,7 +554,8 @@ func TestIdentifyLanguage(t *testing.T) {
 		{"main.rs", "rust", false},
 		{"App.java", "java", false},
 		{"Main.swift", "swift", false},
-		{"README.md", "", true},
+		{"README.md", "markdown", false},
+		{"notes.txt", "", true},
 	}
 
 	for _, tt := range tests {
@@ -642,3 +686,229 @@ func TestExecuteQuery_Go(t *testing.T) {
 		t.Errorf("Expected @definition.function capture, not found")
 	}
 }
+
+func TestGenericCompressor_Compress_Golden(t *testing.T) {
+	tests := []struct {
+		fixture string
+		lang    string
+	}{
+		{"example.go", "go"},
+		{"example.ts", "typescript"},
+	}
+
+	compressor := NewGenericCompressor()
+	for _, tt := range tests {
+		t.Run(tt.fixture, func(t *testing.T) {
+			source, err := os.ReadFile(filepath.Join("testdata", tt.fixture))
+			if err != nil {
+				t.Fatalf("Failed to read test file: %v", err)
+			}
+
+			compressed, err := compressor.Compress(source, tt.lang)
+			if err != nil {
+				t.Fatalf("Compress failed for %s: %v", tt.fixture, err)
+			}
+
+			goldenPath := filepath.Join("testdata", "golden", tt.fixture+".golden")
+			if *updateGolden {
+				if err := os.WriteFile(goldenPath, []byte(compressed), 0644); err != nil {
+					t.Fatalf("Failed to update golden file: %v", err)
+				}
+			}
+
+			expected, err := os.ReadFile(goldenPath)
+			if err != nil {
+				t.Fatalf("Failed to read golden file: %v", err)
+			}
+
+			if compressed != string(expected) {
+				t.Errorf("Compressed output for %s does not match %s (run with -update to regenerate)\n--- got ---\n%s\n--- want ---\n%s", tt.fixture, goldenPath, compressed, expected)
+			}
+		})
+	}
+}
+
+func TestGenericCompressor_Compress_TypeScript(t *testing.T) {
+	compressor := NewGenericCompressor()
+	tsCode := `
+export const handler = async (event: Event): Promise<void> => {
+  console.log(event.type);
+};
+
+const square = (n: number) => n * n;
+`
+	compressed, err := compressor.Compress([]byte(tsCode), "typescript")
+	if err != nil {
+		t.Fatalf("Compress failed for TypeScript: %v", err)
+	}
+
+	t.Logf("Compressed TypeScript code:\n%s", compressed)
+
+	expectedCompressedParts := []string{
+		"export const handler = async (event: Event): Promise<void> => { ... } // Body removed",
+		"const square = (n: number) => { ... } // Body removed",
+	}
+	for _, part := range expectedCompressedParts {
+		if !strings.Contains(compressed, part) {
+			t.Errorf("Compressed TypeScript output does not contain expected part: '%s'", part)
+		}
+	}
+
+	if strings.Contains(compressed, "console.log(event.type)") {
+		t.Errorf("Compressed TypeScript output unexpectedly contains arrow function body content")
+	}
+	if strings.Contains(compressed, "n * n") {
+		t.Errorf("Compressed TypeScript output unexpectedly contains expression arrow function body content")
+	}
+}
+
+func TestGenericCompressor_Compress_Levels(t *testing.T) {
+	goCode, err := os.ReadFile("testdata/example.go")
+	if err != nil {
+		t.Fatalf("Failed to read test file: %v", err)
+	}
+
+	t.Run("signatures", func(t *testing.T) {
+		compressed, err := NewGenericCompressorWithLevel(Signatures).Compress(goCode, "go")
+		if err != nil {
+			t.Fatalf("Compress failed for Go: %v", err)
+		}
+		for _, part := range []string{"package main", "type MyStruct struct", "func main() { ... }"} {
+			if !strings.Contains(compressed, part) {
+				t.Errorf("Signatures output does not contain expected part: '%s'", part)
+			}
+		}
+		for _, part := range []string{`import "fmt"`, "// This is a comment"} {
+			if strings.Contains(compressed, part) {
+				t.Errorf("Signatures output unexpectedly contains '%s'", part)
+			}
+		}
+	})
+
+	t.Run("no-comments", func(t *testing.T) {
+		compressed, err := NewGenericCompressorWithLevel(NoComments).Compress(goCode, "go")
+		if err != nil {
+			t.Fatalf("Compress failed for Go: %v", err)
+		}
+		for _, part := range []string{`import "fmt"`, `return fmt.Sprintf("Positive: %d", val)`, `fmt.Println("Hello, world!")`} {
+			if !strings.Contains(compressed, part) {
+				t.Errorf("NoComments output does not contain expected part: '%s'", part)
+			}
+		}
+		if strings.Contains(compressed, "//") {
+			t.Errorf("NoComments output unexpectedly contains a comment:\n%s", compressed)
+		}
+		if strings.Contains(compressed, "\n\n") {
+			t.Errorf("NoComments output unexpectedly contains blank lines:\n%s", compressed)
+		}
+	})
+
+	t.Run("no-comments keeps comment markers in strings", func(t *testing.T) {
+		rustCode := []byte("let url = \"https://example.com\"; // trailing\n/* block */\nfn main() {}\n")
+		compressed, err := NewGenericCompressorWithLevel(NoComments).Compress(rustCode, "rust")
+		if err != nil {
+			t.Fatalf("Compress failed for Rust: %v", err)
+		}
+		expected := "let url = \"https://example.com\";\nfn main() {}\n"
+		if compressed != expected {
+			t.Errorf("NoComments Rust output = %q, want %q", compressed, expected)
+		}
+	})
+}
+
+func TestParseCompressionLevel(t *testing.T) {
+	tests := []struct {
+		name          string
+		expected      CompressionLevel
+		expectedError bool
+	}{
+		{"", NoBodies, false},
+		{"no-bodies", NoBodies, false},
+		{"signatures", Signatures, false},
+		{"NO-COMMENTS", NoComments, false},
+		{"everything", NoBodies, true},
+	}
+
+	for _, tt := range tests {
+		level, err := ParseCompressionLevel(tt.name)
+		if (err != nil) != tt.expectedError {
+			t.Errorf("ParseCompressionLevel(%q): unexpected error state: %v", tt.name, err)
+		}
+		if level != tt.expected {
+			t.Errorf("ParseCompressionLevel(%q) = %v, want %v", tt.name, level, tt.expected)
+		}
+	}
+}
+
+func TestStripCommentLines(t *testing.T) {
+	tests := []struct {
+		name     string
+		path     string
+		input    string
+		expected string
+	}{
+		{
+			name:     "slash comments",
+			path:     "main.cpp",
+			input:    "// Header\n#include <cstdio>\n\n\n/* Block\n   comment */\nint main() { // Entry\n  puts(\"// not a comment\");\n}\n",
+			expected: "#include <cstdio>\n\nint main() { // Entry\n  puts(\"// not a comment\");\n}\n",
+		},
+		{
+			name:     "block comment followed by code is kept",
+			path:     "main.kt",
+			input:    "/* a */ val x = 1\n",
+			expected: "/* a */ val x = 1\n",
+		},
+		{
+			name:     "multi-line strings are kept",
+			path:     "main.kt",
+			input:    "val s = \"\"\"\n// kept\n\"\"\"\n// dropped\n",
+			expected: "val s = \"\"\"\n// kept\n\"\"\"\n",
+		},
+		{
+			name:     "here-documents are kept",
+			path:     "setup.zsh",
+			input:    "#!/bin/zsh\n# Setup\ncat <<EOF\n# kept\nEOF\n  # dropped\necho done\n",
+			expected: "#!/bin/zsh\ncat <<EOF\n# kept\nEOF\necho done\n",
+		},
+		{
+			name:     "dash comments",
+			path:     "query.sql",
+			input:    "-- Users\nSELECT * FROM users;\n",
+			expected: "SELECT * FROM users;\n",
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, ok := StripCommentLines(tt.path, []byte(tt.input))
+			if !ok {
+				t.Fatalf("StripCommentLines(%s) didn't know the comment syntax", tt.path)
+			}
+			if got != tt.expected {
+				t.Errorf("StripCommentLines(%s) = %q, want %q", tt.path, got, tt.expected)
+			}
+		})
+	}
+
+	if _, ok := StripCommentLines("notes.txt", []byte("# Title\n")); ok {
+		t.Error("Expected files with an unknown comment syntax to be left alone")
+	}
+}
+
+func TestGenericCompressor_CompressWithStats(t *testing.T) {
+	compressor := NewGenericCompressor()
+	goCode := "package main\n\nfunc main() {\n\tfmt.Println(\"one\")\n\tfmt.Println(\"two\")\n}\n"
+	words := func(s string) int { return len(strings.Fields(s)) }
+
+	compressed, stats, err := compressor.CompressWithStats([]byte(goCode), "go", words)
+	if err != nil {
+		t.Fatalf("CompressWithStats failed: %v", err)
+	}
+	if stats.OriginalTokens != words(goCode) || stats.CompressedTokens != words(compressed) {
+		t.Errorf("Expected stats {%d %d}, got %+v", words(goCode), words(compressed), stats)
+	}
+	if stats.Saved() <= 0 {
+		t.Errorf("Expected compression to save tokens, got %+v", stats)
+	}
+}
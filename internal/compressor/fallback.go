@@ -0,0 +1,178 @@
+package compressor
+
+import (
+	"path/filepath"
+	"regexp"
+	"strings"
+)
+
+// commentSyntax is the comment markers of a language, for stripping comments without a grammar.
+type commentSyntax struct {
+	line    []string // Markers that start a comment running to the end of the line
+	block   bool     // Whether /* */ block comments are used
+	heredoc bool     // Whether <<WORD here-documents are used, whose lines are always kept
+}
+
+var (
+	slashComments = commentSyntax{line: []string{"//"}, block: true}
+	hashComments  = commentSyntax{line: []string{"#"}}
+	shellComments = commentSyntax{line: []string{"#"}, heredoc: true}
+	dashComments  = commentSyntax{line: []string{"--"}}
+)
+
+// fallbackSyntaxes maps file extensions without a Tree-sitter grammar to their comment syntax.
+var fallbackSyntaxes = map[string]commentSyntax{
+	".awk":        hashComments,
+	".cc":         slashComments,
+	".cfg":        hashComments,
+	".cmake":      hashComments,
+	".conf":       hashComments,
+	".cpp":        slashComments,
+	".cs":         slashComments,
+	".cxx":        slashComments,
+	".dart":       slashComments,
+	".dockerfile": hashComments,
+	".elm":        dashComments,
+	".ex":         hashComments,
+	".exs":        hashComments,
+	".fish":       shellComments,
+	".gradle":     slashComments,
+	".groovy":     slashComments,
+	".hcl":        {line: []string{"#", "//"}, block: true},
+	".hh":         slashComments,
+	".hpp":        slashComments,
+	".hs":         dashComments,
+	".jl":         hashComments,
+	".jsonc":      slashComments,
+	".kt":         slashComments,
+	".kts":        slashComments,
+	".ksh":        shellComments,
+	".less":       slashComments,
+	".lua":        dashComments,
+	".m":          slashComments,
+	".mm":         slashComments,
+	".nix":        hashComments,
+	".php":        {line: []string{"//", "#"}, block: true, heredoc: true},
+	".pl":         shellComments,
+	".pm":         shellComments,
+	".proto":      slashComments,
+	".ps1":        hashComments,
+	".r":          hashComments,
+	".rb":         shellComments,
+	".scala":      slashComments,
+	".scss":       slashComments,
+	".sol":        slashComments,
+	".sql":        {line: []string{"--"}, block: true},
+	".tf":         {line: []string{"#", "//"}, block: true},
+	".toml":       hashComments,
+	".yaml":       hashComments,
+	".yml":        hashComments,
+	".zig":        slashComments,
+	".zsh":        shellComments,
+}
+
+// fallbackFileNames maps well known extensionless file names to their comment syntax.
+var fallbackFileNames = map[string]commentSyntax{
+	"containerfile": hashComments,
+	"dockerfile":    hashComments,
+	"gemfile":       shellComments,
+	"makefile":      hashComments,
+	"rakefile":      shellComments,
+}
+
+// heredocRegex matches the start of a here-document such as <<EOF, <<-'EOF', <<~EOS or <<<EOT,
+// capturing its terminating word.
+var heredocRegex = regexp.MustCompile(`<<<?[-~]?\s*["']?([A-Za-z_][A-Za-z0-9_]*)["']?`)
+
+// StripCommentLines is the fallback for files without a Tree-sitter grammar. It removes lines
+// that are only a comment, and /* */ blocks that take up whole lines, then collapses runs of
+// blank lines into one. Lines with code before or after a comment are kept as they are, as are
+// lines inside here-documents and multi-line strings. It returns false if the comment syntax
+// of the file isn't known, so it is left alone.
+func StripCommentLines(path string, content []byte) (string, bool) {
+	syntax, ok := fallbackFileNames[strings.ToLower(filepath.Base(path))]
+	if !ok {
+		syntax, ok = fallbackSyntaxes[strings.ToLower(filepath.Ext(path))]
+	}
+	if !ok {
+		return "", false
+	}
+
+	lines := strings.Split(string(content), "\n")
+	kept := make([]string, 0, len(lines))
+	heredocEnd := ""
+	inString := false
+	blank := false
+	for i := 0; i < len(lines); i++ {
+		line := lines[i]
+		trimmed := strings.TrimSpace(line)
+
+		switch {
+		case heredocEnd != "":
+			if trimmed == heredocEnd || strings.TrimSuffix(trimmed, ";") == heredocEnd {
+				heredocEnd = ""
+			}
+		case inString:
+			inString = strings.Count(line, `"""`)%2 == 0
+		case i == 0 && strings.HasPrefix(line, "#!"):
+			// Keep the shebang
+		case isCommentLine(trimmed, syntax.line):
+			continue
+		case syntax.block && strings.HasPrefix(trimmed, "/*"):
+			if end, ok := blockCommentEnd(lines, i); ok {
+				i = end
+				continue
+			}
+		default:
+			if syntax.heredoc {
+				if match := heredocRegex.FindStringSubmatch(line); match != nil {
+					heredocEnd = match[1]
+				}
+			}
+			inString = strings.Count(line, `"""`)%2 == 1
+		}
+
+		// Keep a single blank line where there were several
+		if trimmed == "" && heredocEnd == "" && !inString {
+			if blank {
+				continue
+			}
+			blank = true
+		} else {
+			blank = false
+		}
+		kept = append(kept, line)
+	}
+	return strings.Join(kept, "\n"), true
+}
+
+// isCommentLine reports whether the trimmed line is only a comment starting with one of the
+// markers. Lines starting with #[ are kept, as they are attributes in PHP.
+func isCommentLine(trimmed string, markers []string) bool {
+	for _, marker := range markers {
+		if marker == "#" && strings.HasPrefix(trimmed, "#[") {
+			continue
+		}
+		if strings.HasPrefix(trimmed, marker) {
+			return true
+		}
+	}
+	return false
+}
+
+// blockCommentEnd returns the index of the line closing the /* */ comment starting at lines[start].
+// It returns false if the comment is unterminated or is followed by code on its closing line,
+// in which case the lines are kept.
+func blockCommentEnd(lines []string, start int) (int, bool) {
+	first := strings.TrimSpace(lines[start])[2:]
+	for i := start; i < len(lines); i++ {
+		line := lines[i]
+		if i == start {
+			line = first
+		}
+		if _, after, found := strings.Cut(line, "*/"); found {
+			return i, strings.TrimSpace(after) == ""
+		}
+	}
+	return 0, false
+}
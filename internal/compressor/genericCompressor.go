@@ -10,16 +10,59 @@ import (
 )
 
 // GenericCompressor handles the compression of source code.
-type GenericCompressor struct{}
+type GenericCompressor struct {
+	level CompressionLevel
+}
 
-// NewGenericCompressor creates a new GenericCompressor.
+// NewGenericCompressor creates a new GenericCompressor using the default NoBodies level.
 func NewGenericCompressor() *GenericCompressor {
-	return &GenericCompressor{}
+	return &GenericCompressor{level: NoBodies}
+}
+
+// NewGenericCompressorWithLevel creates a new GenericCompressor using the given level.
+func NewGenericCompressorWithLevel(level CompressionLevel) *GenericCompressor {
+	return &GenericCompressor{level: level}
+}
+
+// Level returns the compression level used by the compressor.
+func (gc *GenericCompressor) Level() CompressionLevel {
+	return gc.level
+}
+
+// Stats are the token counts of code before and after it was compressed.
+type Stats struct {
+	OriginalTokens   int
+	CompressedTokens int
+}
+
+// Saved returns how many tokens compression saved.
+func (s Stats) Saved() int {
+	return s.OriginalTokens - s.CompressedTokens
+}
+
+// CompressWithStats compresses content like Compress, also returning the token counts of
+// content and of the compressed code as counted by count. It counts everything twice, so is
+// only worth using when the savings are wanted.
+func (gc *GenericCompressor) CompressWithStats(content []byte, languageIdentifier string, count func(string) int) (string, Stats, error) {
+	compressed, err := gc.Compress(content, languageIdentifier)
+	if err != nil {
+		return "", Stats{}, err
+	}
+	return compressed, Stats{OriginalTokens: count(string(content)), CompressedTokens: count(compressed)}, nil
 }
 
 // Compress takes source code content and a language identifier,
 // and returns the compressed code as a string.
 func (gc *GenericCompressor) Compress(content []byte, languageIdentifier string) (string, error) {
+	// Blank lines are part of markdown's syntax, so it has its own pass at every level
+	if languageIdentifier == "markdown" {
+		return gc.compressMarkdown(content), nil
+	}
+
+	if gc.level == NoComments {
+		return gc.stripComments(content, languageIdentifier)
+	}
+
 	// Special cases for languages that need custom handling
 	switch languageIdentifier {
 	case "html":
@@ -59,7 +102,18 @@ func (gc *GenericCompressor) Compress(content []byte, languageIdentifier string)
 		return "", fmt.Errorf("could not execute query for '%s': %w", languageIdentifier, err)
 	}
 
-	processedChunks := gc.processCaptures(matches, query, content, languageIdentifier)
+	processedChunks := dropElidedChunks(gc.processCaptures(matches, query, content, languageIdentifier))
+
+	// CSS has no signatures to keep, so it is compressed as it would be with NoBodies
+	if gc.level == Signatures && languageIdentifier != "css" {
+		signatures := processedChunks[:0]
+		for _, chunk := range processedChunks {
+			if isSignatureCapture(chunk.Capture) {
+				signatures = append(signatures, chunk)
+			}
+		}
+		processedChunks = signatures
+	}
 
 	sort.SliceStable(processedChunks, func(i, j int) bool {
 		return processedChunks[i].StartByte < processedChunks[j].StartByte
@@ -138,18 +192,48 @@ func (gc *GenericCompressor) processCaptures(matches []*sitter.QueryMatch, query
 				continue
 			}
 
+			decl := node
+			if isTypeScript(languageIdentifier) {
+				// Keep the export keyword on exported TypeScript declarations
+				node = exportedNode(node)
+			}
+
 			if _, ok := seenNodes[node.StartByte()]; ok {
 				continue
 			}
 
 			captureName := query.CaptureNameForId(capture.Index)
+
+			// Name captures only anchor a pattern, the full definition capture in the
+			// same match produces the chunk.
+			if hasCapture(match, query, captureName+".full") {
+				continue
+			}
+
 			nodeContent := node.Content(source)
 			chunkContent := ""
+			var elidedBody *sitter.Node
 
 			switch {
 			case strings.HasPrefix(captureName, "definition.function"),
 				strings.HasPrefix(captureName, "definition.method"),
-				strings.HasPrefix(captureName, "definition.class"):
+				strings.HasPrefix(captureName, "definition.class"),
+				strings.HasPrefix(captureName, "definition.variable"):
+
+				if isTypeScript(languageIdentifier) {
+					// Declarations are matched directly, so the body can be found without
+					// the export statement handling used for JavaScript below.
+					body := typeScriptBody(decl)
+					if body != nil && body.StartByte() > node.StartByte() {
+						chunkContent = strings.TrimSpace(string(source[node.StartByte():body.StartByte()])) + " { ... } // Body removed"
+						if !strings.HasPrefix(captureName, "definition.class") {
+							elidedBody = body
+						}
+					} else {
+						chunkContent = strings.TrimSpace(nodeContent)
+					}
+					break
+				}
 
 				jsNode := node                  // The node captured by the query pattern.
 				actualDeclarationNode := jsNode // Default for non-JS or simple cases. This is the node that has the .body child.
@@ -193,6 +277,7 @@ func (gc *GenericCompressor) processCaptures(matches []*sitter.QueryMatch, query
 										Content:   chunkContent,
 										StartByte: node.StartByte(),
 										EndByte:   node.EndByte(),
+										Capture:   captureName,
 									})
 									seenNodes[node.StartByte()] = struct{}{}
 								}
@@ -228,6 +313,7 @@ func (gc *GenericCompressor) processCaptures(matches []*sitter.QueryMatch, query
 									Content:   chunkContent,
 									StartByte: node.StartByte(),
 									EndByte:   node.EndByte(),
+									Capture:   captureName,
 								})
 								seenNodes[node.StartByte()] = struct{}{}
 							}
@@ -328,6 +414,9 @@ func (gc *GenericCompressor) processCaptures(matches []*sitter.QueryMatch, query
 					if signatureEndPos > jsNode.StartByte() && signatureEndPos <= jsNode.EndByte() {
 						signaturePortion := string(source[jsNode.StartByte():signatureEndPos])
 						trimmedSignature := strings.TrimSpace(signaturePortion)
+						if !strings.HasPrefix(captureName, "definition.class") {
+							elidedBody = bodyNode
+						}
 
 						placeholder := " { ... }"
 						switch languageIdentifier {
@@ -356,6 +445,7 @@ func (gc *GenericCompressor) processCaptures(matches []*sitter.QueryMatch, query
 				strings.HasPrefix(captureName, "definition.enum"),
 				strings.HasPrefix(captureName, "definition.union"),
 				strings.HasPrefix(captureName, "definition.typedef"),
+				strings.HasPrefix(captureName, "definition.interface"),
 				strings.HasPrefix(captureName, "export.other"),
 				strings.HasPrefix(captureName, "command"),
 				strings.HasPrefix(captureName, "rule_set"),
@@ -373,11 +463,17 @@ func (gc *GenericCompressor) processCaptures(matches []*sitter.QueryMatch, query
 			}
 
 			if chunkContent != "" {
-				chunks = append(chunks, CodeChunk{
+				chunk := CodeChunk{
 					Content:   chunkContent,
 					StartByte: node.StartByte(),
 					EndByte:   node.EndByte(),
-				})
+					Capture:   captureName,
+				}
+				if elidedBody != nil {
+					chunk.BodyStart = elidedBody.StartByte()
+					chunk.BodyEnd = elidedBody.EndByte()
+				}
+				chunks = append(chunks, chunk)
 				seenNodes[node.StartByte()] = struct{}{}
 			}
 		}
@@ -385,6 +481,25 @@ func (gc *GenericCompressor) processCaptures(matches []*sitter.QueryMatch, query
 	return chunks
 }
 
+// dropElidedChunks removes chunks that sit inside a function or method body which has
+// already been replaced with a placeholder, such as comments within that body.
+func dropElidedChunks(chunks []CodeChunk) []CodeChunk {
+	kept := make([]CodeChunk, 0, len(chunks))
+	for _, chunk := range chunks {
+		elided := false
+		for _, outer := range chunks {
+			if outer.BodyEnd > outer.BodyStart && chunk.StartByte >= outer.BodyStart && chunk.EndByte <= outer.BodyEnd {
+				elided = true
+				break
+			}
+		}
+		if !elided {
+			kept = append(kept, chunk)
+		}
+	}
+	return kept
+}
+
 // compressSwift is a specialized function to compress Swift content
 func (gc *GenericCompressor) compressSwift(content []byte) string {
 	// Convert content to string for easier processing
@@ -427,9 +542,11 @@ func (gc *GenericCompressor) compressSwift(content []byte) string {
 
 	// Combine all extracted elements
 	var chunks []string
-	chunks = append(chunks, singleLineComments...)
-	chunks = append(chunks, multiLineComments...)
-	chunks = append(chunks, imports...)
+	if gc.level != Signatures {
+		chunks = append(chunks, singleLineComments...)
+		chunks = append(chunks, multiLineComments...)
+		chunks = append(chunks, imports...)
+	}
 	chunks = append(chunks, structs...)
 	chunks = append(chunks, classes...)
 	chunks = append(chunks, protocols...)
@@ -488,10 +605,14 @@ func (gc *GenericCompressor) compressJava(content []byte) string {
 
 	// Combine all extracted elements
 	var chunks []string
-	chunks = append(chunks, singleLineComments...)
-	chunks = append(chunks, multiLineComments...)
+	if gc.level != Signatures {
+		chunks = append(chunks, singleLineComments...)
+		chunks = append(chunks, multiLineComments...)
+	}
 	chunks = append(chunks, packages...)
-	chunks = append(chunks, imports...)
+	if gc.level != Signatures {
+		chunks = append(chunks, imports...)
+	}
 	chunks = append(chunks, classes...)
 	chunks = append(chunks, interfaces...)
 	chunks = append(chunks, enums...)
@@ -548,9 +669,11 @@ func (gc *GenericCompressor) compressRust(content []byte) string {
 
 	// Combine all extracted elements
 	var chunks []string
-	chunks = append(chunks, singleLineComments...)
-	chunks = append(chunks, multiLineComments...)
-	chunks = append(chunks, imports...)
+	if gc.level != Signatures {
+		chunks = append(chunks, singleLineComments...)
+		chunks = append(chunks, multiLineComments...)
+		chunks = append(chunks, imports...)
+	}
 	chunks = append(chunks, structs...)
 	chunks = append(chunks, traits...)
 	chunks = append(chunks, enums...)
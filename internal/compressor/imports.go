@@ -0,0 +1,143 @@
+package compressor
+
+import (
+	"fmt"
+	"regexp"
+	"strings"
+)
+
+// jsImportQuery captures the module paths of import and export statements, require calls
+// and dynamic imports.
+const jsImportQuery = `
+        (import_statement source: (string) @path)
+        (export_statement source: (string) @path)
+        (call_expression function: (identifier) @function arguments: (arguments . (string) @path))
+        (call_expression function: (import) arguments: (arguments . (string) @path))
+        `
+
+// importQueries capture the module paths imported by a file, for following imports.
+var importQueries = map[string]string{
+	"go":         `(import_spec path: (_) @path)`,
+	"javascript": jsImportQuery,
+	"typescript": jsImportQuery,
+	"tsx":        jsImportQuery,
+}
+
+var (
+	rustModPattern = regexp.MustCompile(`(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_][A-Za-z0-9_]*)\s*;`)
+	rustUsePattern = regexp.MustCompile(`(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);`)
+	rustAsPattern  = regexp.MustCompile(`\s+as\s+[A-Za-z_][A-Za-z0-9_]*`)
+)
+
+// FollowsImports reports whether Imports can find the imports of the language.
+func FollowsImports(languageIdentifier string) bool {
+	_, ok := importQueries[languageIdentifier]
+	return ok || languageIdentifier == "rust"
+}
+
+// Imports returns the module paths imported by content, as written in the source, in the
+// order they appear. Go gives import paths and JavaScript and TypeScript the specifiers of
+// imports, exports and require calls. Rust has no grammar, so its mod declarations and use
+// paths are found by pattern, with each mod declaration given as a self:: path and use groups
+// expanded into a path each.
+func Imports(content []byte, languageIdentifier string) ([]string, error) {
+	if languageIdentifier == "rust" {
+		return rustImports(string(content)), nil
+	}
+
+	queryStr, ok := importQueries[languageIdentifier]
+	if !ok {
+		return nil, fmt.Errorf("following imports is not supported for '%s'", languageIdentifier)
+	}
+	lang, err := GetLanguage(languageIdentifier)
+	if err != nil {
+		return nil, fmt.Errorf("could not get language for '%s': %w", languageIdentifier, err)
+	}
+
+	tree, err := ParseSource(content, lang)
+	if err != nil {
+		return nil, fmt.Errorf("could not parse source for '%s': %w", languageIdentifier, err)
+	}
+	defer tree.Close()
+
+	query, err := CompileQuery(queryStr, lang)
+	if err != nil {
+		return nil, fmt.Errorf("could not compile import query for '%s': %w", languageIdentifier, err)
+	}
+	defer query.Close()
+
+	matches, err := ExecuteQuery(tree, query, content)
+	if err != nil {
+		return nil, fmt.Errorf("could not execute import query for '%s': %w", languageIdentifier, err)
+	}
+
+	var imports []string
+	for _, match := range matches {
+		var path, function string
+		for _, capture := range match.Captures {
+			switch query.CaptureNameForId(capture.Index) {
+			case "path":
+				path = strings.Trim(capture.Node.Content(content), "\"'`")
+			case "function":
+				function = capture.Node.Content(content)
+			}
+		}
+		// Calls only count when they are to require
+		if path != "" && (function == "" || function == "require") {
+			imports = append(imports, path)
+		}
+	}
+	return imports, nil
+}
+
+// rustImports finds the modules a Rust file declares and the paths it uses.
+func rustImports(content string) []string {
+	var imports []string
+	for _, match := range rustModPattern.FindAllStringSubmatch(content, -1) {
+		imports = append(imports, "self::"+match[1])
+	}
+	for _, match := range rustUsePattern.FindAllStringSubmatch(content, -1) {
+		tree := strings.Join(strings.Fields(rustAsPattern.ReplaceAllString(match[1], "")), "")
+		imports = append(imports, expandUseTree(tree)...)
+	}
+	return imports
+}
+
+// expandUseTree expands a Rust use tree without whitespace, such as crate::a::{b,c::{d,self}},
+// into the path of each item it uses.
+func expandUseTree(tree string) []string {
+	open := strings.Index(tree, "{")
+	if open < 0 || !strings.HasSuffix(tree, "}") {
+		return []string{tree}
+	}
+
+	prefix := tree[:open]
+	var items []string
+	depth, start := 0, open+1
+	for i := open + 1; i < len(tree)-1; i++ {
+		switch tree[i] {
+		case '{':
+			depth++
+		case '}':
+			depth--
+		case ',':
+			if depth == 0 {
+				items = append(items, tree[start:i])
+				start = i + 1
+			}
+		}
+	}
+	items = append(items, tree[start:len(tree)-1])
+
+	var paths []string
+	for _, item := range items {
+		switch item {
+		case "":
+		case "self":
+			paths = append(paths, strings.TrimSuffix(prefix, "::"))
+		default:
+			paths = append(paths, expandUseTree(prefix+item)...)
+		}
+	}
+	return paths
+}
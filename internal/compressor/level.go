@@ -0,0 +1,59 @@
+package compressor
+
+import (
+	"fmt"
+	"strings"
+)
+
+// CompressionLevel controls how much of a source file the compressor removes.
+type CompressionLevel int
+
+const (
+	// NoBodies strips function and method bodies but keeps imports, types, top-level
+	// declarations and comments. This is the default level.
+	NoBodies CompressionLevel = iota
+	// Signatures keeps only declarations and signatures, dropping imports, comments and
+	// anything else that isn't a definition.
+	Signatures
+	// NoComments only removes comments and blank lines, leaving all code in place.
+	NoComments
+)
+
+// CompressionLevels lists the accepted names for each level, as used by --compress-level.
+var CompressionLevels = map[string]CompressionLevel{
+	"no-bodies":   NoBodies,
+	"signatures":  Signatures,
+	"no-comments": NoComments,
+}
+
+// ParseCompressionLevel converts a level name such as "signatures" to a CompressionLevel.
+// An empty string returns the default level.
+func ParseCompressionLevel(name string) (CompressionLevel, error) {
+	if name == "" {
+		return NoBodies, nil
+	}
+	level, ok := CompressionLevels[strings.ToLower(strings.TrimSpace(name))]
+	if !ok {
+		return NoBodies, fmt.Errorf("unknown compression level %q (valid levels: signatures, no-bodies, no-comments)", name)
+	}
+	return level, nil
+}
+
+func (l CompressionLevel) String() string {
+	switch l {
+	case Signatures:
+		return "signatures"
+	case NoComments:
+		return "no-comments"
+	default:
+		return "no-bodies"
+	}
+}
+
+// isSignatureCapture reports whether a capture holds a declaration or signature that is kept
+// at the Signatures level.
+func isSignatureCapture(captureName string) bool {
+	return strings.HasPrefix(captureName, "definition.") ||
+		strings.HasPrefix(captureName, "package") ||
+		strings.HasPrefix(captureName, "export.other")
+}
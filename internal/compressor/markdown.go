@@ -0,0 +1,189 @@
+package compressor
+
+import (
+	"fmt"
+	"regexp"
+	"strings"
+)
+
+var (
+	// Markdown badges are images inside a link, or on their own, such as [![CI](badge.svg)](actions)
+	markdownBadgeRegex = regexp.MustCompile(`\[!\[[^\]]*\]\(\s*<?([^)\s>]+)>?[^)]*\)\]\([^)]*\)|!\[[^\]]*\]\(\s*<?([^)\s>]+)>?[^)]*\)`)
+
+	// HTML badges are <img> tags, optionally inside a link
+	htmlBadgeRegex = regexp.MustCompile(`(?i)<a\s[^>]*>\s*<img\s[^>]*src\s*=\s*["']([^"']+)["'][^>]*>\s*</a>|<img\s[^>]*src\s*=\s*["']([^"']+)["'][^>]*>`)
+
+	// Table of contents entries are list items that are only a link to a heading in the file
+	tocEntryRegex = regexp.MustCompile(`^\s*(?:[-*+]|\d+[.)])\s+\[[^\]]+\]\(#[^)\s]*\)\s*$`)
+
+	markdownFenceRegex    = regexp.MustCompile("^ {0,3}(`{3,}|~{3,})")
+	markdownBadgeURLRegex = regexp.MustCompile(`(?i)shields\.io|badgen\.net|badge|/status\.svg|codecov\.io|travis-ci\.(?:org|com)`)
+)
+
+// markdownTOCMinEntries is the fewest entries a list of links to headings needs to be treated
+// as a table of contents.
+const markdownTOCMinEntries = 3
+
+// compressMarkdown removes what a model doesn't need from a markdown file: HTML comments on
+// lines of their own, lines that are only badges, and tables of contents, which are lists of
+// links to the file's own headings, replaced with a note. At the NoComments level only the
+// comments are removed. Headings and prose are always kept, and so is anything in a code block
+// or indented like one. A line is only removed if all of it is one of these, so when in doubt it
+// is kept.
+func (gc *GenericCompressor) compressMarkdown(content []byte) string {
+	lines := strings.Split(string(content), "\n")
+	keep := make([]bool, len(lines))
+	notes := make(map[int]string)
+
+	var fence string
+	for i := 0; i < len(lines); i++ {
+		line := lines[i]
+		keep[i] = true
+
+		// Code blocks are kept as they are
+		if fence != "" {
+			if strings.HasPrefix(strings.TrimSpace(line), fence) {
+				fence = ""
+			}
+			continue
+		}
+		if match := markdownFenceRegex.FindStringSubmatch(line); match != nil {
+			fence = match[1]
+			continue
+		}
+		if isIndentedCode(line) {
+			continue
+		}
+
+		if end, ok := htmlCommentEnd(lines, i); ok {
+			for j := i; j <= end; j++ {
+				keep[j] = false
+			}
+			i = end
+			continue
+		}
+
+		if gc.level == NoComments {
+			continue
+		}
+
+		if isBadgeLine(line) {
+			keep[i] = false
+			continue
+		}
+
+		if end := tocEnd(lines, i); end > i {
+			entries := 0
+			for j := i; j <= end; j++ {
+				if tocEntryRegex.MatchString(lines[j]) {
+					entries++
+				}
+				keep[j] = false
+			}
+			notes[i] = fmt.Sprintf("(Table of contents of %d links removed)", entries)
+			i = end
+		}
+	}
+
+	return joinKeptLines(lines, keep, notes)
+}
+
+// isIndentedCode reports whether a line is indented enough to be part of an indented code
+// block, or a list item's continuation, either way content to keep.
+func isIndentedCode(line string) bool {
+	return strings.HasPrefix(line, "    ") || strings.HasPrefix(line, "\t")
+}
+
+// htmlCommentEnd returns the last line of an HTML comment starting at line i, if the comment
+// takes up whole lines with nothing else before or after it.
+func htmlCommentEnd(lines []string, i int) (int, bool) {
+	if !strings.HasPrefix(strings.TrimSpace(lines[i]), "<!--") {
+		return 0, false
+	}
+	for j := i; j < len(lines); j++ {
+		text := lines[j]
+		if j == i {
+			text = strings.TrimSpace(text)[len("<!--"):]
+		}
+		if end := strings.Index(text, "-->"); end >= 0 {
+			return j, strings.TrimSpace(text[end+len("-->"):]) == ""
+		}
+	}
+	return 0, false
+}
+
+// isBadgeLine reports whether a line is only badges, images linking to a build status, version
+// or similar, with nothing else but whitespace.
+func isBadgeLine(line string) bool {
+	badges := 0
+	rest := line
+	for _, re := range []*regexp.Regexp{markdownBadgeRegex, htmlBadgeRegex} {
+		rest = re.ReplaceAllStringFunc(rest, func(match string) string {
+			groups := re.FindStringSubmatch(match)
+			url := groups[1] + groups[2]
+			if !markdownBadgeURLRegex.MatchString(url) {
+				return match
+			}
+			badges++
+			return ""
+		})
+	}
+	return badges > 0 && strings.TrimSpace(rest) == ""
+}
+
+// tocEnd returns the last line of a table of contents starting at line i: a run of list items
+// that are all links to headings, with any nested items, or i if there isn't one.
+func tocEnd(lines []string, i int) int {
+	if !tocEntryRegex.MatchString(lines[i]) || strings.TrimLeft(lines[i], " ") != lines[i] {
+		return i
+	}
+	end := i
+	for end+1 < len(lines) && tocEntryRegex.MatchString(lines[end+1]) {
+		end++
+	}
+	if end-i+1 < markdownTOCMinEntries {
+		return i
+	}
+	return end
+}
+
+// joinKeptLines joins the kept lines, with each note in place of the removed lines it starts.
+// A run of blank lines around removed lines is collapsed to one, and dropped at the start or
+// end of the file, while other blank lines are kept as they were.
+func joinKeptLines(lines []string, keep []bool, notes map[int]string) string {
+	var out []string
+	for i := 0; i < len(lines); {
+		if keep[i] && strings.TrimSpace(lines[i]) != "" {
+			out = append(out, lines[i])
+			i++
+			continue
+		}
+
+		// A run of blank and removed lines
+		start, removed := i, false
+		var runNotes []string
+		for i < len(lines) && (!keep[i] || strings.TrimSpace(lines[i]) == "") {
+			if !keep[i] {
+				removed = true
+			}
+			if note, ok := notes[i]; ok {
+				runNotes = append(runNotes, note)
+			}
+			i++
+		}
+		if !removed {
+			out = append(out, lines[start:i]...)
+			continue
+		}
+		for _, note := range runNotes {
+			if len(out) > 0 {
+				out = append(out, "")
+			}
+			out = append(out, note)
+		}
+		if len(out) > 0 && i < len(lines) {
+			out = append(out, "")
+		}
+	}
+	return strings.Join(out, "\n")
+}
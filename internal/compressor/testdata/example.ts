@@ -0,0 +1,42 @@
+// This is a TypeScript comment
+import { readFile } from "fs/promises";
+
+export interface User {
+  id: number;
+  name: string;
+}
+
+type Id = string | number;
+
+enum Colour {
+  Red,
+  Green,
+}
+
+export const MAX_USERS = 100;
+
+/** Greets a user. */
+export function greet(user: User): string {
+  // Function body
+  return `Hello, ${user.name}`;
+}
+
+export class UserService {
+  private users: User[] = [];
+
+  constructor(private readonly prefix: string) {
+    this.users = [];
+  }
+
+  public add(user: User): void {
+    // Method body
+    this.users.push(user);
+  }
+}
+
+const double = (x: number): number => x * 2;
+
+export const loadUsers = async (path: string): Promise<User[]> => {
+  const data = await readFile(path);
+  return JSON.parse(data.toString());
+};
@@ -6,19 +6,27 @@ import (
 	"errors"
 	"fmt"
 	"io"
-	"net/url"
 	"os"
 	"path/filepath"
 	"regexp"
+	"slices"
 	"sort"
+	"strconv"
 	"strings"
+	"time"
 
+	"github.com/bmatcuk/doublestar/v4"
 	"github.com/charmbracelet/glamour"
 	"github.com/fatih/color"
+	"github.com/sammcj/ingest/archive"
+	"github.com/sammcj/ingest/budget"
+	"github.com/sammcj/ingest/cache"
 	"github.com/sammcj/ingest/config"
 	"github.com/sammcj/ingest/filesystem"
+	"github.com/sammcj/ingest/format"
 	"github.com/sammcj/ingest/git"
-	"github.com/sammcj/ingest/internal/compressor" // Added compressor import
+	"github.com/sammcj/ingest/ingest"
+	"github.com/sammcj/ingest/redact"
 	"github.com/sammcj/ingest/template"
 	"github.com/sammcj/ingest/token"
 	"github.com/sammcj/ingest/utils"
@@ -26,6 +34,7 @@ import (
 	"github.com/sammcj/quantest"
 	openai "github.com/sashabaranov/go-openai"
 	"github.com/spf13/cobra"
+	"github.com/spf13/pflag"
 )
 
 var (
@@ -34,7 +43,8 @@ var (
 	tokens               bool
 	encoding             string
 	output               string
-	diff                 bool
+	diffMode             string
+	diffRange            string
 	gitDiffBranch        string
 	gitLogBranch         string
 	lineNumber           bool
@@ -46,8 +56,10 @@ var (
 	patternExclude       string
 	printDefaultExcludes bool
 	printDefaultTemplate bool
-	promptPrefix         string
-	promptSuffix         string
+	promptFlag           string
+	llmBaseURL           string
+	llmModel             string
+	llmAPIKey            string
 	report               bool
 	vramFlag             bool
 	modelIDFlag          string
@@ -59,24 +71,111 @@ var (
 	quantTypeFlag        string
 	verbose              bool
 	noDefaultExcludes    bool
+	noGlobalIgnore       bool
+	noGitAttributes      bool
 	followSymlinks       bool
+	symlinksFlag         string
 	Version              string // This will be set by the linker at build time
 	rootCmd              *cobra.Command
 	webCrawl             bool
 	webMaxDepth          int
+	webMaxPages          int
+	webIgnoreRobots      bool
+	webCrawlDelay        time.Duration
+	webNoReadability     bool
 	webAllowedDomains    []string
-	webTimeout           int
+	webTimeout           time.Duration
+	webTimeoutSeconds    int
+	webRetries           int
+	httpHeaderFlag       []string
+	cookieFlag           []string
 	webConcurrentJobs    int
 	compressFlag         bool // Added compress flag
+	compressLevelFlag    string
+	compressFallbackFlag bool
+	compressGlobFlag     []filesystem.CompressGlob
 	anthropicFlag        bool
+	tokenizerBackendFlag string
 	noCorrectionFlag     bool
+	tokenReportFlag      int
+	formatFlag           string
+	concurrencyFlag      int
+	maxFileSizeFlag      string
+	maxTokensFlag        int
+	modelContextFlag     string
+	reserveTokensFlag    int
+	budgetName           string // Describes the --max-tokens set by --model-context in messages
+	splitOutputFlag      string
+	splitLargeFlag       string
+	execFilterFlag       string
+	execFilterKeepGoing  bool
+	priorityFlag         []string
+	orderFlag            []string
+	treeStatsFlag        bool
+	stdinFlag            bool
+	hashOnlyFlag         bool
+	cacheDirFlag         string
+	noCacheFlag          bool
+	clearCacheFlag       bool
+	excludeFromFlag      []string
+	includeFromFlag      []string
+	excludeTestsFlag     bool
+	manifestFlag         string
+	onlyTestsFlag        bool
+	collapseGenerated    bool
+	collapseGlobFlag     []string
+	collapseMinified     bool
+	dedupeFlag           bool
+	collapseSimilarFlag  bool
+	similarityThreshold  float64
+	annotationsFlag      bool
+	annotationMarkerFlag []string
+	configFlag           string
+	printConfigFlag      bool
+	editConfigFlag       bool
+	projectConfigPath    string          // The project config file flags were read from
+	configSetFlags       map[string]bool // The flags set by the project config file
+	minifiedLineLength   int
+	minifiedNewlineRatio float64
+	previewFlag          bool
+	themeFlag            string
+	compareTokenizers    []string
+	pricePer1KFlag       float64
+	summaryOnlyFlag      bool
+	noTreeFlag           bool
+	treeOnlyFlag         bool
+	relativeToFlag       string
+	absolutePaths        bool
+	sinceFlag            string
+	quietFlag            bool
+	dryRunFlag           bool
+	noReadmeFirst        bool
+	countOnlyFlag        bool
+	compressOutputFlag   string
+	charsetFlag          string
+	streamFlag           bool
+	jsonLinesFlag        bool
+	groupByLanguageFlag  bool
+	branchFlag           string
+	depthFlag            int
+	redactFlag           bool
+	noRedactFlag         bool
+	redactPatterns       []string
+	watchFlag            bool
+	followImportsFlag    bool
+	fenceFlag            string
+	fileMetadataFlag     bool
+	noModTimeFlag        bool
 )
 
-type GitData struct {
-	Path          string
-	GitDiff       string
-	GitDiffBranch string
-	GitLogBranch  string
+// watchDebounce is how long --watch waits for changes to settle before regenerating the output.
+const watchDebounce = 300 * time.Millisecond
+
+// lastRun records what the latest run ingested, for --watch.
+var lastRun struct {
+	opts   filesystem.Options // The options local inputs were walked with
+	files  int
+	tokens int
 }
 
 func init() {
@@ -90,47 +189,136 @@ func init() {
 
 	// Define flags
 	rootCmd.Flags().Bool("llm", false, "Send output to any OpenAI compatible API for inference")
+	rootCmd.Flags().StringVar(&llmBaseURL, "llm-base-url", "", "Base URL of the OpenAI compatible API for --llm (e.g. localhost:11434/v1 for Ollama)")
+	rootCmd.Flags().StringVar(&llmModel, "llm-model", "", "Model to use with --llm")
+	rootCmd.Flags().StringVar(&llmAPIKey, "llm-api-key", "", "API key for --llm (default $OPENAI_API_KEY)")
 	rootCmd.Flags().BoolP("version", "V", false, "Print the version number")
 	rootCmd.Flags().BoolVar(&excludeFromTree, "exclude-from-tree", false, "Exclude files/folders from the source tree based on exclude patterns")
 	rootCmd.Flags().BoolVar(&includePriority, "include-priority", false, "Include files in case of conflict between include and exclude patterns")
 	rootCmd.Flags().BoolVar(&jsonOutput, "json", false, "Print output as JSON")
+	rootCmd.Flags().StringVar(&formatFlag, "format", "markdown", "Output format: markdown, json, xml or html")
 	rootCmd.Flags().BoolVar(&noCodeblock, "no-codeblock", false, "Disable wrapping code inside markdown code blocks")
+	rootCmd.Flags().StringSliceVar(&orderFlag, "order", nil, "Comma-separated globs whose matching files come first in the tree and output (e.g. 'README*,src/**')")
+	rootCmd.Flags().BoolVar(&hashOnlyFlag, "hash-only", false, "Print only the content hash of the ingested files and exit")
+	rootCmd.Flags().BoolVar(&stdinFlag, "stdin", false, "Read newline-delimited file paths to ingest from stdin")
+	rootCmd.Flags().BoolVar(&summaryOnlyFlag, "summary-only", false, "Output only the source tree and per-file token counts, leaving out file contents")
+	rootCmd.Flags().BoolVar(&noTreeFlag, "no-tree", false, "Leave the source tree out of the output, keeping each file's path header")
+	rootCmd.Flags().BoolVar(&treeOnlyFlag, "tree-only", false, "Output only the source tree, without reading the files unless --tree-stats needs them")
+	rootCmd.Flags().BoolVar(&treeStatsFlag, "tree-stats", false, "Show line counts and languages of files, and token subtotals of directories, in the tree")
+	rootCmd.Flags().BoolVar(&previewFlag, "preview", false, "Show the output rendered with syntax highlighting in a pager when stdout is a terminal")
+	rootCmd.Flags().StringVar(&themeFlag, "theme", "dark", "Style to render --preview with: dark, light or notty")
 	rootCmd.Flags().BoolVar(&printDefaultExcludes, "print-default-excludes", false, "Print the default exclude patterns")
 	rootCmd.Flags().BoolVar(&printDefaultTemplate, "print-default-template", false, "Print the default template")
 	rootCmd.Flags().BoolVar(&relativePaths, "relative-paths", false, "Use relative paths instead of absolute paths, including the parent directory")
+	rootCmd.Flags().StringVar(&relativeToFlag, "relative-to", "", "Show paths in the tree and file headers relative to this directory, which must contain every ingested path")
+	rootCmd.Flags().BoolVar(&absolutePaths, "absolute-paths", false, "Show absolute paths in the tree and file headers (the default)")
 	rootCmd.Flags().BoolVar(&report, "report", true, "Report the top 10 largest files included in the output")
 	rootCmd.Flags().BoolVar(&tokens, "tokens", true, "Display the token count of the generated prompt")
-	rootCmd.Flags().BoolVarP(&diff, "diff", "d", false, "Include git diff")
-	rootCmd.Flags().BoolVarP(&lineNumber, "line-number", "l", false, "Add line numbers to the source code")
+	rootCmd.Flags().IntVar(&maxTokensFlag, "max-tokens", 0, "Include files until the output would exceed this many tokens, dropping the rest")
+	rootCmd.Flags().StringVar(&modelContextFlag, "model-context", "", "Set --max-tokens to this model's context window (e.g. gpt-4o), less --reserve-tokens")
+	rootCmd.Flags().IntVar(&reserveTokensFlag, "reserve-tokens", 4096, "Tokens of the --model-context window left for the response")
+	rootCmd.Flags().StringVar(&splitOutputFlag, "split-output", "", "Split the output into files of up to --max-tokens each, named from a pattern such as out.md (out-001.md, ...) or out-%02d.md")
+	rootCmd.Flags().StringVar(&splitLargeFlag, "split-large", "whole", "How --split-output handles a file over --max-tokens on its own: whole (a chunk of its own) or lines (split at line boundaries)")
+	rootCmd.Flags().StringSliceVar(&priorityFlag, "priority", nil, "Order files are included in with --max-tokens: smallest, or globs to include first (e.g. 'src/**')")
+	rootCmd.Flags().IntVar(&tokenReportFlag, "token-report", 0, "Print a table of the top N files by token count after the output")
+	rootCmd.Flags().StringVarP(&diffMode, "diff", "d", "", "Only ingest files changed in the git working tree (or the index with --diff=staged), showing each file's diff")
+	rootCmd.Flags().Lookup("diff").NoOptDefVal = "working"
+	rootCmd.Flags().StringVar(&branchFlag, "branch", "", "Branch or tag to check out when ingesting a git URL")
+	rootCmd.Flags().IntVar(&depthFlag, "depth", 1, "Commits of history to clone when ingesting a git URL, 0 for all of them")
+	rootCmd.Flags().StringVar(&diffRange, "diff-range", "", "Only ingest files changed across a git revision range (e.g. main..HEAD)")
+	rootCmd.Flags().BoolVarP(&lineNumber, "line-numbers", "l", false, "Prefix each line of files with its line number, keeping the original numbers in compressed files")
+	rootCmd.Flags().BoolVar(&lineNumber, "line-number", false, "(alias for --line-numbers)")
 	rootCmd.Flags().BoolVarP(&noClipboard, "no-clipboard", "n", false, "Disable copying to clipboard")
 	rootCmd.Flags().BoolVarP(&verbose, "verbose", "v", false, "Enable verbose output")
+	rootCmd.Flags().BoolVar(&noReadmeFirst, "no-readme-first", false, "Don't list each directory's README, CONTRIBUTING and Markdown files before its other files")
+	rootCmd.Flags().StringVar(&compressOutputFlag, "compress-output", "", "Compress the output file or stdout with gzip or zstd, by default -o files ending in .gz or .zst are compressed")
+	rootCmd.Flags().BoolVar(&countOnlyFlag, "count-only", false, "Print only the total token count to stdout, without writing any output")
+	rootCmd.Flags().BoolVar(&dryRunFlag, "dry-run", false, "List the files that would be ingested to stderr, without reading them or producing output")
+	rootCmd.Flags().StringVar(&fenceFlag, "fence", "", "Code fence style, backtick (default) or tilde, always made longer than any run of its character in a file")
+	rootCmd.Flags().BoolVar(&fileMetadataFlag, "file-metadata", false, "Show each file's size, last modified time and permission bits on a line above its contents")
+	rootCmd.Flags().BoolVar(&noModTimeFlag, "no-mtime", false, "Leave the last modified time out of --file-metadata, so the output is reproducible (implies --file-metadata)")
+	rootCmd.Flags().BoolVar(&followImportsFlag, "follow-imports", false, "Also ingest the local files that file arguments import, and the files those import in turn (Go, JavaScript, TypeScript and Rust)")
+	rootCmd.Flags().BoolVar(&groupByLanguageFlag, "group-by-language", false, "List files in one section per language, largest first, with each language's token subtotal, rather than by directory")
+	rootCmd.Flags().BoolVar(&watchFlag, "watch", false, "Keep watching the ingested files, regenerating the output (and copying it to the clipboard) whenever one changes")
+	rootCmd.Flags().BoolVar(&streamFlag, "stream", false, "Write the output a file at a time as files are read, to stdout or --output, so memory use stays bounded")
+	rootCmd.Flags().BoolVar(&jsonLinesFlag, "json-lines", false, "Stream one JSON object per file, as newline-delimited JSON with no tree, printing the total tokens to stderr (implies --stream)")
+	rootCmd.Flags().BoolVar(&quietFlag, "quiet", false, "Only print the output and errors, without progress or other messages")
 	rootCmd.Flags().StringSliceP("exclude", "e", nil, "Patterns to exclude")
 	rootCmd.Flags().StringSliceP("include", "i", nil, "Patterns to include")
+	rootCmd.Flags().StringArrayVar(&excludeFromFlag, "exclude-from", nil, "Read patterns to exclude from a file, one per line")
+	rootCmd.Flags().StringArrayVar(&includeFromFlag, "include-from", nil, "Read patterns to include from a file, one per line")
+	rootCmd.Flags().BoolVar(&excludeTestsFlag, "exclude-tests", false, "Leave out test files, such as *_test.go, test_*.py, *.spec.* and files under test/, tests/ or __tests__/")
+	rootCmd.Flags().BoolVar(&onlyTestsFlag, "only-tests", false, "Only ingest test files, as recognised by --exclude-tests")
+	rootCmd.Flags().BoolVar(&redactFlag, "redact", false, "Also redact JWTs and KEY=, TOKEN=, SECRET= and PASSWORD= style assignments, not just private keys and AWS keys")
+	rootCmd.Flags().StringArrayVar(&redactPatterns, "redact-pattern", nil, "Regular expression of more secrets to redact, only its first capture group if it has one (can be used multiple times)")
+	rootCmd.Flags().BoolVar(&noRedactFlag, "no-redact", false, "Don't redact private keys, AWS keys or anything else")
 	rootCmd.Flags().StringVar(&gitDiffBranch, "git-diff-branch", "", "Generate git diff between two branches")
 	rootCmd.Flags().StringVar(&gitLogBranch, "git-log-branch", "", "Retrieve git log between two branches")
 	rootCmd.Flags().StringVar(&patternExclude, "pattern-exclude", "", "Path to a specific .glob file for exclude patterns")
-	rootCmd.Flags().StringVarP(&encoding, "encoding", "c", "o200k", "Tokeniser to use for token count (o200k, cl100k, p50k, r50k)")
+	rootCmd.Flags().StringVarP(&charsetFlag, "encoding", "c", "", "Charset to decode files from (e.g. shift_jis, latin1), or auto to detect it for each file")
+	rootCmd.Flags().StringVar(&encoding, "tokenizer", "o200k", "Tokeniser encoding to use for token count (o200k_base, cl100k_base, p50k_base, r50k_base), overrides --model")
 	rootCmd.Flags().StringVarP(&output, "output", "o", "", "Optional output file path")
-	rootCmd.Flags().StringArrayP("prompt", "p", nil, "Prompt suffix to append to the generated content")
-	rootCmd.Flags().StringVarP(&templatePath, "template", "t", "", "Optional Path to a custom Handlebars template")
+	rootCmd.Flags().StringVar(&manifestFlag, "manifest", "", "Write a JSON log of every path found, whether it was included and why not, to this file")
+	rootCmd.Flags().StringArrayP("prompt", "p", nil, "Prompt suffix to append to the generated content, or to send before it with --llm")
+	rootCmd.Flags().StringVarP(&templatePath, "template", "t", "", "Optional path to a custom Go text/template file")
 	rootCmd.Flags().BoolP("save", "s", false, "Automatically save the generated markdown to ~/ingest/<dirname>.md")
-	rootCmd.Flags().Bool("config", false, "Open the config file in the default editor")
+	rootCmd.Flags().StringVar(&configFlag, "config", "", "Project config file of flag defaults (default the nearest .ingest.toml)")
+	rootCmd.Flags().BoolVar(&editConfigFlag, "edit-config", false, "Open the LLM config file in the default editor")
+	rootCmd.Flags().BoolVar(&printConfigFlag, "print-config", false, "Print the flags in effect once the project config file is applied, as TOML, and exit")
 	rootCmd.Flags().BoolVar(&noDefaultExcludes, "no-default-excludes", false, "Disable default exclude patterns")
-	rootCmd.Flags().BoolVar(&followSymlinks, "follow-symlinks", false, "Follow symlinked files and directories")
+	rootCmd.Flags().BoolVar(&noGitAttributes, "no-gitattributes", false, "Don't skip export-ignore paths or collapse linguist-generated files listed in .gitattributes files")
+	rootCmd.Flags().BoolVar(&noGlobalIgnore, "no-global-ignore", false, "Don't apply your global git excludes file (core.excludesFile), only the repository's own ignore rules")
+	rootCmd.Flags().StringVar(&symlinksFlag, "symlinks", "skip", "How to handle symlinks: skip, follow, or follow-safe to only follow links within the root")
+	rootCmd.Flags().BoolVar(&followSymlinks, "follow-symlinks", false, "(alias for --symlinks=follow)")
+	rootCmd.Flags().StringVar(&cacheDirFlag, "cache-dir", "", "Directory to cache processed files in (default $XDG_CACHE_HOME/ingest)")
+	rootCmd.Flags().BoolVar(&noCacheFlag, "no-cache", false, "Process every file without reading or writing the cache")
+	rootCmd.Flags().BoolVar(&clearCacheFlag, "clear-cache", false, "Remove all cached files and exit")
+	rootCmd.Flags().BoolVar(&collapseGenerated, "collapse-generated", false, "Replace the content of lockfiles and generated files with a one-line stub, still showing them in the tree")
+	rootCmd.Flags().StringSliceVar(&collapseGlobFlag, "collapse-glob", nil, "Additional globs of files to collapse (implies --collapse-generated)")
+	rootCmd.Flags().BoolVar(&dedupeFlag, "dedupe", false, "Show the content of identical files once, replacing later copies with a note naming the first")
+	rootCmd.Flags().BoolVar(&collapseSimilarFlag, "collapse-similar", false, "Show the content of near-identical files once, replacing later ones with a note naming the first and how similar they are")
+	rootCmd.Flags().Float64Var(&similarityThreshold, "similarity-threshold", filesystem.DefaultSimilarityThreshold, "Estimated share of a file that must match an earlier one for --collapse-similar to collapse it, from 0.5 to 1 (implies --collapse-similar)")
+	rootCmd.Flags().BoolVar(&annotationsFlag, "annotations", false, "List the lines marked TODO, FIXME, XXX, HACK or @security in an Annotations section")
+	rootCmd.Flags().StringSliceVar(&annotationMarkerFlag, "annotation-marker", nil, "Markers for --annotations to list instead of the defaults (implies --annotations)")
+	rootCmd.Flags().BoolVar(&collapseMinified, "collapse-minified", true, "Replace the content of files that look minified, such as bundled JavaScript and CSS, with a one-line stub")
+	rootCmd.Flags().IntVar(&minifiedLineLength, "minified-line-length", filesystem.DefaultMinifiedThresholds.LineLength, "Average line length in bytes from which --collapse-minified treats a file as minified")
+	rootCmd.Flags().Float64Var(&minifiedNewlineRatio, "minified-newline-ratio", filesystem.DefaultMinifiedThresholds.NewlineRatio, "Share of a file's bytes that are newlines below which --collapse-minified treats it as minified")
+	rootCmd.Flags().IntVar(&concurrencyFlag, "concurrency", 0, "Number of files to process concurrently (default GOMAXPROCS)")
+	rootCmd.Flags().StringVar(&sinceFlag, "since", "", "Only ingest files modified within a duration (e.g. 24h, 7d) or since a date (e.g. 2024-01-01)")
+	rootCmd.Flags().StringVar(&maxFileSizeFlag, "max-file-size", "10MB", "Skip files larger than this size (bytes, or e.g. 512KB, 10MB), 0 for no limit")
+	rootCmd.Flags().StringVar(&execFilterFlag, "exec-filter", "", "Shell command to pipe each file's content through before it is counted and output, with the file's path in INGEST_FILE_PATH")
+	rootCmd.Flags().BoolVar(&execFilterKeepGoing, "exec-filter-keep-going", false, "Skip files --exec-filter fails on instead of failing the run")
 	rootCmd.Flags().BoolVar(&compressFlag, "compress", false, "Enable code compression using Tree-sitter") // Added compress flag
+	rootCmd.Flags().BoolVar(&compressFallbackFlag, "compress-fallback", false, "Strip comment lines from files in languages without a Tree-sitter grammar (implies --compress)")
+	rootCmd.Flags().StringVar(&compressLevelFlag, "compress-level", "no-bodies", "Compression level: signatures, no-bodies, or no-comments (implies --compress)")
+	rootCmd.Flags().Var(&compressGlobsValue{globs: &compressGlobFlag, compress: true}, "compress-glob", "Compress files matching this glob, even without --compress (can be repeated, the last matching glob wins)")
+	rootCmd.Flags().Var(&compressGlobsValue{globs: &compressGlobFlag, compress: false}, "no-compress-glob", "Leave files matching this glob uncompressed with --compress (can be repeated, the last matching glob wins)")
 	rootCmd.Flags().BoolVarP(&anthropicFlag, "anthropic", "a", false, "Use Anthropic API for token counting (requires ANTHROPIC_API_KEY, ANTHROPIC_TOKEN, or ANTHROPIC_TOKEN_COUNT_KEY)")
+	rootCmd.Flags().StringVar(&tokenizerBackendFlag, "tokenizer-backend", "tiktoken", "Tokeniser to count with: tiktoken, huggingface (--tokenizer is a tokenizer.json path or model ID) or anthropic (like --anthropic)")
+	rootCmd.Flags().StringSliceVar(&compareTokenizers, "compare-tokenizers", nil, "Comma-separated tokeniser encodings to compare the output's token count across (e.g. cl100k_base,o200k_base)")
+	rootCmd.Flags().Float64Var(&pricePer1KFlag, "price-per-1k", 0, "Price per 1,000 input tokens, to show the approximate cost with --compare-tokenizers")
 	rootCmd.Flags().BoolVar(&noCorrectionFlag, "no-correction", false, "Disable offline tokeniser correction factor (use raw token count)")
 
 	// Web Crawler flags
 	rootCmd.Flags().BoolVar(&webCrawl, "web", false, "Enable web crawling mode")
-	rootCmd.Flags().IntVar(&webMaxDepth, "web-depth", 1, "Maximum crawling depth for web pages")
+	rootCmd.Flags().IntVar(&webMaxDepth, "crawl-depth", 1, "Depth of same-origin links to follow when crawling (default 0 for URLs with a path)")
+	rootCmd.Flags().IntVar(&webMaxDepth, "web-depth", 1, "(alias for --crawl-depth)")
+	rootCmd.Flags().IntVar(&webMaxPages, "crawl-max-pages", 100, "Maximum number of pages to fetch when crawling, 0 for no limit")
+	rootCmd.Flags().DurationVar(&webCrawlDelay, "crawl-delay", 500*time.Millisecond, "Minimum delay between requests to the same host when crawling")
+	rootCmd.Flags().BoolVar(&webIgnoreRobots, "ignore-robots", false, "Crawl pages even if robots.txt disallows them")
+	rootCmd.Flags().BoolVar(&webNoReadability, "no-readability", false, "Convert full web pages rather than only their main content")
 	rootCmd.Flags().StringSliceVar(&webAllowedDomains, "web-domains", nil, "Allowed domains for web crawling")
-	rootCmd.Flags().IntVar(&webTimeout, "web-timeout", 120, "Timeout in seconds for web requests")
-	rootCmd.Flags().IntVar(&webConcurrentJobs, "web-concurrent", 6, "Number of concurrent crawling jobs")
+	rootCmd.Flags().DurationVar(&webTimeout, "http-timeout", 2*time.Minute, "Timeout of each request when fetching URLs, including reading the response, 0 for none")
+	rootCmd.Flags().IntVar(&webTimeoutSeconds, "web-timeout", 120, "(alias for --http-timeout in seconds)")
+	rootCmd.Flags().StringArrayVar(&httpHeaderFlag, "http-header", nil, "Header to send with every URL request, as \"Name: Value\" with ${ENV_VAR} references expanded (can be repeated)")
+	rootCmd.Flags().StringArrayVar(&cookieFlag, "cookie", nil, "Cookie to send with every URL request, as \"name=value\" with ${ENV_VAR} references expanded (can be repeated)")
+	rootCmd.Flags().IntVar(&webRetries, "http-retries", 2, "Times to retry a URL request that fails, times out or gets a 429 or 503 response, waiting twice as long each time")
+	rootCmd.Flags().IntVar(&webConcurrentJobs, "crawl-concurrency", 4, "Maximum number of pages to fetch at once when crawling, still waiting --crawl-delay between requests to a host")
+	rootCmd.Flags().IntVar(&webConcurrentJobs, "web-concurrent", 4, "(alias for --crawl-concurrency)")
 
 	// VRAM estimation flags
 	rootCmd.Flags().BoolVar(&vramFlag, "vram", false, "Estimate vRAM usage")
-	rootCmd.Flags().StringVarP(&modelIDFlag, "model", "m", "", "vRAM Estimation - Model ID")
+	rootCmd.Flags().StringVarP(&modelIDFlag, "model", "m", "", "Model name used to select the tokeniser (e.g. gpt-4o), or the model ID for vRAM estimation")
 	rootCmd.Flags().StringVarP(&quantFlag, "quant", "q", "", "vRAM Estimation - Quantization type (e.g., q4_k_m) or bits per weight (e.g., 5.0)")
 	rootCmd.Flags().IntVar(&contextFlag, "context", 0, "vRAM Estimation - Context length for vRAM estimation")
 	rootCmd.Flags().StringVar(&kvCacheFlag, "kvcache", "fp16", "vRAM Estimation - KV cache quantization: fp16, q8_0, or q4_0")
@@ -185,7 +373,40 @@ func main() {
 	}
 }
 
+// run ingests the arguments, then with --watch regenerates the output as they change.
 func run(cmd *cobra.Command, args []string) error {
+	if err := applyProjectConfig(cmd, args); err != nil {
+		return err
+	}
+	if printConfigFlag {
+		printConfig(cmd)
+		return nil
+	}
+
+	utils.Quiet = quietFlag
+	if !watchFlag {
+		return runOnce(cmd, args)
+	}
+
+	if err := checkWatchFlags(cmd, args); err != nil {
+		return err
+	}
+	if err := runOnce(cmd, args); err != nil {
+		return err
+	}
+	return watchInputs(cmd, args)
+}
+
+func runOnce(cmd *cobra.Command, args []string) error {
+	if stdinFlag {
+		if len(args) > 0 {
+			return fmt.Errorf("--stdin cannot be combined with path arguments")
+		}
+		if diffMode != "" || diffRange != "" {
+			return fmt.Errorf("--stdin cannot be combined with --diff or --diff-range")
+		}
+	}
+
 	// If no arguments are provided, use the current directory
 	if len(args) == 0 {
 		currentDir, err := os.Getwd()
@@ -200,7 +421,7 @@ func run(cmd *cobra.Command, args []string) error {
 		return nil
 	}
 
-	if configFlag, _ := cmd.Flags().GetBool("config"); configFlag {
+	if editConfigFlag {
 		if err := config.OpenConfig(); err != nil {
 			return fmt.Errorf("failed to open config: %w", err)
 		}
@@ -215,18 +436,10 @@ func run(cmd *cobra.Command, args []string) error {
 	if err := utils.EnsureConfigDirectories(); err != nil {
 		return fmt.Errorf("failed to ensure config directories: %w", err)
 	}
-	// If no arguments are provided, use the current directory
-	if len(args) == 0 {
-		currentDir, err := os.Getwd()
-		if err != nil {
-			return fmt.Errorf("failed to get current directory: %w", err)
-		}
-		args = []string{currentDir}
-	}
 
 	// Handle the prompt flag
 	promptArray, _ := cmd.Flags().GetStringArray("prompt")
-	promptSuffix = strings.Join(promptArray, " ")
+	promptFlag = strings.Join(promptArray, " ")
 
 	if printDefaultExcludes {
 		filesystem.PrintDefaultExcludes()
@@ -238,180 +451,475 @@ func run(cmd *cobra.Command, args []string) error {
 		return nil
 	}
 
-	includePatterns, _ := cmd.Flags().GetStringSlice("include")
-	excludePatterns, _ := cmd.Flags().GetStringSlice("exclude")
-
-	// Setup template
-	tmpl, err := template.SetupTemplate(templatePath)
+	cacheDir, err := cache.Dir(cacheDirFlag)
 	if err != nil {
-		return fmt.Errorf("failed to set up template: %w", err)
+		return err
+	}
+	if clearCacheFlag {
+		if err := cache.Clear(cacheDir); err != nil {
+			return err
+		}
+		utils.PrintColouredMessage("✅", fmt.Sprintf("Cleared cache at %s", cacheDir), color.FgGreen)
+		return nil
 	}
 
-	// Setup progress spinner
-	spinner := utils.SetupSpinner("Traversing directory and building tree..")
-	defer func() {
-		if err := spinner.Finish(); err != nil {
-			fmt.Printf("Error finishing spinner: %v\n", err)
+	// --encoding used to select the tokeniser, so tokeniser names still do
+	if _, err := token.ParseEncoding(charsetFlag); err == nil {
+		utils.PrintColouredMessage("⚠️", "--encoding now sets the charset of files, use --tokenizer to select the tokeniser", color.FgYellow)
+		if !cmd.Flags().Changed("tokenizer") {
+			encoding = charsetFlag
+		}
+		charsetFlag = ""
+	} else if charsetFlag != "" {
+		if err := filesystem.ValidateCharset(charsetFlag); err != nil {
+			return fmt.Errorf("invalid --encoding: %w", err)
 		}
-	}()
+	}
 
-	// If verbose, print active excludes
-	if verbose {
-		activeExcludes, err := filesystem.ReadExcludePatterns(patternExclude, false)
-		if err != nil {
-			return fmt.Errorf("failed to read exclude patterns: %w", err)
+	if err := resolveTokenizerBackend(cmd); err != nil {
+		return err
+	}
+	if err := resolveTokenizer(cmd); err != nil {
+		return err
+	}
+	if err := resolveModelContext(cmd); err != nil {
+		return err
+	}
+
+	compareEncodings := make([]string, len(compareTokenizers))
+	for i, name := range compareTokenizers {
+		if compareEncodings[i], err = token.ParseEncoding(name); err != nil {
+			return fmt.Errorf("invalid --compare-tokenizers: %w", err)
 		}
-		printExcludePatterns(activeExcludes)
+	}
+	if pricePer1KFlag != 0 && len(compareEncodings) == 0 {
+		return fmt.Errorf("--price-per-1k requires --compare-tokenizers")
 	}
 
-	// Process all provided paths
-	var allFiles []filesystem.FileInfo
-	var allTrees []string
-	var gitData []GitData
-	var allExcluded []*filesystem.ExcludedInfo
+	if diffMode != "" && diffMode != "working" && diffMode != "staged" {
+		return fmt.Errorf("invalid --diff mode %q, must be working or staged", diffMode)
+	}
+	if diffMode != "" && diffRange != "" {
+		return fmt.Errorf("--diff and --diff-range cannot be used together")
+	}
 
-	remainingArgs := make([]string, len(args))
-	copy(remainingArgs, args)
+	outputFormat, err := format.Parse(formatFlag)
+	if err != nil {
+		return err
+	}
+	if outputFormat != format.Markdown {
+		if jsonOutput {
+			return fmt.Errorf("--json cannot be combined with --format %s", outputFormat)
+		}
+		// Structured formats carry file contents verbatim
+		noCodeblock = true
+	}
+	if jsonLinesFlag {
+		if cmd.Flags().Changed("format") {
+			return fmt.Errorf("--json-lines cannot be combined with --format")
+		}
+		outputFormat = format.JSONLines
+		noCodeblock = true
+	}
+	streaming := streamFlag || jsonLinesFlag
+	useLLM, _ := cmd.Flags().GetBool("llm")
+	warnUnusedPrompt(useLLM, outputFormat, streaming)
 
-	for i := range remainingArgs {
-		arg := remainingArgs[i]
+	switch themeFlag {
+	case "dark", "light", "notty":
+	default:
+		return fmt.Errorf("invalid --theme %q, must be dark, light or notty", themeFlag)
+	}
 
-		// Check if this is a URL (either with --web flag or auto-detected)
-		if webCrawl || isURL(arg) {
-			if !isURL(arg) {
-				return fmt.Errorf("web crawling is enabled but the argument '%s' is not a URL", arg)
-			}
+	priority, err := budget.ParsePriority(priorityFlag)
+	if err != nil {
+		return fmt.Errorf("invalid --priority: %w", err)
+	}
 
-			utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Processing URL: %s", arg), color.FgBlue)
+	for _, pattern := range orderFlag {
+		if !doublestar.ValidatePattern(pattern) {
+			return fmt.Errorf("invalid --order pattern %q", pattern)
+		}
+	}
 
-			// Process as web URL - now passing excludePatterns
-			result, err := processWebInput(arg, excludePatterns)
-			if err != nil {
-				return fmt.Errorf("failed to process web URL %s: %w", arg, err)
-			}
+	if followSymlinks && !cmd.Flags().Changed("symlinks") {
+		symlinksFlag = string(filesystem.SymlinksFollow)
+	}
+	symlinkPolicy, err := filesystem.ParseSymlinkPolicy(symlinksFlag)
+	if err != nil {
+		return err
+	}
 
-			allFiles = append(allFiles, result.Files...)
-			allTrees = append(allTrees, result.TreeString)
-			continue
+	var pathBase string
+	if relativeToFlag != "" {
+		if relativePaths || absolutePaths {
+			return fmt.Errorf("--relative-to cannot be combined with --relative-paths or --absolute-paths")
 		}
+		if pathBase, err = filepath.Abs(relativeToFlag); err != nil {
+			return fmt.Errorf("failed to get absolute path for --relative-to: %w", err)
+		}
+	}
+	if absolutePaths && relativePaths {
+		return fmt.Errorf("--absolute-paths and --relative-paths cannot be used together")
+	}
+	if noTreeFlag && treeOnlyFlag {
+		return fmt.Errorf("--no-tree cannot be combined with --tree-only")
+	}
+	if excludeTestsFlag && onlyTestsFlag {
+		return fmt.Errorf("--exclude-tests cannot be combined with --only-tests")
+	}
+	if manifestFlag != "" && treeOnlyFlag {
+		return fmt.Errorf("--manifest cannot be combined with --tree-only")
+	}
 
-		// Process as local file/directory
-		absPath, err := filepath.Abs(arg)
-		if err != nil {
-			return fmt.Errorf("failed to get absolute path for %s: %w", arg, err)
+	if compressOutputFlag != "" && !slices.Contains(utils.OutputCompressions, compressOutputFlag) {
+		return fmt.Errorf("invalid --compress-output %q (valid: %s)", compressOutputFlag, strings.Join(utils.OutputCompressions, ", "))
+	}
+
+	if fenceFlag != "" && !slices.Contains(filesystem.FenceStyles, fenceFlag) {
+		return fmt.Errorf("invalid --fence %q (valid: %s)", fenceFlag, strings.Join(filesystem.FenceStyles, ", "))
+	}
+
+	if splitOutputFlag != "" {
+		if err := checkSplitFlags(cmd); err != nil {
+			return err
 		}
+	} else if cmd.Flags().Changed("split-large") {
+		return fmt.Errorf("--split-large requires --split-output")
+	}
+	if execFilterKeepGoing && execFilterFlag == "" {
+		return fmt.Errorf("--exec-filter-keep-going requires --exec-filter")
+	}
 
-		fileInfo, err := os.Stat(absPath)
-		if err != nil {
-			return fmt.Errorf("failed to get file info for %s: %w", arg, err)
+	var since time.Time
+	if sinceFlag != "" {
+		if since, err = utils.ParseSince(sinceFlag, time.Now()); err != nil {
+			return fmt.Errorf("invalid --since: %w", err)
 		}
+	}
+
+	maxFileSize, err := utils.ParseSize(maxFileSizeFlag)
+	if err != nil {
+		return fmt.Errorf("invalid --max-file-size: %w", err)
+	}
 
-		var files []filesystem.FileInfo
-		var tree string
-		var excluded *filesystem.ExcludedInfo
+	includePatterns, _ := cmd.Flags().GetStringSlice("include")
+	excludePatterns, _ := cmd.Flags().GetStringSlice("exclude")
 
-		// Initialize the compressor if the flag is set
-		var comp *compressor.GenericCompressor
-		if compressFlag {
-			comp = compressor.NewGenericCompressor()
+	// Patterns from files are added to the inline ones
+	for _, path := range includeFromFlag {
+		patterns, err := filesystem.ReadPatternFile(path)
+		if err != nil {
+			return fmt.Errorf("invalid --include-from: %w", err)
 		}
+		includePatterns = append(includePatterns, patterns...)
+	}
+	for _, path := range excludeFromFlag {
+		patterns, err := filesystem.ReadPatternFile(path)
+		if err != nil {
+			return fmt.Errorf("invalid --exclude-from: %w", err)
+		}
+		excludePatterns = append(excludePatterns, patterns...)
+	}
 
-		if fileInfo.IsDir() {
-			// Existing directory processing logic
-			tree, files, excluded, err = filesystem.WalkDirectory(absPath, includePatterns, excludePatterns, patternExclude, includePriority, lineNumber, relativePaths, excludeFromTree, noCodeblock, noDefaultExcludes, followSymlinks, comp) // Pass compressor
-			if err != nil {
-				return fmt.Errorf("failed to process directory %s: %w", arg, err)
-			}
-			// Use relative path for tree header when relativePaths flag is set
-			treePath := absPath
-			if relativePaths {
-				treePath = filepath.Base(absPath)
-			}
-			tree = fmt.Sprintf("%s:\n%s", treePath, tree)
-		} else {
-			// New file processing logic
-			file, err := filesystem.ProcessSingleFile(absPath, lineNumber, relativePaths, noCodeblock, followSymlinks, comp) // Pass compressor
-			if err != nil {
-				return fmt.Errorf("failed to process file %s: %w", arg, err)
-			}
-			files = []filesystem.FileInfo{file}
-			// Use relative path for file header when relativePaths flag is set
-			filePath := absPath
-			if relativePaths {
-				filePath = filepath.Base(absPath)
+	if cmd.Flags().Changed("compress-level") || compressFallbackFlag {
+		compressFlag = true
+	}
+
+	// Compress globs override whether --compress applies to the files they match, so without
+	// --compress only the files matching a --compress-glob are compressed
+	for _, glob := range compressGlobFlag {
+		if !doublestar.ValidatePattern(glob.Pattern) {
+			name := "--compress-glob"
+			if !glob.Compress {
+				name = "--no-compress-glob"
 			}
-			tree = fmt.Sprintf("File: %s", filePath)
+			return fmt.Errorf("invalid %s pattern %q", name, glob.Pattern)
 		}
+	}
+	compressing := compressFlag || len(compressGlobFlag) > 0
+	if noModTimeFlag {
+		fileMetadataFlag = true
+	}
 
-		allFiles = append(allFiles, files...)
-		allTrees = append(allTrees, tree)
-		if excluded != nil {
-			allExcluded = append(allExcluded, excluded)
+	var collapsePatterns []string
+	for _, pattern := range collapseGlobFlag {
+		if !doublestar.ValidatePattern(pattern) {
+			return fmt.Errorf("invalid --collapse-glob pattern %q", pattern)
 		}
+	}
+	if collapseGenerated || len(collapseGlobFlag) > 0 {
+		collapsePatterns = append(slices.Clone(filesystem.GeneratedPatterns), collapseGlobFlag...)
+	}
 
-		// Handle git operations for each path
-		gitDiffContent := ""
-		gitDiffBranchContent := ""
-		gitLogBranchContent := ""
+	var annotationMarkers []string
+	for _, marker := range annotationMarkerFlag {
+		if strings.TrimSpace(marker) == "" {
+			return fmt.Errorf("invalid --annotation-marker %q, must not be blank", marker)
+		}
+	}
+	if len(annotationMarkerFlag) > 0 {
+		annotationMarkers = annotationMarkerFlag
+	} else if annotationsFlag {
+		annotationMarkers = filesystem.DefaultAnnotationMarkers
+	}
 
-		if diff {
-			gitDiffContent, err = git.GetGitDiff(absPath)
-			if err != nil {
-				// Log the error but continue processing
-				fmt.Printf("Warning: failed to get git diff for %s: %v\n", absPath, err)
-			}
+	var manifest *filesystem.Manifest
+	if manifestFlag != "" {
+		manifest = filesystem.NewManifest()
+	}
+
+	if cmd.Flags().Changed("similarity-threshold") {
+		collapseSimilarFlag = true
+	}
+	if similarityThreshold < 0.5 || similarityThreshold > 1 {
+		return fmt.Errorf("invalid --similarity-threshold %g, must be from 0.5 to 1", similarityThreshold)
+	}
+
+	if webConcurrentJobs <= 0 {
+		return fmt.Errorf("invalid --crawl-concurrency %d, must be positive", webConcurrentJobs)
+	}
+	if cmd.Flags().Changed("web-timeout") && !cmd.Flags().Changed("http-timeout") {
+		webTimeout = time.Duration(webTimeoutSeconds) * time.Second
+	}
+	if webTimeout < 0 {
+		return fmt.Errorf("invalid --http-timeout %s, must not be negative", webTimeout)
+	}
+	if webRetries < 0 {
+		return fmt.Errorf("invalid --http-retries %d, must not be negative", webRetries)
+	}
+	webHeaders, err := web.ParseHeaders(httpHeaderFlag)
+	if err != nil {
+		return fmt.Errorf("invalid --http-header: %w", err)
+	}
+	webCookies, err := web.ParseCookies(cookieFlag)
+	if err != nil {
+		return fmt.Errorf("invalid --cookie: %w", err)
+	}
+
+	var minified *filesystem.MinifiedThresholds
+	if collapseMinified {
+		if minifiedLineLength <= 0 {
+			return fmt.Errorf("invalid --minified-line-length %d, must be positive", minifiedLineLength)
+		}
+		if minifiedNewlineRatio <= 0 || minifiedNewlineRatio > 1 {
+			return fmt.Errorf("invalid --minified-newline-ratio %g, must be above 0 and at most 1", minifiedNewlineRatio)
+		}
+		minified = &filesystem.MinifiedThresholds{LineLength: minifiedLineLength, NewlineRatio: minifiedNewlineRatio}
+	} else if cmd.Flags().Changed("minified-line-length") || cmd.Flags().Changed("minified-newline-ratio") {
+		return fmt.Errorf("--minified-line-length and --minified-newline-ratio cannot be combined with --collapse-minified=false")
+	}
+
+	// Private keys and AWS keys are always redacted, unless --no-redact turns redaction off
+	var redactor *redact.Redactor
+	if noRedactFlag && (redactFlag || len(redactPatterns) > 0) {
+		return fmt.Errorf("--no-redact cannot be combined with --redact or --redact-pattern")
+	}
+	if !noRedactFlag {
+		if redactor, err = redact.New(redactFlag, redactPatterns); err != nil {
+			return err
 		}
+	}
 
-		if gitDiffBranch != "" {
-			branches := strings.Split(gitDiffBranch, ",")
-			if len(branches) == 2 {
-				gitDiffBranchContent, err = git.GetGitDiffBetweenBranches(absPath, branches[0], branches[1])
-				if err != nil {
-					fmt.Printf("Warning: failed to get git diff between branches for %s: %v\n", absPath, err)
-				}
-			}
+	if streaming {
+		if err := checkStreamFlags(cmd, args); err != nil {
+			return err
 		}
+	}
 
-		if gitLogBranch != "" {
-			branches := strings.Split(gitLogBranch, ",")
-			if len(branches) == 2 {
-				gitLogBranchContent, err = git.GetGitLog(absPath, branches[0], branches[1])
-				if err != nil {
-					fmt.Printf("Warning: failed to get git log for %s: %v\n", absPath, err)
-				}
-			}
+	// Progress is shown on stderr while it's a terminal, and stopped before any output
+	progress := utils.NewProgress()
+	defer progress.Finish()
+
+	// If verbose, print active excludes
+	if verbose {
+		activeExcludes, err := filesystem.ReadExcludePatterns(patternExclude, false)
+		if err != nil {
+			return fmt.Errorf("failed to read exclude patterns: %w", err)
 		}
+		printExcludePatterns(activeExcludes)
+	}
 
-		gitData = append(gitData, GitData{
-			Path:          absPath,
-			GitDiff:       gitDiffContent,
-			GitDiffBranch: gitDiffBranchContent,
-			GitLogBranch:  gitLogBranchContent,
-		})
+	// Unchanged files are reused from earlier runs unless --no-cache is set. A cache that can't
+	// be opened only costs speed, so carry on without it.
+	var fileCache *cache.Cache
+	if !noCacheFlag {
+		fileCache, err = cache.Open(cacheDir)
+		if err != nil {
+			utils.PrintColouredMessage("⚠️", fmt.Sprintf("Continuing without the cache: %v", err), color.FgYellow)
+		}
 	}
 
-	// Prepare data for template
-	var excludedInfo any
-	if len(allExcluded) > 0 {
-		excludedInfo = allExcluded[0] // Use the first excluded info if available
+	var execFilter *filesystem.ExecFilter
+	if execFilterFlag != "" {
+		execFilter = filesystem.NewExecFilter(execFilterFlag, execFilterKeepGoing)
 	}
 
-	data := map[string]any{
-		"source_trees": strings.Join(allTrees, "\n\n"),
-		"files":        allFiles,
-		"git_data":     gitData,
-		"excluded":     excludedInfo,
+	walkOpts := filesystem.Options{
+		IncludePatterns:   includePatterns,
+		ExcludePatterns:   excludePatterns,
+		PatternExclude:    patternExclude,
+		IncludePriority:   includePriority,
+		LineNumber:        lineNumber,
+		RelativePaths:     relativePaths,
+		PathBase:          pathBase,
+		ExcludeFromTree:   excludeFromTree,
+		NoCodeblock:       noCodeblock,
+		Fence:             fenceFlag,
+		NoDefaultExcludes: noDefaultExcludes,
+		NoGlobalIgnore:    noGlobalIgnore,
+		NoGitAttributes:   noGitAttributes,
+		Charset:           charsetFlag,
+		Symlinks:          symlinkPolicy,
+		CompressGlobs:     compressGlobFlag,
+		Concurrency:       concurrencyFlag,
+		MaxFileSize:       maxFileSize,
+		Order:             orderFlag,
+		ReadmeFirst:       !noReadmeFirst,
+		TreeStats:         treeStatsFlag,
+		Cache:             fileCache,
+		CollapsePatterns:  collapsePatterns,
+		Minified:          minified,
+		Since:             since,
+		Progress:          progress.Files,
+		CompressFallback:  compressFallbackFlag,
+		FileMetadata:      fileMetadataFlag,
+		NoModTime:         noModTimeFlag,
+		Redactor:          redactor,
+		ExecFilter:        execFilter,
+		AnnotationMarkers: annotationMarkers,
+		ExcludeTests:      excludeTestsFlag,
+		OnlyTests:         onlyTestsFlag,
+		Manifest:          manifest,
 	}
+	lastRun.opts = walkOpts
 
-	if err := spinner.Finish(); err != nil {
-		return fmt.Errorf("failed to finish spinner: %w", err)
+	crawl := web.CrawlOptions{
+		MaxDepth:       webMaxDepth,
+		MaxPages:       webMaxPages,
+		AllowedDomains: webAllowedDomains,
+		Timeout:        webTimeout,
+		Retries:        webRetries,
+		ConcurrentJobs: webConcurrentJobs,
+		IgnoreRobots:   webIgnoreRobots,
+		Delay:          webCrawlDelay,
+		UserAgent:      userAgent(),
+		NoReadability:  webNoReadability,
+		Headers:        webHeaders,
+		Cookies:        webCookies,
+	}
+
+	// The default --compress-level is only used once something is compressed
+	compressionLevel := ""
+	if compressFlag {
+		compressionLevel = compressLevelFlag
+	}
+	similarity := 0.0
+	if collapseSimilarFlag {
+		similarity = similarityThreshold
+	}
+	// With --llm the prompt is sent before the output instead
+	promptSuffix := ""
+	if !useLLM {
+		promptSuffix = promptFlag
+	}
+	// The total is only counted when it's shown, as with the Anthropic API each count is a request
+	countTotal := countOnlyFlag || !useLLM && (tokens || jsonOutput || tokenReportFlag > 0)
+
+	opts := ingest.Options{
+		Options:          walkOpts,
+		Paths:            args,
+		Compress:         compressFlag,
+		CompressionLevel: compressionLevel,
+		Template:         templatePath,
+		Format:           outputFormat,
+		GroupByLanguage:  groupByLanguageFlag,
+		Dedupe:           dedupeFlag,
+		CollapseSimilar:  similarity,
+		NoTree:           noTreeFlag,
+		TreeOnly:         treeOnlyFlag,
+		DryRun:           dryRunFlag,
+		SummaryOnly:      summaryOnlyFlag,
+		PromptSuffix:     promptSuffix,
+		MaxTokens:        maxTokensFlag,
+		BudgetName:       budgetName,
+		Priority:         priority,
+		Split:            splitOutputFlag != "",
+		SplitLines:       splitLargeFlag == "lines",
+		NoFileTokens:     tokenReportFlag == 0,
+		NoTotal:          !countTotal,
+		Tokenizer:        tokenizer(),
+		FollowImports:    followImportsFlag,
+		DiffMode:         diffMode,
+		DiffRange:        diffRange,
+		GitDiffBranch:    gitDiffBranch,
+		GitLogBranch:     gitLogBranch,
+		Branch:           branchFlag,
+		CloneDepth:       depthFlag,
+		Web:              crawl,
+		WebOnly:          webCrawl,
+		CrawlPaths:       cmd.Flags().Changed("crawl-depth") || cmd.Flags().Changed("web-depth"),
+		Phase:            progress.Phase,
+	}
+	if stdinFlag {
+		opts.Stdin = os.Stdin
 	}
 
-	// Render template
-	rendered, err := template.RenderTemplate(tmpl, data)
+	if streaming {
+		return runStream(opts, progress)
+	}
+
+	result, err := ingest.Run(opts)
 	if err != nil {
-		return fmt.Errorf("failed to render template: %w", err)
+		return err
+	}
+	if err := writeManifest(manifest); err != nil {
+		return err
+	}
+
+	if dryRunFlag {
+		progress.Finish()
+		printDryRun(result.Files)
+		reportSkipped(result.Excluded)
+		utils.PrintMessages()
+		return nil
+	}
+
+	if result.Deduped > 0 {
+		utils.AddMessage("ℹ️", fmt.Sprintf("Replaced %s identical files with a note naming their first copy (--dedupe).", utils.FormatNumber(result.Deduped)), color.FgCyan, 30)
+	}
+	if result.Collapsed > 0 {
+		utils.AddMessage("ℹ️", fmt.Sprintf("Replaced %s near-identical files with a note naming the file they match (--collapse-similar).", utils.FormatNumber(result.Collapsed)), color.FgCyan, 30)
+	}
+	if len(annotationMarkers) > 0 {
+		annotations := filesystem.Annotations(result.Files)
+		utils.AddMessage("📌", fmt.Sprintf("Found %s annotations marked %s.", utils.FormatNumber(len(annotations)), strings.Join(annotationMarkers, ", ")), color.FgCyan, 30)
+	}
+	reportSkipped(result.Excluded)
+	reportRedactions(result.Files)
+	if compressing {
+		reportCompression(result.Files)
+	}
+
+	if splitOutputFlag != "" {
+		return writeSplitOutput(result, progress)
+	}
+	reportDropped(result.Dropped, len(result.Files)+len(result.Dropped))
+
+	// The count is of the output a real run would write, once it fits within --max-tokens
+	if countOnlyFlag {
+		progress.Finish()
+		fmt.Println(result.TotalTokens)
+		utils.PrintMessages()
+		return nil
+	}
+
+	if hashOnlyFlag {
+		fmt.Println(result.ContentHash)
+		return nil
 	}
+	progress.Finish()
+	rendered := result.Rendered
 
 	// Check if save is set in config or flag
 	autoSave, _ := cmd.Flags().GetBool("save")
@@ -429,11 +937,11 @@ func run(cmd *cobra.Command, args []string) error {
 			utils.PrintColouredMessage("❌", fmt.Sprintf("VRAM estimation error: %v", err), color.FgRed)
 		}
 	}
-	useLLM, _ := cmd.Flags().GetBool("llm")
+	totalTokens := result.TotalTokens
 
 	// Handle output
 	if useLLM {
-		if err := handleLLMOutput(rendered, cfg.LLM, tokens, encoding); err != nil {
+		if err := handleLLMOutput(rendered, llmConfigFromFlags(cfg.LLM), tokens, encoding); err != nil {
 			utils.PrintColouredMessage("❌", fmt.Sprintf("LLM output error: %v", err), color.FgRed)
 		}
 	} else {
@@ -443,9 +951,28 @@ func run(cmd *cobra.Command, args []string) error {
 		if (cfg.AutoSave || autoSave) && output != "" {
 			outputForHandleOutput = ""
 		}
-		if err := handleOutput(rendered, tokens, encoding, noClipboard, outputForHandleOutput, jsonOutput, report || verbose, allFiles); err != nil {
+		// The token report supersedes the default largest files report
+		showReport := (report || verbose) && tokenReportFlag == 0
+		// Preview is only for people, so piped output stays raw
+		var preview string
+		if previewFlag && !jsonOutput && utils.IsTerminal(os.Stdout) {
+			preview = rendered
+			if outputFormat != format.Markdown {
+				fence := filesystem.Fence(rendered, fenceFlag)
+				preview = fmt.Sprintf("%s%s\n%s\n%s\n", fence, outputFormat, rendered, fence)
+			}
+		}
+		lastRun.files, lastRun.tokens = len(result.Files), totalTokens
+		if err := handleOutput(rendered, totalTokens, tokens, encoding, noClipboard, outputForHandleOutput, jsonOutput, showReport, result.Files, preview); err != nil {
 			return fmt.Errorf("failed to handle output: %w", err)
 		}
+
+		if tokenReportFlag > 0 {
+			printTokenReport(result.Files, tokenReportFlag, totalTokens)
+		}
+		if len(compareEncodings) > 0 {
+			printTokenizerComparison(rendered, compareEncodings)
+		}
 	}
 
 	// Print all collected messages at the end
@@ -454,7 +981,521 @@ func run(cmd *cobra.Command, args []string) error {
 	return nil
 }
 
+// configOnlyFlags are the flags a project config file can't set, as they choose the config file
+// or don't ingest anything.
+var configOnlyFlags = map[string]bool{"config": true, "print-config": true, "edit-config": true, "help": true, "version": true}
+
+// safeConfigFlags are the flags a discovered .ingest.toml can set. They only choose what's
+// ingested and how it's rendered and counted, whereas the other flags can run commands, write
+// files, reach the network, follow symlinks or turn off redaction, and the project being
+// ingested may not be trusted. A file given with --config can set any flag.
+var safeConfigFlags = map[string]bool{
+	// Choosing files
+	"exclude": true, "include": true, "exclude-from": true, "include-from": true,
+	"pattern-exclude": true, "exclude-tests": true, "only-tests": true, "no-default-excludes": true,
+	"no-gitattributes": true, "no-global-ignore": true, "since": true, "max-file-size": true,
+	"follow-imports": true, "diff": true, "diff-range": true, "git-diff-branch": true,
+	"git-log-branch": true, "branch": true, "depth": true,
+
+	// Rendering
+	"format": true, "json": true, "json-lines": true, "no-codeblock": true, "fence": true,
+	"order": true, "no-readme-first": true, "group-by-language": true, "include-priority": true,
+	"exclude-from-tree": true, "no-tree": true, "tree-only": true, "tree-stats": true,
+	"hash-only": true, "summary-only": true, "relative-paths": true, "relative-to": true,
+	"absolute-paths": true, "line-numbers": true, "line-number": true, "file-metadata": true,
+	"no-mtime": true, "encoding": true, "redact": true, "redact-pattern": true,
+	"collapse-generated": true, "collapse-glob": true, "collapse-minified": true,
+	"minified-line-length": true, "minified-newline-ratio": true, "dedupe": true,
+	"collapse-similar": true, "similarity-threshold": true, "annotations": true,
+	"annotation-marker": true, "compress": true, "compress-level": true, "compress-fallback": true,
+	"compress-glob": true, "no-compress-glob": true, "compress-output": true, "stream": true,
+	"prompt": true,
+
+	// Counting and reporting
+	"tokens": true, "tokenizer": true, "model": true, "no-correction": true,
+	"compare-tokenizers": true, "price-per-1k": true, "max-tokens": true, "model-context": true,
+	"reserve-tokens": true, "split-large": true, "priority": true, "token-report": true,
+	"report": true, "count-only": true, "dry-run": true,
+
+	// Running
+	"concurrency": true, "no-cache": true, "no-clipboard": true, "preview": true, "theme": true,
+	"verbose": true, "quiet": true,
+}
+
+// secretFlags are the flags other than API keys whose values --print-config hides, as they can
+// hold credentials.
+var secretFlags = map[string]bool{"http-header": true, "cookie": true}
+
+// applyProjectConfig sets the flags that weren't given on the command line from the project
+// config file: the --config file, or the nearest .ingest.toml in or above the first local path
+// being ingested. Flags on the command line always override the file. Keys are flag names,
+// with - or _ between words, and keys that aren't flags are warned about and ignored, as are
+// flags other than safeConfigFlags in a file that wasn't given with --config.
+func applyProjectConfig(cmd *cobra.Command, args []string) error {
+	if editConfigFlag {
+		return nil
+	}
+	path := configFlag
+	if path == "" {
+		var err error
+		if path, err = config.FindProjectConfig(configSearchDir(args)); err != nil || path == "" {
+			return err
+		}
+	}
+
+	settings, err := config.LoadProjectConfig(path)
+	if err != nil {
+		return err
+	}
+	projectConfigPath = path
+	configSetFlags = make(map[string]bool)
+
+	for _, setting := range settings {
+		name := strings.ReplaceAll(setting.Key, "_", "-")
+		flag := cmd.Flags().Lookup(name)
+		if flag == nil || configOnlyFlags[name] {
+			utils.PrintColouredMessage("⚠️", fmt.Sprintf("Ignoring unknown key %q on line %d of %s", setting.Key, setting.Line, path), color.FgYellow)
+			continue
+		}
+		if configFlag == "" && !safeConfigFlags[name] {
+			utils.PrintColouredMessage("⚠️", fmt.Sprintf("Ignoring %q on line %d of %s, it can only be set in a config file given with --config", setting.Key, setting.Line, path), color.FgYellow)
+			continue
+		}
+		if flag.Changed {
+			continue
+		}
+
+		// Array elements are taken as they are, rather than split at commas like a flag's value
+		slice, isSlice := flag.Value.(pflag.SliceValue)
+		switch {
+		case setting.Array && !isSlice:
+			return fmt.Errorf("%s line %d: %s takes a single value, not an array", path, setting.Line, setting.Key)
+		case setting.Array:
+			if err := slice.Replace(setting.Values); err != nil {
+				return fmt.Errorf("%s line %d: invalid %s: %w", path, setting.Line, setting.Key, err)
+			}
+			flag.Changed = true
+		default:
+			if err := cmd.Flags().Set(name, setting.Values[0]); err != nil {
+				return fmt.Errorf("%s line %d: invalid %s: %w", path, setting.Line, setting.Key, err)
+			}
+		}
+		configSetFlags[name] = true
+	}
+	return nil
+}
+
+// configSearchDir returns the directory the project config file is looked for from: that of the
+// first local path given, or the current directory.
+func configSearchDir(args []string) string {
+	for _, arg := range args {
+		if ingest.IsURL(arg) || git.IsRemoteURL(arg) {
+			continue
+		}
+		path, _ := ingest.SplitLineRangeArg(arg)
+		if info, err := os.Stat(path); err == nil && !info.IsDir() {
+			return filepath.Dir(path)
+		}
+		return path
+	}
+	return "."
+}
+
+// printConfig prints every flag with its value once the project config file is applied, as
+// TOML that can be copied into a config file. Flags that aren't at their default say where they
+// were set. API keys, headers and cookies are hidden.
+func printConfig(cmd *cobra.Command) {
+	if projectConfigPath != "" {
+		fmt.Printf("# Flags from the command line, then %s, then the defaults\n", projectConfigPath)
+	} else {
+		fmt.Printf("# Flags from the command line, then the defaults, as no %s was found\n", config.ProjectConfigName)
+	}
+
+	cmd.Flags().VisitAll(func(flag *pflag.Flag) {
+		if configOnlyFlags[flag.Name] {
+			return
+		}
+		value := tomlValue(flag)
+		if (strings.HasSuffix(flag.Name, "api-key") || secretFlags[flag.Name]) && flag.Value.String() != flag.DefValue {
+			value = `"(hidden)"`
+		}
+
+		switch {
+		case configSetFlags[flag.Name]:
+			fmt.Printf("%s = %s # %s\n", flag.Name, value, projectConfigPath)
+		case flag.Changed:
+			fmt.Printf("%s = %s # command line\n", flag.Name, value)
+		default:
+			fmt.Printf("%s = %s\n", flag.Name, value)
+		}
+	})
+}
+
+// tomlValue returns the value of flag as a TOML value.
+func tomlValue(flag *pflag.Flag) string {
+	if slice, ok := flag.Value.(pflag.SliceValue); ok {
+		items := make([]string, len(slice.GetSlice()))
+		for i, item := range slice.GetSlice() {
+			items[i] = strconv.Quote(item)
+		}
+		return "[" + strings.Join(items, ", ") + "]"
+	}
+	switch flag.Value.Type() {
+	case "bool", "int", "int64", "uint", "float32", "float64", "count":
+		return flag.Value.String()
+	}
+	return strconv.Quote(flag.Value.String())
+}
+
+// compressGlobsValue is the value of --compress-glob and --no-compress-glob, which share a
+// list so that the last matching glob wins whichever of the flags it was given with.
+type compressGlobsValue struct {
+	globs    *[]filesystem.CompressGlob
+	compress bool
+}
+
+func (v *compressGlobsValue) Set(pattern string) error {
+	*v.globs = append(*v.globs, filesystem.CompressGlob{Pattern: pattern, Compress: v.compress})
+	return nil
+}
+
+func (v *compressGlobsValue) Type() string {
+	return "stringArray"
+}
+
+func (v *compressGlobsValue) String() string {
+	return "[" + strings.Join(v.GetSlice(), ",") + "]"
+}
+
+func (v *compressGlobsValue) Append(pattern string) error {
+	return v.Set(pattern)
+}
+
+// Replace replaces this flag's globs, as a project config file does. They go before the other
+// flag's globs, which came from the command line, so those still win.
+func (v *compressGlobsValue) Replace(patterns []string) error {
+	globs := make([]filesystem.CompressGlob, 0, len(patterns)+len(*v.globs))
+	for _, pattern := range patterns {
+		globs = append(globs, filesystem.CompressGlob{Pattern: pattern, Compress: v.compress})
+	}
+	for _, glob := range *v.globs {
+		if glob.Compress != v.compress {
+			globs = append(globs, glob)
+		}
+	}
+	*v.globs = globs
+	return nil
+}
+
+func (v *compressGlobsValue) GetSlice() []string {
+	var patterns []string
+	for _, glob := range *v.globs {
+		if glob.Compress == v.compress {
+			patterns = append(patterns, glob.Pattern)
+		}
+	}
+	return patterns
+}
+
+// warnUnusedPrompt warns when --prompt is given with flags whose output it isn't appended to.
+func warnUnusedPrompt(useLLM bool, outputFormat format.Format, streaming bool) {
+	if promptFlag == "" || useLLM {
+		return
+	}
+	unused := []struct {
+		flag string
+		set  bool
+	}{
+		{"--format " + string(outputFormat), outputFormat != format.Markdown && outputFormat != format.JSONLines},
+		{"--stream", streaming},
+		{"--dry-run", dryRunFlag},
+		{"--hash-only", hashOnlyFlag},
+		{"--summary-only", summaryOnlyFlag},
+	}
+	for _, f := range unused {
+		if f.set {
+			utils.PrintColouredMessage("⚠️", fmt.Sprintf("--prompt has no effect with %s, as it's only appended to markdown output or sent with --llm", f.flag), color.FgYellow)
+			return
+		}
+	}
+}
+
+// checkWatchFlags rejects the flags and arguments --watch can't be used with, as they don't
+// read local files that can be watched or don't regenerate the usual output.
+func checkWatchFlags(cmd *cobra.Command, args []string) error {
+	useLLM, _ := cmd.Flags().GetBool("llm")
+	incompatible := []struct {
+		flag string
+		set  bool
+	}{
+		{"--stdin", stdinFlag},
+		{"--stream", streamFlag},
+		{"--json-lines", jsonLinesFlag},
+		{"--dry-run", dryRunFlag},
+		{"--count-only", countOnlyFlag},
+		{"--hash-only", hashOnlyFlag},
+		{"--follow-imports", followImportsFlag},
+		{"--preview", previewFlag},
+		{"--llm", useLLM},
+		{"--vram", vramFlag},
+	}
+	for _, f := range incompatible {
+		if f.set {
+			return fmt.Errorf("--watch cannot be combined with %s", f.flag)
+		}
+	}
+	for _, arg := range args {
+		if webCrawl || ingest.IsURL(arg) || git.IsRemoteURL(arg) {
+			return fmt.Errorf("--watch only watches local files, but %s is a URL", arg)
+		}
+	}
+	return nil
+}
+
+// watchInputs regenerates the output whenever a file the arguments would ingest changes, until
+// interrupted. Rebuilds only print a one line summary, along with any errors.
+func watchInputs(cmd *cobra.Command, args []string) error {
+	if len(args) == 0 {
+		args = []string{"."}
+	}
+	roots := make([]string, len(args))
+	for i, arg := range args {
+		inputPath, _ := ingest.SplitLineRangeArg(arg)
+		absPath, err := filepath.Abs(inputPath)
+		if err != nil {
+			return fmt.Errorf("failed to get absolute path for %s: %w", arg, err)
+		}
+		roots[i] = absPath
+	}
+
+	// Writing the output mustn't trigger another rebuild
+	var ignored []string
+	if output != "" {
+		if absOutput, err := filepath.Abs(output); err == nil {
+			ignored = append(ignored, absOutput)
+		}
+	}
+
+	watcher, err := filesystem.NewWatcher(roots, ignored, lastRun.opts)
+	if err != nil {
+		return err
+	}
+	defer watcher.Close()
+
+	utils.PrintColouredMessage("👀", fmt.Sprintf("Watching %s for changes, press Ctrl+C to stop", strings.Join(args, ", ")), color.FgCyan)
+	for {
+		if err := watcher.Wait(watchDebounce); err != nil {
+			return err
+		}
+
+		utils.Quiet = true
+		err := runOnce(cmd, args)
+		utils.Quiet = quietFlag
+		if err != nil {
+			utils.PrintColouredMessage("❌", fmt.Sprintf("Failed to regenerate the output: %v", err), color.FgRed)
+			continue
+		}
+
+		summary := fmt.Sprintf("Regenerated %s files", utils.FormatNumber(lastRun.files))
+		if lastRun.tokens > 0 {
+			summary += fmt.Sprintf(", %s tokens", utils.FormatNumber(lastRun.tokens))
+		}
+		utils.PrintColouredMessage("🔄", summary, color.FgCyan)
+	}
+}
+
+// checkStreamFlags rejects the flags that --stream can't be combined with, as they need every
+// file before any output is written, don't read local files or count with the Anthropic API.
+func checkStreamFlags(cmd *cobra.Command, args []string) error {
+	useLLM, _ := cmd.Flags().GetBool("llm")
+	autoSave, _ := cmd.Flags().GetBool("save")
+	incompatible := []struct {
+		flag string
+		set  bool
+	}{
+		{"--stdin", stdinFlag},
+		{"--dry-run", dryRunFlag},
+		{"--count-only", countOnlyFlag},
+		{"--hash-only", hashOnlyFlag},
+		{"--summary-only", summaryOnlyFlag},
+		{"--tree-only", treeOnlyFlag},
+		{"--model-context", modelContextFlag != ""},
+		{"--max-tokens", maxTokensFlag > 0},
+		{"--tree-stats", treeStatsFlag},
+		{"--token-report", tokenReportFlag > 0},
+		{"--compare-tokenizers", len(compareTokenizers) > 0},
+		{"--group-by-language", groupByLanguageFlag},
+		{"--dedupe", dedupeFlag},
+		{"--collapse-similar", collapseSimilarFlag},
+		{"--annotations", annotationsFlag || len(annotationMarkerFlag) > 0},
+		{"--manifest", manifestFlag != ""},
+		{"--follow-imports", followImportsFlag},
+		{"--preview", previewFlag},
+		{"--json", jsonOutput},
+		{"--llm", useLLM},
+		{"--vram", vramFlag},
+		{"--save", autoSave},
+		{"--anthropic", anthropicFlag},
+		{"--diff-range", diffRange != ""},
+		{"--git-diff-branch", gitDiffBranch != ""},
+		{"--git-log-branch", gitLogBranch != ""},
+	}
+	name := "--stream"
+	if jsonLinesFlag {
+		name = "--json-lines"
+	}
+	for _, f := range incompatible {
+		if f.set {
+			return fmt.Errorf("%s cannot be combined with %s", name, f.flag)
+		}
+	}
+	for _, arg := range args {
+		if webCrawl || (ingest.IsURL(arg) && !git.IsRemoteURL(arg)) {
+			return fmt.Errorf("%s only reads local files, but %s is a URL", name, arg)
+		}
+		if archive.IsArchive(arg) {
+			return fmt.Errorf("%s only reads local files and directories, but %s is an archive", name, arg)
+		}
+	}
+	return nil
+}
+
+// runStream writes the output for --stream to --output or stdout as the files are read.
+func runStream(opts ingest.Options, progress *utils.Progress) error {
+	out := io.Writer(os.Stdout)
+	if output != "" {
+		file, err := os.Create(output)
+		if err != nil {
+			return fmt.Errorf("failed to create %s: %w", output, err)
+		}
+		defer file.Close()
+		out = file
+	}
+	compressed, err := utils.CompressWriter(out, compressOutputFlag, output)
+	if err != nil {
+		return err
+	}
+	result, err := ingest.Stream(opts, compressed)
+	if err != nil {
+		return err
+	}
+	if err := compressed.Close(); err != nil {
+		return err
+	}
+	progress.Finish()
+
+	reportSkipped(result.Excluded)
+	reportRedactions(result.Files)
+	// JSON lines have no total of their own, so it's always printed, summed from the files
+	if tokens || opts.Format == format.JSONLines {
+		utils.AddMessage("ℹ️", fmt.Sprintf("Tokens (Approximate): %v (%s)", utils.FormatNumber(result.TotalTokens), tokenizer().Label()), color.FgYellow, 1)
+	}
+	if output != "" {
+		utils.AddMessage("✅", fmt.Sprintf("Written to file: %s", output), color.FgGreen, 20)
+	}
+	utils.PrintMessages()
+	return nil
+}
+
+// resolveTokenizerBackend applies --tokenizer-backend. The Hugging Face tokeniser is the
+// tokenizer.json path or model ID in --tokenizer, or the model ID in --model if --tokenizer
+// isn't given, and is loaded now so a bad one is reported before any files are read.
+func resolveTokenizerBackend(cmd *cobra.Command) error {
+	if !slices.Contains(token.Backends, tokenizerBackendFlag) {
+		return fmt.Errorf("invalid --tokenizer-backend %q (valid: %s)", tokenizerBackendFlag, strings.Join(token.Backends, ", "))
+	}
+	if anthropicFlag && tokenizerBackendFlag != "anthropic" && cmd.Flags().Changed("tokenizer-backend") {
+		return fmt.Errorf("--anthropic can't be used with --tokenizer-backend=%s", tokenizerBackendFlag)
+	}
+
+	switch tokenizerBackendFlag {
+	case "anthropic":
+		anthropicFlag = true
+	case "huggingface":
+		// A --watch rebuild resolves the backend again, when the encoding is already resolved
+		source, ok := token.HuggingFaceSource(encoding)
+		if !ok {
+			source = encoding
+		}
+		if !cmd.Flags().Changed("tokenizer") {
+			if modelIDFlag == "" {
+				return errors.New("--tokenizer-backend=huggingface needs a tokenizer.json path or model ID in --tokenizer or --model")
+			}
+			source = modelIDFlag
+		}
+		if err := token.LoadHuggingFace(source); err != nil {
+			return fmt.Errorf("failed to load the Hugging Face tokeniser: %w", err)
+		}
+		encoding = token.HuggingFaceEncoding(source)
+	}
+	return nil
+}
+
+// resolveTokenizer selects the token encoding from --model when it names a known model.
+// An explicit --tokenizer always wins. With --vram, --model is a model ID for the estimate
+// rather than a tokeniser selection, so it is left alone, as it is when --model names the
+// Hugging Face tokeniser.
+func resolveTokenizer(cmd *cobra.Command) error {
+	if modelIDFlag == "" || vramFlag || tokenizerBackendFlag == "huggingface" {
+		return nil
+	}
+
+	if cmd.Flags().Changed("tokenizer") || (cmd.Flags().Changed("encoding") && charsetFlag == "") {
+		utils.PrintColouredMessage("⚠️", fmt.Sprintf("Both --model and --tokenizer were provided, using the %s tokeniser", encoding), color.FgYellow)
+		return nil
+	}
+
+	modelEncoding, err := token.EncodingForModel(modelIDFlag)
+	if err != nil {
+		return err
+	}
+	encoding = modelEncoding
+	return nil
+}
+
+// resolveModelContext sets --max-tokens from --model-context, the model's context window less
+// --reserve-tokens for the response. An explicit --max-tokens always wins, and is needed for
+// models that aren't known.
+func resolveModelContext(cmd *cobra.Command) error {
+	if modelContextFlag == "" {
+		if cmd.Flags().Changed("reserve-tokens") {
+			return fmt.Errorf("--reserve-tokens requires --model-context")
+		}
+		return nil
+	}
+
+	if cmd.Flags().Changed("max-tokens") {
+		utils.PrintColouredMessage("⚠️", fmt.Sprintf("Both --model-context and --max-tokens were provided, using --max-tokens %s", utils.FormatNumber(maxTokensFlag)), color.FgYellow)
+		return nil
+	}
+
+	contextSize, err := token.ContextForModel(modelContextFlag)
+	if err != nil {
+		return fmt.Errorf("invalid --model-context: %w, set --max-tokens for other models", err)
+	}
+	if reserveTokensFlag < 0 || reserveTokensFlag >= contextSize {
+		return fmt.Errorf("invalid --reserve-tokens %d, must be at least 0 and less than the %s tokens of %s", reserveTokensFlag, utils.FormatNumber(contextSize), modelContextFlag)
+	}
+	maxTokensFlag = contextSize - reserveTokensFlag
+	budgetName = fmt.Sprintf("the %s context window (%s tokens, with %s reserved)", modelContextFlag, utils.FormatNumber(maxTokensFlag), utils.FormatNumber(reserveTokensFlag))
+	return nil
+}
+
+// tokenBudget returns the description of the token budget for messages.
+func tokenBudget() string {
+	if budgetName != "" {
+		return budgetName
+	}
+	return "--max-tokens " + utils.FormatNumber(maxTokensFlag)
+}
+
+// reportLargestFiles prints the 15 largest files and their token counts on stderr, unless
+// --quiet is set.
 func reportLargestFiles(files []filesystem.FileInfo) {
+	if utils.Quiet {
+		return
+	}
+
 	sort.Slice(files, func(i, j int) bool {
 		return len(files[i].Code) > len(files[j].Code)
 	})
@@ -493,17 +1534,294 @@ func reportLargestFiles(files []filesystem.FileInfo) {
 	// Print the files with their token counts
 	for i := range displayCount {
 		colour := colourRange[i]
-		fmt.Printf("- %d. %s (%s tokens)\n", i+1, files[i].Path, colour.Sprint(utils.FormatNumber(tokenCounts[i])))
+		fmt.Fprintf(os.Stderr, "- %d. %s (%s tokens)\n", i+1, files[i].Path, colour.Sprint(utils.FormatNumber(tokenCounts[i])))
+	}
+
+	fmt.Fprintln(os.Stderr)
+}
+
+// printDryRun lists the files a run would ingest, and how many there are, on stderr.
+func printDryRun(files []filesystem.FileInfo) {
+	for _, file := range files {
+		fmt.Fprintln(os.Stderr, file.Path)
+	}
+	fmt.Fprintf(os.Stderr, "\n%s files would be ingested\n", utils.FormatNumber(len(files)))
+}
+
+// reportRedactions adds a summary of the secrets that were redacted, if any.
+func reportRedactions(files []filesystem.FileInfo) {
+	redactions, redactedFiles := 0, 0
+	for _, file := range files {
+		if file.Redactions > 0 {
+			redactions += file.Redactions
+			redactedFiles++
+		}
+	}
+	if redactions > 0 {
+		utils.AddMessage("🔒", fmt.Sprintf("Redacted %s secrets in %s files.", utils.FormatNumber(redactions), utils.FormatNumber(redactedFiles)), color.FgYellow, 30)
+	}
+}
+
+// reportCompression adds a summary of the tokens compression saved, from the files whose
+// uncompressed token count is known.
+func reportCompression(files []filesystem.FileInfo) {
+	raw, saved := 0, 0
+	for _, file := range files {
+		if file.RawTokens > 0 {
+			raw += file.RawTokens
+			saved += file.RawTokens - file.TokenCount
+		}
+	}
+	if raw > 0 {
+		utils.AddMessage("ℹ️", fmt.Sprintf("Compression saved %s tokens (%.1f%%).", utils.FormatNumber(saved), float64(saved)/float64(raw)*100), color.FgCyan, 25)
+	}
+}
+
+// reportSkipped adds a summary of the files that matched but were not ingested, such as
+// binary files or those exceeding --max-file-size, and of symlinks followed outside the root.
+func reportSkipped(excluded []*filesystem.ExcludedInfo) {
+	var tooLarge, skipped, external []string
+	notModified := 0
+	for _, info := range excluded {
+		notModified += info.NotModified
+		for _, file := range info.TooLarge {
+			tooLarge = append(tooLarge, fmt.Sprintf("  - %s (%s)", file.Path, utils.FormatSize(file.Size)))
+		}
+		for _, file := range info.Skipped {
+			skipped = append(skipped, fmt.Sprintf("  - %s (%s)", file.Path, file.Reason))
+		}
+		for _, link := range info.External {
+			external = append(external, "  - "+link)
+		}
+	}
+
+	if len(tooLarge) > 0 {
+		message := fmt.Sprintf("Skipped (too large, over --max-file-size %s):\n%s", maxFileSizeFlag, strings.Join(tooLarge, "\n"))
+		utils.AddMessage("⚠️", message, color.FgYellow, 30)
+	}
+	if len(skipped) > 0 {
+		utils.AddMessage("⚠️", fmt.Sprintf("Skipped:\n%s", strings.Join(skipped, "\n")), color.FgYellow, 30)
+	}
+	if notModified > 0 {
+		utils.AddMessage("ℹ️", fmt.Sprintf("Left out %s files not modified since %s (--since).", utils.FormatNumber(notModified), sinceFlag), color.FgCyan, 30)
+	}
+	if len(external) > 0 {
+		utils.AddMessage("⚠️", fmt.Sprintf("Followed symlinks outside the ingested directory:\n%s", strings.Join(external, "\n")), color.FgYellow, 30)
+	}
+}
+
+// checkSplitFlags checks --split-output has the --max-tokens it splits by, and rejects the
+// flags that choose another destination for the output or don't write it.
+func checkSplitFlags(cmd *cobra.Command) error {
+	if maxTokensFlag <= 0 {
+		return fmt.Errorf("--split-output needs --max-tokens for the size of each file")
+	}
+	if !slices.Contains(budget.SplitModes, splitLargeFlag) {
+		return fmt.Errorf("invalid --split-large %q (valid: %s)", splitLargeFlag, strings.Join(budget.SplitModes, ", "))
+	}
+	if _, err := splitOutputPath(splitOutputFlag, 1); err != nil {
+		return err
+	}
+
+	useLLM, _ := cmd.Flags().GetBool("llm")
+	autoSave, _ := cmd.Flags().GetBool("save")
+	incompatible := []struct {
+		flag string
+		set  bool
+	}{
+		{"--output", output != ""},
+		{"--summary-only", summaryOnlyFlag},
+		{"--tree-only", treeOnlyFlag},
+		{"--count-only", countOnlyFlag},
+		{"--hash-only", hashOnlyFlag},
+		{"--compare-tokenizers", len(compareTokenizers) > 0},
+		{"--preview", previewFlag},
+		{"--json", jsonOutput},
+		{"--llm", useLLM},
+		{"--vram", vramFlag},
+		{"--save", autoSave},
+	}
+	for _, f := range incompatible {
+		if f.set {
+			return fmt.Errorf("--split-output cannot be combined with %s", f.flag)
+		}
+	}
+	return nil
+}
+
+// splitOutputPath returns the name of the nth file of --split-output. A pattern with a verb
+// such as out-%02d.md is formatted with n, and otherwise -001 and so on is added before the
+// extension.
+func splitOutputPath(pattern string, n int) (string, error) {
+	if !strings.Contains(pattern, "%") {
+		ext := filepath.Ext(pattern)
+		return fmt.Sprintf("%s-%03d%s", strings.TrimSuffix(pattern, ext), n, ext), nil
+	}
+	path := fmt.Sprintf(pattern, n)
+	if strings.Contains(path, "%!") || path == fmt.Sprintf(pattern, n+1) {
+		return "", fmt.Errorf("invalid --split-output %q, it needs exactly one number verb such as %%03d", pattern)
+	}
+	return path, nil
+}
+
+// writeSplitOutput writes each of the parts ingest.Run split the files into for --split-output
+// to a file of its own.
+func writeSplitOutput(result ingest.Result, progress *utils.Progress) error {
+	var paths, oversized []string
+	for i, part := range result.Parts {
+		path, err := splitOutputPath(splitOutputFlag, i+1)
+		if err != nil {
+			return err
+		}
+		if err := utils.WriteToFile(path, part.Rendered, compressOutputFlag); err != nil {
+			return fmt.Errorf("failed to write %s: %w", path, err)
+		}
+		if part.Tokens > maxTokensFlag {
+			oversized = append(oversized, fmt.Sprintf("  - %s (%s tokens, %s)", path, utils.FormatNumber(part.Tokens), part.Files[0].Path))
+		}
+		paths = append(paths, path)
+	}
+	progress.Finish()
+
+	if tokens {
+		utils.AddMessage("ℹ️", fmt.Sprintf("Tokens (Approximate): %v (%s)", utils.FormatNumber(result.TotalTokens), tokenizer().Label()), color.FgYellow, 1)
+	}
+	written := paths[0]
+	if len(paths) > 1 {
+		written = fmt.Sprintf("%s to %s", paths[0], paths[len(paths)-1])
+	}
+	utils.AddMessage("✅", fmt.Sprintf("Written %d files of up to %s tokens each: %s", len(paths), utils.FormatNumber(maxTokensFlag), written), color.FgGreen, 20)
+	if len(oversized) > 0 {
+		reason := "a file too large for the budget, which --split-large=lines would split"
+		if splitLargeFlag == "lines" {
+			reason = "a line too long to split at"
+		}
+		message := fmt.Sprintf("%d files are over --max-tokens, as each holds %s:\n%s", len(oversized), reason, strings.Join(oversized, "\n"))
+		utils.AddMessage("⚠️", message, color.FgYellow, 30)
+	}
+	if tokenReportFlag > 0 {
+		printTokenReport(result.Files, tokenReportFlag, result.TotalTokens)
+	}
+	utils.PrintMessages()
+	return nil
+}
+
+// writeManifest writes the --manifest, if there is one, once ingest.Run has recorded the files.
+func writeManifest(manifest *filesystem.Manifest) error {
+	if manifest == nil {
+		return nil
+	}
+	return manifest.WriteFile(manifestFlag)
+}
+
+// reportDropped adds a summary of the files left out to fit within --max-tokens.
+func reportDropped(dropped []filesystem.FileInfo, total int) {
+	if len(dropped) == 0 {
+		return
+	}
+
+	lines := make([]string, len(dropped))
+	for i, file := range dropped {
+		lines[i] = fmt.Sprintf("  - %s (%s tokens)", file.Path, utils.FormatNumber(file.TokenCount))
+	}
+	message := fmt.Sprintf("Dropped %d of %d files to fit within %s:\n%s", len(dropped), total, tokenBudget(), strings.Join(lines, "\n"))
+	utils.AddMessage("⚠️", message, color.FgYellow, 30)
+}
+
+// tokenizer returns the tokeniser selected by the flags.
+func tokenizer() ingest.Tokenizer {
+	return ingest.Tokenizer{Encoding: encoding, Anthropic: anthropicFlag, NoCorrection: noCorrectionFlag}
+}
+
+// printTokenReport prints the top n files by token count on stderr along with their share of the
+// total token count of the generated prompt, and when files were compressed the tokens
+// compression saved on each of them.
+func printTokenReport(files []filesystem.FileInfo, n int, totalTokens int) {
+	if utils.Quiet {
+		return
+	}
+
+	sorted := make([]filesystem.FileInfo, len(files))
+	copy(sorted, files)
+	sort.SliceStable(sorted, func(i, j int) bool {
+		if sorted[i].TokenCount != sorted[j].TokenCount {
+			return sorted[i].TokenCount > sorted[j].TokenCount
+		}
+		return sorted[i].Path < sorted[j].Path
+	})
+
+	displayCount := min(len(sorted), n)
+	pathWidth := len("File")
+	for _, file := range sorted[:displayCount] {
+		pathWidth = max(pathWidth, len(file.Path))
+	}
+
+	fmt.Fprintln(os.Stderr)
+	utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Top %d files by token count:", displayCount), color.FgCyan)
+	showSaved := slices.ContainsFunc(files, func(file filesystem.FileInfo) bool { return file.Compressed })
+	header := fmt.Sprintf("%-*s  %10s  %7s", pathWidth, "File", "Tokens", "Total")
+	if showSaved {
+		header += fmt.Sprintf("  %10s", "Saved")
 	}
+	fmt.Fprintln(os.Stderr, header)
+	for _, file := range sorted[:displayCount] {
+		percentage := 0.0
+		if totalTokens > 0 {
+			percentage = float64(file.TokenCount) / float64(totalTokens) * 100
+		}
+		row := fmt.Sprintf("%-*s  %10s  %6.2f%%", pathWidth, file.Path, utils.FormatNumber(file.TokenCount), percentage)
+		if showSaved {
+			saved := "-"
+			if file.Compressed && file.RawTokens > 0 {
+				saved = utils.FormatNumber(file.RawTokens - file.TokenCount)
+			}
+			row += fmt.Sprintf("  %10s", saved)
+		}
+		fmt.Fprintln(os.Stderr, row)
+	}
+	fmt.Fprintln(os.Stderr)
+}
+
+// printTokenizerComparison prints the token count of the output with each encoding, along with
+// its approximate cost when --price-per-1k is given. Only the counting is repeated, the files
+// have already been read once.
+func printTokenizerComparison(rendered string, encodings []string) {
+	if utils.Quiet {
+		return
+	}
+
+	counts := token.CountEach(rendered, encodings, noCorrectionFlag)
 
-	fmt.Println()
+	encodingWidth := len("Encoding")
+	for _, encoding := range encodings {
+		encodingWidth = max(encodingWidth, len(encoding))
+	}
+
+	fmt.Fprintln(os.Stderr)
+	utils.PrintColouredMessage("ℹ️", "Token counts by tokeniser:", color.FgCyan)
+	header := fmt.Sprintf("%-*s  %10s", encodingWidth, "Encoding", "Tokens")
+	if pricePer1KFlag > 0 {
+		header += fmt.Sprintf("  %10s", "Cost")
+	}
+	fmt.Fprintln(os.Stderr, header)
+	for i, encoding := range encodings {
+		row := fmt.Sprintf("%-*s  %10s", encodingWidth, encoding, utils.FormatNumber(counts[i]))
+		if pricePer1KFlag > 0 {
+			row += fmt.Sprintf("  %10s", fmt.Sprintf("$%.4f", float64(counts[i])/1000*pricePer1KFlag))
+		}
+		fmt.Fprintln(os.Stderr, row)
+	}
+	fmt.Fprintln(os.Stderr)
 }
 
-func handleOutput(rendered string, countTokens bool, encoding string, noClipboard bool, output string, jsonOutput bool, report bool, files []filesystem.FileInfo) error {
+// handleOutput writes the rendered output to the output file and clipboard, printing it when
+// neither is used. If preview is set, that markdown is shown in a pager instead of printing.
+func handleOutput(rendered string, tokenCount int, countTokens bool, encoding string, noClipboard bool, output string, jsonOutput bool, report bool, files []filesystem.FileInfo, preview string) error {
 	if countTokens {
-		tokenCount := token.CountTokens(rendered, encoding, anthropicFlag, noCorrectionFlag)
-		println()
-		utils.AddMessage("ℹ️", fmt.Sprintf("Tokens (Approximate): %v", utils.FormatNumber(tokenCount)), color.FgYellow, 1)
+		if !utils.Quiet {
+			println()
+		}
+		utils.AddMessage("ℹ️", fmt.Sprintf("Tokens (Approximate): %v (%s)", utils.FormatNumber(tokenCount), tokenizer().Label()), color.FgYellow, 1)
 	}
 
 	if report {
@@ -513,18 +1831,21 @@ func handleOutput(rendered string, countTokens bool, encoding string, noClipboar
 	if jsonOutput {
 		jsonData := map[string]any{
 			"prompt":      rendered,
-			"token_count": token.CountTokens(rendered, encoding, anthropicFlag, noCorrectionFlag),
+			"token_count": tokenCount,
+			"tokenizer":   tokenizer().Label(),
 			"model_info":  token.GetModelInfo(encoding),
 		}
 		jsonBytes, err := json.MarshalIndent(jsonData, "", "  ")
 		if err != nil {
 			return fmt.Errorf("failed to marshal JSON: %w", err)
 		}
-		fmt.Println(string(jsonBytes))
+		if err := printOutput(string(jsonBytes) + "\n"); err != nil {
+			return err
+		}
 	} else {
 		outputWritten := false
 		if output != "" {
-			err := utils.WriteToFile(output, rendered)
+			err := utils.WriteToFile(output, rendered, compressOutputFlag)
 			if err != nil {
 				// Report the error but continue to potentially copy to clipboard or print
 				utils.PrintColouredMessage("❌", fmt.Sprintf("Failed to write to file %s: %v", output, err), color.FgRed)
@@ -534,29 +1855,78 @@ func handleOutput(rendered string, countTokens bool, encoding string, noClipboar
 			}
 		}
 
+		// The clipboard copy is independent of the output file, both happen when requested
 		clipboardCopied := false
+		savedToTemp := false
 		if !noClipboard {
 			err := utils.CopyToClipboard(rendered)
 			if err == nil {
-				utils.AddMessage("✅", "Copied to clipboard successfully.", color.FgGreen, 5)
+				copied := utils.FormatSize(int64(len(rendered)))
+				if countTokens {
+					copied += fmt.Sprintf(", %s tokens", utils.FormatNumber(tokenCount))
+				}
+				utils.AddMessage("✅", fmt.Sprintf("Copied to clipboard (%s).", copied), color.FgGreen, 5)
 				clipboardCopied = true
+			} else if outputWritten || !utils.IsTerminal(os.Stdout) {
+				// Piped output is printed below, as it is when there's no clipboard to try
+				utils.AddMessage("⚠️", fmt.Sprintf("Failed to copy to clipboard: %v.", err), color.FgYellow, 5)
 			} else {
-				// Only show clipboard error if we didn't write to a file
-				if !outputWritten {
+				// Without a clipboard (such as on a headless machine) save the output somewhere it can be found
+				tempPath, tempErr := utils.WriteTempFile(rendered)
+				if tempErr == nil {
+					utils.AddMessage("⚠️", fmt.Sprintf("Failed to copy to clipboard: %v. Output written to %s", err, tempPath), color.FgYellow, 5)
+					savedToTemp = true
+				} else {
 					utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to copy to clipboard: %v.", err), color.FgYellow)
 				}
 			}
 		}
 
 		// If neither output file was written nor clipboard was copied, print to console
-		if !outputWritten && !clipboardCopied {
-			fmt.Print(rendered)
+		if preview != "" {
+			if err := previewOutput(preview); err != nil {
+				return err
+			}
+		} else if !outputWritten && !clipboardCopied && !savedToTemp {
+			if err := printOutput(rendered); err != nil {
+				return err
+			}
 		}
 	}
 
 	return nil
 }
 
+// printOutput prints the output to stdout, compressed if --compress-output is set.
+func printOutput(content string) error {
+	if compressOutputFlag == "" {
+		_, err := fmt.Print(content)
+		return err
+	}
+	compressed, err := utils.CompressOutput(content, compressOutputFlag, "")
+	if err != nil {
+		return err
+	}
+	_, err = os.Stdout.Write(compressed)
+	return err
+}
+
+// previewOutput renders markdown with glamour in the --theme style and shows it in a pager.
+func previewOutput(markdown string) error {
+	r, err := glamour.NewTermRenderer(
+		glamour.WithStandardStyle(themeFlag),
+		glamour.WithWordWrap(min(utils.GetTerminalWidth(), 160)-10),
+	)
+	if err != nil {
+		return fmt.Errorf("failed to create renderer: %w", err)
+	}
+	styled, err := r.Render(markdown)
+	if err != nil {
+		return fmt.Errorf("failed to render preview: %w", err)
+	}
+	return utils.Page(styled)
+}
+
 func printExcludePatterns(patterns []string) {
 	utils.PrintColouredMessage("i", "Active exclude patterns:", color.FgCyan)
 
@@ -607,24 +1977,48 @@ func printExcludePatterns(patterns []string) {
 	}
 }
 
-func handleLLMOutput(rendered string, llmConfig config.LLMConfig, countTokens bool, encoding string) error {
-	if countTokens {
-		tokenCount := token.CountTokens(rendered, encoding, anthropicFlag, noCorrectionFlag)
-		utils.AddMessage("ℹ️", fmt.Sprintf("Tokens (Approximate): %v", utils.FormatNumber(tokenCount)), color.FgYellow, 40)
+// llmConfigFromFlags applies the --llm-* flags over the configured LLM settings.
+func llmConfigFromFlags(llmConfig config.LLMConfig) config.LLMConfig {
+	if llmBaseURL != "" {
+		llmConfig.BaseURL = llmBaseURL
+	}
+	if llmModel != "" {
+		llmConfig.Model = llmModel
+	}
+	if llmAPIKey != "" {
+		llmConfig.AuthToken = llmAPIKey
+	}
+	if llmConfig.AuthToken == "" {
+		llmConfig.AuthToken = os.Getenv("OPENAI_API_KEY")
 	}
 
-	if promptPrefix != "" {
-		rendered = promptPrefix + "\n" + rendered
+	// Allow host:port/path without a scheme, as is common for local servers such as Ollama
+	if llmConfig.BaseURL != "" && !strings.Contains(llmConfig.BaseURL, "://") {
+		llmConfig.BaseURL = "http://" + llmConfig.BaseURL
 	}
+	return llmConfig
+}
 
-	if promptSuffix != "" {
-		rendered += "\n" + promptSuffix
+// handleLLMOutput sends the generated content, preceded by any --prompt, as the user message
+// to an OpenAI compatible chat completions API and streams the response to stdout. The
+// response is rendered as markdown when stdout is a terminal.
+func handleLLMOutput(rendered string, llmConfig config.LLMConfig, countTokens bool, encoding string) error {
+	message := rendered
+	if promptFlag != "" {
+		message = promptFlag + "\n\n" + rendered
 	}
 
-	if llmConfig.AuthToken == "" {
-		return fmt.Errorf("LLM auth token is empty")
+	if countTokens || maxTokensFlag > 0 {
+		tokenCount := token.CountTokens(message, encoding, anthropicFlag, noCorrectionFlag)
+		if maxTokensFlag > 0 && tokenCount > maxTokensFlag {
+			return fmt.Errorf("the prompt is %s tokens, over %s, not sending it", utils.FormatNumber(tokenCount), tokenBudget())
+		}
+		if countTokens {
+			utils.AddMessage("ℹ️", fmt.Sprintf("Tokens (Approximate): %v (%s)", utils.FormatNumber(tokenCount), tokenizer().Label()), color.FgYellow, 40)
+		}
 	}
 
+	// Local servers such as Ollama don't need an API key
 	clientConfig := openai.DefaultConfig(llmConfig.AuthToken)
 	clientConfig.BaseURL = llmConfig.BaseURL
 	clientConfig.APIType = openai.APIType(llmConfig.APIType)
@@ -632,11 +2026,13 @@ func handleLLMOutput(rendered string, llmConfig config.LLMConfig, countTokens bo
 	c := openai.NewClientWithConfig(clientConfig)
 	ctx := context.Background()
 
-	req := openai.CompletionRequest{
+	req := openai.ChatCompletionRequest{
 		Model:     llmConfig.Model,
 		MaxTokens: llmConfig.MaxTokens,
-		Prompt:    rendered,
-		Stream:    true,
+		Messages: []openai.ChatCompletionMessage{
+			{Role: openai.ChatMessageRoleUser, Content: message},
+		},
+		Stream: true,
 	}
 
 	if llmConfig.Temperature != nil {
@@ -652,12 +2048,29 @@ func handleLLMOutput(rendered string, llmConfig config.LLMConfig, countTokens bo
 		req.FrequencyPenalty = *llmConfig.FrequencyPenalty
 	}
 
-	stream, err := c.CreateCompletionStream(ctx, req)
+	stream, err := c.CreateChatCompletionStream(ctx, req)
 	if err != nil {
 		return fmt.Errorf("LLM CompletionStream error: %w", err)
 	}
 	defer stream.Close()
 
+	// Print the response as it arrives when it isn't going to a terminal
+	if !utils.IsTerminal(os.Stdout) {
+		for {
+			response, err := stream.Recv()
+			if errors.Is(err, io.EOF) {
+				fmt.Println()
+				return nil
+			}
+			if err != nil {
+				return fmt.Errorf("stream error: %w", err)
+			}
+			if len(response.Choices) > 0 {
+				fmt.Print(response.Choices[0].Delta.Content)
+			}
+		}
+	}
+
 	termWidth := min(
 		// if the term width is over 160, set it to 160
 		utils.GetTerminalWidth(), 160)
@@ -681,7 +2094,10 @@ func handleLLMOutput(rendered string, llmConfig config.LLMConfig, countTokens bo
 			return fmt.Errorf("stream error: %w", err)
 		}
 
-		buffer.WriteString(response.Choices[0].Text)
+		if len(response.Choices) == 0 {
+			continue
+		}
+		buffer.WriteString(response.Choices[0].Delta.Content)
 
 		// Process complete lines
 		for {
@@ -831,8 +2247,12 @@ func autoSaveOutput(content string, outputPath string, sourcePath string) error
 		return fmt.Errorf("failed to create directory %s for auto-save file: %w", finalDir, err)
 	}
 
-	// Write the file using os.WriteFile
-	if err := os.WriteFile(finalPath, []byte(content), 0600); err != nil {
+	// Write the file using os.WriteFile, compressed if the file name or --compress-output asks for it
+	compressed, err := utils.CompressOutput(content, compressOutputFlag, finalPath)
+	if err != nil {
+		return err
+	}
+	if err := os.WriteFile(finalPath, compressed, 0600); err != nil {
 		return fmt.Errorf("failed to write auto-save file to %s: %w", finalPath, err)
 	}
 
@@ -857,18 +2277,11 @@ func runCompletion(cmd *cobra.Command, args []string) {
 	}
 }
 
-func processWebInput(urlStr string, excludePatterns []string) (*web.CrawlResult, error) {
-	options := web.CrawlOptions{
-		MaxDepth:       webMaxDepth,
-		AllowedDomains: webAllowedDomains,
-		Timeout:        webTimeout,
-		ConcurrentJobs: webConcurrentJobs,
+// userAgent identifies ingest to the sites it crawls.
+func userAgent() string {
+	if Version == "" {
+		return "ingest/dev"
 	}
-
-	return web.ProcessWebURL(urlStr, options, excludePatterns)
+	return "ingest/" + Version
 }
 
-func isURL(str string) bool {
-	u, err := url.Parse(str)
-	return err == nil && u.Scheme != "" && u.Host != ""
-}
@@ -0,0 +1,64 @@
+package main
+
+import (
+	"bytes"
+	"compress/gzip"
+	"io"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestCompressOutputWithReports(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("USERPROFILE", home)
+	t.Setenv("XDG_CACHE_HOME", filepath.Join(home, ".cache"))
+
+	root := t.TempDir()
+	if err := os.WriteFile(filepath.Join(root, "main.go"), []byte("package main\n\nfunc main() {}\n"), 0o644); err != nil {
+		t.Fatalf("Failed to write main.go: %v", err)
+	}
+
+	// Everything written to stdout must be part of the archive, so the reports can't be mixed in
+	rootCmd.SetArgs([]string{"--no-clipboard", "--no-cache", "--compress-output", "gzip", "--report", "--token-report", "5", "--compare-tokenizers", "cl100k_base,o200k_base", root})
+	stdout := captureStdout(t, func() {
+		if err := rootCmd.Execute(); err != nil {
+			t.Errorf("ingest failed: %v", err)
+		}
+	})
+
+	reader, err := gzip.NewReader(bytes.NewReader(stdout))
+	if err != nil {
+		t.Fatalf("Expected stdout to be gzip compressed: %v", err)
+	}
+	output, err := io.ReadAll(reader)
+	if err != nil {
+		t.Fatalf("Expected stdout to be a single gzip archive: %v", err)
+	}
+	if !strings.Contains(string(output), "func main() {}") {
+		t.Errorf("Expected the output to include main.go, got:\n%s", output)
+	}
+}
+
+// captureStdout returns what fn writes to os.Stdout.
+func captureStdout(t *testing.T, fn func()) []byte {
+	t.Helper()
+	r, w, err := os.Pipe()
+	if err != nil {
+		t.Fatalf("Failed to create pipe: %v", err)
+	}
+	stdout := os.Stdout
+	os.Stdout = w
+	defer func() { os.Stdout = stdout }()
+
+	read := make(chan []byte)
+	go func() {
+		data, _ := io.ReadAll(r)
+		read <- data
+	}()
+	fn()
+	w.Close()
+	return <-read
+}
@@ -0,0 +1,268 @@
+// office/office.go
+
+package office
+
+import (
+	"archive/zip"
+	"bytes"
+	"encoding/csv"
+	"encoding/xml"
+	"fmt"
+	"io"
+	"path"
+	"strconv"
+	"strings"
+)
+
+// IsOfficeDocument reports whether a file extension is for a supported office document.
+func IsOfficeDocument(extension string) bool {
+	switch strings.ToLower(extension) {
+	case ".docx", ".xlsx":
+		return true
+	}
+	return false
+}
+
+// ExtractText extracts the text of a .docx or .xlsx file. Password-protected documents are not
+// zip archives and so fail to open, like corrupt ones.
+func ExtractText(filePath string) (string, error) {
+	r, err := zip.OpenReader(filePath)
+	if err != nil {
+		return "", fmt.Errorf("failed to open document, it may be corrupt or password-protected: %w", err)
+	}
+	defer r.Close()
+
+	switch strings.ToLower(path.Ext(filePath)) {
+	case ".docx":
+		return extractDocx(&r.Reader)
+	case ".xlsx":
+		return extractXlsx(&r.Reader)
+	default:
+		return "", fmt.Errorf("unsupported office document: %s", filePath)
+	}
+}
+
+func readZipFile(r *zip.Reader, name string) ([]byte, error) {
+	f, err := r.Open(name)
+	if err != nil {
+		return nil, fmt.Errorf("failed to open %s: %w", name, err)
+	}
+	defer f.Close()
+	return io.ReadAll(f)
+}
+
+// extractDocx returns the paragraph text of word/document.xml, one paragraph per line.
+func extractDocx(r *zip.Reader) (string, error) {
+	data, err := readZipFile(r, "word/document.xml")
+	if err != nil {
+		return "", err
+	}
+
+	var buf strings.Builder
+	var paragraph strings.Builder
+	inText := false
+
+	decoder := xml.NewDecoder(bytes.NewReader(data))
+	for {
+		tok, err := decoder.Token()
+		if err == io.EOF {
+			break
+		}
+		if err != nil {
+			return "", fmt.Errorf("failed to parse document.xml: %w", err)
+		}
+
+		switch t := tok.(type) {
+		case xml.StartElement:
+			switch t.Name.Local {
+			case "t":
+				inText = true
+			case "tab":
+				paragraph.WriteString("\t")
+			case "br", "cr":
+				paragraph.WriteString("\n")
+			}
+		case xml.EndElement:
+			switch t.Name.Local {
+			case "t":
+				inText = false
+			case "p":
+				if text := strings.TrimSpace(paragraph.String()); text != "" {
+					buf.WriteString(text)
+					buf.WriteString("\n\n")
+				}
+				paragraph.Reset()
+			}
+		case xml.CharData:
+			if inText {
+				paragraph.Write(t)
+			}
+		}
+	}
+
+	return strings.TrimSuffix(buf.String(), "\n\n"), nil
+}
+
+type workbook struct {
+	Sheets []struct {
+		Name string `xml:"name,attr"`
+		ID   string `xml:"http://schemas.openxmlformats.org/officeDocument/2006/relationships id,attr"`
+	} `xml:"sheets>sheet"`
+}
+
+type relationships struct {
+	Relationships []struct {
+		ID     string `xml:"Id,attr"`
+		Target string `xml:"Target,attr"`
+	} `xml:"Relationship"`
+}
+
+type sharedStrings struct {
+	Items []richText `xml:"si"`
+}
+
+// richText is a string that is either plain (t) or made up of formatted runs (r/t).
+type richText struct {
+	Text string   `xml:"t"`
+	Runs []string `xml:"r>t"`
+}
+
+func (rt richText) String() string {
+	if len(rt.Runs) > 0 {
+		return strings.Join(rt.Runs, "")
+	}
+	return rt.Text
+}
+
+type worksheet struct {
+	Rows []struct {
+		Cells []struct {
+			Ref    string   `xml:"r,attr"`
+			Type   string   `xml:"t,attr"`
+			Value  string   `xml:"v"`
+			Inline richText `xml:"is"`
+		} `xml:"c"`
+	} `xml:"sheetData>row"`
+}
+
+// extractXlsx returns each sheet as CSV rows, preceded by a "--- sheet NAME ---" line.
+func extractXlsx(r *zip.Reader) (string, error) {
+	var wb workbook
+	if err := unmarshalZipFile(r, "xl/workbook.xml", &wb); err != nil {
+		return "", err
+	}
+	var rels relationships
+	if err := unmarshalZipFile(r, "xl/_rels/workbook.xml.rels", &rels); err != nil {
+		return "", err
+	}
+	targets := make(map[string]string, len(rels.Relationships))
+	for _, rel := range rels.Relationships {
+		targets[rel.ID] = rel.Target
+	}
+
+	// Workbooks without any text cells have no shared strings
+	var strs sharedStrings
+	if hasZipFile(r, "xl/sharedStrings.xml") {
+		if err := unmarshalZipFile(r, "xl/sharedStrings.xml", &strs); err != nil {
+			return "", err
+		}
+	}
+
+	var buf strings.Builder
+	for _, sheet := range wb.Sheets {
+		target := targets[sheet.ID]
+		if target == "" {
+			continue
+		}
+		// Targets are relative to xl/, or absolute within the package
+		sheetPath := strings.TrimPrefix(target, "/")
+		if !strings.HasPrefix(target, "/") {
+			sheetPath = path.Join("xl", target)
+		}
+
+		var ws worksheet
+		if err := unmarshalZipFile(r, sheetPath, &ws); err != nil {
+			return "", err
+		}
+
+		fmt.Fprintf(&buf, "--- sheet %s ---\n\n", sheet.Name)
+		w := csv.NewWriter(&buf)
+		for _, row := range ws.Rows {
+			cells := make(map[int]string)
+			lastCol := -1
+			for i, cell := range row.Cells {
+				col := i
+				if c := columnIndex(cell.Ref); c >= 0 {
+					col = c
+				}
+				cells[col] = cellText(cell.Type, cell.Value, cell.Inline, strs)
+				lastCol = max(lastCol, col)
+			}
+
+			record := make([]string, lastCol+1)
+			for col, text := range cells {
+				record[col] = text
+			}
+			if err := w.Write(record); err != nil {
+				return "", fmt.Errorf("failed to write sheet %s: %w", sheet.Name, err)
+			}
+		}
+		w.Flush()
+		buf.WriteString("\n")
+	}
+
+	return strings.TrimSuffix(buf.String(), "\n\n"), nil
+}
+
+func hasZipFile(r *zip.Reader, name string) bool {
+	for _, f := range r.File {
+		if f.Name == name {
+			return true
+		}
+	}
+	return false
+}
+
+func unmarshalZipFile(r *zip.Reader, name string, v any) error {
+	data, err := readZipFile(r, name)
+	if err != nil {
+		return err
+	}
+	if err := xml.Unmarshal(data, v); err != nil {
+		return fmt.Errorf("failed to parse %s: %w", name, err)
+	}
+	return nil
+}
+
+func cellText(cellType, value string, inline richText, strs sharedStrings) string {
+	switch cellType {
+	case "s":
+		i, err := strconv.Atoi(value)
+		if err != nil || i < 0 || i >= len(strs.Items) {
+			return ""
+		}
+		return strs.Items[i].String()
+	case "inlineStr":
+		return inline.String()
+	case "b":
+		if value == "1" {
+			return "TRUE"
+		}
+		return "FALSE"
+	default:
+		return value
+	}
+}
+
+// columnIndex returns the zero-based column of a cell reference such as "AB12", or -1 if the
+// reference has no column.
+func columnIndex(ref string) int {
+	col := 0
+	for _, r := range ref {
+		if r < 'A' || r > 'Z' {
+			break
+		}
+		col = col*26 + int(r-'A'+1)
+	}
+	return col - 1
+}
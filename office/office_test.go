@@ -0,0 +1,88 @@
+package office
+
+import (
+	"archive/zip"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func writeZip(t *testing.T, path string, files map[string]string) {
+	t.Helper()
+	f, err := os.Create(path)
+	if err != nil {
+		t.Fatalf("Failed to create %s: %v", path, err)
+	}
+	defer f.Close()
+
+	w := zip.NewWriter(f)
+	for name, content := range files {
+		fw, err := w.Create(name)
+		if err != nil {
+			t.Fatalf("Failed to add %s: %v", name, err)
+		}
+		if _, err := fw.Write([]byte(content)); err != nil {
+			t.Fatalf("Failed to write %s: %v", name, err)
+		}
+	}
+	if err := w.Close(); err != nil {
+		t.Fatalf("Failed to close zip: %v", err)
+	}
+}
+
+func TestExtractDocx(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "design.docx")
+	writeZip(t, path, map[string]string{
+		"word/document.xml": `<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Design</w:t></w:r><w:r><w:t xml:space="preserve"> overview</w:t></w:r></w:p>
+<w:p></w:p>
+<w:p><w:r><w:t>Second &amp; last</w:t></w:r></w:p>
+</w:body></w:document>`,
+	})
+
+	text, err := ExtractText(path)
+	if err != nil {
+		t.Fatalf("ExtractText failed: %v", err)
+	}
+	if expected := "Design overview\n\nSecond & last"; text != expected {
+		t.Errorf("Expected %q, got %q", expected, text)
+	}
+}
+
+func TestExtractXlsx(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "data.xlsx")
+	writeZip(t, path, map[string]string{
+		"xl/workbook.xml": `<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<sheets><sheet name="Fields" sheetId="1" r:id="rId1"/></sheets></workbook>`,
+		"xl/_rels/workbook.xml.rels": `<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>`,
+		"xl/sharedStrings.xml": `<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+<si><t>name</t></si><si><t>type</t></si><si><r><t>user</t></r><r><t>_id</t></r></si></sst>`,
+		"xl/worksheets/sheet1.xml": `<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>
+<row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c></row>
+<row r="2"><c r="A2" t="s"><v>2</v></c><c r="C2"><v>42</v></c></row>
+</sheetData></worksheet>`,
+	})
+
+	text, err := ExtractText(path)
+	if err != nil {
+		t.Fatalf("ExtractText failed: %v", err)
+	}
+	if expected := "--- sheet Fields ---\n\nname,type\nuser_id,,42"; text != expected {
+		t.Errorf("Expected %q, got %q", expected, text)
+	}
+}
+
+func TestExtractCorrupt(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "broken.docx")
+	if err := os.WriteFile(path, []byte("not a zip"), 0644); err != nil {
+		t.Fatalf("Failed to write file: %v", err)
+	}
+
+	_, err := ExtractText(path)
+	if err == nil || !strings.Contains(err.Error(), "corrupt or password-protected") {
+		t.Errorf("Expected a corrupt document error, got %v", err)
+	}
+}
@@ -3,6 +3,7 @@
 package pdf
 
 import (
+	"errors"
 	"fmt"
 	"io"
 	"net/http"
@@ -13,34 +14,38 @@ import (
 	"github.com/ledongthuc/pdf"
 )
 
-// ConvertPDFToMarkdown converts a PDF file to markdown format
-
+// ConvertPDFToMarkdown extracts the text of a PDF like ExtractText, downloading it first if
+// path is a URL.
 func ConvertPDFToMarkdown(path string, isURL bool) (string, error) {
-	var reader io.ReadCloser
-	var err error
-
-	if isURL {
-		reader, err = downloadPDF(path)
-		if err != nil {
-			return "", fmt.Errorf("failed to download PDF: %w", err)
-		}
-		defer reader.Close()
+	if !isURL {
+		return ExtractText(path)
+	}
 
-		tempFile, err := os.CreateTemp("", "ingest-*.pdf")
-		if err != nil {
-			return "", fmt.Errorf("failed to create temp file: %w", err)
-		}
-		defer os.Remove(tempFile.Name())
-		defer tempFile.Close()
+	reader, err := downloadPDF(path)
+	if err != nil {
+		return "", fmt.Errorf("failed to download PDF: %w", err)
+	}
+	defer reader.Close()
 
-		if _, err := io.Copy(tempFile, reader); err != nil {
-			return "", fmt.Errorf("failed to save PDF: %w", err)
-		}
+	tempFile, err := os.CreateTemp("", "ingest-*.pdf")
+	if err != nil {
+		return "", fmt.Errorf("failed to create temp file: %w", err)
+	}
+	defer os.Remove(tempFile.Name())
+	defer tempFile.Close()
 
-		path = tempFile.Name()
+	if _, err := io.Copy(tempFile, reader); err != nil {
+		return "", fmt.Errorf("failed to save PDF: %w", err)
 	}
+	return ExtractText(tempFile.Name())
+}
+
+// ErrNoText is returned by ExtractText for PDFs without a text layer, such as scanned documents.
+var ErrNoText = errors.New("no extractable text, the PDF may be image-only or scanned")
 
-	// Open and read the PDF
+// ExtractText extracts the text of a local PDF page by page, separating pages with a
+// "--- page N ---" line. Pages without text are omitted.
+func ExtractText(path string) (string, error) {
 	f, r, err := pdf.Open(path)
 	if err != nil {
 		return "", fmt.Errorf("failed to open PDF: %w", err)
@@ -48,11 +53,7 @@ func ConvertPDFToMarkdown(path string, isURL bool) (string, error) {
 	defer f.Close()
 
 	var buf strings.Builder
-	buf.WriteString(fmt.Sprintf("# PDF Content: %s\n\n", filepath.Base(path)))
-
-	// Extract text from each page
-	totalPages := r.NumPage()
-	for pageNum := 1; pageNum <= totalPages; pageNum++ {
+	for pageNum := 1; pageNum <= r.NumPage(); pageNum++ {
 		page := r.Page(pageNum)
 		if page.V.IsNull() {
 			continue
@@ -63,21 +64,15 @@ func ConvertPDFToMarkdown(path string, isURL bool) (string, error) {
 			return "", fmt.Errorf("failed to extract text from page %d: %w", pageNum, err)
 		}
 
-		// Clean and process the text
-		cleanedText := cleanText(text)
-		if cleanedText != "" {
-			buf.WriteString(fmt.Sprintf("## Page %d\n\n", pageNum))
-			buf.WriteString(cleanedText)
-			buf.WriteString("\n\n")
+		if cleanedText := cleanText(text); cleanedText != "" {
+			fmt.Fprintf(&buf, "--- page %d ---\n\n%s\n\n", pageNum, cleanedText)
 		}
 	}
 
-	result := buf.String()
-	if strings.TrimSpace(result) == strings.TrimSpace(fmt.Sprintf("# PDF Content: %s\n\n", filepath.Base(path))) {
-		return "", fmt.Errorf("no text content could be extracted from PDF")
+	if buf.Len() == 0 {
+		return "", ErrNoText
 	}
-
-	return result, nil
+	return strings.TrimSuffix(buf.String(), "\n\n"), nil
 }
 
 // IsPDF checks if a file is a PDF based on its content type or extension
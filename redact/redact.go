@@ -0,0 +1,94 @@
+// Package redact replaces secrets in file contents, such as API keys and private keys, with a
+// placeholder before they are written to a prompt.
+package redact
+
+import (
+	"fmt"
+	"regexp"
+	"strings"
+)
+
+// Placeholder replaces each secret.
+const Placeholder = "***REDACTED***"
+
+// rule matches a secret. Only the text of group is replaced, so names and delimiters around
+// the secret are kept; group 0 replaces the whole match.
+type rule struct {
+	name  string
+	re    *regexp.Regexp
+	group int
+}
+
+// defaultRules match secrets that are very unlikely to be anything else, so are always on.
+var defaultRules = []rule{
+	{"private key", regexp.MustCompile(`(?s)-----BEGIN [A-Z ]*PRIVATE KEY(?: BLOCK)?-----\r?\n(.+?)\r?\n-----END [A-Z ]*PRIVATE KEY(?: BLOCK)?-----`), 1},
+	{"AWS access key", regexp.MustCompile(`\b((?:AKIA|ASIA|ABIA|ACCA)[0-9A-Z]{16})\b`), 1},
+	{"AWS secret key", regexp.MustCompile(`(?i)aws_?secret_?(?:access_?)?key["']?\s*[:=]\s*["']?([A-Za-z0-9/+=]{40})\b`), 1},
+}
+
+// extendedRules match secrets by their shape or the name they're assigned to, which also
+// catches things that aren't secrets, so are only on when asked for.
+var extendedRules = []rule{
+	{"JWT", regexp.MustCompile(`\beyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+`), 0},
+	{"assignment", regexp.MustCompile(`(?im)^\s*(?:export\s+)?["']?[\w.-]*(?:key|token|secret|password|passwd)[\w.-]*["']?\s*[:=]\s*["']?([^\s"'#,;]+)`), 1},
+}
+
+// Redactor replaces the secrets its rules match.
+type Redactor struct {
+	rules []rule
+}
+
+// New returns a Redactor for the default rules, which match private keys and AWS keys, along
+// with the rules for JWTs and KEY=, TOKEN=, SECRET= and PASSWORD= style assignments if
+// extended is set. Each of patterns is a further regular expression whose first capture
+// group, or whole match if it has none, is redacted.
+func New(extended bool, patterns []string) (*Redactor, error) {
+	r := &Redactor{rules: append([]rule(nil), defaultRules...)}
+	if extended {
+		r.rules = append(r.rules, extendedRules...)
+	}
+	for _, pattern := range patterns {
+		re, err := regexp.Compile(pattern)
+		if err != nil {
+			return nil, fmt.Errorf("invalid redact pattern %q: %w", pattern, err)
+		}
+		r.rules = append(r.rules, rule{name: pattern, re: re, group: min(re.NumSubexp(), 1)})
+	}
+	return r, nil
+}
+
+// Key identifies the rules of the Redactor, for caching what it produces.
+func (r *Redactor) Key() string {
+	names := make([]string, len(r.rules))
+	for i, rule := range r.rules {
+		names[i] = rule.name
+	}
+	return strings.Join(names, "\n")
+}
+
+// Redact returns content with every secret replaced by Placeholder, and how many were replaced.
+func (r *Redactor) Redact(content string) (string, int) {
+	count := 0
+	for _, rule := range r.rules {
+		matches := rule.re.FindAllStringSubmatchIndex(content, -1)
+		if len(matches) == 0 {
+			continue
+		}
+
+		var b strings.Builder
+		last := 0
+		for _, match := range matches {
+			start, end := match[2*rule.group], match[2*rule.group+1]
+			if start < 0 || content[start:end] == Placeholder {
+				continue
+			}
+			b.WriteString(content[last:start])
+			b.WriteString(Placeholder)
+			last = end
+			count++
+		}
+		b.WriteString(content[last:])
+		content = b.String()
+	}
+	return content, count
+}
@@ -1,17 +1,27 @@
 package template
 
 import (
+	_ "embed"
 	"fmt"
 	"os"
 	"path/filepath"
 	"strings"
 	"text/template"
+	"text/template/parse"
 
 	"github.com/fatih/color"
 	"github.com/mitchellh/go-homedir"
 	"github.com/sammcj/ingest/utils"
 )
 
+// defaultTemplate is the built-in markdown output format.
+//
+//go:embed default.tmpl
+var defaultTemplate string
+
+// SetupTemplate reads and parses the template at templatePath, or the default template if it is
+// empty. It is called before any files are read so mistakes in a template are reported
+// straight away, with the line they are on.
 func SetupTemplate(templatePath string) (*template.Template, error) {
 	var templateContent string
 	var err error
@@ -26,7 +36,13 @@ func SetupTemplate(templatePath string) (*template.Template, error) {
 		return nil, fmt.Errorf("failed to read template: %w", err)
 	}
 
-	tmpl, err := template.New("default").Parse(templateContent)
+	name := "default"
+	if templatePath != "" {
+		name = filepath.Base(templatePath)
+	}
+
+	// Parse errors include the template name and line, e.g. "template: custom.tmpl:12: ..."
+	tmpl, err := template.New(name).Parse(templateContent)
 	if err != nil {
 		return nil, fmt.Errorf("failed to parse template: %w", err)
 	}
@@ -58,59 +74,91 @@ func getDefaultTemplate() (string, error) {
 }
 
 func readEmbeddedTemplate() (string, error) {
-	return `
-Source Trees:
-
-{{.source_trees}}
-
-{{if .excluded}}
-Excluded Content:
-{{if le .excluded.TotalFiles 20}}
-Files:
-{{range .excluded.Files}}
-- {{.}}
-{{end}}
-{{else}}
-Directories with excluded files:
-{{range $dir, $count := .excluded.Directories}}
-{{if gt $count 0}}- {{$dir}}: {{$count}} files{{end}}
-{{end}}
-
-File extensions excluded:
-{{range $ext, $count := .excluded.Extensions}}
-- {{$ext}}: {{$count}} files
-{{end}}
-{{end}}
-
-{{end}}
-
-{{range .files}}
-{{if .Code}}
-` + "`{{.Path}}:`" + `
-
-{{.Code}}
-
-{{end}}
-{{end}}
-
-{{range .git_data}}
-{{if or .GitDiff .GitDiffBranch .GitLogBranch}}
-Git Information for {{.Path}}:
-{{if .GitDiff}}
-Git Diff:
-{{.GitDiff}}
-{{end}}
-{{if .GitDiffBranch}}
-Git Diff Between Branches:
-{{.GitDiffBranch}}
-{{end}}
-{{if .GitLogBranch}}
-Git Log Between Branches:
-{{.GitLogBranch}}
-{{end}}
-{{end}}
-{{end}}
-`, nil
+	return defaultTemplate, nil
+}
+
+// UsesField reports whether the template refers to any of the named fields, such as
+// TotalTokens, so data that is costly to gather is only computed when it is needed.
+func UsesField(tmpl *template.Template, names ...string) bool {
+	for _, t := range tmpl.Templates() {
+		if t.Tree != nil && nodeUsesField(t.Tree.Root, names) {
+			return true
+		}
+	}
+	return false
+}
+
+func nodeUsesField(node parse.Node, names []string) bool {
+	switch n := node.(type) {
+	case *parse.ListNode:
+		if n == nil {
+			return false
+		}
+		for _, child := range n.Nodes {
+			if nodeUsesField(child, names) {
+				return true
+			}
+		}
+	case *parse.ActionNode:
+		return nodeUsesField(n.Pipe, names)
+	case *parse.IfNode:
+		return branchUsesField(&n.BranchNode, names)
+	case *parse.RangeNode:
+		return branchUsesField(&n.BranchNode, names)
+	case *parse.WithNode:
+		return branchUsesField(&n.BranchNode, names)
+	case *parse.TemplateNode:
+		return nodeUsesField(n.Pipe, names)
+	case *parse.PipeNode:
+		if n == nil {
+			return false
+		}
+		for _, cmd := range n.Cmds {
+			if nodeUsesField(cmd, names) {
+				return true
+			}
+		}
+	case *parse.CommandNode:
+		for _, arg := range n.Args {
+			if nodeUsesField(arg, names) {
+				return true
+			}
+		}
+	case *parse.FieldNode:
+		return identUsesField(n.Ident, names)
+	case *parse.ChainNode:
+		return nodeUsesField(n.Node, names) || identUsesField(n.Field, names)
+	case *parse.VariableNode:
+		return identUsesField(n.Ident, names)
+	}
+	return false
+}
+
+func branchUsesField(branch *parse.BranchNode, names []string) bool {
+	return nodeUsesField(branch.Pipe, names) || nodeUsesField(branch.List, names) || nodeUsesField(branch.ElseList, names)
+}
+
+func identUsesField(idents []string, names []string) bool {
+	for _, ident := range idents {
+		for _, name := range names {
+			if ident == name {
+				return true
+			}
+		}
+	}
+	return false
+}
+
+// Streamable reports whether tmpl defines the templates needed to render it a file at a time
+// with --stream, as the default template does: header before the files, file for each file,
+// and footer after them.
+func Streamable(tmpl *template.Template) bool {
+	for _, name := range []string{"header", "file", "footer"} {
+		if tmpl.Lookup(name) == nil {
+			return false
+		}
+	}
+	return true
 }
 
 func RenderTemplate(tmpl *template.Template, data map[string]any) (string, error) {
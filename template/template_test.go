@@ -0,0 +1,103 @@
+package template
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"text/template"
+)
+
+func TestUsesField(t *testing.T) {
+	tests := []struct {
+		name     string
+		text     string
+		expected bool
+	}{
+		{"unused", "{{.Tree}}{{range .Files}}{{.Path}}{{end}}", false},
+		{"top level", "Total: {{.TotalTokens}}", true},
+		{"in range", "{{range .Files}}{{.Path}} {{.TokenCount}}{{end}}", true},
+		{"in condition", "{{if gt .TotalTokens 100}}big{{end}}", true},
+		{"in else", "{{if .Tree}}tree{{else}}{{.TotalTokens}}{{end}}", true},
+		{"variable", "{{range $f := .Files}}{{$f.TokenCount}}{{end}}", true},
+		{"defined template", `{{define "file"}}{{.TokenCount}}{{end}}{{range .Files}}{{template "file" .}}{{end}}`, true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			tmpl := template.Must(template.New("test").Parse(tt.text))
+			if got := UsesField(tmpl, "TokenCount", "TotalTokens"); got != tt.expected {
+				t.Errorf("UsesField(%q) = %v, expected %v", tt.text, got, tt.expected)
+			}
+		})
+	}
+}
+
+func TestSetupTemplateParseErrorLine(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "custom.tmpl")
+	if err := os.WriteFile(path, []byte("{{.Tree}}\n\n{{nosuchfunc .Files}}\n"), 0644); err != nil {
+		t.Fatalf("Failed to write template: %v", err)
+	}
+
+	_, err := SetupTemplate(path)
+	if err == nil {
+		t.Fatal("Expected a parse error")
+	}
+	if !strings.Contains(err.Error(), "custom.tmpl:3") {
+		t.Errorf("Expected the error to name the file and line, got: %v", err)
+	}
+}
+
+func TestRenderTemplateData(t *testing.T) {
+	tmpl := template.Must(template.New("test").Parse("{{.Tree}}\n{{range .Files}}{{.Path}}: {{.Content}}\n{{end}}{{.TotalTokens}}"))
+	data := map[string]any{
+		"Tree":        "repo/",
+		"Files":       []struct{ Path, Content string }{{"main.go", "package main"}},
+		"TotalTokens": 42,
+	}
+
+	rendered, err := RenderTemplate(tmpl, data)
+	if err != nil {
+		t.Fatalf("RenderTemplate failed: %v", err)
+	}
+	if expected := "repo/\nmain.go: package main\n42"; rendered != expected {
+		t.Errorf("Expected %q, got %q", expected, rendered)
+	}
+}
+
+func TestDefaultTemplateStreams(t *testing.T) {
+	tmpl, err := SetupTemplate("")
+	if err != nil {
+		t.Fatalf("SetupTemplate failed: %v", err)
+	}
+	if !Streamable(tmpl) {
+		t.Fatal("Expected the default template to be streamable")
+	}
+
+	files := []map[string]any{{"Path": "main.go", "Code": "package main"}, {"Path": "empty.go", "Code": ""}}
+	data := map[string]any{"source_trees": "repo/\n└── main.go", "files": files, "FileOrder": "README first."}
+	whole, err := RenderTemplate(tmpl, data)
+	if err != nil {
+		t.Fatalf("RenderTemplate failed: %v", err)
+	}
+
+	// Rendering the parts in turn must give the same output as rendering the template at once
+	var streamed strings.Builder
+	if err := tmpl.ExecuteTemplate(&streamed, "header", data); err != nil {
+		t.Fatalf("header failed: %v", err)
+	}
+	for _, file := range files {
+		if err := tmpl.ExecuteTemplate(&streamed, "file", file); err != nil {
+			t.Fatalf("file failed: %v", err)
+		}
+	}
+	if err := tmpl.ExecuteTemplate(&streamed, "footer", data); err != nil {
+		t.Fatalf("footer failed: %v", err)
+	}
+	if streamed.String() != whole {
+		t.Errorf("Expected the streamed parts to match the whole template:\n%q\n%q", streamed.String(), whole)
+	}
+	if !strings.Contains(whole, "`main.go:`\n\npackage main") {
+		t.Errorf("Expected the file in the output, got %q", whole)
+	}
+}
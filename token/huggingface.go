@@ -0,0 +1,687 @@
+package token
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"os"
+	"path/filepath"
+	"regexp"
+	"strings"
+	"sync"
+	"time"
+	"unicode"
+
+	"github.com/dlclark/regexp2"
+	"github.com/fatih/color"
+	"github.com/sammcj/ingest/cache"
+	"github.com/sammcj/ingest/utils"
+	"golang.org/x/text/unicode/norm"
+)
+
+// huggingFacePrefix marks an encoding as a Hugging Face tokeniser. The rest of the encoding
+// is the path of its tokenizer.json or the ID of its model on the Hugging Face Hub.
+const huggingFacePrefix = "huggingface:"
+
+// maxBPEWord is the longest word, in characters, merged in one piece. BPE is quadratic in the
+// length of a word, so longer runs without a break, such as base64 blobs, are split first.
+const maxBPEWord = 512
+
+// gpt2Pattern is the pre-tokeniser pattern of byte-level BPE tokenisers that use a regex.
+const gpt2Pattern = `'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)|\s+`
+
+// hfModelID matches Hugging Face Hub model IDs such as meta-llama/Llama-3.1-8B.
+var hfModelID = regexp.MustCompile(`^[A-Za-z0-9][\w.-]*(/[\w.-]+)?$`)
+
+// hfDownloadClient fetches tokenizer.json files, which run to tens of megabytes.
+var hfDownloadClient = &http.Client{Timeout: 5 * time.Minute}
+
+var (
+	hfMu         sync.Mutex
+	hfTokenizers = make(map[string]*hfTokenizer) // Loaded tokenisers by source
+)
+
+// byteLevelAlphabet maps each byte to the character byte-level BPE represents it with, as
+// GPT-2 does: printable bytes stand for themselves and the rest are shifted past 255.
+var byteLevelAlphabet = func() [256]rune {
+	var alphabet [256]rune
+	shifted := 0
+	for b := range 256 {
+		if (b >= '!' && b <= '~') || (b >= 0xA1 && b <= 0xAC) || (b >= 0xAE && b <= 0xFF) {
+			alphabet[b] = rune(b)
+		} else {
+			alphabet[b] = rune(256 + shifted)
+			shifted++
+		}
+	}
+	return alphabet
+}()
+
+// HuggingFaceEncoding returns the encoding that counts with the Hugging Face tokeniser at
+// source, a tokenizer.json path, a directory holding one, or a model ID on the Hugging Face Hub.
+func HuggingFaceEncoding(source string) string {
+	return huggingFacePrefix + source
+}
+
+// HuggingFaceSource returns the source of a Hugging Face encoding, or false if it isn't one.
+func HuggingFaceSource(encoding string) (string, bool) {
+	return strings.CutPrefix(encoding, huggingFacePrefix)
+}
+
+// LoadHuggingFace loads the Hugging Face tokeniser at source, downloading it if source is a
+// model ID that hasn't been fetched before, so problems are reported before counting starts.
+func LoadHuggingFace(source string) error {
+	_, err := loadHuggingFace(source)
+	return err
+}
+
+func loadHuggingFace(source string) (*hfTokenizer, error) {
+	hfMu.Lock()
+	defer hfMu.Unlock()
+	if tk, ok := hfTokenizers[source]; ok {
+		return tk, nil
+	}
+
+	data, err := readTokenizerJSON(source)
+	if err != nil {
+		return nil, err
+	}
+	tk, err := parseHFTokenizer(data)
+	if err != nil {
+		return nil, fmt.Errorf("failed to load %s: %w", source, err)
+	}
+	hfTokenizers[source] = tk
+	return tk, nil
+}
+
+// readTokenizerJSON reads the tokenizer.json of source, from disk if it is a path and otherwise
+// from the local Hugging Face cache, ingest's cache, or the Hugging Face Hub.
+func readTokenizerJSON(source string) ([]byte, error) {
+	if info, err := os.Stat(source); err == nil {
+		if info.IsDir() {
+			source = filepath.Join(source, "tokenizer.json")
+		}
+		return os.ReadFile(source)
+	}
+	if strings.HasSuffix(source, ".json") || !hfModelID.MatchString(source) {
+		return nil, fmt.Errorf("%s is not a tokenizer.json file or a Hugging Face model ID", source)
+	}
+
+	// Models already downloaded by Hugging Face's own tools are reused
+	snapshots, _ := filepath.Glob(filepath.Join(huggingFaceHubDir(), "models--"+strings.ReplaceAll(source, "/", "--"), "snapshots", "*", "tokenizer.json"))
+	for _, path := range snapshots {
+		if data, err := os.ReadFile(path); err == nil {
+			return data, nil
+		}
+	}
+
+	dir, err := cache.Dir("")
+	if err != nil {
+		return nil, err
+	}
+	path := filepath.Join(dir, "tokenizers", strings.ReplaceAll(source, "/", "--"), "tokenizer.json")
+	if data, err := os.ReadFile(path); err == nil {
+		return data, nil
+	}
+
+	data, err := downloadTokenizer(source)
+	if err != nil {
+		return nil, err
+	}
+	if err := writeFileAtomic(path, data); err != nil {
+		utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to cache the %s tokeniser: %v", source, err), color.FgYellow)
+	}
+	return data, nil
+}
+
+// huggingFaceHubDir returns the directory Hugging Face's tools cache downloaded models in.
+func huggingFaceHubDir() string {
+	if dir := os.Getenv("HF_HUB_CACHE"); dir != "" {
+		return dir
+	}
+	if home := os.Getenv("HF_HOME"); home != "" {
+		return filepath.Join(home, "hub")
+	}
+	home, err := os.UserHomeDir()
+	if err != nil {
+		return ""
+	}
+	return filepath.Join(home, ".cache", "huggingface", "hub")
+}
+
+// downloadTokenizer fetches the tokenizer.json of a model from the Hugging Face Hub. Gated
+// models need an access token in HF_TOKEN or HUGGING_FACE_HUB_TOKEN.
+func downloadTokenizer(model string) ([]byte, error) {
+	utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Downloading the %s tokeniser from Hugging Face", model), color.FgCyan)
+
+	req, err := http.NewRequest("GET", "https://huggingface.co/"+model+"/resolve/main/tokenizer.json", nil)
+	if err != nil {
+		return nil, fmt.Errorf("failed to create request: %w", err)
+	}
+	for _, name := range []string{"HF_TOKEN", "HUGGING_FACE_HUB_TOKEN"} {
+		if hfToken := os.Getenv(name); hfToken != "" {
+			req.Header.Set("Authorization", "Bearer "+hfToken)
+			break
+		}
+	}
+
+	resp, err := hfDownloadClient.Do(req)
+	if err != nil {
+		return nil, fmt.Errorf("failed to download the %s tokeniser: %w", model, err)
+	}
+	defer resp.Body.Close()
+
+	switch resp.StatusCode {
+	case http.StatusOK:
+	case http.StatusUnauthorized, http.StatusForbidden:
+		return nil, fmt.Errorf("access to %s was denied (status %d), gated models need an access token in HF_TOKEN", model, resp.StatusCode)
+	case http.StatusNotFound:
+		return nil, fmt.Errorf("%s has no tokenizer.json on Hugging Face", model)
+	default:
+		return nil, fmt.Errorf("failed to download the %s tokeniser: status %d", model, resp.StatusCode)
+	}
+
+	data, err := io.ReadAll(resp.Body)
+	if err != nil {
+		return nil, fmt.Errorf("failed to download the %s tokeniser: %w", model, err)
+	}
+	return data, nil
+}
+
+func writeFileAtomic(path string, data []byte) error {
+	if err := os.MkdirAll(filepath.Dir(path), 0700); err != nil {
+		return err
+	}
+	tmp, err := os.CreateTemp(filepath.Dir(path), ".tokenizer-*")
+	if err != nil {
+		return err
+	}
+	defer os.Remove(tmp.Name())
+	if _, err := tmp.Write(data); err != nil {
+		tmp.Close()
+		return err
+	}
+	if err := tmp.Close(); err != nil {
+		return err
+	}
+	return os.Rename(tmp.Name(), path)
+}
+
+// hfTokenizer counts tokens with a BPE tokeniser loaded from a Hugging Face tokenizer.json.
+// Added tokens and post-processors are ignored, as they rarely appear in or add to the
+// content being counted. It is safe for concurrent use.
+type hfTokenizer struct {
+	normalizers   []func(string) string
+	preTokenizers []func([]string) []string
+	vocab         map[string]int
+	merges        map[[2]string]int // Rank of each merge, lower merges first
+	byteFallback  bool              // Characters missing from the vocabulary are a token per byte
+	ignoreMerges  bool              // Words in the vocabulary are a token without merging
+	prefix        string            // continuing_subword_prefix, added to all but the first symbol
+	suffix        string            // end_of_word_suffix, added to the last symbol
+}
+
+// hfTokenizerFile is the part of a tokenizer.json needed to count tokens.
+type hfTokenizerFile struct {
+	Normalizer   *hfComponent `json:"normalizer"`
+	PreTokenizer *hfComponent `json:"pre_tokenizer"`
+	Model        struct {
+		Type                    string          `json:"type"`
+		Vocab                   json.RawMessage `json:"vocab"`
+		Merges                  json.RawMessage `json:"merges"`
+		ByteFallback            bool            `json:"byte_fallback"`
+		IgnoreMerges            bool            `json:"ignore_merges"`
+		ContinuingSubwordPrefix *string         `json:"continuing_subword_prefix"`
+		EndOfWordSuffix         *string         `json:"end_of_word_suffix"`
+	} `json:"model"`
+}
+
+// hfComponent is a normaliser or pre-tokeniser, with the options of every type they come in.
+type hfComponent struct {
+	Type             string        `json:"type"`
+	Normalizers      []hfComponent `json:"normalizers"`
+	Pretokenizers    []hfComponent `json:"pretokenizers"`
+	Pattern          hfPattern     `json:"pattern"`
+	Content          string        `json:"content"`
+	Prepend          string        `json:"prepend"`
+	Behavior         string        `json:"behavior"`
+	Invert           bool          `json:"invert"`
+	Replacement      string        `json:"replacement"`
+	PrependScheme    string        `json:"prepend_scheme"`
+	Split            *bool         `json:"split"`
+	AddPrefixSpace   *bool         `json:"add_prefix_space"`
+	UseRegex         *bool         `json:"use_regex"`
+	IndividualDigits bool          `json:"individual_digits"`
+	StripLeft        bool          `json:"strip_left"`
+	StripRight       bool          `json:"strip_right"`
+}
+
+// hfPattern is a literal String or a Regex to split or replace on.
+type hfPattern struct {
+	String *string `json:"String"`
+	Regex  *string `json:"Regex"`
+}
+
+func parseHFTokenizer(data []byte) (*hfTokenizer, error) {
+	var file hfTokenizerFile
+	if err := json.Unmarshal(data, &file); err != nil {
+		return nil, fmt.Errorf("invalid tokenizer.json: %w", err)
+	}
+	if file.Model.Type != "" && file.Model.Type != "BPE" {
+		return nil, fmt.Errorf("only BPE tokenisers are supported, not %s", file.Model.Type)
+	}
+
+	tk := &hfTokenizer{byteFallback: file.Model.ByteFallback, ignoreMerges: file.Model.IgnoreMerges}
+	if file.Model.ContinuingSubwordPrefix != nil {
+		tk.prefix = *file.Model.ContinuingSubwordPrefix
+	}
+	if file.Model.EndOfWordSuffix != nil {
+		tk.suffix = *file.Model.EndOfWordSuffix
+	}
+	if err := json.Unmarshal(file.Model.Vocab, &tk.vocab); err != nil {
+		return nil, fmt.Errorf("invalid vocab: %w", err)
+	}
+	merges, err := parseMerges(file.Model.Merges)
+	if err != nil {
+		return nil, err
+	}
+	tk.merges = make(map[[2]string]int, len(merges))
+	for rank, merge := range merges {
+		if _, ok := tk.merges[merge]; !ok {
+			tk.merges[merge] = rank
+		}
+	}
+
+	if file.Normalizer != nil {
+		if tk.normalizers, err = parseNormalizer(*file.Normalizer); err != nil {
+			return nil, err
+		}
+	}
+	if file.PreTokenizer != nil {
+		if tk.preTokenizers, err = parsePreTokenizer(*file.PreTokenizer); err != nil {
+			return nil, err
+		}
+	}
+	return tk, nil
+}
+
+// parseMerges reads merges written as "a b" strings, as older files do, or as pairs.
+func parseMerges(data json.RawMessage) ([][2]string, error) {
+	if len(data) == 0 {
+		return nil, nil
+	}
+
+	var pairs [][2]string
+	if err := json.Unmarshal(data, &pairs); err == nil {
+		return pairs, nil
+	}
+	var lines []string
+	if err := json.Unmarshal(data, &lines); err != nil {
+		return nil, fmt.Errorf("invalid merges: %w", err)
+	}
+	pairs = make([][2]string, 0, len(lines))
+	for _, line := range lines {
+		left, right, ok := strings.Cut(line, " ")
+		if !ok {
+			return nil, fmt.Errorf("invalid merge %q", line)
+		}
+		pairs = append(pairs, [2]string{left, right})
+	}
+	return pairs, nil
+}
+
+func parseNormalizer(c hfComponent) ([]func(string) string, error) {
+	switch c.Type {
+	case "Sequence":
+		var normalizers []func(string) string
+		for _, child := range c.Normalizers {
+			parsed, err := parseNormalizer(child)
+			if err != nil {
+				return nil, err
+			}
+			normalizers = append(normalizers, parsed...)
+		}
+		return normalizers, nil
+	case "Prepend":
+		return []func(string) string{func(s string) string {
+			if s == "" {
+				return s
+			}
+			return c.Prepend + s
+		}}, nil
+	case "Replace":
+		if c.Pattern.String != nil {
+			old := *c.Pattern.String
+			return []func(string) string{func(s string) string { return strings.ReplaceAll(s, old, c.Content) }}, nil
+		}
+		re, err := compileHFPattern(c.Pattern)
+		if err != nil {
+			return nil, err
+		}
+		return []func(string) string{func(s string) string {
+			replaced, err := re.Replace(s, c.Content, -1, -1)
+			if err != nil {
+				return s
+			}
+			return replaced
+		}}, nil
+	case "Lowercase":
+		return []func(string) string{strings.ToLower}, nil
+	case "Strip":
+		return []func(string) string{func(s string) string {
+			if c.StripLeft {
+				s = strings.TrimLeftFunc(s, unicode.IsSpace)
+			}
+			if c.StripRight {
+				s = strings.TrimRightFunc(s, unicode.IsSpace)
+			}
+			return s
+		}}, nil
+	case "NFC":
+		return []func(string) string{norm.NFC.String}, nil
+	case "NFD":
+		return []func(string) string{norm.NFD.String}, nil
+	case "NFKC":
+		return []func(string) string{norm.NFKC.String}, nil
+	case "NFKD":
+		return []func(string) string{norm.NFKD.String}, nil
+	}
+	return nil, fmt.Errorf("unsupported normalizer %s", c.Type)
+}
+
+func parsePreTokenizer(c hfComponent) ([]func([]string) []string, error) {
+	switch c.Type {
+	case "Sequence":
+		var preTokenizers []func([]string) []string
+		for _, child := range c.Pretokenizers {
+			parsed, err := parsePreTokenizer(child)
+			if err != nil {
+				return nil, err
+			}
+			preTokenizers = append(preTokenizers, parsed...)
+		}
+		return preTokenizers, nil
+
+	case "Split":
+		s := hfSplitter{literal: c.Pattern.String, behavior: c.Behavior, invert: c.Invert}
+		if s.literal == nil {
+			re, err := compileHFPattern(c.Pattern)
+			if err != nil {
+				return nil, err
+			}
+			s.re = re
+		}
+		return []func([]string) []string{s.splitAll}, nil
+
+	case "ByteLevel":
+		addPrefixSpace := c.AddPrefixSpace == nil || *c.AddPrefixSpace
+		var s *hfSplitter
+		if c.UseRegex == nil || *c.UseRegex {
+			s = &hfSplitter{re: regexp2.MustCompile(gpt2Pattern, regexp2.None), behavior: "Isolated"}
+		}
+		return []func([]string) []string{func(pieces []string) []string {
+			if addPrefixSpace && len(pieces) > 0 && !strings.HasPrefix(pieces[0], " ") {
+				pieces[0] = " " + pieces[0]
+			}
+			if s != nil {
+				pieces = s.splitAll(pieces)
+			}
+			for i, piece := range pieces {
+				pieces[i] = toByteLevel(piece)
+			}
+			return pieces
+		}}, nil
+
+	case "Metaspace":
+		replacement := c.Replacement
+		if replacement == "" {
+			replacement = "▁"
+		}
+		scheme := c.PrependScheme
+		if scheme == "" {
+			scheme = "never"
+			if c.AddPrefixSpace == nil || *c.AddPrefixSpace {
+				scheme = "always"
+			}
+		}
+		var s *hfSplitter
+		if c.Split == nil || *c.Split {
+			s = &hfSplitter{literal: &replacement, behavior: "MergedWithNext"}
+		}
+		return []func([]string) []string{func(pieces []string) []string {
+			for i, piece := range pieces {
+				piece = strings.ReplaceAll(piece, " ", replacement)
+				if (scheme == "always" || (scheme == "first" && i == 0)) && !strings.HasPrefix(piece, replacement) {
+					piece = replacement + piece
+				}
+				pieces[i] = piece
+			}
+			if s != nil {
+				pieces = s.splitAll(pieces)
+			}
+			return pieces
+		}}, nil
+
+	case "Whitespace":
+		s := hfSplitter{re: regexp2.MustCompile(`\w+|[^\w\s]+`, regexp2.None), behavior: "Removed", invert: true}
+		return []func([]string) []string{s.splitAll}, nil
+
+	case "WhitespaceSplit":
+		return []func([]string) []string{func(pieces []string) []string {
+			var out []string
+			for _, piece := range pieces {
+				out = append(out, strings.Fields(piece)...)
+			}
+			return out
+		}}, nil
+
+	case "Digits":
+		pattern := `\p{N}+`
+		if c.IndividualDigits {
+			pattern = `\p{N}`
+		}
+		s := hfSplitter{re: regexp2.MustCompile(pattern, regexp2.None), behavior: "Isolated"}
+		return []func([]string) []string{s.splitAll}, nil
+
+	case "Punctuation":
+		s := hfSplitter{re: regexp2.MustCompile(`\p{P}`, regexp2.None), behavior: c.Behavior}
+		if s.behavior == "" {
+			s.behavior = "Isolated"
+		}
+		return []func([]string) []string{s.splitAll}, nil
+	}
+	return nil, fmt.Errorf("unsupported pre-tokenizer %s", c.Type)
+}
+
+func compileHFPattern(p hfPattern) (*regexp2.Regexp, error) {
+	if p.Regex == nil {
+		return nil, errors.New("pattern has neither a String nor a Regex")
+	}
+	re, err := regexp2.Compile(*p.Regex, regexp2.None)
+	if err != nil {
+		return nil, fmt.Errorf("invalid pattern %q: %w", *p.Regex, err)
+	}
+	return re, nil
+}
+
+// toByteLevel replaces each byte of s with the character byte-level BPE represents it with.
+func toByteLevel(s string) string {
+	var b strings.Builder
+	for i := 0; i < len(s); i++ {
+		b.WriteRune(byteLevelAlphabet[s[i]])
+	}
+	return b.String()
+}
+
+// hfSplitter splits text on a literal or regex pattern, keeping, dropping or merging the
+// matches as the behaviour of a Split pre-tokeniser says: Isolated, Removed,
+// MergedWithPrevious, MergedWithNext or Contiguous. With invert, the matches are kept and
+// the text between them is split on instead.
+type hfSplitter struct {
+	literal  *string
+	re       *regexp2.Regexp
+	behavior string
+	invert   bool
+}
+
+// hfSegment is a part of split text and whether it is a delimiter.
+type hfSegment struct {
+	text      string
+	delimiter bool
+}
+
+func (s hfSplitter) splitAll(pieces []string) []string {
+	var out []string
+	for _, piece := range pieces {
+		out = append(out, s.split(piece)...)
+	}
+	return out
+}
+
+func (s hfSplitter) split(text string) []string {
+	var pieces []string
+	var next string // Delimiters merged with the next piece
+	prevDelimiter := false
+	for _, seg := range s.segments(text) {
+		switch {
+		case !seg.delimiter:
+			pieces = append(pieces, next+seg.text)
+			next = ""
+		case s.behavior == "Removed":
+		case s.behavior == "MergedWithPrevious" && len(pieces) > 0:
+			pieces[len(pieces)-1] += seg.text
+		case s.behavior == "MergedWithNext":
+			next += seg.text
+		case s.behavior == "Contiguous" && prevDelimiter:
+			pieces[len(pieces)-1] += seg.text
+		default:
+			pieces = append(pieces, seg.text)
+		}
+		prevDelimiter = seg.delimiter
+	}
+	if next != "" {
+		pieces = append(pieces, next)
+	}
+	return pieces
+}
+
+func (s hfSplitter) segments(text string) []hfSegment {
+	var segments []hfSegment
+	add := func(text string, delimiter bool) {
+		if text != "" {
+			segments = append(segments, hfSegment{text: text, delimiter: delimiter != s.invert})
+		}
+	}
+
+	if s.literal != nil {
+		if *s.literal == "" {
+			add(text, false)
+			return segments
+		}
+		for {
+			i := strings.Index(text, *s.literal)
+			if i < 0 {
+				break
+			}
+			add(text[:i], false)
+			add(*s.literal, true)
+			text = text[i+len(*s.literal):]
+		}
+		add(text, false)
+		return segments
+	}
+
+	// regexp2 gives match positions in runes
+	runes := []rune(text)
+	last := 0
+	match, _ := s.re.FindRunesMatch(runes)
+	for match != nil {
+		add(string(runes[last:match.Index]), false)
+		add(string(runes[match.Index:match.Index+match.Length]), true)
+		last = match.Index + match.Length
+		match, _ = s.re.FindNextMatch(match)
+	}
+	add(string(runes[last:]), false)
+	return segments
+}
+
+// count returns the number of tokens in content, using and adding to words, the token
+// counts of the words already seen.
+func (t *hfTokenizer) count(content string, words map[string]int) int {
+	for _, normalize := range t.normalizers {
+		content = normalize(content)
+	}
+	pieces := []string{content}
+	for _, preTokenize := range t.preTokenizers {
+		pieces = preTokenize(pieces)
+	}
+
+	total := 0
+	for _, piece := range pieces {
+		if piece == "" {
+			continue
+		}
+		count, ok := words[piece]
+		if !ok {
+			count = t.countWord(piece)
+			words[piece] = count
+		}
+		total += count
+	}
+	return total
+}
+
+func (t *hfTokenizer) countWord(word string) int {
+	if t.ignoreMerges {
+		if _, ok := t.vocab[word]; ok {
+			return 1
+		}
+	}
+
+	runes := []rune(word)
+	if len(runes) > maxBPEWord {
+		total := 0
+		for start := 0; start < len(runes); start += maxBPEWord {
+			total += t.countWord(string(runes[start:min(start+maxBPEWord, len(runes))]))
+		}
+		return total
+	}
+
+	symbols := make([]string, len(runes))
+	for i, r := range runes {
+		symbols[i] = string(r)
+		if i > 0 {
+			symbols[i] = t.prefix + symbols[i]
+		}
+	}
+	symbols[len(symbols)-1] += t.suffix
+
+	// Merge the lowest ranked pair until none of the pairs has a merge
+	for len(symbols) > 1 {
+		best, bestRank := -1, 0
+		for i := 0; i < len(symbols)-1; i++ {
+			if rank, ok := t.merges[[2]string{symbols[i], symbols[i+1]}]; ok && (best < 0 || rank < bestRank) {
+				best, bestRank = i, rank
+			}
+		}
+		if best < 0 {
+			break
+		}
+		merged := symbols[best] + strings.TrimPrefix(symbols[best+1], t.prefix)
+		symbols = append(symbols[:best+1], symbols[best+2:]...)
+		symbols[best] = merged
+	}
+
+	count := 0
+	for _, symbol := range symbols {
+		if _, ok := t.vocab[symbol]; ok || !t.byteFallback {
+			count++
+		} else {
+			// Byte fallback spells out unknown characters as a <0xXX> token per byte
+			count += len(strings.TrimSuffix(strings.TrimPrefix(symbol, t.prefix), t.suffix))
+		}
+	}
+	return count
+}
@@ -0,0 +1,69 @@
+package token
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func TestHuggingFaceCounter(t *testing.T) {
+	tests := []struct {
+		name      string
+		tokenizer string
+		content   string
+		expected  int
+	}{
+		{
+			name: "byte-level BPE",
+			tokenizer: `{
+				"pre_tokenizer": {"type": "ByteLevel", "add_prefix_space": false, "use_regex": true},
+				"model": {
+					"type": "BPE",
+					"vocab": {"h": 0, "e": 1, "l": 2, "o": 3, "Ġ": 4, "w": 5, "r": 6, "d": 7, "he": 8, "ll": 9, "hell": 10, "hello": 11},
+					"merges": ["h e", "l l", "he ll", "hell o"]
+				}
+			}`,
+			content:  "hello world",
+			expected: 7, // hello, then Ġ w o r l d
+		},
+		{
+			name: "metaspace with byte fallback",
+			tokenizer: `{
+				"pre_tokenizer": {"type": "Metaspace", "replacement": "▁", "prepend_scheme": "always", "split": true},
+				"model": {
+					"type": "BPE",
+					"vocab": {"▁": 0, "h": 1, "i": 2, "▁h": 3, "▁hi": 4},
+					"merges": [["▁", "h"], ["▁h", "i"]],
+					"byte_fallback": true
+				}
+			}`,
+			content:  "hi é",
+			expected: 4, // ▁hi, then ▁ and the two bytes of é
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			path := filepath.Join(t.TempDir(), "tokenizer.json")
+			if err := os.WriteFile(path, []byte(tt.tokenizer), 0644); err != nil {
+				t.Fatal(err)
+			}
+			if err := LoadHuggingFace(path); err != nil {
+				t.Fatalf("LoadHuggingFace: unexpected error: %v", err)
+			}
+
+			counter := NewCounter(HuggingFaceEncoding(path), false)
+			if got := counter.Count(tt.content); got != tt.expected {
+				t.Errorf("Count(%q): expected %d, got %d", tt.content, tt.expected, got)
+			}
+			// Counts of words already seen come from the counter's cache
+			if got := counter.Count(tt.content); got != tt.expected {
+				t.Errorf("Count(%q) again: expected %d, got %d", tt.content, tt.expected, got)
+			}
+		})
+	}
+
+	if err := LoadHuggingFace(filepath.Join(t.TempDir(), "missing.json")); err == nil {
+		t.Error("LoadHuggingFace of a missing file: expected error, got nil")
+	}
+}
@@ -2,7 +2,10 @@ package token
 
 import (
 	"fmt"
+	"slices"
+	"strings"
 	"sync"
+	"sync/atomic"
 
 	"github.com/fatih/color"
 	"github.com/pkoukk/tiktoken-go"
@@ -12,6 +15,7 @@ import (
 var (
 	apiUsedOnce    sync.Once
 	apiWarningOnce sync.Once
+	apiFellBack    atomic.Bool // Whether a count fell back to the offline tokeniser
 )
 
 // CorrectionMultiplier is applied to offline token counts for better accuracy.
@@ -20,18 +24,119 @@ var (
 // A 1.18x multiplier reduces average error from ~17% to ~2%.
 const CorrectionMultiplier = 1.18
 
+// Backends are the tokenisers tokens can be counted with: tiktoken encodings, Hugging Face
+// tokenizer.json files, or the Anthropic API.
+var Backends = []string{"tiktoken", "huggingface", "anthropic"}
+
+// ModelEncodings maps known model names to the tiktoken encoding they use.
+var ModelEncodings = map[string]string{
+	"gpt-4o":                 "o200k_base",
+	"gpt-4o-mini":            "o200k_base",
+	"gpt-4.1":                "o200k_base",
+	"gpt-4.5":                "o200k_base",
+	"o1":                     "o200k_base",
+	"o3":                     "o200k_base",
+	"gpt-4":                  "cl100k_base",
+	"gpt-4-turbo":            "cl100k_base",
+	"gpt-3.5-turbo":          "cl100k_base",
+	"text-embedding-3-small": "cl100k_base",
+	"text-embedding-3-large": "cl100k_base",
+	"text-embedding-ada-002": "cl100k_base",
+}
+
+// SupportedModels returns the sorted list of model names known to EncodingForModel.
+func SupportedModels() []string {
+	models := make([]string, 0, len(ModelEncodings))
+	for model := range ModelEncodings {
+		models = append(models, model)
+	}
+	slices.Sort(models)
+	return models
+}
+
+// EncodingForModel returns the tiktoken encoding used by the given model name.
+// Unknown model names return an error listing the supported names.
+func EncodingForModel(model string) (string, error) {
+	encoding, ok := ModelEncodings[strings.ToLower(strings.TrimSpace(model))]
+	if !ok {
+		return "", fmt.Errorf("unknown model %q, supported models: %s", model, strings.Join(SupportedModels(), ", "))
+	}
+	return encoding, nil
+}
+
+// ModelContexts maps known model names to their context window in tokens, for --model-context.
+var ModelContexts = map[string]int{
+	"gpt-4o":            128000,
+	"gpt-4o-mini":       128000,
+	"gpt-4.1":           1047576,
+	"gpt-4.1-mini":      1047576,
+	"gpt-4.5":           128000,
+	"gpt-5":             400000,
+	"o1":                200000,
+	"o3":                200000,
+	"o4-mini":           200000,
+	"gpt-4":             8192,
+	"gpt-4-turbo":       128000,
+	"gpt-3.5-turbo":     16385,
+	"claude-opus-4-1":   200000,
+	"claude-sonnet-4-5": 200000,
+	"claude-haiku-4-5":  200000,
+	"claude-3-5-haiku":  200000,
+	"gemini-2.5-pro":    1048576,
+	"gemini-2.5-flash":  1048576,
+	"llama-3.1":         131072,
+	"qwen3":             131072,
+}
+
+// ContextForModel returns the context window in tokens of the given model name. Unknown model
+// names return an error listing the known names.
+func ContextForModel(model string) (int, error) {
+	size, ok := ModelContexts[strings.ToLower(strings.TrimSpace(model))]
+	if !ok {
+		models := make([]string, 0, len(ModelContexts))
+		for name := range ModelContexts {
+			models = append(models, name)
+		}
+		slices.Sort(models)
+		return 0, fmt.Errorf("unknown model %q, known models: %s", model, strings.Join(models, ", "))
+	}
+	return size, nil
+}
+
+// Encodings are the offline tokeniser encodings, by their short and full names.
+var Encodings = map[string]string{
+	"cl100k":      "cl100k_base",
+	"cl100k_base": "cl100k_base",
+	"o200k":       "o200k_base",
+	"o200k_base":  "o200k_base",
+	"p50k":        "p50k_base",
+	"p50k_base":   "p50k_base",
+	"r50k":        "r50k_base",
+	"r50k_base":   "r50k_base",
+}
+
+// ParseEncoding returns the full name of an offline tokeniser encoding, accepting short names
+// such as o200k.
+func ParseEncoding(name string) (string, error) {
+	encoding, ok := Encodings[strings.ToLower(strings.TrimSpace(name))]
+	if !ok {
+		return "", fmt.Errorf("unknown encoding %q, must be one of o200k_base, cl100k_base, p50k_base or r50k_base", name)
+	}
+	return encoding, nil
+}
+
 func GetTokenizer(encoding string) *tiktoken.Tiktoken {
 	var err error
 	var tk *tiktoken.Tiktoken
 
 	switch encoding {
-	case "o200k", "gpt-4o", "gpt-4.1", "gpt-4.5":
+	case "o200k", "o200k_base", "gpt-4o", "gpt-4.1", "gpt-4.5":
 		tk, err = tiktoken.GetEncoding("o200k_base")
-	case "cl100k", "llama3", "llama-3", "gpt-4", "gpt-3.5-turbo", "text-embedding-3-large", "text-embedding-3-small", "text-embedding-ada-002", "text-ada-002":
+	case "cl100k", "cl100k_base", "llama3", "llama-3", "gpt-4", "gpt-3.5-turbo", "text-embedding-3-large", "text-embedding-3-small", "text-embedding-ada-002", "text-ada-002":
 		tk, err = tiktoken.GetEncoding("cl100k_base")
-	case "p50k":
+	case "p50k", "p50k_base":
 		tk, err = tiktoken.GetEncoding("p50k_base")
-	case "r50k", "gpt2", "text-ada-001", "text-curie-001", "text-babbage-001":
+	case "r50k", "r50k_base", "gpt2", "text-ada-001", "text-curie-001", "text-babbage-001":
 		tk, err = tiktoken.GetEncoding("r50k_base")
 	default:
 		// Default to o200k_base for modern Anthropic Claude and OpenAI models
@@ -39,59 +144,104 @@ func GetTokenizer(encoding string) *tiktoken.Tiktoken {
 	}
 
 	if err != nil {
-		fmt.Printf("Failed to get tokenizer: %v\n", err)
+		utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to get tokenizer: %v", err), color.FgYellow)
 		return nil
 	}
 	return tk
 }
 
 func GetModelInfo(encoding string) string {
+	if source, ok := HuggingFaceSource(encoding); ok {
+		return "Hugging Face tokeniser " + source
+	}
+
 	switch encoding {
-	case "o200k", "gpt-4o", "gpt-4.1", "gpt-4.5":
+	case "o200k", "o200k_base", "gpt-4o", "gpt-4.1", "gpt-4.5":
 		return "OpenAI gpt-4+, Anthropic Claude Haiku/Sonnet/Opus 3+ models"
-	case "cl100k":
+	case "cl100k", "cl100k_base":
 		return "Llama3, OpenAI <4o models, text-embedding-ada-002, gpt-4 etc..."
-	case "p50k":
+	case "p50k", "p50k_base":
 		return "OpenAI code models, text-davinci-002, text-davinci-003 etc..."
-	case "r50k", "gpt2", "llama2", "llama-2":
+	case "r50k", "r50k_base", "gpt2", "llama2", "llama-2":
 		return "Legacy models like llama2, GPT-3, davinci etc..."
 	default:
 		return "OpenAI gpt-4+, Anthropic Claude Haiku/Sonnet/Opus 3+ models"
 	}
 }
 
+// Label names the tokeniser that counts with encoding, or with the Anthropic API, to label
+// the counts it produces, as counts from different tokenisers differ. Once a count has fallen
+// back from the API to the offline tokeniser, the offline tokeniser is named.
+func Label(encoding string, useAnthropicAPI bool) string {
+	if useAnthropicAPI && !apiFellBack.Load() {
+		return "Anthropic API, " + AnthropicModel
+	}
+	if source, ok := HuggingFaceSource(encoding); ok {
+		return "Hugging Face " + source
+	}
+	if name, err := ParseEncoding(encoding); err == nil {
+		encoding = name
+	}
+	return "tiktoken " + encoding
+}
+
 func CountTokens(rendered string, encoding string, useAnthropicAPI bool, noCorrection bool) int {
 	if useAnthropicAPI {
 		count, err := CountTokensAPI(rendered)
 		if err != nil {
 			apiWarningOnce.Do(func() {
-				fmt.Printf("Warning: Failed to count tokens using Anthropic API: %v\nFalling back to offline tokeniser\n", err)
+				utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to count tokens using Anthropic API: %v. Falling back to offline tokeniser", err), color.FgYellow)
 			})
+			apiFellBack.Store(true)
 			// Fall back to offline tokenizer
 		} else {
 			apiUsedOnce.Do(func() {
-				fmt.Println() // Add blank line for visibility
 				utils.PrintColouredMessage("✓", fmt.Sprintf("Using Anthropic API (%s) for token counting", AnthropicModel), color.FgYellow)
 			})
 			return count
 		}
 	}
 
-	tk := GetTokenizer(encoding)
-	if tk == nil {
-		return 0
+	return NewCounter(encoding, noCorrection).Count(rendered)
+}
+
+// Counter counts tokens offline with its own tokeniser instance. A Counter is not safe for
+// concurrent use, so each goroutine should create its own.
+type Counter struct {
+	tk           *tiktoken.Tiktoken
+	hf           *hfTokenizer
+	words        map[string]int // Token counts of the words hf has counted, as BPE is slow
+	noCorrection bool
+}
+
+// NewCounter returns a Counter for the given encoding. Hugging Face tokenisers are counted
+// as they are, without the correction factor, which is tuned for tiktoken encodings.
+func NewCounter(encoding string, noCorrection bool) *Counter {
+	if source, ok := HuggingFaceSource(encoding); ok {
+		hf, err := loadHuggingFace(source)
+		if err != nil {
+			utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to get tokenizer: %v", err), color.FgYellow)
+			return &Counter{}
+		}
+		return &Counter{hf: hf, words: make(map[string]int)}
 	}
+	return &Counter{tk: GetTokenizer(encoding), noCorrection: noCorrection}
+}
 
-	tokens := tk.Encode(rendered, nil, nil)
-	rawCount := len(tokens)
+// Count returns the token count of content, or 0 if the tokeniser could not be loaded.
+func (c *Counter) Count(content string) int {
+	if c.hf != nil {
+		return c.hf.count(content, c.words)
+	}
+	if c.tk == nil {
+		return 0
+	}
 
-	// Apply correction multiplier for better accuracy unless disabled
-	if noCorrection {
+	rawCount := len(c.tk.Encode(content, nil, nil))
+	if c.noCorrection {
 		return rawCount
 	}
-
-	correctedCount := float64(rawCount) * CorrectionMultiplier
-	return int(correctedCount)
+	return int(float64(rawCount) * CorrectionMultiplier)
 }
 
 // CountTokensBatch counts tokens for multiple strings, using parallel API calls if Anthropic API is enabled.
@@ -105,12 +255,12 @@ func CountTokensBatch(contents []string, encoding string, useAnthropicAPI bool,
 		counts, err := CountTokensBatchAPI(contents, 4)
 		if err != nil {
 			apiWarningOnce.Do(func() {
-				fmt.Printf("Warning: Failed to count tokens using Anthropic API: %v\nFalling back to offline tokeniser\n", err)
+				utils.PrintColouredMessage("⚠️", fmt.Sprintf("Failed to count tokens using Anthropic API: %v. Falling back to offline tokeniser", err), color.FgYellow)
 			})
+			apiFellBack.Store(true)
 			// Fall back to offline tokeniser for all items
 		} else {
 			apiUsedOnce.Do(func() {
-				fmt.Println() // Add blank line for visibility
 				utils.PrintColouredMessage("✓", fmt.Sprintf("Using Anthropic API (%s) for token counting", AnthropicModel), color.FgYellow)
 			})
 			return counts
@@ -119,22 +269,24 @@ func CountTokensBatch(contents []string, encoding string, useAnthropicAPI bool,
 
 	// Use offline tokeniser for all items
 	results := make([]int, len(contents))
-	tk := GetTokenizer(encoding)
-	if tk == nil {
-		return results
-	}
-
+	counter := NewCounter(encoding, noCorrection)
 	for i, content := range contents {
-		tokens := tk.Encode(content, nil, nil)
-		rawCount := len(tokens)
-
-		if noCorrection {
-			results[i] = rawCount
-		} else {
-			correctedCount := float64(rawCount) * CorrectionMultiplier
-			results[i] = int(correctedCount)
-		}
+		results[i] = counter.Count(content)
 	}
 
 	return results
 }
+
+// CountEach counts content once with each of the offline encodings, in parallel. Counts are in
+// the same order as the encodings.
+func CountEach(content string, encodings []string, noCorrection bool) []int {
+	counts := make([]int, len(encodings))
+	var wg sync.WaitGroup
+	for i, encoding := range encodings {
+		wg.Go(func() {
+			counts[i] = NewCounter(encoding, noCorrection).Count(content)
+		})
+	}
+	wg.Wait()
+	return counts
+}
@@ -0,0 +1,59 @@
+package token
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestEncodingForModel(t *testing.T) {
+	tests := []struct {
+		model         string
+		expected      string
+		expectedError bool
+	}{
+		{"gpt-4o", "o200k_base", false},
+		{"GPT-4", "cl100k_base", false},
+		{"gpt-3.5-turbo", "cl100k_base", false},
+		{"text-embedding-3-small", "cl100k_base", false},
+		{"not-a-model", "", true},
+	}
+
+	for _, tt := range tests {
+		encoding, err := EncodingForModel(tt.model)
+		if tt.expectedError {
+			if err == nil {
+				t.Errorf("EncodingForModel(%s): expected error, got nil", tt.model)
+			} else if !strings.Contains(err.Error(), "gpt-4o") {
+				t.Errorf("EncodingForModel(%s): error should list supported models, got: %v", tt.model, err)
+			}
+			continue
+		}
+		if err != nil {
+			t.Errorf("EncodingForModel(%s): unexpected error: %v", tt.model, err)
+		}
+		if encoding != tt.expected {
+			t.Errorf("EncodingForModel(%s): expected %s, got %s", tt.model, tt.expected, encoding)
+		}
+	}
+}
+
+func TestContextForModel(t *testing.T) {
+	if size, err := ContextForModel(" GPT-4o "); err != nil || size != 128000 {
+		t.Errorf("ContextForModel(gpt-4o): expected 128000, got %d, %v", size, err)
+	}
+	if _, err := ContextForModel("not-a-model"); err == nil || !strings.Contains(err.Error(), "claude-sonnet-4-5") {
+		t.Errorf("ContextForModel(not-a-model): expected an error listing the known models, got %v", err)
+	}
+}
+
+func TestParseEncoding(t *testing.T) {
+	for name, expected := range map[string]string{"o200k": "o200k_base", "CL100K_BASE": "cl100k_base", " p50k ": "p50k_base"} {
+		encoding, err := ParseEncoding(name)
+		if err != nil || encoding != expected {
+			t.Errorf("ParseEncoding(%q): expected %s, got %s (%v)", name, expected, encoding, err)
+		}
+	}
+	if _, err := ParseEncoding("gpt-4o"); err == nil {
+		t.Error("Expected model names to be rejected")
+	}
+}
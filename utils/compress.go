@@ -0,0 +1,68 @@
+package utils
+
+import (
+	"bytes"
+	"compress/gzip"
+	"fmt"
+	"io"
+	"path/filepath"
+	"strings"
+
+	"github.com/klauspost/compress/zstd"
+)
+
+// OutputCompressions are the accepted values of --compress-output.
+var OutputCompressions = []string{"gzip", "zstd"}
+
+// CompressOutput compresses content with method, gzip or zstd, or if method is empty with the
+// compression implied by the extension of path (.gz or .zst), returning it unchanged if there
+// is none.
+func CompressOutput(content, method, path string) ([]byte, error) {
+	var buf bytes.Buffer
+	writer, err := CompressWriter(&buf, method, path)
+	if err != nil {
+		return nil, err
+	}
+	if _, err := io.WriteString(writer, content); err != nil {
+		return nil, err
+	}
+	if err := writer.Close(); err != nil {
+		return nil, err
+	}
+	return buf.Bytes(), nil
+}
+
+// CompressWriter returns a writer that compresses what is written to it into w, choosing the
+// compression like CompressOutput. The compressed output is only complete once it is closed,
+// which doesn't close w.
+func CompressWriter(w io.Writer, method, path string) (io.WriteCloser, error) {
+	if method == "" {
+		switch strings.ToLower(filepath.Ext(path)) {
+		case ".gz":
+			method = "gzip"
+		case ".zst":
+			method = "zstd"
+		}
+	}
+
+	switch method {
+	case "":
+		return nopWriteCloser{w}, nil
+	case "gzip":
+		return gzip.NewWriter(w), nil
+	case "zstd":
+		writer, err := zstd.NewWriter(w)
+		if err != nil {
+			return nil, fmt.Errorf("failed to zstd output: %w", err)
+		}
+		return writer, nil
+	default:
+		return nil, fmt.Errorf("unknown output compression %q (valid: %s)", method, strings.Join(OutputCompressions, ", "))
+	}
+}
+
+type nopWriteCloser struct {
+	io.Writer
+}
+
+func (nopWriteCloser) Close() error { return nil }
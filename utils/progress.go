@@ -0,0 +1,102 @@
+package utils
+
+import (
+	"fmt"
+	"os"
+	"sync"
+	"time"
+
+	"github.com/schollz/progressbar/v3"
+)
+
+// Quiet suppresses informational messages and progress, leaving only the output and errors.
+var Quiet bool
+
+// Progress shows the phase of a run on stderr, with the number of files found while walking
+// and read so far, so piped output stays clean. A nil Progress does nothing, which is what
+// NewProgress returns when stderr isn't a terminal or Quiet is set.
+type Progress struct {
+	mu    sync.Mutex
+	bar   *progressbar.ProgressBar
+	total int
+}
+
+// NewProgress returns a Progress writing to stderr, or nil if it shouldn't be shown.
+func NewProgress() *Progress {
+	if Quiet || !IsTerminal(os.Stderr) {
+		return nil
+	}
+	return &Progress{}
+}
+
+// Phase starts a new phase of the run, such as counting tokens, shown as a spinner.
+func (p *Progress) Phase(description string) {
+	if p == nil {
+		return
+	}
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.total = 0
+	p.replace(newProgressBar(-1, description))
+}
+
+// Files reports that done files have been read out of total, or while the total is still 0,
+// that done files have been found by the walk. It is safe for concurrent use.
+func (p *Progress) Files(done, total int) {
+	if p == nil {
+		return
+	}
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	if total == 0 {
+		if p.bar == nil || p.total != 0 {
+			p.total = 0
+			p.replace(newProgressBar(-1, "Walking"))
+		}
+		p.bar.Describe(fmt.Sprintf("Walking: %s files found", FormatNumber(done)))
+	} else if p.total != total {
+		p.total = total
+		p.replace(newProgressBar(total, "Reading files"))
+	}
+	_ = p.bar.Set(done)
+}
+
+// Finish clears the progress from the terminal.
+func (p *Progress) Finish() {
+	if p == nil {
+		return
+	}
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.replace(nil)
+}
+
+// replace clears the current bar, if any, and shows bar in its place.
+func (p *Progress) replace(bar *progressbar.ProgressBar) {
+	if p.bar != nil {
+		_ = p.bar.Finish()
+	}
+	p.bar = bar
+	if bar != nil {
+		_ = bar.RenderBlank()
+	}
+}
+
+// newProgressBar returns a bar out of total on stderr, or a spinner if total is -1.
+func newProgressBar(total int, description string) *progressbar.ProgressBar {
+	return progressbar.NewOptions(total,
+		progressbar.OptionSetWriter(os.Stderr),
+		progressbar.OptionSetDescription(description),
+		progressbar.OptionSpinnerType(14),
+		progressbar.OptionShowCount(),
+		progressbar.OptionThrottle(100*time.Millisecond),
+		progressbar.OptionClearOnFinish(),
+		progressbar.OptionSetTheme(progressbar.Theme{
+			Saucer:        "=",
+			SaucerHead:    ">",
+			SaucerPadding: " ",
+			BarStart:      "[",
+			BarEnd:        "]",
+		}),
+	)
+}
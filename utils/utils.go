@@ -8,12 +8,13 @@ import (
 	"strconv"
 	"strings"
 	"syscall"
+	"time"
 	"unsafe"
 
 	"github.com/atotto/clipboard"
+	"github.com/bmatcuk/doublestar/v4"
 	"github.com/fatih/color"
 	"github.com/mitchellh/go-homedir"
-	"github.com/schollz/progressbar/v3"
 )
 
 type termSize struct {
@@ -70,27 +71,71 @@ func writeClipboard(text string) error {
 	return nil
 }
 
-func WriteToFile(outputPath string, rendered string) error {
-	err := os.WriteFile(outputPath, []byte(rendered), 0644)
+// WriteToFile writes rendered to outputPath, compressed with compression or as implied by the
+// file's extension (see CompressOutput).
+func WriteToFile(outputPath string, rendered string, compression string) error {
+	content, err := CompressOutput(rendered, compression, outputPath)
 	if err != nil {
+		return err
+	}
+	if err := os.WriteFile(outputPath, content, 0644); err != nil {
 		return fmt.Errorf("failed to write to file: %v", err)
 	}
-	fmt.Printf("%s Prompt written to file: %s\n", color.GreenString("✓"), outputPath)
 	return nil
 }
 
-func SetupSpinner(message string) *progressbar.ProgressBar {
-	return progressbar.NewOptions(-1,
-		progressbar.OptionSetDescription(message),
-		progressbar.OptionSpinnerType(14),
-		progressbar.OptionSetTheme(progressbar.Theme{
-			Saucer:        "=",
-			SaucerHead:    ">",
-			SaucerPadding: " ",
-			BarStart:      "[",
-			BarEnd:        "]",
-		}),
-	)
+// MatchPathSuffix reports whether the glob pattern matches the slash separated path or any
+// trailing part of it, so src/** matches both src/main.go and /home/me/repo/src/main.go.
+func MatchPathSuffix(pattern, path string) bool {
+	parts := strings.Split(filepath.ToSlash(path), "/")
+	for i := range parts {
+		if match, _ := doublestar.Match(pattern, strings.Join(parts[i:], "/")); match {
+			return true
+		}
+	}
+	return false
+}
+
+// WriteTempFile writes content to a new file in the system temp directory and returns its path.
+func WriteTempFile(content string) (string, error) {
+	file, err := os.CreateTemp("", "ingest-*.md")
+	if err != nil {
+		return "", fmt.Errorf("failed to create temp file: %w", err)
+	}
+	defer file.Close()
+
+	if _, err := file.WriteString(content); err != nil {
+		return "", fmt.Errorf("failed to write temp file: %w", err)
+	}
+	return file.Name(), nil
+}
+
+// IsTerminal reports whether f is a terminal rather than a pipe or file.
+func IsTerminal(f *os.File) bool {
+	stat, err := f.Stat()
+	return err == nil && stat.Mode()&os.ModeCharDevice != 0
+}
+
+// Page shows content in $PAGER, or less if it isn't set, printing it directly if no pager can
+// be run.
+func Page(content string) error {
+	pager := strings.Fields(os.Getenv("PAGER"))
+	if len(pager) == 0 {
+		pager = []string{"less", "-FRX"}
+	}
+	if _, err := exec.LookPath(pager[0]); err != nil {
+		_, err := fmt.Print(content)
+		return err
+	}
+
+	cmd := exec.Command(pager[0], pager[1:]...)
+	cmd.Stdin = strings.NewReader(content)
+	cmd.Stdout = os.Stdout
+	cmd.Stderr = os.Stderr
+	if err := cmd.Run(); err != nil {
+		return fmt.Errorf("failed to run pager %s: %w", pager[0], err)
+	}
+	return nil
 }
 
 func Label(path string) string {
@@ -104,11 +149,16 @@ func Label(path string) string {
 	return path
 }
 
+// PrintColouredMessage prints a message to stderr so it never mixes with output printed to
+// stdout. Only errors, shown in red, are printed when Quiet is set.
 func PrintColouredMessage(symbol string, message string, messageColor color.Attribute) {
+	if Quiet && messageColor != color.FgRed {
+		return
+	}
 	white := color.New(color.FgWhite, color.Bold).SprintFunc()
 	colouredMessage := color.New(messageColor).SprintFunc()
 
-	fmt.Printf("%s%s%s %s\n", white("["), white(symbol), white("]"), colouredMessage(message))
+	fmt.Fprintf(os.Stderr, "%s%s%s %s\n", white("["), white(symbol), white("]"), colouredMessage(message))
 }
 
 func EnsureConfigDirectories() error {
@@ -161,6 +211,79 @@ func FormatNumber(n int) string {
 	}
 }
 
+// sizeUnits are the suffixes accepted by ParseSize, longest first so "MB" is matched before "B".
+var sizeUnits = []struct {
+	suffix     string
+	multiplier float64
+}{
+	{"KIB", 1 << 10}, {"MIB", 1 << 20}, {"GIB", 1 << 30},
+	{"KB", 1 << 10}, {"MB", 1 << 20}, {"GB", 1 << 30},
+	{"K", 1 << 10}, {"M", 1 << 20}, {"G", 1 << 30},
+	{"B", 1},
+}
+
+// ParseSize parses a size in bytes, optionally with a KB, MB or GB suffix (powers of 1024),
+// such as "1048576", "512KB" or "1.5MB".
+func ParseSize(size string) (int64, error) {
+	s := strings.ToUpper(strings.TrimSpace(size))
+	multiplier := 1.0
+	for _, unit := range sizeUnits {
+		if strings.HasSuffix(s, unit.suffix) {
+			s = strings.TrimSpace(strings.TrimSuffix(s, unit.suffix))
+			multiplier = unit.multiplier
+			break
+		}
+	}
+
+	value, err := strconv.ParseFloat(s, 64)
+	if err != nil || value < 0 {
+		return 0, fmt.Errorf("invalid size %q, expected bytes or a value such as 512KB or 10MB", size)
+	}
+	return int64(value * multiplier), nil
+}
+
+// sinceLayouts are the date formats accepted by ParseSince, interpreted in local time.
+var sinceLayouts = []string{time.RFC3339, "2006-01-02T15:04:05", "2006-01-02 15:04", "2006-01-02"}
+
+// ParseSince parses a --since value into the time it refers to: a duration before now, such as
+// "24h", "90m" or "7d" (days are 24 hours and weeks "w" are 7 days), or a date such as
+// "2024-01-01" or an RFC 3339 timestamp.
+func ParseSince(value string, now time.Time) (time.Time, error) {
+	s := strings.TrimSpace(value)
+	for _, layout := range sinceLayouts {
+		if t, err := time.ParseInLocation(layout, s, time.Local); err == nil {
+			return t, nil
+		}
+	}
+
+	// time.ParseDuration has no units above hours, so days and weeks are handled here
+	for suffix, unit := range map[string]time.Duration{"d": 24 * time.Hour, "w": 7 * 24 * time.Hour} {
+		if number, ok := strings.CutSuffix(s, suffix); ok {
+			if n, err := strconv.ParseFloat(number, 64); err == nil && n >= 0 {
+				return now.Add(-time.Duration(n * float64(unit))), nil
+			}
+		}
+	}
+	if d, err := time.ParseDuration(s); err == nil && d >= 0 {
+		return now.Add(-d), nil
+	}
+	return time.Time{}, fmt.Errorf("invalid time %q, expected a duration such as 24h or 7d, or a date such as 2024-01-01", value)
+}
+
+// FormatSize formats a size in bytes for display, such as "1.5 MB".
+func FormatSize(bytes int64) string {
+	switch {
+	case bytes >= 1<<30:
+		return fmt.Sprintf("%.1f GB", float64(bytes)/(1<<30))
+	case bytes >= 1<<20:
+		return fmt.Sprintf("%.1f MB", float64(bytes)/(1<<20))
+	case bytes >= 1<<10:
+		return fmt.Sprintf("%.1f KB", float64(bytes)/(1<<10))
+	default:
+		return fmt.Sprintf("%d B", bytes)
+	}
+}
+
 func GetTerminalWidth() int {
 	ws := &termSize{}
 	retCode, _, _ := syscall.Syscall(syscall.SYS_IOCTL,
@@ -0,0 +1,78 @@
+package utils
+
+import (
+	"bytes"
+	"compress/gzip"
+	"io"
+	"testing"
+	"time"
+
+	"github.com/klauspost/compress/zstd"
+)
+
+func TestParseSince(t *testing.T) {
+	now := time.Date(2024, 6, 15, 12, 0, 0, 0, time.Local)
+	tests := []struct {
+		value    string
+		expected time.Time
+	}{
+		{"24h", now.Add(-24 * time.Hour)},
+		{"90m", now.Add(-90 * time.Minute)},
+		{"7d", now.Add(-7 * 24 * time.Hour)},
+		{"2w", now.Add(-14 * 24 * time.Hour)},
+		{"2024-01-01", time.Date(2024, 1, 1, 0, 0, 0, 0, time.Local)},
+		{"2024-01-01T09:30:00Z", time.Date(2024, 1, 1, 9, 30, 0, 0, time.UTC)},
+	}
+	for _, tt := range tests {
+		got, err := ParseSince(tt.value, now)
+		if err != nil {
+			t.Errorf("ParseSince(%q): unexpected error: %v", tt.value, err)
+			continue
+		}
+		if !got.Equal(tt.expected) {
+			t.Errorf("ParseSince(%q): expected %v, got %v", tt.value, tt.expected, got)
+		}
+	}
+
+	for _, value := range []string{"", "yesterday", "-5h", "2024-13-01"} {
+		if _, err := ParseSince(value, now); err == nil {
+			t.Errorf("ParseSince(%q): expected an error", value)
+		}
+	}
+}
+
+func TestCompressOutputGzip(t *testing.T) {
+	compressed, err := CompressOutput("hello\n", "", "prompt.md.gz")
+	if err != nil {
+		t.Fatalf("CompressOutput failed: %v", err)
+	}
+	reader, err := gzip.NewReader(bytes.NewReader(compressed))
+	if err != nil {
+		t.Fatalf("Expected gzip output: %v", err)
+	}
+	content, err := io.ReadAll(reader)
+	if err != nil || string(content) != "hello\n" {
+		t.Errorf("Expected the gzip output to decompress to the content, got %q (%v)", content, err)
+	}
+
+	plain, err := CompressOutput("hello\n", "", "prompt.md")
+	if err != nil || string(plain) != "hello\n" {
+		t.Errorf("Expected other files to be left uncompressed, got %q (%v)", plain, err)
+	}
+}
+
+func TestCompressOutputZstd(t *testing.T) {
+	compressed, err := CompressOutput("hello\n", "", "prompt.md.zst")
+	if err != nil {
+		t.Fatalf("CompressOutput failed: %v", err)
+	}
+	reader, err := zstd.NewReader(bytes.NewReader(compressed))
+	if err != nil {
+		t.Fatalf("Expected zstd output: %v", err)
+	}
+	defer reader.Close()
+	content, err := io.ReadAll(reader)
+	if err != nil || string(content) != "hello\n" {
+		t.Errorf("Expected the zstd output to decompress to the content, got %q (%v)", content, err)
+	}
+}
@@ -3,9 +3,14 @@
 package web
 
 import (
+	"errors"
+	"fmt"
 	"io"
+	"mime"
 	"net/http"
 	"net/url"
+	"os"
+	"strconv"
 	"strings"
 	"sync"
 	"time"
@@ -14,13 +19,33 @@ import (
 	"github.com/JohannesKaufmann/html-to-markdown/plugin"
 	"github.com/PuerkitoBio/goquery"
 	"github.com/bmatcuk/doublestar/v4"
+	"github.com/fatih/color"
+	"github.com/sammcj/ingest/pdf"
+	"github.com/sammcj/ingest/utils"
+)
+
+const (
+	// defaultRetryDelay is how long to wait before retrying a request whose response doesn't say,
+	// doubled after each attempt.
+	defaultRetryDelay = time.Second
+
+	// maxRetryWait caps the wait before a retry, so a large Retry-After doesn't stall a crawl.
+	maxRetryWait = time.Minute
 )
 
 type CrawlOptions struct {
-	MaxDepth       int
+	MaxDepth       int // Link depth to follow from the start page, 0 fetches only the start page
+	MaxPages       int // Maximum number of pages to fetch, 0 for no limit
 	AllowedDomains []string
-	Timeout        int
-	ConcurrentJobs int
+	Timeout        time.Duration // Timeout of each request, including reading its body, 0 for none
+	Retries        int           // Times a request that fails or gets a 429 or 503 response is retried
+	ConcurrentJobs int           // Maximum number of pages fetched at once, defaults to 1
+	IgnoreRobots   bool          // Fetch pages even if robots.txt disallows them
+	Delay          time.Duration // Minimum time between requests to the same host
+	UserAgent      string
+	NoReadability  bool           // Convert the full page rather than only its main content
+	Headers        http.Header    // Sent with every request, such as an API token
+	Cookies        []*http.Cookie // Sent with every request, such as a login session
 }
 
 type WebPage struct {
@@ -39,19 +64,20 @@ type Crawler struct {
 	options         CrawlOptions
 	converter       *md.Converter
 	excludePatterns []string
-	initialPath     string // Store the initial URL path
-	singlePageMode  bool   // True if crawling a specific page
+	host            string // Host of the start URL, links to other hosts are never followed
+	client          *http.Client
+	robots          *robotsCache
+	delayLock       sync.Mutex
+	nextRequest     map[string]time.Time // Host -> earliest time of the next request
+	retryDelay      time.Duration        // Wait before the first retry of a request, doubled after each
 }
 
 func NewCrawler(options CrawlOptions, startURL string) *Crawler {
-	parsedURL, err := url.Parse(startURL)
-	initialPath := "/"
-	singlePageMode := false
-
-	if err == nil && parsedURL.Path != "" && parsedURL.Path != "/" {
-		initialPath = strings.TrimSuffix(parsedURL.Path, "/")
-		singlePageMode = true
+	host := ""
+	if parsedURL, err := url.Parse(startURL); err == nil {
+		host = strings.ToLower(parsedURL.Host)
 	}
+
 	// Create a new converter with GitHub Flavored Markdown support
 	converter := md.NewConverter("", true, &md.Options{
 		// Configure the converter to handle common edge cases
@@ -72,12 +98,165 @@ func NewCrawler(options CrawlOptions, startURL string) *Crawler {
 	converter.Remove("script", "style", "iframe", "noscript") // Remove unwanted elements
 
 	return &Crawler{
-		visited:        make(map[string]bool),
-		options:        options,
-		converter:      converter,
-		initialPath:    initialPath,
-		singlePageMode: singlePageMode,
+		visited:     make(map[string]bool),
+		options:     options,
+		converter:   converter,
+		host:        host,
+		client:      &http.Client{Timeout: options.Timeout, CheckRedirect: options.checkRedirect},
+		robots:      newRobotsCache(),
+		nextRequest: make(map[string]time.Time),
+		retryDelay:  defaultRetryDelay,
+	}
+}
+
+// get fetches a URL, retrying as request does.
+func (c *Crawler) get(urlStr string) (*http.Response, error) {
+	return c.request(http.MethodGet, urlStr)
+}
+
+// request sends a request with the crawler's User-Agent, waiting first so requests to the same
+// host are at least the crawl delay apart. A request that fails, times out or gets a 429 or 503
+// response is retried up to Retries times, after the response's Retry-After or else a delay
+// that doubles with each attempt. If the retries run out on a 429 or 503 response, its status
+// is returned as the error.
+func (c *Crawler) request(method, urlStr string) (*http.Response, error) {
+	for attempt := 0; ; attempt++ {
+		req, err := http.NewRequest(method, urlStr, nil)
+		if err != nil {
+			return nil, err
+		}
+		if c.options.UserAgent != "" {
+			req.Header.Set("User-Agent", c.options.UserAgent)
+		}
+		for name, values := range c.options.Headers {
+			req.Header[name] = values
+		}
+		for _, cookie := range c.options.Cookies {
+			req.AddCookie(cookie)
+		}
+
+		c.waitForHost(req.URL.Host)
+		resp, err := c.client.Do(req)
+		if err == nil && !isRetryableStatus(resp.StatusCode) {
+			return resp, nil
+		}
+
+		wait := c.retryDelay << min(attempt, 16)
+		if err == nil {
+			if after, ok := retryAfter(resp.Header.Get("Retry-After")); ok {
+				wait = after
+			}
+			resp.Body.Close()
+			err = fmt.Errorf("%s", resp.Status)
+		}
+		if attempt >= c.options.Retries {
+			if attempt > 0 {
+				err = fmt.Errorf("%w, after %d retries", err, attempt)
+			}
+			return nil, err
+		}
+
+		wait = min(wait, maxRetryWait)
+		utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Retrying %s in %s: %v", urlStr, wait, err), color.FgCyan)
+		time.Sleep(wait)
+	}
+}
+
+// checkRedirect follows up to 10 redirects like the default policy, and keeps the headers given
+// in the options from being sent on to another host. The client already does this for cookies.
+func (o CrawlOptions) checkRedirect(req *http.Request, via []*http.Request) error {
+	if len(via) >= 10 {
+		return errors.New("stopped after 10 redirects")
+	}
+	if !strings.EqualFold(req.URL.Host, via[0].URL.Host) {
+		for name := range o.Headers {
+			req.Header.Del(name)
+		}
+	}
+	return nil
+}
+
+// isRetryableStatus reports whether a response with the status code is worth retrying, as the
+// server is rate limiting or briefly unavailable.
+func isRetryableStatus(code int) bool {
+	return code == http.StatusTooManyRequests || code == http.StatusServiceUnavailable
+}
+
+// retryAfter parses a Retry-After header, either a number of seconds or a date.
+func retryAfter(header string) (time.Duration, bool) {
+	if header == "" {
+		return 0, false
+	}
+	if seconds, err := strconv.Atoi(strings.TrimSpace(header)); err == nil {
+		return time.Duration(max(seconds, 0)) * time.Second, true
+	}
+	if date, err := http.ParseTime(header); err == nil {
+		return max(time.Until(date), 0), true
+	}
+	return 0, false
+}
+
+// isPDF reports whether urlStr is a PDF, going by the Content-Type of a HEAD request.
+func (c *Crawler) isPDF(urlStr string) (bool, error) {
+	resp, err := c.request(http.MethodHead, urlStr)
+	if err != nil {
+		return false, err
+	}
+	resp.Body.Close()
+	mediaType, _, _ := mime.ParseMediaType(resp.Header.Get("Content-Type"))
+	return mediaType == "application/pdf", nil
+}
+
+// fetchPDF downloads the PDF at urlStr and extracts its text.
+func (c *Crawler) fetchPDF(urlStr string) (string, error) {
+	resp, err := c.get(urlStr)
+	if err != nil {
+		return "", err
+	}
+	defer resp.Body.Close()
+	if resp.StatusCode != http.StatusOK {
+		return "", fmt.Errorf("%s", resp.Status)
+	}
+
+	file, err := os.CreateTemp("", "ingest-*.pdf")
+	if err != nil {
+		return "", fmt.Errorf("failed to create temp file: %w", err)
+	}
+	defer os.Remove(file.Name())
+	defer file.Close()
+	if _, err := io.Copy(file, resp.Body); err != nil {
+		return "", fmt.Errorf("failed to save PDF: %w", err)
+	}
+	return pdf.ExtractText(file.Name())
+}
+
+func (c *Crawler) waitForHost(host string) {
+	if c.options.Delay <= 0 {
+		return
+	}
+
+	c.delayLock.Lock()
+	now := time.Now()
+	next := c.nextRequest[host]
+	if next.Before(now) {
+		next = now
+	}
+	c.nextRequest[host] = next.Add(c.options.Delay)
+	c.delayLock.Unlock()
+
+	time.Sleep(time.Until(next))
+}
+
+// robotsAllowed reports whether robots.txt permits fetching the URL.
+func (c *Crawler) robotsAllowed(urlStr string) bool {
+	if c.options.IgnoreRobots {
+		return true
 	}
+	parsedURL, err := url.Parse(urlStr)
+	if err != nil {
+		return false
+	}
+	return c.robots.allowed(parsedURL, c.options.UserAgent, c.get)
 }
 
 func (c *Crawler) SetExcludePatterns(patterns []string) {
@@ -108,47 +287,46 @@ func (c *Crawler) shouldExclude(urlStr string) bool {
 	return false
 }
 
-func (c *Crawler) hasVisited(urlStr string) bool {
-	c.visitedLock.Lock()
-	defer c.visitedLock.Unlock()
-	return c.visited[urlStr]
-}
-
-func (c *Crawler) markVisited(urlStr string) {
+// claim marks a URL as visited, returning false if it had already been visited or the page
+// limit has been reached. Claiming before fetching stops workers fetching the same page and
+// cycles from looping forever.
+func (c *Crawler) claim(urlStr string) bool {
 	c.visitedLock.Lock()
 	defer c.visitedLock.Unlock()
+	if c.visited[urlStr] {
+		return false
+	}
+	if c.options.MaxPages > 0 && len(c.visited) >= c.options.MaxPages {
+		return false
+	}
 	c.visited[urlStr] = true
+	return true
 }
 
+// fetchPage fetches and converts a claimed URL, returning nil if it isn't a page to include.
 func (c *Crawler) fetchPage(urlStr string, depth int) (*WebPage, error) {
-	if depth > c.options.MaxDepth {
+	if !c.robotsAllowed(urlStr) {
+		utils.PrintColouredMessage("ℹ️", fmt.Sprintf("Skipping %s, disallowed by robots.txt", urlStr), color.FgCyan)
 		return nil, nil
 	}
 
-	if c.hasVisited(urlStr) {
-		return nil, nil
+	resp, err := c.get(urlStr)
+	if err != nil {
+		return nil, err
 	}
+	defer resp.Body.Close()
 
-	if !c.isAllowed(urlStr) {
+	// Error pages, such as a 404 for a broken link, aren't content
+	if resp.StatusCode < 200 || resp.StatusCode > 299 {
+		utils.PrintColouredMessage("⚠️", fmt.Sprintf("Skipping %s, it returned %s", urlStr, resp.Status), color.FgYellow)
 		return nil, nil
 	}
 
-	if c.shouldExclude(urlStr) {
+	// Linked pages must be HTML and must not have redirected to another host
+	if depth > 0 && (!isHTML(resp.Header.Get("Content-Type")) || !c.isAllowed(resp.Request.URL.String())) {
 		return nil, nil
 	}
 
-	client := &http.Client{
-		Timeout: time.Duration(c.options.Timeout) * time.Second,
-	}
-
-	resp, err := client.Get(urlStr)
-	if err != nil {
-		return nil, err
-	}
-	defer resp.Body.Close()
-
-	c.markVisited(urlStr)
-
 	body, err := io.ReadAll(resp.Body)
 	if err != nil {
 		return nil, err
@@ -164,8 +342,16 @@ func (c *Crawler) fetchPage(urlStr string, depth int) (*WebPage, error) {
 	title := doc.Find("title").Text()
 	links := c.extractLinks(doc, urlStr)
 
+	// Strip navigation and other boilerplate unless the full page was asked for
+	html := string(body)
+	if !c.options.NoReadability {
+		if html, err = extractMainContent(doc); err != nil {
+			return nil, err
+		}
+	}
+
 	// Convert HTML to Markdown
-	markdown, err := c.converter.ConvertString(string(body))
+	markdown, err := c.converter.ConvertString(html)
 	if err != nil {
 		return nil, err
 	}
@@ -210,15 +396,31 @@ func (c *Crawler) resolveURL(base *url.URL, ref string) string {
 		return ""
 	}
 
+	// Fragments refer to the same page, so drop them to avoid fetching it again
+	resolvedURL.Fragment = ""
 	return resolvedURL.String()
 }
 
+// isHTML reports whether a Content-Type header is for an HTML document.
+func isHTML(contentType string) bool {
+	mediaType, _, err := mime.ParseMediaType(contentType)
+	if err != nil {
+		return false
+	}
+	return mediaType == "text/html" || mediaType == "application/xhtml+xml"
+}
+
 func (c *Crawler) isAllowed(urlStr string) bool {
 	parsedURL, err := url.Parse(urlStr)
 	if err != nil {
 		return false
 	}
 
+	// Off-origin links are never followed
+	if strings.ToLower(parsedURL.Host) != c.host {
+		return false
+	}
+
 	// Check domain restrictions if any
 	if len(c.options.AllowedDomains) > 0 {
 		domainAllowed := false
@@ -233,50 +435,73 @@ func (c *Crawler) isAllowed(urlStr string) bool {
 		}
 	}
 
-	// If we're in single page mode, only allow the exact same path
-	if c.singlePageMode {
-		currentPath := strings.TrimSuffix(parsedURL.Path, "/")
-		// Only allow the exact same path or same path with a fragment
-		return currentPath == c.initialPath
-	}
-
 	return true
 }
 
+// Crawl fetches startURL and the same-origin pages it links to, breadth first up to MaxDepth.
+// The pages at each depth are fetched by up to ConcurrentJobs workers at once, and once they
+// are all fetched their links are followed in the order they were found. So the pages, their
+// order and which are left out by MaxPages only depend on the site, not on which requests
+// finish first, and repeated crawls of a site that hasn't changed give the same output. Linked
+// pages that can't be fetched are reported and left out, only the start page failing is an
+// error.
 func (c *Crawler) Crawl(startURL string) ([]*WebPage, error) {
 	var pages []*WebPage
-	var pagesLock sync.Mutex
-	var wg sync.WaitGroup
-	semaphore := make(chan struct{}, c.options.ConcurrentJobs)
+	frontier := c.discover([]string{startURL})
+	for depth := 0; len(frontier) > 0; depth++ {
+		fetched, errs := c.fetchAll(frontier, depth)
+		if depth == 0 && errs[0] != nil {
+			return nil, fmt.Errorf("failed to fetch %s: %w", startURL, errs[0])
+		}
 
-	var crawlPage func(urlStr string, depth int)
-	crawlPage = func(urlStr string, depth int) {
-		defer wg.Done()
-		semaphore <- struct{}{}        // Acquire
-		defer func() { <-semaphore }() // Release
+		var links []string
+		for i, page := range fetched {
+			if errs[i] != nil {
+				utils.PrintColouredMessage("⚠️", fmt.Sprintf("Skipping %s, failed to fetch it: %v", frontier[i], errs[i]), color.FgYellow)
+			}
+			if page != nil {
+				pages = append(pages, page)
+				links = append(links, page.Links...)
+			}
+		}
+		if depth >= c.options.MaxDepth {
+			break
+		}
+		frontier = c.discover(links)
+	}
+	return pages, nil
+}
 
-		page, err := c.fetchPage(urlStr, depth)
-		if err != nil || page == nil {
-			return
+// discover claims the links that are to be crawled, in the order they are given, returning them.
+func (c *Crawler) discover(links []string) []string {
+	var urls []string
+	for _, link := range links {
+		if c.isAllowed(link) && !c.shouldExclude(link) && c.claim(link) {
+			urls = append(urls, link)
 		}
+	}
+	return urls
+}
 
-		pagesLock.Lock()
-		pages = append(pages, page)
-		pagesLock.Unlock()
+// fetchAll fetches the pages at urls concurrently, returning them in the same order with nil
+// for those that aren't included, along with the error of each page that failed.
+func (c *Crawler) fetchAll(urls []string, depth int) ([]*WebPage, []error) {
+	pages := make([]*WebPage, len(urls))
+	errs := make([]error, len(urls))
+	next := make(chan int)
 
-		if depth < c.options.MaxDepth {
-			for _, link := range page.Links {
-				if !c.hasVisited(link) && c.isAllowed(link) {
-					wg.Add(1)
-					go crawlPage(link, depth+1)
-				}
+	var wg sync.WaitGroup
+	for range min(max(c.options.ConcurrentJobs, 1), len(urls)) {
+		wg.Go(func() {
+			for i := range next {
+				pages[i], errs[i] = c.fetchPage(urls[i], depth)
 			}
-		}
+		})
 	}
-
-	wg.Add(1)
-	go crawlPage(startURL, 0)
+	for i := range urls {
+		next <- i
+	}
+	close(next)
 	wg.Wait()
-
-	return pages, nil
+	return pages, errs
 }
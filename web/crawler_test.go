@@ -0,0 +1,142 @@
+package web
+
+import (
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"reflect"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+)
+
+func TestCrawlOrder(t *testing.T) {
+	links := map[string][]string{
+		"/":  {"/a", "/b", "/c"},
+		"/a": {"/a1", "/b"},
+		"/b": {"/b1"},
+		"/c": nil,
+	}
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		// The first links finish last, so the order can't come from when requests finish
+		if r.URL.Path == "/a" {
+			time.Sleep(50 * time.Millisecond)
+		}
+		var body strings.Builder
+		fmt.Fprintf(&body, "<html><head><title>%s</title></head><body><p>Page %s</p>", r.URL.Path, r.URL.Path)
+		for _, link := range links[r.URL.Path] {
+			fmt.Fprintf(&body, `<a href="%s">%s</a>`, link, link)
+		}
+		body.WriteString("</body></html>")
+		w.Header().Set("Content-Type", "text/html")
+		fmt.Fprint(w, body.String())
+	}))
+	defer server.Close()
+
+	crawl := func(maxPages int) []string {
+		options := CrawlOptions{MaxDepth: 2, MaxPages: maxPages, ConcurrentJobs: 4, IgnoreRobots: true, NoReadability: true}
+		pages, err := NewCrawler(options, server.URL).Crawl(server.URL + "/")
+		if err != nil {
+			t.Fatalf("Crawl failed: %v", err)
+		}
+		var paths []string
+		for _, page := range pages {
+			paths = append(paths, strings.TrimPrefix(page.URL, server.URL))
+		}
+		return paths
+	}
+
+	expected := []string{"/", "/a", "/b", "/c", "/a1", "/b1"}
+	for range 3 {
+		if got := crawl(0); !reflect.DeepEqual(got, expected) {
+			t.Errorf("Expected %v, got %v", expected, got)
+		}
+	}
+	if got := crawl(4); !reflect.DeepEqual(got, expected[:4]) {
+		t.Errorf("Expected the first 4 pages found %v, got %v", expected[:4], got)
+	}
+}
+
+func TestCrawlRetries(t *testing.T) {
+	var mu sync.Mutex
+	requests := make(map[string]int)
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		mu.Lock()
+		requests[r.URL.Path]++
+		attempt := requests[r.URL.Path]
+		mu.Unlock()
+
+		switch {
+		case r.URL.Path == "/flaky" && attempt == 1:
+			w.Header().Set("Retry-After", "0")
+			w.WriteHeader(http.StatusServiceUnavailable)
+			return
+		case r.URL.Path == "/limited":
+			w.WriteHeader(http.StatusTooManyRequests)
+			return
+		}
+		w.Header().Set("Content-Type", "text/html")
+		fmt.Fprintf(w, `<html><body><p>Page %s</p><a href="/flaky">flaky</a><a href="/limited">limited</a></body></html>`, r.URL.Path)
+	}))
+	defer server.Close()
+
+	options := CrawlOptions{MaxDepth: 1, Retries: 2, IgnoreRobots: true, NoReadability: true}
+	crawler := NewCrawler(options, server.URL)
+	crawler.retryDelay = time.Millisecond
+	pages, err := crawler.Crawl(server.URL + "/")
+	if err != nil {
+		t.Fatalf("Crawl failed: %v", err)
+	}
+
+	var paths []string
+	for _, page := range pages {
+		paths = append(paths, strings.TrimPrefix(page.URL, server.URL))
+	}
+	if expected := []string{"/", "/flaky"}; !reflect.DeepEqual(paths, expected) {
+		t.Errorf("Expected the page that gave up to be skipped, leaving %v, got %v", expected, paths)
+	}
+	mu.Lock()
+	defer mu.Unlock()
+	if requests["/flaky"] != 2 || requests["/limited"] != 3 {
+		t.Errorf("Expected 2 requests for /flaky and 3 for /limited, got %v", requests)
+	}
+}
+
+func TestCrawlSkipsErrorPages(t *testing.T) {
+	statuses := map[string]int{"/missing": http.StatusNotFound, "/gone": http.StatusGone, "/broken": http.StatusInternalServerError}
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Content-Type", "text/html")
+		if status, ok := statuses[r.URL.Path]; ok {
+			w.WriteHeader(status)
+		}
+		fmt.Fprintf(w, `<html><body><p>Page %s</p><a href="/ok">ok</a><a href="/missing">missing</a><a href="/gone">gone</a><a href="/broken">broken</a></body></html>`, r.URL.Path)
+	}))
+	defer server.Close()
+
+	options := CrawlOptions{MaxDepth: 1, IgnoreRobots: true, NoReadability: true}
+	pages, err := NewCrawler(options, server.URL).Crawl(server.URL + "/")
+	if err != nil {
+		t.Fatalf("Crawl failed: %v", err)
+	}
+
+	var paths []string
+	for _, page := range pages {
+		paths = append(paths, strings.TrimPrefix(page.URL, server.URL))
+	}
+	if expected := []string{"/", "/ok"}; !reflect.DeepEqual(paths, expected) {
+		t.Errorf("Expected the error pages to be skipped, leaving %v, got %v", expected, paths)
+	}
+}
+
+func TestRetryAfter(t *testing.T) {
+	if wait, ok := retryAfter("5"); !ok || wait != 5*time.Second {
+		t.Errorf("Expected 5s, got %s, %v", wait, ok)
+	}
+	if wait, ok := retryAfter(time.Now().Add(-time.Hour).UTC().Format(http.TimeFormat)); !ok || wait != 0 {
+		t.Errorf("Expected a date in the past to be no wait, got %s, %v", wait, ok)
+	}
+	if _, ok := retryAfter("soon"); ok {
+		t.Error("Expected an invalid Retry-After to be ignored")
+	}
+}
@@ -0,0 +1,78 @@
+package web
+
+import (
+	"fmt"
+	"net/http"
+	"os"
+	"regexp"
+	"strings"
+)
+
+var (
+	// envReferenceRegex matches a ${NAME} reference to an environment variable.
+	envReferenceRegex = regexp.MustCompile(`\$\{([A-Za-z_][A-Za-z0-9_]*)\}`)
+
+	// headerNameRegex matches the characters a header's name can be made of.
+	headerNameRegex = regexp.MustCompile("^[!#$%&'*+.^_`|~0-9A-Za-z-]+$")
+)
+
+// ParseHeaders parses headers given as "Name: Value", expanding ${NAME} references to
+// environment variables in their values so secrets needn't be on the command line. A header
+// given more than once is sent with each value. Errors never include an expanded value.
+func ParseHeaders(headers []string) (http.Header, error) {
+	parsed := make(http.Header)
+	for _, header := range headers {
+		name, value, ok := strings.Cut(header, ":")
+		name = strings.TrimSpace(name)
+		if !ok || !headerNameRegex.MatchString(name) {
+			return nil, fmt.Errorf("%q is not a header of the form \"Name: Value\"", header)
+		}
+		value, err := expandEnv(strings.TrimSpace(value))
+		if err != nil {
+			return nil, fmt.Errorf("header %s: %w", name, err)
+		}
+		if strings.ContainsAny(value, "\r\n\x00") {
+			return nil, fmt.Errorf("header %s has an invalid value", name)
+		}
+		parsed.Add(name, value)
+	}
+	return parsed, nil
+}
+
+// ParseCookies parses cookies given as "name=value", or several separated by semicolons as in
+// a Cookie header, expanding ${NAME} references to environment variables like ParseHeaders.
+func ParseCookies(cookies []string) ([]*http.Cookie, error) {
+	var parsed []*http.Cookie
+	for _, cookie := range cookies {
+		name, _, _ := strings.Cut(cookie, "=")
+		line, err := expandEnv(cookie)
+		if err != nil {
+			return nil, fmt.Errorf("cookie %s: %w", strings.TrimSpace(name), err)
+		}
+		// The errors of parsing the expanded line don't include it, so a secret isn't shown
+		found, err := http.ParseCookie(line)
+		if err != nil {
+			return nil, fmt.Errorf("cookie %s is not of the form \"name=value\": %w", strings.TrimSpace(name), err)
+		}
+		parsed = append(parsed, found...)
+	}
+	return parsed, nil
+}
+
+// expandEnv replaces each ${NAME} in s with the value of the environment variable, failing if
+// one isn't set. A $ that isn't part of a reference is left as it is.
+func expandEnv(s string) (string, error) {
+	var missing string
+	expanded := envReferenceRegex.ReplaceAllStringFunc(s, func(reference string) string {
+		name := envReferenceRegex.FindStringSubmatch(reference)[1]
+		value, ok := os.LookupEnv(name)
+		if !ok && missing == "" {
+			missing = name
+		}
+		return value
+	})
+	if missing != "" {
+		return "", fmt.Errorf("environment variable %s is not set", missing)
+	}
+	return expanded, nil
+}
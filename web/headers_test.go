@@ -0,0 +1,90 @@
+package web
+
+import (
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+)
+
+func TestParseHeaders(t *testing.T) {
+	t.Setenv("INGEST_TEST_TOKEN", "s3cret")
+
+	headers, err := ParseHeaders([]string{"Authorization: Bearer ${INGEST_TEST_TOKEN}", "X-Price: $5", "X-Tag: a", "X-Tag: b"})
+	if err != nil {
+		t.Fatalf("ParseHeaders failed: %v", err)
+	}
+	if got := headers.Get("Authorization"); got != "Bearer s3cret" {
+		t.Errorf("Expected the token to be expanded, got %q", got)
+	}
+	if got := headers.Get("X-Price"); got != "$5" {
+		t.Errorf("Expected a $ that isn't a reference to be kept, got %q", got)
+	}
+	if got := headers.Values("X-Tag"); len(got) != 2 {
+		t.Errorf("Expected both values of a repeated header, got %v", got)
+	}
+
+	for _, header := range []string{"no colon", "Bad Name: x", "X-Token: ${INGEST_TEST_UNSET}"} {
+		if _, err := ParseHeaders([]string{header}); err == nil {
+			t.Errorf("Expected %q to be rejected", header)
+		}
+	}
+
+	cookies, err := ParseCookies([]string{"session=${INGEST_TEST_TOKEN}", "a=1; b=2"})
+	if err != nil {
+		t.Fatalf("ParseCookies failed: %v", err)
+	}
+	if len(cookies) != 3 || cookies[0].Name != "session" || cookies[0].Value != "s3cret" {
+		t.Errorf("Expected 3 cookies starting with session=s3cret, got %v", cookies)
+	}
+	if _, err := ParseCookies([]string{"session=${INGEST_TEST_TOKEN}\""}); err == nil || strings.Contains(err.Error(), "s3cret") {
+		t.Errorf("Expected an invalid cookie to be rejected without showing its value, got %v", err)
+	}
+}
+
+func TestCrawlSendsHeaders(t *testing.T) {
+	other := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.Header.Get("X-Token") != "" || r.Header.Get("Cookie") != "" {
+			t.Errorf("Expected no credentials to be sent to another host, got %v", r.Header)
+		}
+		w.Header().Set("Content-Type", "text/html")
+		fmt.Fprint(w, "<html><body><p>Elsewhere</p></body></html>")
+	}))
+	defer other.Close()
+
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.URL.Path == "/moved" {
+			http.Redirect(w, r, strings.Replace(other.URL, "127.0.0.1", "localhost", 1), http.StatusFound)
+			return
+		}
+		cookie, err := r.Cookie("session")
+		if r.Header.Get("X-Token") != "abc" || err != nil || cookie.Value != "xyz" {
+			w.WriteHeader(http.StatusUnauthorized)
+			return
+		}
+		w.Header().Set("Content-Type", "text/html")
+		fmt.Fprint(w, "<html><body><p>Secret docs</p></body></html>")
+	}))
+	defer server.Close()
+
+	options := CrawlOptions{
+		IgnoreRobots: true,
+		Headers:      http.Header{"X-Token": {"abc"}},
+		Cookies:      []*http.Cookie{{Name: "session", Value: "xyz"}},
+	}
+	crawler := NewCrawler(options, server.URL)
+	pages, err := crawler.Crawl(server.URL + "/")
+	if err != nil {
+		t.Fatalf("Crawl failed: %v", err)
+	}
+	if len(pages) != 1 || pages[0].StatusCode != http.StatusOK {
+		t.Fatalf("Expected the page to be fetched with the headers and cookies, got %+v", pages)
+	}
+
+	resp, err := crawler.get(server.URL + "/moved")
+	if err != nil {
+		t.Fatalf("Redirect failed: %v", err)
+	}
+	resp.Body.Close()
+}
@@ -9,7 +9,6 @@ import (
 	"strings"
 
 	"github.com/sammcj/ingest/filesystem"
-	"github.com/sammcj/ingest/pdf"
 )
 
 type CrawlResult struct {
@@ -18,14 +17,29 @@ type CrawlResult struct {
 }
 
 func ProcessWebURL(urlStr string, options CrawlOptions, excludePatterns []string) (*CrawlResult, error) {
+	// Validate URL
+	parsedURL, err := url.Parse(urlStr)
+	if err != nil {
+		return nil, fmt.Errorf("invalid URL: %w", err)
+	}
+
+	if !strings.HasPrefix(parsedURL.Scheme, "http") {
+		return nil, fmt.Errorf("URL must start with http:// or https://")
+	}
+
+	// Initialize crawler with the start URL, its requests are retried and time out as the
+	// options say for PDFs too
+	crawler := NewCrawler(options, urlStr)
+	crawler.SetExcludePatterns(excludePatterns)
+
 	// Check if URL points to a PDF
-	isPDF, err := pdf.IsPDF(urlStr)
+	isPDF, err := crawler.isPDF(urlStr)
 	if err != nil {
 		return nil, fmt.Errorf("error checking PDF: %w", err)
 	}
 
 	if isPDF {
-		content, err := pdf.ConvertPDFToMarkdown(urlStr, true)
+		content, err := crawler.fetchPDF(urlStr)
 		if err != nil {
 			return nil, fmt.Errorf("error converting PDF: %w", err)
 		}
@@ -40,20 +54,6 @@ func ProcessWebURL(urlStr string, options CrawlOptions, excludePatterns []string
 		}, nil
 	}
 
-	// Validate URL
-	parsedURL, err := url.Parse(urlStr)
-	if err != nil {
-		return nil, fmt.Errorf("invalid URL: %w", err)
-	}
-
-	if !strings.HasPrefix(parsedURL.Scheme, "http") {
-		return nil, fmt.Errorf("URL must start with http:// or https://")
-	}
-
-	// Initialize crawler with the start URL
-	crawler := NewCrawler(options, urlStr)
-	crawler.SetExcludePatterns(excludePatterns)
-
 	// Perform crawl
 	pages, err := crawler.Crawl(urlStr)
 	if err != nil {
@@ -71,7 +71,7 @@ func ProcessWebURL(urlStr string, options CrawlOptions, excludePatterns []string
 		files = append(files, filesystem.FileInfo{
 			Path:      page.URL,
 			Extension: ".md",
-			Code:      page.Content,
+			Code:      fmt.Sprintf("# %s\n\n%s", page.URL, page.Content),
 		})
 	}
 
@@ -83,23 +83,6 @@ func ProcessWebURL(urlStr string, options CrawlOptions, excludePatterns []string
 		treeString = fmt.Sprintf("Web Page: %s", files[0].Path)
 	}
 
-	// If we're crawling a specific page, only return that page's content
-	if parsedURL.Path != "/" && parsedURL.Path != "" {
-		for _, file := range files {
-			fileURL, err := url.Parse(file.Path)
-			if err != nil {
-				continue
-			}
-			// Find the exact matching path (ignoring trailing slashes)
-			if strings.TrimSuffix(fileURL.Path, "/") == strings.TrimSuffix(parsedURL.Path, "/") {
-				return &CrawlResult{
-					TreeString: fmt.Sprintf("Web Page: %s", file.Path),
-					Files:      []filesystem.FileInfo{file},
-				}, nil
-			}
-		}
-	}
-
 	return &CrawlResult{
 		TreeString: treeString,
 		Files:      files,
@@ -0,0 +1,80 @@
+// web/readability.go
+
+package web
+
+import (
+	"strings"
+
+	"github.com/PuerkitoBio/goquery"
+)
+
+// boilerplateSelector matches page furniture that is dropped before conversion.
+const boilerplateSelector = "nav, header, footer, aside, script, style, noscript, form, iframe, " +
+	"[role=navigation], [role=banner], [role=contentinfo], " +
+	"[id*=cookie], [class*=cookie], [id*=consent], [class*=consent]"
+
+// extractMainContent returns the HTML of the main content of a page. It prefers a <main> or
+// <article> element, falling back to the container with the most paragraph text, and drops
+// navigation and other boilerplate. The document is modified.
+func extractMainContent(doc *goquery.Document) (string, error) {
+	doc.Find(boilerplateSelector).Remove()
+
+	content := doc.Find("main, [role=main]").First()
+	if content.Length() == 0 {
+		content = largestArticle(doc)
+	}
+	if content == nil {
+		content = densestContainer(doc)
+	}
+	if content == nil {
+		content = doc.Find("body")
+	}
+
+	return goquery.OuterHtml(content)
+}
+
+// largestArticle returns the <article> with the most text, as listing pages often have several.
+func largestArticle(doc *goquery.Document) *goquery.Selection {
+	var best *goquery.Selection
+	bestLen := 0
+	doc.Find("article").Each(func(_ int, s *goquery.Selection) {
+		if l := len(strings.TrimSpace(s.Text())); l > bestLen {
+			best, bestLen = s, l
+		}
+	})
+	return best
+}
+
+// densestContainer scores each container by the paragraph text within it, with half credit to
+// the container's parent, and returns the highest scoring one. Link text does not count, so
+// link-heavy blocks such as menus score poorly.
+func densestContainer(doc *goquery.Document) *goquery.Selection {
+	scores := make(map[any]int) // Keyed by the container's *html.Node
+
+	doc.Find("p, pre, li, blockquote").Each(func(_ int, s *goquery.Selection) {
+		textLen := len(strings.TrimSpace(s.Text())) - len(strings.TrimSpace(s.Find("a").Text()))
+		if textLen <= 0 {
+			return
+		}
+
+		parent := s.Parent()
+		if parent.Length() == 0 || parent.Is("body, html") {
+			return
+		}
+		scores[parent.Get(0)] += textLen
+
+		grandparent := parent.Parent()
+		if grandparent.Length() > 0 && !grandparent.Is("body, html") {
+			scores[grandparent.Get(0)] += textLen / 2
+		}
+	})
+
+	var best *goquery.Selection
+	bestScore := 0
+	doc.Find("div, section, td").Each(func(_ int, s *goquery.Selection) {
+		if score := scores[s.Get(0)]; score > bestScore {
+			best, bestScore = s, score
+		}
+	})
+	return best
+}
@@ -0,0 +1,56 @@
+package web
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/PuerkitoBio/goquery"
+)
+
+func TestExtractMainContent(t *testing.T) {
+	tests := []struct {
+		name     string
+		html     string
+		expected string
+		dropped  []string
+	}{
+		{
+			name: "main element",
+			html: `<html><body><nav><a href="/">Home</a></nav><header>Site</header>
+<main><h1>Guide</h1><p>Install the tool.</p></main>
+<footer>Copyright</footer><div class="cookie-banner">Accept cookies</div></body></html>`,
+			expected: "Install the tool.",
+			dropped:  []string{"Home", "Site", "Copyright", "Accept cookies"},
+		},
+		{
+			name: "densest container",
+			html: `<html><body><div id="menu"><ul><li><a href="/a">A</a></li><li><a href="/b">B</a></li></ul></div>
+<div id="content"><p>First paragraph with plenty of words in it.</p><p>Second paragraph with more words.</p></div>
+<div id="sidebar"><p>Short.</p></div></body></html>`,
+			expected: "Second paragraph",
+			dropped:  []string{"Short.", "/a"},
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := goquery.NewDocumentFromReader(strings.NewReader(tt.html))
+			if err != nil {
+				t.Fatalf("Failed to parse HTML: %v", err)
+			}
+
+			content, err := extractMainContent(doc)
+			if err != nil {
+				t.Fatalf("extractMainContent failed: %v", err)
+			}
+			if !strings.Contains(content, tt.expected) {
+				t.Errorf("Expected content to contain %q, got:\n%s", tt.expected, content)
+			}
+			for _, dropped := range tt.dropped {
+				if strings.Contains(content, dropped) {
+					t.Errorf("Expected %q to be dropped, got:\n%s", dropped, content)
+				}
+			}
+		})
+	}
+}
@@ -0,0 +1,167 @@
+// web/robots.go
+
+package web
+
+import (
+	"bufio"
+	"io"
+	"net/http"
+	"net/url"
+	"strings"
+	"sync"
+)
+
+// robotsRule is an Allow or Disallow line from a robots.txt group.
+type robotsRule struct {
+	pattern string
+	allow   bool
+}
+
+// robotsRules are the rules of the robots.txt group that applies to ingest.
+type robotsRules []robotsRule
+
+// robotsCache fetches and parses robots.txt once per host.
+type robotsCache struct {
+	mu    sync.Mutex
+	hosts map[string]*robotsEntry
+}
+
+type robotsEntry struct {
+	once  sync.Once
+	rules robotsRules
+}
+
+func newRobotsCache() *robotsCache {
+	return &robotsCache{hosts: make(map[string]*robotsEntry)}
+}
+
+// allowed reports whether the crawler may fetch u. The host's robots.txt is fetched with fetch
+// the first time one of its URLs is checked.
+func (rc *robotsCache) allowed(u *url.URL, userAgent string, fetch func(string) (*http.Response, error)) bool {
+	host := strings.ToLower(u.Host)
+
+	rc.mu.Lock()
+	entry, ok := rc.hosts[host]
+	if !ok {
+		entry = &robotsEntry{}
+		rc.hosts[host] = entry
+	}
+	rc.mu.Unlock()
+
+	entry.once.Do(func() {
+		robotsURL := url.URL{Scheme: u.Scheme, Host: u.Host, Path: "/robots.txt"}
+		resp, err := fetch(robotsURL.String())
+		if err != nil {
+			return // An unreachable robots.txt places no restrictions
+		}
+		defer resp.Body.Close()
+		if resp.StatusCode != http.StatusOK {
+			return
+		}
+		entry.rules = parseRobots(resp.Body, userAgent)
+	})
+
+	path := u.EscapedPath()
+	if path == "" {
+		path = "/"
+	}
+	if u.RawQuery != "" {
+		path += "?" + u.RawQuery
+	}
+	return entry.rules.allowed(path)
+}
+
+// parseRobots returns the rules of the group for the user agent's product token, or of the
+// "*" group if there is no specific one.
+func parseRobots(r io.Reader, userAgent string) robotsRules {
+	product := strings.ToLower(strings.SplitN(userAgent, "/", 2)[0])
+
+	var specific, wildcard robotsRules
+	var haveSpecific bool
+	var agents []string
+	inRules := false
+
+	scanner := bufio.NewScanner(r)
+	for scanner.Scan() {
+		line, _, _ := strings.Cut(scanner.Text(), "#")
+		key, value, found := strings.Cut(line, ":")
+		if !found {
+			continue
+		}
+		key = strings.ToLower(strings.TrimSpace(key))
+		value = strings.TrimSpace(value)
+
+		switch key {
+		case "user-agent":
+			// Consecutive user-agent lines share a group, a user-agent after rules starts a new one
+			if inRules {
+				agents = nil
+				inRules = false
+			}
+			agents = append(agents, strings.ToLower(value))
+		case "allow", "disallow":
+			inRules = true
+			if key == "disallow" && value == "" {
+				continue // An empty Disallow allows everything
+			}
+			rule := robotsRule{pattern: value, allow: key == "allow"}
+			for _, agent := range agents {
+				switch {
+				case agent == "*":
+					wildcard = append(wildcard, rule)
+				case agent != "" && strings.Contains(product, agent):
+					specific = append(specific, rule)
+					haveSpecific = true
+				}
+			}
+		}
+	}
+
+	if haveSpecific {
+		return specific
+	}
+	return wildcard
+}
+
+// allowed applies the longest matching rule to path, with Allow winning ties.
+func (rules robotsRules) allowed(path string) bool {
+	allowed := true
+	longest := -1
+	for _, rule := range rules {
+		if !robotsMatch(rule.pattern, path) {
+			continue
+		}
+		if len(rule.pattern) > longest || (len(rule.pattern) == longest && rule.allow) {
+			longest = len(rule.pattern)
+			allowed = rule.allow
+		}
+	}
+	return allowed
+}
+
+// robotsMatch matches a robots.txt path pattern, where * matches any sequence of characters
+// and a trailing $ anchors the pattern to the end of the path.
+func robotsMatch(pattern, path string) bool {
+	anchored := strings.HasSuffix(pattern, "$")
+	pattern = strings.TrimSuffix(pattern, "$")
+
+	parts := strings.Split(pattern, "*")
+	if !strings.HasPrefix(path, parts[0]) {
+		return false
+	}
+	pos := len(parts[0])
+	for _, part := range parts[1:] {
+		i := strings.Index(path[pos:], part)
+		if i < 0 {
+			return false
+		}
+		pos += i + len(part)
+	}
+
+	if !anchored {
+		return true
+	}
+	// The final part must end the path, so check it matches at the very end
+	last := parts[len(parts)-1]
+	return (len(parts) > 1 && strings.HasSuffix(path, last)) || pos == len(path)
+}
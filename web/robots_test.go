@@ -0,0 +1,43 @@
+package web
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestParseRobots(t *testing.T) {
+	robots := `
+User-agent: *
+Disallow: /private/
+Allow: /private/public.html
+
+User-agent: otherbot
+Disallow: /
+
+User-agent: ingest
+Disallow: /search
+Disallow: /*.pdf$
+`
+
+	tests := []struct {
+		userAgent string
+		path      string
+		allowed   bool
+	}{
+		{"ingest/1.0", "/docs/", true},
+		{"ingest/1.0", "/search?q=go", false},
+		{"ingest/1.0", "/files/guide.pdf", false},
+		{"ingest/1.0", "/files/guide.pdf.html", true},
+		{"ingest/1.0", "/private/secret.html", true}, // Only the ingest group applies
+		{"somebot/2.0", "/private/secret.html", false},
+		{"somebot/2.0", "/private/public.html", true},
+		{"otherbot/1.0", "/anything", false},
+	}
+
+	for _, tt := range tests {
+		rules := parseRobots(strings.NewReader(robots), tt.userAgent)
+		if got := rules.allowed(tt.path); got != tt.allowed {
+			t.Errorf("%s %s: expected allowed=%v, got %v", tt.userAgent, tt.path, tt.allowed, got)
+		}
+	}
+}